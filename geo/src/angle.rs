@@ -1,9 +1,66 @@
 //! Module that contains simple utilities to work with angles.
 
-use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::ops::{Add, Sub};
 
-use point::Point;
-use utils::cmp_floats;
+use crate::orientation::{orientation, ExactOrientation, PointOrientation};
+use crate::point::Point;
+
+/// An angle, stored internally in radians. Unlike a raw `f64`, `Angle` forces
+/// call sites to say whether they're handing over radians or degrees,
+/// ruling out the classic mixup between the two units.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Angle(f64);
+
+impl Angle {
+    /// Build an `Angle` from a value in radians.
+    pub fn from_radians(radians: f64) -> Self {
+        Angle(radians)
+    }
+
+    /// Build an `Angle` from a value in degrees.
+    pub fn from_degrees(degrees: f64) -> Self {
+        Angle(degrees.to_radians())
+    }
+
+    /// This angle's value in radians.
+    pub fn radians(self) -> f64 {
+        self.0
+    }
+
+    /// This angle's value in degrees.
+    pub fn degrees(self) -> f64 {
+        self.0.to_degrees()
+    }
+
+    /// This angle folded into `[0, 2π)`.
+    pub fn normalized(self) -> Self {
+        let two_pi = 2.0 * PI;
+        let wrapped = self.0 % two_pi;
+
+        Angle(if wrapped < 0.0 {
+            wrapped + two_pi
+        } else {
+            wrapped
+        })
+    }
+}
+
+impl Add for Angle {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Angle(self.0 + other.0)
+    }
+}
+
+impl Sub for Angle {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Angle(self.0 - other.0)
+    }
+}
 
 /// The orientation of an angle, for example between three points.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -19,27 +76,80 @@ pub enum AngleOrientation {
 }
 
 /// Calculate the polar angle between the two points.
-pub fn polar_angle(p1: &Point<f64>, p2: &Point<f64>) -> f64 {
-    f64::atan2(p2.y - p1.y, p2.x - p1.x)
+pub fn polar_angle(p1: &Point<f64>, p2: &Point<f64>) -> Angle {
+    Angle::from_radians(f64::atan2(p2.y - p1.y, p2.x - p1.x))
 }
 
 /// Calculate the angle orientation between three points where p2 is the center
-/// point.
+/// point. A thin wrapper over `robust_orientation` for the common `f64` case.
 pub fn angle_orientation(p1: &Point<f64>, p2: &Point<f64>, p3: &Point<f64>) -> AngleOrientation {
-    let area = (p2.x - p1.x) * (p3.y - p1.y) - (p2.y - p1.y) * (p3.x - p1.x);
+    robust_orientation(p1, p2, p3)
+}
 
-    match cmp_floats(area, 0.0) {
-        Ordering::Equal => AngleOrientation::Colinear,
-        Ordering::Less => AngleOrientation::Clockwise,
-        Ordering::Greater => AngleOrientation::CounterClockwise,
+/// Like `angle_orientation`, but over any `ExactOrientation` coordinate type,
+/// via `orientation::orientation` rather than a direct `f64` cross product.
+/// This runs over any exact coordinate type, including
+/// `num_rational::Rational64`, so collinear points map to exactly `Colinear`
+/// with no rounding, which is not guaranteed for `f64`'s cross product on
+/// near-degenerate or very large inputs.
+pub fn robust_orientation<T: ExactOrientation>(
+    p1: &Point<T>,
+    p2: &Point<T>,
+    p3: &Point<T>,
+) -> AngleOrientation {
+    match orientation(p1, p2, p3) {
+        PointOrientation::OnTheLine => AngleOrientation::Colinear,
+        PointOrientation::Right => AngleOrientation::Clockwise,
+        PointOrientation::Left => AngleOrientation::CounterClockwise,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{angle_orientation, AngleOrientation};
+    use super::{angle_orientation, polar_angle, robust_orientation, Angle, AngleOrientation};
+
+    use std::f64::consts::PI;
 
     use geo::PointF64;
+    use num_rational::Rational64;
+
+    #[test]
+    fn test_angle_radians_degrees() {
+        assert!((Angle::from_radians(PI).degrees() - 180.0).abs() < 1e-9);
+        assert!((Angle::from_degrees(180.0).radians() - PI).abs() < 1e-9);
+        assert!((Angle::from_degrees(90.0).degrees() - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_angle_normalized() {
+        assert_eq!(
+            Angle::from_radians(2.0 * PI).normalized(),
+            Angle::from_radians(0.0)
+        );
+        assert!((Angle::from_degrees(-90.0).normalized().degrees() - 270.0).abs() < 1e-9);
+        assert!((Angle::from_degrees(450.0).normalized().degrees() - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_angle_add_sub() {
+        let sum = Angle::from_degrees(30.0) + Angle::from_degrees(60.0);
+        assert!((sum.degrees() - 90.0).abs() < 1e-9);
+
+        let diff = Angle::from_degrees(90.0) - Angle::from_degrees(30.0);
+        assert!((diff.degrees() - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_polar_angle() {
+        assert_eq!(
+            polar_angle(&PointF64::new(0.0, 0.0), &PointF64::new(1.0, 0.0)),
+            Angle::from_radians(0.0)
+        );
+        assert_eq!(
+            polar_angle(&PointF64::new(0.0, 0.0), &PointF64::new(0.0, 1.0)),
+            Angle::from_radians(PI / 2.0)
+        );
+    }
 
     #[test]
     fn test_angle_orientation() {
@@ -79,4 +189,27 @@ mod tests {
             AngleOrientation::Colinear
         );
     }
+
+    #[test]
+    fn test_robust_orientation_rational() {
+        use geo::Point;
+
+        let p =
+            |x: i64, y: i64| Point::new(Rational64::from_integer(x), Rational64::from_integer(y));
+
+        assert_eq!(
+            robust_orientation(&p(0, 0), &p(2, 2), &p(4, 4)),
+            AngleOrientation::Colinear
+        );
+
+        assert_eq!(
+            robust_orientation(&p(0, 0), &p(2, 2), &p(4, 0)),
+            AngleOrientation::Clockwise
+        );
+
+        assert_eq!(
+            robust_orientation(&p(0, 0), &p(4, 0), &p(2, 2)),
+            AngleOrientation::CounterClockwise
+        );
+    }
 }
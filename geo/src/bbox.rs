@@ -142,6 +142,59 @@ where
             (self.min.y + self.max.y) / T::from(2),
         )
     }
+
+    /// Return the intersection of this bounding box and `other`, i.e. the
+    /// largest bounding box contained in both. Empty if the two boxes don't
+    /// overlap.
+    pub fn intersection(&self, other: &Self) -> Self {
+        BoundingBox {
+            min: self.min.highest(&other.min),
+            max: self.max.lowest(&other.max),
+        }
+    }
+
+    /// Return the union of this bounding box and `other`, i.e. the smallest
+    /// bounding box containing both.
+    pub fn union(&self, other: &Self) -> Self {
+        BoundingBox {
+            min: self.min.lowest(&other.min),
+            max: self.max.highest(&other.max),
+        }
+    }
+
+    /// Return whether this bounding box and `other` overlap.
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Return whether `other` is entirely contained within this bounding box.
+    pub fn contains_bbox(&self, other: &Self) -> bool {
+        !other.is_empty() && self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    /// Return the squared distance between this bounding box and `pt`, 0 if
+    /// `pt` lies inside it. Computed per axis as the squared gap between
+    /// `pt`'s coordinate and the box's `[min, max]` range on that axis,
+    /// summed -- the standard box/point distance used by spatial indices.
+    pub fn squared_distance_to(&self, pt: &Point<T>) -> T {
+        let dx = axis_gap(self.min.x, self.max.x, pt.x);
+        let dy = axis_gap(self.min.y, self.max.y, pt.y);
+
+        dx * dx + dy * dy
+    }
+}
+
+// the gap between `p` and the range `[min, max]`, 0 when `p` is inside it.
+fn axis_gap<T>(min: T, max: T, p: T) -> T
+where
+    T: num::Num + From<u8> + Copy + PartialOrd,
+{
+    let zero = T::from(0);
+
+    let below = if min > p { min - p } else { zero };
+    let above = if p > max { p - max } else { zero };
+
+    below + above
 }
 
 impl<T> Default for BoundingBox<T>
@@ -185,11 +238,117 @@ where
     }
 }
 
+/// A rectangle that isn't necessarily axis aligned, as returned by
+/// `min_area_rect`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrientedRect {
+    /// The rectangle's corners, in order around its perimeter.
+    pub corners: [Point<f64>; 4],
+
+    /// The angle, in radians, of the `corners[0] -> corners[1]` edge
+    /// relative to the x axis.
+    pub angle: f64,
+}
+
+/// Compute the true minimum-area enclosing rectangle of `pts`, which unlike
+/// `BoundingBox` does not have to be axis aligned. Uses [rotating
+/// calipers](https://en.wikipedia.org/wiki/Rotating_calipers) over the
+/// convex hull of `pts`: every hull edge is tried in turn as a candidate
+/// rectangle axis, the hull is projected onto that axis and its
+/// perpendicular to get a width and height, and the edge giving the
+/// smallest `width * height` wins. Degenerates to a single point or a
+/// zero-width segment when `pts` has fewer than 3 distinct points.
+pub fn min_area_rect(pts: &[Point<f64>]) -> OrientedRect {
+    let hull = crate::convex_hull::convex_hull(pts.iter().cloned());
+
+    if hull.len() < 3 {
+        return degenerate_rect(&hull);
+    }
+
+    let mut best: Option<(f64, OrientedRect)> = None;
+
+    for i in 0..hull.len() {
+        let a = hull[i];
+        let b = hull[(i + 1) % hull.len()];
+
+        let dx = b.x - a.x;
+        let dy = b.y - a.y;
+        let edge_len = (dx * dx + dy * dy).sqrt();
+
+        // a zero-length "edge" only happens if two consecutive hull points
+        // coincide, and leaves the caliper direction undefined, so skip it.
+        if edge_len == 0.0 {
+            continue;
+        }
+
+        let ux = dx / edge_len;
+        let uy = dy / edge_len;
+
+        let (mut min_u, mut max_u) = (f64::INFINITY, f64::NEG_INFINITY);
+        let (mut min_v, mut max_v) = (f64::INFINITY, f64::NEG_INFINITY);
+
+        for p in &hull {
+            let u = p.x * ux + p.y * uy;
+            let v = p.x * -uy + p.y * ux;
+
+            min_u = min_u.min(u);
+            max_u = max_u.max(u);
+            min_v = min_v.min(v);
+            max_v = max_v.max(v);
+        }
+
+        let area = (max_u - min_u) * (max_v - min_v);
+
+        if best.as_ref().map_or(true, |(best_area, _)| area < *best_area) {
+            // project the rectangle's corners, expressed in the rotated
+            // `(u, v)` frame, back into the original coordinate system.
+            let corner = |u: f64, v: f64| Point::new(u * ux - v * uy, u * uy + v * ux);
+
+            best = Some((
+                area,
+                OrientedRect {
+                    corners: [
+                        corner(min_u, min_v),
+                        corner(max_u, min_v),
+                        corner(max_u, max_v),
+                        corner(min_u, max_v),
+                    ],
+                    angle: uy.atan2(ux),
+                },
+            ));
+        }
+    }
+
+    best.map(|(_, rect)| rect).unwrap_or_else(|| degenerate_rect(&hull))
+}
+
+fn degenerate_rect(pts: &[Point<f64>]) -> OrientedRect {
+    match pts {
+        [] => OrientedRect {
+            corners: [Point::new(0.0, 0.0); 4],
+            angle: 0.0,
+        },
+        [p] => OrientedRect {
+            corners: [*p; 4],
+            angle: 0.0,
+        },
+        _ => {
+            let a = pts[0];
+            let b = pts[pts.len() - 1];
+
+            OrientedRect {
+                corners: [a, b, b, a],
+                angle: (b.y - a.y).atan2(b.x - a.x),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::BoundingBox;
+    use super::{min_area_rect, BoundingBox};
 
-    use geo::PointU32;
+    use geo::{PointF64, PointU32};
 
     #[test]
     fn test_contains() {
@@ -311,4 +470,95 @@ mod test {
             ))
         );
     }
+
+    #[test]
+    fn test_min_area_rect_on_axis_aligned_square_matches_bounding_box() {
+        let points = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let rect = min_area_rect(&points);
+
+        let area = (rect.corners[0].dist::<f64>(&rect.corners[1]))
+            * (rect.corners[1].dist::<f64>(&rect.corners[2]));
+
+        assert!((area - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_min_area_rect_on_rotated_square_is_tighter_than_bounding_box() {
+        // a square of side √2 rotated 45°, whose axis-aligned bounding box
+        // has area 4 but whose true minimum-area rectangle has area 2.
+        let points = vec![
+            PointF64::new(0.0, 1.0),
+            PointF64::new(1.0, 2.0),
+            PointF64::new(2.0, 1.0),
+            PointF64::new(1.0, 0.0),
+        ];
+
+        let rect = min_area_rect(&points);
+
+        let area = (rect.corners[0].dist::<f64>(&rect.corners[1]))
+            * (rect.corners[1].dist::<f64>(&rect.corners[2]));
+
+        assert!((area - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_intersection_and_union() {
+        let a = BoundingBox::from_dimensions_and_origin(&PointU32::new(0, 0), 10, 10);
+        let b = BoundingBox::from_dimensions_and_origin(&PointU32::new(5, 5), 10, 10);
+
+        assert_eq!(
+            a.intersection(&b),
+            BoundingBox {
+                min: PointU32::new(5, 5),
+                max: PointU32::new(10, 10),
+            }
+        );
+        assert_eq!(
+            a.union(&b),
+            BoundingBox {
+                min: PointU32::new(0, 0),
+                max: PointU32::new(15, 15),
+            }
+        );
+        assert!(a.intersects(&b));
+
+        let c = BoundingBox::from_dimensions_and_origin(&PointU32::new(20, 20), 5, 5);
+        assert!(a.intersection(&c).is_empty());
+        assert!(!a.intersects(&c));
+    }
+
+    #[test]
+    fn test_contains_bbox() {
+        let outer = BoundingBox::from_dimensions_and_origin(&PointU32::new(0, 0), 10, 10);
+        let inner = BoundingBox::from_dimensions_and_origin(&PointU32::new(2, 2), 4, 4);
+        let overlapping = BoundingBox::from_dimensions_and_origin(&PointU32::new(8, 8), 10, 10);
+
+        assert!(outer.contains_bbox(&inner));
+        assert!(!outer.contains_bbox(&overlapping));
+        assert!(!outer.contains_bbox(&BoundingBox::new()));
+    }
+
+    #[test]
+    fn test_squared_distance_to() {
+        let bbox = BoundingBox::from_dimensions_and_origin(&PointU32::new(0, 0), 10, 10);
+
+        assert_eq!(bbox.squared_distance_to(&PointU32::new(5, 5)), 0);
+        assert_eq!(bbox.squared_distance_to(&PointU32::new(13, 4)), 9);
+        assert_eq!(bbox.squared_distance_to(&PointU32::new(13, 14)), 25);
+    }
+
+    #[test]
+    fn test_min_area_rect_degenerates_on_a_single_point() {
+        let points = vec![PointF64::new(3.0, 4.0)];
+
+        let rect = min_area_rect(&points);
+
+        assert_eq!(rect.corners, [points[0]; 4]);
+    }
 }
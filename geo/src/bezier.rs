@@ -0,0 +1,224 @@
+//! Bézier curves, flattened into polylines for `LineSegment`/`Drawer`/
+//! triangulation code that only knows how to deal with straight edges.
+
+use crate::point::PointF64;
+
+/// A quadratic (3 control point) Bézier curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuadraticBezier {
+    /// The curve's control points, in order.
+    pub points: [PointF64; 3],
+}
+
+/// A cubic (4 control point) Bézier curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubicBezier {
+    /// The curve's control points, in order.
+    pub points: [PointF64; 4],
+}
+
+fn lerp(a: PointF64, b: PointF64, t: f64) -> PointF64 {
+    PointF64::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+// perpendicular distance of `p` from the (infinite) line through `a` and `b`.
+fn distance_from_chord(p: PointF64, a: PointF64, b: PointF64) -> f64 {
+    let chord_len = a.dist::<f64>(&b);
+
+    if chord_len == 0.0 {
+        return p.dist::<f64>(&a);
+    }
+
+    ((b.x - a.x) * (a.y - p.y) - (a.x - p.x) * (b.y - a.y)).abs() / chord_len
+}
+
+impl QuadraticBezier {
+    /// Create a new `QuadraticBezier` from its 3 control points.
+    pub fn new(p0: PointF64, p1: PointF64, p2: PointF64) -> Self {
+        QuadraticBezier { points: [p0, p1, p2] }
+    }
+
+    /// Evaluate the curve at `t ∈ [0, 1]` via direct quadratic interpolation.
+    pub fn point_at(&self, t: f64) -> PointF64 {
+        let [p0, p1, p2] = self.points;
+
+        lerp(lerp(p0, p1, t), lerp(p1, p2, t), t)
+    }
+
+    /// Split this curve at `t` into two curves covering `[0, t]` and
+    /// `[t, 1]`, via de Casteljau's algorithm.
+    pub fn split(&self, t: f64) -> (Self, Self) {
+        let [p0, p1, p2] = self.points;
+
+        let p01 = lerp(p0, p1, t);
+        let p12 = lerp(p1, p2, t);
+        let mid = lerp(p01, p12, t);
+
+        (
+            QuadraticBezier::new(p0, p01, mid),
+            QuadraticBezier::new(mid, p12, p2),
+        )
+    }
+
+    fn is_flat(&self, tolerance: f64) -> bool {
+        distance_from_chord(self.points[1], self.points[0], self.points[2]) <= tolerance
+    }
+
+    /// Flatten this curve into a sequence of points that approximate it
+    /// within `tolerance` units, by recursively subdividing at `t = 0.5`
+    /// while the control point strays further than `tolerance` from the
+    /// chord connecting the curve's endpoints.
+    pub fn flatten(&self, tolerance: f64) -> Vec<PointF64> {
+        if self.is_flat(tolerance) {
+            return vec![self.points[0], self.points[2]];
+        }
+
+        let (left, right) = self.split(0.5);
+
+        let mut points = left.flatten(tolerance);
+        points.pop();
+        points.extend(right.flatten(tolerance));
+
+        points
+    }
+}
+
+impl CubicBezier {
+    /// Create a new `CubicBezier` from its 4 control points.
+    pub fn new(p0: PointF64, p1: PointF64, p2: PointF64, p3: PointF64) -> Self {
+        CubicBezier { points: [p0, p1, p2, p3] }
+    }
+
+    /// Evaluate the curve at `t ∈ [0, 1]` via direct cubic interpolation.
+    pub fn point_at(&self, t: f64) -> PointF64 {
+        let [p0, p1, p2, p3] = self.points;
+
+        let p01 = lerp(p0, p1, t);
+        let p12 = lerp(p1, p2, t);
+        let p23 = lerp(p2, p3, t);
+
+        lerp(lerp(p01, p12, t), lerp(p12, p23, t), t)
+    }
+
+    /// Split this curve at `t` into two curves covering `[0, t]` and
+    /// `[t, 1]`, via de Casteljau's algorithm.
+    pub fn split(&self, t: f64) -> (Self, Self) {
+        let [p0, p1, p2, p3] = self.points;
+
+        let p01 = lerp(p0, p1, t);
+        let p12 = lerp(p1, p2, t);
+        let p23 = lerp(p2, p3, t);
+
+        let p012 = lerp(p01, p12, t);
+        let p123 = lerp(p12, p23, t);
+
+        let mid = lerp(p012, p123, t);
+
+        (
+            CubicBezier::new(p0, p01, p012, mid),
+            CubicBezier::new(mid, p123, p23, p3),
+        )
+    }
+
+    fn is_flat(&self, tolerance: f64) -> bool {
+        let [p0, p1, p2, p3] = self.points;
+
+        distance_from_chord(p1, p0, p3).max(distance_from_chord(p2, p0, p3)) <= tolerance
+    }
+
+    /// Flatten this curve into a sequence of points that approximate it
+    /// within `tolerance` units, by recursively subdividing at `t = 0.5`
+    /// while either control point strays further than `tolerance` from the
+    /// chord connecting the curve's endpoints.
+    pub fn flatten(&self, tolerance: f64) -> Vec<PointF64> {
+        if self.is_flat(tolerance) {
+            return vec![self.points[0], self.points[3]];
+        }
+
+        let (left, right) = self.split(0.5);
+
+        let mut points = left.flatten(tolerance);
+        points.pop();
+        points.extend(right.flatten(tolerance));
+
+        points
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CubicBezier, QuadraticBezier};
+
+    use crate::point::PointF64;
+
+    #[test]
+    fn test_quadratic_point_at_endpoints() {
+        let curve = QuadraticBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(5.0, 10.0),
+            PointF64::new(10.0, 0.0),
+        );
+
+        assert_eq!(curve.point_at(0.0), PointF64::new(0.0, 0.0));
+        assert_eq!(curve.point_at(1.0), PointF64::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn test_quadratic_split_rejoins_at_t() {
+        let curve = QuadraticBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(5.0, 10.0),
+            PointF64::new(10.0, 0.0),
+        );
+
+        let (left, right) = curve.split(0.25);
+
+        assert_eq!(left.points[0], curve.points[0]);
+        assert_eq!(right.points[2], curve.points[2]);
+        assert_eq!(left.points[2], right.points[0]);
+        assert_eq!(left.points[2], curve.point_at(0.25));
+    }
+
+    #[test]
+    fn test_quadratic_flatten_straight_curve_keeps_only_endpoints() {
+        let curve = QuadraticBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(5.0, 0.0),
+            PointF64::new(10.0, 0.0),
+        );
+
+        assert_eq!(
+            curve.flatten(0.005),
+            vec![PointF64::new(0.0, 0.0), PointF64::new(10.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_cubic_point_at_endpoints() {
+        let curve = CubicBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(0.0, 10.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(10.0, 0.0),
+        );
+
+        assert_eq!(curve.point_at(0.0), PointF64::new(0.0, 0.0));
+        assert_eq!(curve.point_at(1.0), PointF64::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn test_cubic_flatten_curved_adds_intermediate_points() {
+        let curve = CubicBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(0.0, 100.0),
+            PointF64::new(100.0, 100.0),
+            PointF64::new(100.0, 0.0),
+        );
+
+        let flattened = curve.flatten(0.005);
+
+        assert!(flattened.len() > 2);
+        assert_eq!(flattened[0], PointF64::new(0.0, 0.0));
+        assert_eq!(flattened[flattened.len() - 1], PointF64::new(100.0, 0.0));
+    }
+}
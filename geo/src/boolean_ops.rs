@@ -0,0 +1,407 @@
+//! Boolean set operations (union, intersection, difference, xor) on
+//! `Polygon<f64>`, so the art generators can combine overlapping shapes
+//! instead of just stacking their fills.
+//!
+//! This follows the same 2 phases as the [Martinez-Rueda sweep-line
+//! algorithm](https://www.sciencedirect.com/science/article/pii/S0925772108001192),
+//! minus the actual sweep: every edge of both polygons is split wherever
+//! it crosses an edge of the other polygon, so each fragment lies entirely
+//! inside or outside the other polygon; fragments collinear with (and
+//! overlapping) an edge of the other polygon are deduped away, since
+//! crossing a shared edge never changes which regions a point belongs to;
+//! each remaining fragment is classified by whether it's inside the other
+//! polygon, kept or dropped according to the requested operation, and the
+//! survivors are stitched back into closed contours by chaining shared
+//! endpoints.
+
+use std::collections::HashMap;
+
+use crate::line::{IntersectionResult, LineSegment};
+use crate::point::Point;
+use crate::polygon::Polygon;
+use crate::utils::cmp_floats;
+
+/// Which boolean set operation `Polygon::boolean_op` should compute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Points inside `self` or `other` (or both).
+    Union,
+
+    /// Points inside both `self` and `other`.
+    Intersection,
+
+    /// Points inside `self` but not inside `other`.
+    Difference,
+
+    /// Points inside exactly one of `self` and `other`.
+    Xor,
+}
+
+// which polygon a split edge fragment came from, since union/intersection
+// classify subject and clip fragments against each other's interior while
+// difference additionally needs to reverse the clip fragments it keeps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    Subject,
+    Clip,
+}
+
+impl Polygon<f64> {
+    /// Points inside `self` or `other` (or both).
+    pub fn union(&self, other: &Self) -> Vec<Self> {
+        self.boolean_op(other, BooleanOp::Union)
+    }
+
+    /// Points inside both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Vec<Self> {
+        self.boolean_op(other, BooleanOp::Intersection)
+    }
+
+    /// Points inside `self` but not inside `other`.
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        self.boolean_op(other, BooleanOp::Difference)
+    }
+
+    /// Points inside exactly one of `self` and `other`.
+    pub fn xor(&self, other: &Self) -> Vec<Self> {
+        self.boolean_op(other, BooleanOp::Xor)
+    }
+
+    /// Compute `op` between `self` and `other`, returning the resulting
+    /// (possibly disjoint) contours. See the module docs for the algorithm.
+    pub fn boolean_op(&self, other: &Self, op: BooleanOp) -> Vec<Self> {
+        let subject_edges = split_against(self, other);
+        let clip_edges = split_against(other, self);
+
+        let (subject_edges, clip_edges) = drop_coincident(subject_edges, clip_edges);
+
+        let mut kept = Vec::with_capacity(subject_edges.len() + clip_edges.len());
+
+        for edge in subject_edges {
+            let inside_other = other.contains(&edge.start.midpoint(&edge.end));
+
+            if let Some(edge) = keep(op, Source::Subject, inside_other, edge) {
+                kept.push(edge);
+            }
+        }
+
+        for edge in clip_edges {
+            let inside_other = self.contains(&edge.start.midpoint(&edge.end));
+
+            if let Some(edge) = keep(op, Source::Clip, inside_other, edge) {
+                kept.push(edge);
+            }
+        }
+
+        stitch(kept)
+    }
+}
+
+// whether a fragment classified with `inside_other` should survive `op`,
+// and in which direction; clip fragments kept for `Difference` carve a
+// hole out of the subject so they need to run the opposite way.
+fn keep(
+    op: BooleanOp,
+    source: Source,
+    inside_other: bool,
+    edge: LineSegment<f64>,
+) -> Option<LineSegment<f64>> {
+    let reversed = LineSegment::new(edge.end, edge.start);
+
+    match (op, source, inside_other) {
+        (BooleanOp::Union, _, false) => Some(edge),
+        (BooleanOp::Union, _, true) => None,
+
+        (BooleanOp::Intersection, _, true) => Some(edge),
+        (BooleanOp::Intersection, _, false) => None,
+
+        (BooleanOp::Difference, Source::Subject, false) => Some(edge),
+        (BooleanOp::Difference, Source::Subject, true) => None,
+        (BooleanOp::Difference, Source::Clip, true) => Some(reversed),
+        (BooleanOp::Difference, Source::Clip, false) => None,
+
+        (BooleanOp::Xor, _, _) => Some(edge),
+    }
+}
+
+// split every edge of `polygon` at every point where it crosses an edge of
+// `other`, so no returned fragment straddles `other`'s boundary.
+//
+// Each cut keeps the actual crossing `Point` alongside its parameter instead
+// of just the parameter, so the fragments built here reuse the exact same
+// `Point` (same bit pattern) that the *other* polygon's own `split_against`
+// call reuses for its matching fragment, rather than each side
+// re-interpolating the crossing along its own (differently-rounding) edge.
+// `stitch` needs that to recognize the 2 sides' fragments as sharing an
+// endpoint.
+fn split_against(polygon: &Polygon<f64>, other: &Polygon<f64>) -> Vec<LineSegment<f64>> {
+    let mut fragments = vec![];
+
+    for (&p0, &p1) in polygon.edges() {
+        let edge = LineSegment::new(p0, p1);
+
+        let mut cuts = vec![(0.0, p0), (1.0, p1)];
+        for (&q0, &q1) in other.edges() {
+            let other_edge = LineSegment::new(q0, q1);
+
+            match edge.segment_intersection(&other_edge) {
+                IntersectionResult::Point(p) => cuts.push((param_of(&edge, &p), p)),
+                IntersectionResult::Overlap(overlap) => {
+                    cuts.push((param_of(&edge, &overlap.start), overlap.start));
+                    cuts.push((param_of(&edge, &overlap.end), overlap.end));
+                }
+                IntersectionResult::None => {}
+            }
+        }
+
+        cuts.sort_by(|a, b| cmp_floats(a.0, b.0));
+        cuts.dedup_by(|a, b| cmp_floats(a.0, b.0) == std::cmp::Ordering::Equal);
+
+        for pair in cuts.windows(2) {
+            let (_, start) = pair[0];
+            let (_, end) = pair[1];
+
+            if start != end {
+                fragments.push(LineSegment::new(start, end));
+            }
+        }
+    }
+
+    fragments
+}
+
+// the fraction of `edge` (0 at `edge.start`, 1 at `edge.end`) at which `p`
+// lies; `p` is assumed to already be on the edge's line.
+fn param_of(edge: &LineSegment<f64>, p: &Point<f64>) -> f64 {
+    let dx = edge.end.x - edge.start.x;
+    let dy = edge.end.y - edge.start.y;
+
+    if dx.abs() >= dy.abs() {
+        if dx == 0.0 {
+            0.0
+        } else {
+            (p.x - edge.start.x) / dx
+        }
+    } else if dy == 0.0 {
+        0.0
+    } else {
+        (p.y - edge.start.y) / dy
+    }
+}
+
+// remove pairs of fragments that coincide (ignoring direction) between the
+// 2 polygons' edges: the 2 regions' boundaries cancel out along a shared
+// edge, since crossing it never changes which side of either polygon a
+// point is on.
+fn drop_coincident(
+    subject: Vec<LineSegment<f64>>,
+    clip: Vec<LineSegment<f64>>,
+) -> (Vec<LineSegment<f64>>, Vec<LineSegment<f64>>) {
+    let is_same = |a: &LineSegment<f64>, b: &LineSegment<f64>| {
+        (a.start == b.start && a.end == b.end) || (a.start == b.end && a.end == b.start)
+    };
+
+    let mut clip_used = vec![false; clip.len()];
+
+    let subject = subject
+        .into_iter()
+        .filter(|s| {
+            let shared = clip
+                .iter()
+                .zip(clip_used.iter_mut())
+                .find(|(c, used)| !**used && is_same(s, *c));
+
+            match shared {
+                Some((_, used)) => {
+                    *used = true;
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let clip = clip
+        .into_iter()
+        .zip(clip_used)
+        .filter(|(_, used)| !used)
+        .map(|(c, _)| c)
+        .collect();
+
+    (subject, clip)
+}
+
+// chain the surviving directed fragments into closed contours by following
+// each one's end point to a fragment starting there, the way a final
+// Martinez-Rueda pass stitches its kept edges back into polygons.
+fn stitch(edges: Vec<LineSegment<f64>>) -> Vec<Polygon<f64>> {
+    let mut outgoing: HashMap<PointKey, Vec<LineSegment<f64>>> = HashMap::new();
+    for edge in edges {
+        outgoing.entry(key(&edge.start)).or_default().push(edge);
+    }
+
+    let mut polygons = vec![];
+
+    let starts = outgoing.keys().cloned().collect::<Vec<_>>();
+    for start in starts {
+        while let Some(edge) = outgoing.get_mut(&start).and_then(Vec::pop) {
+            let mut points = vec![edge.start];
+            let mut current = edge.end;
+
+            while current != edge.start {
+                let next_edge = match outgoing.get_mut(&key(&current)).and_then(Vec::pop) {
+                    Some(e) => e,
+                    None => break,
+                };
+
+                points.push(current);
+                current = next_edge.end;
+            }
+
+            if let Some(polygon) = Polygon::new(points) {
+                polygons.push(polygon);
+            }
+        }
+    }
+
+    polygons
+}
+
+type PointKey = (u64, u64);
+
+fn key(p: &Point<f64>) -> PointKey {
+    (p.x.to_bits(), p.y.to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::point::PointF64;
+
+    fn square(min: f64, max: f64) -> Polygon<f64> {
+        Polygon::new(vec![
+            PointF64::new(min, min),
+            PointF64::new(max, min),
+            PointF64::new(max, max),
+            PointF64::new(min, max),
+        ])
+        .unwrap()
+    }
+
+    fn total_area(polygons: &[Polygon<f64>]) -> f64 {
+        polygons.iter().map(polygon_area).sum()
+    }
+
+    fn polygon_area(polygon: &Polygon<f64>) -> f64 {
+        let points = polygon.points();
+        let sum = points
+            .windows(2)
+            .map(|e| e[0].x * e[1].y - e[1].x * e[0].y)
+            .sum::<f64>();
+
+        (sum / 2.0).abs()
+    }
+
+    #[test]
+    fn union_of_overlapping_squares_covers_both_areas() {
+        let a = square(0.0, 2.0);
+        let b = square(1.0, 3.0);
+
+        let union = a.union(&b);
+
+        assert_eq!(union.len(), 1);
+        assert_eq!(total_area(&union), 7.0);
+    }
+
+    #[test]
+    fn intersection_of_overlapping_squares_is_the_overlap() {
+        let a = square(0.0, 2.0);
+        let b = square(1.0, 3.0);
+
+        let intersection = a.intersection(&b);
+
+        assert_eq!(intersection.len(), 1);
+        assert_eq!(total_area(&intersection), 1.0);
+    }
+
+    #[test]
+    fn difference_of_overlapping_squares_removes_the_overlap() {
+        let a = square(0.0, 2.0);
+        let b = square(1.0, 3.0);
+
+        let difference = a.difference(&b);
+
+        assert_eq!(total_area(&difference), 3.0);
+    }
+
+    #[test]
+    fn xor_of_overlapping_squares_is_union_minus_intersection() {
+        let a = square(0.0, 2.0);
+        let b = square(1.0, 3.0);
+
+        let xor = a.xor(&b);
+
+        assert_eq!(total_area(&xor), 6.0);
+    }
+
+    #[test]
+    fn disjoint_squares_union_to_2_separate_contours() {
+        let a = square(0.0, 1.0);
+        let b = square(5.0, 6.0);
+
+        let union = a.union(&b);
+
+        assert_eq!(union.len(), 2);
+        assert_eq!(total_area(&union), 2.0);
+    }
+
+    #[test]
+    fn intersection_of_disjoint_squares_is_empty() {
+        let a = square(0.0, 1.0);
+        let b = square(5.0, 6.0);
+
+        assert!(a.intersection(&b).is_empty());
+    }
+
+    // a square rotated by `angle` radians around `center`, with corners
+    // `half_size` away from it: crossing an axis-aligned square's edges at
+    // non-grid-aligned points, unlike every other fixture in this file.
+    fn rotated_square(center: (f64, f64), half_size: f64, angle: f64) -> Polygon<f64> {
+        let (cx, cy) = center;
+
+        let points = [
+            (-half_size, -half_size),
+            (half_size, -half_size),
+            (half_size, half_size),
+            (-half_size, half_size),
+        ]
+        .iter()
+        .map(|&(x, y)| {
+            PointF64::new(
+                cx + x * angle.cos() - y * angle.sin(),
+                cy + x * angle.sin() + y * angle.cos(),
+            )
+        })
+        .collect();
+
+        Polygon::new(points).unwrap()
+    }
+
+    #[test]
+    fn union_of_an_axis_aligned_and_a_rotated_square_is_a_single_contour() {
+        // `b`'s corners poke out past `a`'s sides, so their edges cross at
+        // several non-axis-aligned points; a regression in how those
+        // crossing points are shared between the 2 polygons' independently
+        // split edges would make `stitch` exit early instead of closing the
+        // contour, silently returning a broken (partial or multi-piece)
+        // result instead of this single, fully-stitched one.
+        let a = square(-2.0, 2.0);
+        let b = rotated_square((0.0, 0.0), 1.8, 0.3);
+
+        let union = a.union(&b);
+
+        assert_eq!(union.len(), 1);
+        assert!((total_area(&union) - 16.448_236_943_660_73).abs() < 1e-9);
+    }
+}
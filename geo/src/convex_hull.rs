@@ -2,11 +2,15 @@
 //! Hull](https://en.wikipedia.org/wiki/Convex_hull) of a set of points.
 
 extern crate num;
+extern crate num_rational;
 
 use std::cmp::Ordering;
 
-use angle::{angle_orientation, polar_angle, AngleOrientation};
+use self::num_rational::Rational64;
+
+use angle::{angle_orientation, polar_angle, robust_orientation, AngleOrientation};
 use point::Point;
+use polygon::Polygon;
 use utils::cmp_floats;
 
 /// Calculate the convex hull of a set of points and return the points that
@@ -31,13 +35,14 @@ where
             } else {
                 ycmp
             }
-        }).unwrap();
+        })
+        .unwrap();
 
     // sort in descending order so that we remove points from the back which is
     // amortized O(1).
     points.sort_unstable_by(|p1, p2| {
-        let a1 = polar_angle(&lowest_point, p1);
-        let a2 = polar_angle(&lowest_point, p2);
+        let a1 = polar_angle(&lowest_point, p1).radians();
+        let a2 = polar_angle(&lowest_point, p2).radians();
 
         let angle_cmp = cmp_floats(a2, a1);
 
@@ -75,9 +80,207 @@ where
     hull
 }
 
+/// Like `convex_hull`, but for integer point clouds: every turn decision is
+/// routed through `robust_orientation` over the `i64` coordinates, whose
+/// determinant is evaluated in `i128`, instead of `angle_orientation`'s `f64`
+/// cross product, so collinear and
+/// near-collinear points are classified exactly regardless of the input's
+/// scale, at the cost of only accepting exact integer coordinates.
+pub fn convex_hull_exact<I>(points: I) -> Vec<Point<i64>>
+where
+    I: IntoIterator<Item = Point<i64>>,
+{
+    let mut points = points.into_iter().collect::<Vec<_>>();
+
+    if points.len() < 2 {
+        return points;
+    }
+
+    let lowest_idx = points
+        .iter()
+        .enumerate()
+        .min_by(|(_, p1), (_, p2)| p1.y.cmp(&p2.y).then_with(|| p1.x.cmp(&p2.x)))
+        .map(|(i, _)| i)
+        .unwrap();
+
+    let lowest = points.remove(lowest_idx);
+
+    points.sort_unstable_by(|p1, p2| match robust_orientation(&lowest, p1, p2) {
+        AngleOrientation::CounterClockwise => Ordering::Less,
+        AngleOrientation::Clockwise => Ordering::Greater,
+        AngleOrientation::Colinear => squared_dist_i128(&lowest, p1).cmp(&squared_dist_i128(&lowest, p2)),
+    });
+
+    let mut hull = vec![lowest, points[0]];
+
+    for &point in &points[1..] {
+        while hull.len() >= 2 {
+            let orientation =
+                robust_orientation(&hull[hull.len() - 2], hull.last().unwrap(), &point);
+
+            match orientation {
+                AngleOrientation::Clockwise | AngleOrientation::Colinear => hull.pop(),
+                AngleOrientation::CounterClockwise => break,
+            };
+        }
+
+        hull.push(point);
+    }
+
+    hull
+}
+
+// squared distance in `i128`, so it can't overflow for any `i64` points whose
+// coordinate differences fit in an `i64`.
+fn squared_dist_i128(a: &Point<i64>, b: &Point<i64>) -> i128 {
+    let dx = i128::from(b.x) - i128::from(a.x);
+    let dy = i128::from(b.y) - i128::from(a.y);
+
+    dx * dx + dy * dy
+}
+
+/// Calculate a tighter boundary around a set of points than `convex_hull`
+/// gives, one that follows concavities, using the k-nearest-neighbors
+/// approach: starting from the lowest point, repeatedly walk to whichever
+/// of the `k` nearest unused points turns the most clockwise relative to
+/// the incoming direction and whose new edge doesn't cross the hull built
+/// so far, until the walk closes back on the start. If no choice of
+/// neighbor lets the walk close into a polygon that contains every input
+/// point, retry with `k + 1`, falling back to `convex_hull` once `k`
+/// reaches the point count.
+pub fn concave_hull<I>(points: I, k: usize) -> Vec<Point<f64>>
+where
+    I: IntoIterator<Item = Point<f64>>,
+{
+    let points = points.into_iter().collect::<Vec<_>>();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    concave_hull_with_k(&points, k.max(3))
+}
+
+fn concave_hull_with_k(points: &[Point<f64>], k: usize) -> Vec<Point<f64>> {
+    if k >= points.len() {
+        return convex_hull(points.iter().cloned());
+    }
+
+    match try_concave_hull(points, k) {
+        Some(hull) => hull,
+        None => concave_hull_with_k(points, k + 1),
+    }
+}
+
+fn try_concave_hull(points: &[Point<f64>], k: usize) -> Option<Vec<Point<f64>>> {
+    let start_idx = points
+        .iter()
+        .enumerate()
+        .min_by(|(_, p1), (_, p2)| {
+            let ycmp = cmp_floats(p1.y, p2.y);
+
+            if let Ordering::Equal = ycmp {
+                cmp_floats(p1.x, p2.x)
+            } else {
+                ycmp
+            }
+        })
+        .map(|(i, _)| i)
+        .unwrap();
+
+    let start = points[start_idx];
+
+    let mut used = vec![false; points.len()];
+    used[start_idx] = true;
+
+    let mut hull = vec![start];
+    let mut current = start;
+
+    // there's no real incoming edge yet, so fake one arriving from due west;
+    // it only affects which of the first step's candidates sorts first.
+    let mut prev = Point::new(start.x - 1.0, start.y);
+
+    loop {
+        let mut candidates = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !used[*i] || (hull.len() >= 3 && *i == start_idx))
+            .map(|(_, p)| *p)
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|a, b| {
+            cmp_floats(
+                current.squared_dist::<f64>(a),
+                current.squared_dist::<f64>(b),
+            )
+        });
+        candidates.truncate(k);
+
+        candidates.sort_by(|a, b| {
+            cmp_floats(turn_angle(&prev, &current, b), turn_angle(&prev, &current, a))
+        });
+
+        let next = candidates.into_iter().find(|candidate| {
+            !hull
+                .windows(2)
+                .any(|edge| segments_properly_cross(current, *candidate, edge[0], edge[1]))
+        });
+
+        match next {
+            Some(next) if next == start && hull.len() >= 3 => break,
+            Some(next) => {
+                let idx = points.iter().position(|p| *p == next).unwrap();
+                used[idx] = true;
+
+                hull.push(next);
+                prev = current;
+                current = next;
+            }
+            None => return None,
+        }
+    }
+
+    let polygon = Polygon::new(hull.clone())?;
+
+    if points.iter().all(|p| polygon.contains(p)) {
+        Some(hull)
+    } else {
+        None
+    }
+}
+
+// the clockwise turn, in `[0, 2π)`, that the direction `current -> candidate`
+// makes relative to the incoming direction `prev -> current`; sorting
+// candidates by this value descending tries the sharpest right-hand turns
+// first, which is what hugs the walk to the boundary of a concavity.
+fn turn_angle(prev: &Point<f64>, current: &Point<f64>, candidate: &Point<f64>) -> f64 {
+    let incoming = polar_angle(prev, current);
+    let outgoing = polar_angle(current, candidate);
+
+    (incoming - outgoing).normalized().radians()
+}
+
+// whether segment `p1`-`p2` crosses segment `q1`-`q2` at a point interior to
+// both, using the standard 4-orientation test; shared or collinear
+// endpoints (as when an edge is adjacent to the one being tested) always
+// yield a `Colinear` orientation and so never count as crossing.
+fn segments_properly_cross(p1: Point<f64>, p2: Point<f64>, q1: Point<f64>, q2: Point<f64>) -> bool {
+    let o1 = angle_orientation(&p1, &p2, &q1);
+    let o2 = angle_orientation(&p1, &p2, &q2);
+    let o3 = angle_orientation(&q1, &q2, &p1);
+    let o4 = angle_orientation(&q1, &q2, &p2);
+
+    o1 != AngleOrientation::Colinear
+        && o2 != AngleOrientation::Colinear
+        && o3 != AngleOrientation::Colinear
+        && o4 != AngleOrientation::Colinear
+        && o1 != o2
+        && o3 != o4
+}
+
 #[cfg(test)]
 mod tests {
-    use super::convex_hull;
+    use super::{concave_hull, convex_hull, convex_hull_exact};
 
     extern crate proptest;
 
@@ -155,6 +358,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_convex_hull_exact_colinear() {
+        let points = vec![
+            Point::new(12, 41),
+            Point::new(17, 36),
+            Point::new(42, 11),
+            Point::new(0, 12),
+        ];
+
+        let hull = convex_hull_exact(points);
+
+        assert_eq!(
+            hull,
+            vec![Point::new(42, 11), Point::new(12, 41), Point::new(0, 12)]
+        );
+    }
+
+    #[test]
+    fn test_convex_hull_exact_handles_large_coordinates_without_rounding() {
+        // `big` is large enough that an `f64` cross product between these
+        // points would need more than the 52 bits of mantissa `f64` has,
+        // risking misclassifying `(big, big)` as off the `(0,0)-(2*big,
+        // 2*big)` line instead of exactly on it; `convex_hull_exact`'s
+        // rational arithmetic doesn't lose that precision.
+        let big = 1_000_000_000_000_i64;
+        let points = vec![
+            Point::new(0, 0),
+            Point::new(big, big),
+            Point::new(2 * big, 2 * big),
+            Point::new(big, 0),
+        ];
+
+        let hull = convex_hull_exact(points);
+
+        assert_eq!(
+            hull,
+            vec![Point::new(0, 0), Point::new(big, 0), Point::new(2 * big, 2 * big)]
+        );
+    }
+
+    #[test]
+    fn test_concave_hull_falls_back_to_convex_hull_when_k_covers_all_points() {
+        let points = vec![
+            Point::new(392.0, 23.0),
+            Point::new(134.0, 59.0),
+            Point::new(251.0, 127.0),
+            Point::new(266.0, 143.0),
+            Point::new(380.0, 183.0),
+            Point::new(337.0, 44.0),
+            Point::new(229.0, 20.0),
+            Point::new(378.0, 496.0),
+        ];
+
+        assert_eq!(
+            concave_hull(points.clone(), points.len()),
+            convex_hull(points)
+        );
+    }
+
+    #[test]
+    fn test_concave_hull_contains_all_the_points() {
+        // an L-shaped cloud: a concave hull should follow the notch instead
+        // of cutting across it the way a convex hull would.
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 4.0),
+            Point::new(4.0, 4.0),
+            Point::new(4.0, 10.0),
+            Point::new(0.0, 10.0),
+        ];
+
+        let hull = concave_hull(points.clone(), 3);
+        let polygon = Polygon::new(hull).unwrap();
+
+        for pt in &points {
+            assert!(polygon.contains(pt));
+        }
+    }
+
     proptest! {
         #![proptest_config(proptest::test_runner::Config::with_cases(500))]
         #[test]
@@ -1,109 +1,991 @@
 //! Simple module that implements [Delaunay
 //! triangulation](https://en.wikipedia.org/wiki/Delaunay_triangulation)
 
+use std::collections::HashMap;
+use std::fmt;
+
 use crate::bbox::BoundingBox;
 use crate::point::{Point, PointF64};
 use crate::triangle::Triangle;
+use crate::utils::cmp_floats;
 
-/// Triangulate the given set of points. This blows up if degenerate triangles
-/// are formed(e.g. completely flat triangles).
-pub fn triangulate(bounding_box: &BoundingBox<f64>, points: Vec<PointF64>) -> Vec<Triangle<f64>> {
+/// Triangulate `points` from scratch, generic over any coordinate type
+/// `Triangle`/`Point` support, via incremental [Bowyer-Watson
+/// insertion](https://en.wikipedia.org/wiki/Bowyer%E2%80%93Watson_algorithm):
+/// seed the triangulation with a single "super-triangle" enclosing every
+/// point's bounding box, then insert points one at a time, each time finding
+/// every "bad" triangle whose circumcircle strictly contains the new point,
+/// removing them, and re-triangulating the star-shaped cavity they leave
+/// behind by fanning its boundary edges out to the new point. Finally, every
+/// triangle still touching a super-triangle vertex is dropped.
+///
+/// Unlike `DelaunayMesh`, this re-triangulates from scratch on every call and
+/// keeps no adjacency graph around, so it's a better fit for a one-shot
+/// triangulation of an arbitrary `Point<P>` than for incremental,
+/// frame-by-frame rendering.
+pub fn delaunay<P>(points: &[Point<P>]) -> Vec<Triangle<P>>
+where
+    P: num::Num + num::Signed + num::Bounded + From<u8> + Copy + PartialOrd + fmt::Debug,
+{
     if points.len() < 3 {
         return vec![];
     }
 
-    let mut points = points.into_iter();
-    let super_triangles = super_triangles(bounding_box, &points.next().unwrap());
+    let super_triangle = bowyer_watson_super_triangle(points);
+    let mut triangles = vec![super_triangle.clone()];
 
-    // theoretically we should remove the triangles that share vertices with the
-    // initial point, but this thing is not for real use.
+    for &point in points {
+        triangles = bowyer_watson_insert(triangles, point);
+    }
 
-    points.fold(super_triangles, |triangles, point| {
-        add_point(triangles, &point)
-    })
+    triangles
+        .into_iter()
+        .filter(|triangle| {
+            triangle
+                .points
+                .iter()
+                .all(|p| !super_triangle.points.contains(p))
+        })
+        .collect()
 }
 
-// the original algorithm works by finding a super triangle that encloses
-// all the points, but since we live in a finite space just pickup a random
-// point and divide the bounding box in 4 triangles that always cover the
-// entire space. It's not acceptable for real triangulation but we're having
-// fun here :).
-fn super_triangles(bounding_box: &BoundingBox<f64>, first_point: &PointF64) -> Vec<Triangle<f64>> {
-    let bounds = bounding_box.points();
+// a single triangle built from `points`' bounding box, expanded by a margin
+// generous enough to strictly contain every point, so the first few
+// insertions never produce a degenerate (empty) cavity.
+fn bowyer_watson_super_triangle<P>(points: &[Point<P>]) -> Triangle<P>
+where
+    P: num::Num + num::Bounded + From<u8> + Copy + PartialOrd,
+{
+    let bbox = BoundingBox::from_points(points);
+    let min = *bbox.min();
+    let max = *bbox.max();
 
-    (0..bounds.len())
-        .map(|i| Triangle::new(bounds[i], bounds[(i + 1) % bounds.len()], *first_point))
-        .collect()
+    let dx = max.x - min.x;
+    let dy = max.y - min.y;
+    let delta_max = max_of(max_of(dx, dy), P::from(1)) * P::from(20);
+
+    let midx = (min.x + max.x) / P::from(2);
+    let midy = (min.y + max.y) / P::from(2);
+
+    Triangle::new(
+        Point::new(midx - delta_max, midy - delta_max),
+        Point::new(midx, midy + delta_max),
+        Point::new(midx + delta_max, midy - delta_max),
+    )
 }
 
-fn add_point(triangles: Vec<Triangle<f64>>, point: &Point<f64>) -> Vec<Triangle<f64>> {
-    let mut edges = vec![];
-    let mut new_triangles = Vec::with_capacity(triangles.len());
+// insert `point` into `triangles`: collect every "bad" triangle whose
+// circumcircle strictly contains it (a triangle with no circumcenter, i.e. a
+// degenerate/collinear one, is never bad), then replace their union with a
+// fan of new triangles joining `point` to each edge of the boundary they
+// leave behind.
+fn bowyer_watson_insert<P>(triangles: Vec<Triangle<P>>, point: Point<P>) -> Vec<Triangle<P>>
+where
+    P: num::Num + num::Signed + From<u8> + Copy + PartialOrd + fmt::Debug,
+{
+    let mut bad = vec![];
+    let mut ok = vec![];
 
     for triangle in triangles {
-        let (circumcenter, radius) = triangle.squared_circumcircle().unwrap();
+        let is_bad = match triangle.squared_circumcircle::<P>() {
+            Some((circumcenter, squared_radius)) => {
+                circumcenter.squared_dist::<P>(&point) < squared_radius
+            }
+            None => false,
+        };
 
-        if circumcenter.squared_dist::<f64>(point) <= radius {
-            edges.push((triangle.points[0], triangle.points[1]));
-            edges.push((triangle.points[1], triangle.points[2]));
-            edges.push((triangle.points[2], triangle.points[0]));
+        if is_bad {
+            bad.push(triangle);
         } else {
-            new_triangles.push(triangle);
+            ok.push(triangle);
         }
     }
 
-    edges = dedup_edges(&edges);
-
-    new_triangles.extend(
-        edges
+    ok.extend(
+        boundary_edges(&bad)
             .into_iter()
-            .map(|(pt0, pt1)| Triangle::new(pt0, pt1, *point)),
+            .map(|(p1, p2)| Triangle::new(p1, p2, point)),
     );
 
-    new_triangles
+    ok
+}
+
+// the edges of the triangles in `bad` that appear in exactly one of them,
+// i.e. the boundary of the star-shaped cavity their union forms.
+fn boundary_edges<P>(bad: &[Triangle<P>]) -> Vec<(Point<P>, Point<P>)>
+where
+    P: num::Num + Copy + PartialEq,
+{
+    let mut edges = vec![];
+
+    for triangle in bad {
+        let [a, b, c] = triangle.points;
+        edges.push((a, b));
+        edges.push((b, c));
+        edges.push((c, a));
+    }
+
+    edges
+        .iter()
+        .filter(|&&(p1, p2)| {
+            edges
+                .iter()
+                .filter(|&&(q1, q2)| (p1 == q1 && p2 == q2) || (p1 == q2 && p2 == q1))
+                .count()
+                == 1
+        })
+        .cloned()
+        .collect()
+}
+
+fn max_of<P: PartialOrd>(a: P, b: P) -> P {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// A handle to a triangle in the index-based mesh `triangulate` builds
+/// internally.
+type TriId = usize;
+
+/// An undirected edge between two points, identified by their index into the
+/// point set `triangulate` is working over rather than by coordinates, so
+/// (unlike `Point<f64>`) it can live in a `HashMap` key. Always stored with
+/// the smaller index first so both orderings of the same edge hash alike.
+type Edge = (usize, usize);
+
+fn edge_key(a: usize, b: usize) -> Edge {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+// a triangle in the index-based mesh, referencing its corners by index into
+// the point set rather than storing `Point<f64>` values directly.
+struct IndexedTriangle {
+    v: [usize; 3],
+}
+
+/// Triangulate the given set of points via incremental Bowyer-Watson
+/// insertion, seeded with a super-triangle enclosing `bounding_box`. Unlike
+/// the naive version this replaced, it keeps a proper triangle mesh plus a
+/// `HashMap<Edge, _>` of at-most-two triangles bordering each edge (keyed by
+/// point index rather than by `Point<f64>`, since floats can't hash), so
+/// insertion locates the containing triangle by walking this adjacency graph
+/// and dedups edges in O(1) instead of the old O(n^2) coordinate comparison.
+/// The super-triangle's own vertices are dropped from the result, so unlike
+/// before this never hands back a degenerate triangle touching them.
+pub fn triangulate(bounding_box: &BoundingBox<f64>, points: Vec<PointF64>) -> Vec<Triangle<f64>> {
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    let (pts, triangles, _, n) = build_mesh(bounding_box, points);
+
+    triangles
+        .into_iter()
+        .flatten()
+        .filter(|t| t.v.iter().all(|&idx| idx < n))
+        .map(|t| Triangle::new(pts[t.v[0]], pts[t.v[1]], pts[t.v[2]]))
+        .collect()
+}
+
+/// Return each of `points`' Voronoi cells, as the site paired with its cell
+/// polygon, built as the dual of the Delaunay triangulation: every
+/// triangle's circumcenter is a Voronoi vertex, and a site's cell is the
+/// polygon formed by the circumcenters of every triangle incident to it
+/// (after a scan for the first one, found by walking `build_mesh`'s adjacency
+/// map), sorted angularly around the site with `Point::angle_to`.
+/// Circumcenters are clamped into `bounding_box` rather than properly
+/// clipping each cell edge against it, since sites near the hull have cells
+/// that are unbounded in the true, infinite-plane Voronoi diagram.
+pub fn voronoi(
+    bounding_box: &BoundingBox<f64>,
+    points: Vec<PointF64>,
+) -> Vec<(PointF64, Vec<PointF64>)> {
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    let (pts, triangles, edges, n) = build_mesh(bounding_box, points);
+
+    (0..n)
+        .map(|i| {
+            let site = pts[i];
+            let mut cell = incident_triangles(&triangles, &edges, i)
+                .filter_map(|id| triangles[id].as_ref())
+                .filter_map(|t| Triangle::new(pts[t.v[0]], pts[t.v[1]], pts[t.v[2]]).circumcenter())
+                .map(|c| clamp_to_bbox(bounding_box, c))
+                .collect::<Vec<_>>();
+
+            cell.sort_by(|a, b| cmp_floats(site.angle_to(a).radians(), site.angle_to(b).radians()));
+
+            (site, cell)
+        })
+        .collect()
+}
+
+/// A single cell of a Voronoi diagram: an input site paired with the
+/// polygon of its surrounding region.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoronoiCell {
+    /// The input point this cell surrounds.
+    pub site: PointF64,
+
+    /// The cell's boundary, as a ring of Voronoi vertices sorted angularly
+    /// around `site`.
+    pub polygon: Vec<PointF64>,
+}
+
+/// Same as `voronoi`, but wraps each `(site, polygon)` pair into a named
+/// `VoronoiCell` instead of a bare tuple.
+pub fn voronoi_cells(bounding_box: &BoundingBox<f64>, points: Vec<PointF64>) -> Vec<VoronoiCell> {
+    voronoi(bounding_box, points)
+        .into_iter()
+        .map(|(site, polygon)| VoronoiCell { site, polygon })
+        .collect()
+}
+
+// seed a mesh with a super-triangle enclosing `bounding_box`, then insert
+// every point in `points` one at a time via incremental Bowyer-Watson.
+// Returns the full point set (the original points followed by the 3
+// super-triangle vertices, at indices `>= points.len()`), the resulting
+// triangle mesh, its edge adjacency map, and the original point count.
+fn build_mesh(
+    bounding_box: &BoundingBox<f64>,
+    points: Vec<PointF64>,
+) -> (
+    Vec<PointF64>,
+    Vec<Option<IndexedTriangle>>,
+    HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    usize,
+) {
+    let n = points.len();
+    let mut pts = points;
+
+    let min = *bounding_box.min();
+    let max = *bounding_box.max();
+    let delta_max = (max.x - min.x).max(max.y - min.y).max(1.0) * 20.0;
+    let midx = (min.x + max.x) / 2.0;
+    let midy = (min.y + max.y) / 2.0;
+
+    pts.push(Point::new(midx - delta_max, midy - delta_max));
+    pts.push(Point::new(midx, midy + delta_max));
+    pts.push(Point::new(midx + delta_max, midy - delta_max));
+
+    let mut triangles: Vec<Option<IndexedTriangle>> = vec![];
+    let mut edges: HashMap<Edge, (Option<TriId>, Option<TriId>)> = HashMap::new();
+
+    let mut last = add_triangle(&mut triangles, &mut edges, [n, n + 1, n + 2]);
+
+    for i in 0..n {
+        last = insert_indexed_point(&pts, &mut triangles, &mut edges, i, last);
+    }
+
+    (pts, triangles, edges, n)
+}
+
+// every triangle id incident to point index `v`: a linear scan finds the
+// first triangle containing `v`, then the rest are found by walking the edge
+// adjacency map outwards from it across `v`'s edges.
+fn incident_triangles<'a>(
+    triangles: &'a [Option<IndexedTriangle>],
+    edges: &'a HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    v: usize,
+) -> impl Iterator<Item = TriId> + 'a {
+    let start = triangles
+        .iter()
+        .position(|t| t.as_ref().map_or(false, |t| t.v.contains(&v)));
+
+    let mut seen = vec![];
+    let mut stack = vec![];
+
+    if let Some(start) = start {
+        seen.push(start);
+        stack.push(start);
+    }
+
+    while let Some(id) = stack.pop() {
+        let v_of_id = triangles[id].as_ref().unwrap().v;
+        let other_corners: Vec<usize> = v_of_id.iter().cloned().filter(|&c| c != v).collect();
+
+        for &(p1, p2) in &[(v, other_corners[0]), (v, other_corners[1])] {
+            if let Some(n) = neighbor_across(edges, id, p1, p2) {
+                if triangles[n].as_ref().map_or(false, |t| t.v.contains(&v)) && !seen.contains(&n) {
+                    seen.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+    }
+
+    seen.into_iter()
+}
+
+// clamp `p` into `bounding_box`'s extent, our stand-in for properly clipping
+// a Voronoi cell edge against it.
+fn clamp_to_bbox(bounding_box: &BoundingBox<f64>, p: PointF64) -> PointF64 {
+    let min = *bounding_box.min();
+    let max = *bounding_box.max();
+
+    Point::new(p.x.max(min.x).min(max.x), p.y.max(min.y).min(max.y))
+}
+
+// insert a new triangle over the three given point indices, registering it
+// against each of its edges in `edges`.
+fn add_triangle(
+    triangles: &mut Vec<Option<IndexedTriangle>>,
+    edges: &mut HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    v: [usize; 3],
+) -> TriId {
+    let id = triangles.len();
+    triangles.push(Some(IndexedTriangle { v }));
+
+    for &(a, b) in &[(v[0], v[1]), (v[1], v[2]), (v[2], v[0])] {
+        let slot = edges.entry(edge_key(a, b)).or_insert((None, None));
+
+        if slot.0.is_none() {
+            slot.0 = Some(id);
+        } else {
+            slot.1 = Some(id);
+        }
+    }
+
+    id
+}
+
+// remove a triangle, unregistering it from each of its edges in `edges`.
+fn remove_triangle(
+    triangles: &mut [Option<IndexedTriangle>],
+    edges: &mut HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    id: TriId,
+) {
+    let v = triangles[id].take().unwrap().v;
+
+    for &(a, b) in &[(v[0], v[1]), (v[1], v[2]), (v[2], v[0])] {
+        if let Some(slot) = edges.get_mut(&edge_key(a, b)) {
+            if slot.0 == Some(id) {
+                slot.0 = slot.1.take();
+            } else if slot.1 == Some(id) {
+                slot.1 = None;
+            }
+        }
+    }
+}
+
+// the triangle on the other side of the edge `(a, b)` from `id`, if any.
+fn neighbor_across(
+    edges: &HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    id: TriId,
+    a: usize,
+    b: usize,
+) -> Option<TriId> {
+    match edges.get(&edge_key(a, b)) {
+        Some(&(Some(x), Some(y))) if x == id => Some(y),
+        Some(&(Some(x), Some(y))) if y == id => Some(x),
+        _ => None,
+    }
+}
+
+// find the triangle containing `point`, starting the walk at `start` and
+// stepping, at each triangle, across whichever edge `point` lies on the
+// opposite side of from the rest of the triangle. Mirrors `DelaunayMesh`'s
+// `locate`, but steps across the `edges` map instead of a neighbor array.
+fn locate(
+    pts: &[PointF64],
+    triangles: &[Option<IndexedTriangle>],
+    edges: &HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    point: &PointF64,
+    start: TriId,
+) -> TriId {
+    let mut current = start;
+
+    loop {
+        let [a, b, c] = triangles[current].as_ref().unwrap().v;
+        let corners = [(b, c, a), (c, a, b), (a, b, c)];
+
+        let mut stepped = None;
+
+        for &(p1i, p2i, oppi) in &corners {
+            let reference = orientation(&pts[p1i], &pts[p2i], &pts[oppi]);
+            let towards_point = orientation(&pts[p1i], &pts[p2i], point);
+
+            if reference.signum() != towards_point.signum() {
+                if let Some(next) = neighbor_across(edges, current, p1i, p2i) {
+                    stepped = Some(next);
+                    break;
+                }
+            }
+        }
+
+        match stepped {
+            Some(next) => current = next,
+            None => return current,
+        }
+    }
+}
+
+// insert the point at `point_idx` into the mesh: locate the triangle it
+// falls in (starting the walk from `start`), flood-fill the cavity of every
+// triangle whose circumcircle contains it, then remove the cavity and fan
+// its boundary edges out to the new point. Returns the id of one of the new
+// fan triangles, to seed the next `locate` walk from.
+fn insert_indexed_point(
+    pts: &[PointF64],
+    triangles: &mut Vec<Option<IndexedTriangle>>,
+    edges: &mut HashMap<Edge, (Option<TriId>, Option<TriId>)>,
+    point_idx: usize,
+    start: TriId,
+) -> TriId {
+    let point = pts[point_idx];
+    let containing = locate(pts, triangles, edges, &point, start);
+
+    let mut cavity = vec![containing];
+    let mut stack = vec![containing];
+
+    while let Some(id) = stack.pop() {
+        let [a, b, c] = triangles[id].as_ref().unwrap().v;
+
+        for &(p1, p2) in &[(b, c), (c, a), (a, b)] {
+            let n = match neighbor_across(edges, id, p1, p2) {
+                Some(n) if !cavity.contains(&n) => n,
+                _ => continue,
+            };
+
+            let nv = triangles[n].as_ref().unwrap().v;
+            let neighbor_triangle = Triangle::new(pts[nv[0]], pts[nv[1]], pts[nv[2]]);
+
+            if let Some((circumcenter, radius)) = neighbor_triangle.squared_circumcircle::<f64>() {
+                if circumcenter.squared_dist::<f64>(&point) <= radius {
+                    cavity.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+    }
+
+    let mut boundary = vec![];
+
+    for &id in &cavity {
+        let [a, b, c] = triangles[id].as_ref().unwrap().v;
+
+        for &(p1, p2) in &[(b, c), (c, a), (a, b)] {
+            let inside_cavity =
+                neighbor_across(edges, id, p1, p2).map_or(false, |n| cavity.contains(&n));
+
+            if !inside_cavity {
+                boundary.push((p1, p2));
+            }
+        }
+    }
+
+    for &id in &cavity {
+        remove_triangle(triangles, edges, id);
+    }
+
+    let mut last = containing;
+    for &(p1, p2) in &boundary {
+        last = add_triangle(triangles, edges, [p1, p2, point_idx]);
+    }
+
+    last
+}
+
+/// A handle to a triangle stored in a [`DelaunayMesh`]. Stable across
+/// insertions until the triangle it points to is removed (by `insert` or
+/// `remove_super_triangle`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TriangleHandle(usize);
+
+/// What lies across one edge of a mesh triangle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Neighbor {
+    /// The triangle sharing this edge.
+    Triangle(TriangleHandle),
+
+    /// This edge lies on the mesh's outer hull, e.g. an edge of the initial
+    /// super triangle.
+    Border,
+}
+
+struct MeshTriangle {
+    triangle: Triangle<f64>,
+
+    // neighbors[0] is the triangle across the edge opposite
+    // `triangle.points[0]`(i.e. the edge between points[1] and points[2]),
+    // neighbors[1] is opposite points[1] and neighbors[2] is opposite
+    // points[2].
+    neighbors: [Neighbor; 3],
 }
 
-fn dedup_edges(edges: &[(Point<f64>, Point<f64>)]) -> Vec<(Point<f64>, Point<f64>)> {
-    // super ugly and super inefficient, but we cannot use hashmaps with f64...
+/// `triangulate` re-triangulates the whole point set from scratch every time
+/// a point is added, which is wasteful if we want to e.g. render an
+/// intermediate frame after every insertion. `DelaunayMesh` instead keeps a
+/// triangle adjacency graph around so that points can be added one at a time
+/// via [Bowyer-Watson's
+/// algorithm](https://en.wikipedia.org/wiki/Bowyer%E2%80%93Watson_algorithm):
+/// locate the triangle containing the new point by walking neighbor links,
+/// collect the "cavity" of triangles whose circumcircle contains it, and
+/// re-fan the resulting star-shaped hole around the new point.
+pub struct DelaunayMesh {
+    // `None` entries are tombstones left behind by removed triangles so that
+    // every previously handed out `TriangleHandle` stays a valid index.
+    triangles: Vec<Option<MeshTriangle>>,
+    free: Vec<usize>,
+    super_triangle: [Point<f64>; 3],
+    last: TriangleHandle,
+}
+
+impl DelaunayMesh {
+    /// Create a new mesh covering `bounding_box`, seeded with a single super
+    /// triangle big enough to enclose it. Call `remove_super_triangle` once
+    /// enough points have been inserted to get rid of it.
+    pub fn new(bounding_box: &BoundingBox<f64>) -> Self {
+        let min = *bounding_box.min();
+        let max = *bounding_box.max();
+
+        let dx = max.x - min.x;
+        let dy = max.y - min.y;
+        let delta_max = dx.max(dy).max(1.0);
+
+        let midx = (min.x + max.x) / 2.0;
+        let midy = (min.y + max.y) / 2.0;
+
+        let super_triangle = [
+            Point::new(midx - 20.0 * delta_max, midy - delta_max),
+            Point::new(midx, midy + 20.0 * delta_max),
+            Point::new(midx + 20.0 * delta_max, midy - delta_max),
+        ];
+
+        DelaunayMesh {
+            triangles: vec![Some(MeshTriangle {
+                triangle: Triangle::new(super_triangle[0], super_triangle[1], super_triangle[2]),
+                neighbors: [Neighbor::Border; 3],
+            })],
+            free: vec![],
+            super_triangle,
+            last: TriangleHandle(0),
+        }
+    }
+
+    /// Insert `point` into the mesh, updating the triangulation (and its
+    /// adjacency graph) in place.
+    pub fn insert(&mut self, point: Point<f64>) {
+        let containing = self.locate(&point, self.last);
+
+        // collect the cavity: every triangle, starting from `containing`,
+        // whose circumcircle contains `point`, found by walking outwards
+        // across neighbor links instead of checking every triangle in the
+        // mesh.
+        let mut cavity = vec![containing];
+        let mut stack = vec![containing];
+
+        while let Some(handle) = stack.pop() {
+            let neighbors = self.mesh_triangle(handle).neighbors;
+
+            for neighbor in &neighbors {
+                if let Neighbor::Triangle(n) = *neighbor {
+                    if cavity.contains(&n) {
+                        continue;
+                    }
+
+                    let (circumcenter, radius) = self
+                        .mesh_triangle(n)
+                        .triangle
+                        .squared_circumcircle()
+                        .unwrap();
+
+                    if circumcenter.squared_dist::<f64>(&point) <= radius {
+                        cavity.push(n);
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+
+        // the edges of the cavity that don't border another cavity triangle
+        // are its boundary: the star-shaped hole that gets re-fanned around
+        // `point`. keep track of which (now removed) triangle each boundary
+        // edge came from, so the new fan triangle replacing it can take over
+        // its outside neighbor's back-reference.
+        let mut boundary = vec![];
+
+        for &handle in &cavity {
+            let mt = self.mesh_triangle(handle);
+            let [a, b, c] = mt.triangle.points;
+            let edges = [
+                (b, c, mt.neighbors[0]),
+                (c, a, mt.neighbors[1]),
+                (a, b, mt.neighbors[2]),
+            ];
+
+            for &(p1, p2, neighbor) in &edges {
+                let inside_cavity = match neighbor {
+                    Neighbor::Triangle(n) => cavity.contains(&n),
+                    Neighbor::Border => false,
+                };
+
+                if !inside_cavity {
+                    boundary.push((p1, p2, handle, neighbor));
+                }
+            }
+        }
+
+        for &handle in &cavity {
+            self.remove(handle);
+        }
+
+        let mut fan = vec![];
+
+        for &(p1, p2, removed, outside) in &boundary {
+            let triangle = Triangle::new(p1, p2, point);
+            let handle =
+                self.insert_triangle(triangle, [Neighbor::Border, Neighbor::Border, outside]);
+
+            if let Neighbor::Triangle(outside_handle) = outside {
+                self.relink(outside_handle, removed, Neighbor::Triangle(handle));
+            }
+
+            fan.push((p1, p2, handle));
+        }
+
+        // stitch the new fan triangles to each other: the triangle whose
+        // base edge is (p1, p2) shares its (p2, point) edge with whichever
+        // fan triangle's base starts at p2, and its (point, p1) edge with
+        // whichever fan triangle's base ends at p1.
+        for &(p1, p2, handle) in &fan {
+            if let Some(&(_, _, next)) = fan.iter().find(|&&(np1, _, _)| np1 == p2) {
+                self.set_neighbor(handle, 0, Neighbor::Triangle(next));
+            }
+
+            if let Some(&(_, _, prev)) = fan.iter().find(|&&(_, np2, _)| np2 == p1) {
+                self.set_neighbor(handle, 1, Neighbor::Triangle(prev));
+            }
+
+            self.last = handle;
+        }
+    }
 
-    let mut out = vec![];
+    /// Remove every triangle that still has one of the initial super
+    /// triangle's vertices, undoing the triangle `new` seeded the mesh with.
+    pub fn remove_super_triangle(&mut self) {
+        let to_remove = (0..self.triangles.len())
+            .filter(|&i| {
+                self.triangles[i].as_ref().map_or(false, |mt| {
+                    mt.triangle
+                        .points
+                        .iter()
+                        .any(|p| self.super_triangle.contains(p))
+                })
+            })
+            .map(TriangleHandle)
+            .collect::<Vec<_>>();
 
-    for i in 0..edges.len() {
-        let mut count = 0;
+        for &handle in &to_remove {
+            let neighbors = self.mesh_triangle(handle).neighbors;
 
-        for j in 0..edges.len() {
-            let (start, end) = &edges[j];
-            if edges[i] == (*start, *end) || edges[i] == (*end, *start) {
-                count += 1;
+            for neighbor in &neighbors {
+                if let Neighbor::Triangle(n) = *neighbor {
+                    if !to_remove.contains(&n) {
+                        self.relink(n, handle, Neighbor::Border);
+                    }
+                }
             }
         }
 
-        if count == 1 {
-            out.push(edges[i]);
+        for &handle in &to_remove {
+            self.remove(handle);
+        }
+    }
+
+    /// Return the triangles currently in the mesh.
+    pub fn triangles(&self) -> impl Iterator<Item = &Triangle<f64>> {
+        self.triangles
+            .iter()
+            .filter_map(|mt| mt.as_ref().map(|mt| &mt.triangle))
+    }
+
+    // find the triangle containing `point`, starting the walk at `start` and
+    // stepping, at each triangle, across whichever edge `point` lies on the
+    // opposite side of from the rest of the triangle.
+    fn locate(&self, point: &Point<f64>, start: TriangleHandle) -> TriangleHandle {
+        let mut current = start;
+
+        loop {
+            let mt = self.mesh_triangle(current);
+            let [a, b, c] = mt.triangle.points;
+            let edges = [
+                (b, c, a, mt.neighbors[0]),
+                (c, a, b, mt.neighbors[1]),
+                (a, b, c, mt.neighbors[2]),
+            ];
+
+            let mut stepped = None;
+
+            for &(p1, p2, opposite, neighbor) in &edges {
+                let reference = orientation(&p1, &p2, &opposite);
+                let towards_point = orientation(&p1, &p2, point);
+
+                if reference.signum() != towards_point.signum() {
+                    if let Neighbor::Triangle(next) = neighbor {
+                        stepped = Some(next);
+                        break;
+                    }
+                }
+            }
+
+            match stepped {
+                Some(next) => current = next,
+                None => return current,
+            }
+        }
+    }
+
+    fn mesh_triangle(&self, handle: TriangleHandle) -> &MeshTriangle {
+        self.triangles[handle.0].as_ref().unwrap()
+    }
+
+    fn insert_triangle(
+        &mut self,
+        triangle: Triangle<f64>,
+        neighbors: [Neighbor; 3],
+    ) -> TriangleHandle {
+        let mt = MeshTriangle {
+            triangle,
+            neighbors,
+        };
+
+        if let Some(i) = self.free.pop() {
+            self.triangles[i] = Some(mt);
+            TriangleHandle(i)
+        } else {
+            self.triangles.push(Some(mt));
+            TriangleHandle(self.triangles.len() - 1)
+        }
+    }
+
+    fn remove(&mut self, handle: TriangleHandle) {
+        self.triangles[handle.0] = None;
+        self.free.push(handle.0);
+    }
+
+    fn set_neighbor(&mut self, handle: TriangleHandle, idx: usize, neighbor: Neighbor) {
+        self.triangles[handle.0].as_mut().unwrap().neighbors[idx] = neighbor;
+    }
+
+    // replace, in `handle`'s neighbor list, whichever slot points at `old`
+    // with `new`.
+    fn relink(&mut self, handle: TriangleHandle, old: TriangleHandle, new: Neighbor) {
+        for neighbor in &mut self.triangles[handle.0].as_mut().unwrap().neighbors {
+            if *neighbor == Neighbor::Triangle(old) {
+                *neighbor = new;
+                return;
+            }
         }
     }
+}
 
-    out
+// positive or negative depending on which side of the directed line `p1` ->
+// `p2` the point `p` lies on; zero if it's on the line. Used to tell which
+// side of a triangle's edge a point falls on without assuming any particular
+// winding order for the triangle's own points.
+fn orientation(p1: &Point<f64>, p2: &Point<f64>, p: &Point<f64>) -> f64 {
+    (p2.x - p1.x) * (p.y - p1.y) - (p2.y - p1.y) * (p.x - p1.x)
 }
 
 #[cfg(test)]
 mod test {
-    use super::dedup_edges;
+    use super::{delaunay, triangulate, voronoi, voronoi_cells, DelaunayMesh};
+
+    use geo::{BoundingBox, PointF64, PointI32};
+
+    #[test]
+    fn test_voronoi_too_few_points() {
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        assert_eq!(
+            voronoi(
+                &bbox,
+                vec![PointF64::new(0.0, 0.0), PointF64::new(1.0, 1.0)]
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_voronoi_square() {
+        // 4 sites forming a square: each one gets a cell, and every cell
+        // vertex should be the circumcenter of some Delaunay triangle, i.e.
+        // inside (or on the border of) the bounding box we clamp against.
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let points = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let cells = voronoi(&bbox, points.clone());
+
+        assert_eq!(cells.len(), 4);
+
+        for (site, cell) in &cells {
+            assert!(points.contains(site));
+            assert!(!cell.is_empty());
+
+            for p in cell {
+                assert!(bbox.contains(p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_voronoi_cells_wraps_voronoi_tuples() {
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let points = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let cells = voronoi_cells(&bbox, points.clone());
+        let tuples = voronoi(&bbox, points);
+
+        assert_eq!(cells.len(), tuples.len());
+
+        for (cell, (site, polygon)) in cells.iter().zip(tuples.iter()) {
+            assert_eq!(&cell.site, site);
+            assert_eq!(&cell.polygon, polygon);
+        }
+    }
 
-    use geo::Point;
+    #[test]
+    fn test_triangulate_too_few_points() {
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        assert_eq!(
+            triangulate(
+                &bbox,
+                vec![PointF64::new(0.0, 0.0), PointF64::new(1.0, 1.0)]
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_triangulate_square() {
+        // a square always splits into exactly 2 triangles, whichever
+        // diagonal gets picked, and none of them should touch the
+        // super-triangle's vertices.
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let points = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let triangles = triangulate(&bbox, points.clone());
+
+        assert_eq!(triangles.len(), 2);
+
+        for triangle in &triangles {
+            for p in &triangle.points {
+                assert!(points.contains(p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_delaunay_too_few_points() {
+        assert_eq!(
+            delaunay(&[PointI32::new(0, 0), PointI32::new(1, 1)]),
+            vec![]
+        );
+    }
 
     #[test]
-    fn test_dedup_edges() {
-        let edge1 = (Point::new(42.0, 12.0), Point::new(7.0, 12.0));
-        let redge1 = (edge1.1, edge1.0);
+    fn test_delaunay_square() {
+        // a square always splits into exactly 2 triangles, whichever
+        // diagonal gets picked.
+        let triangles = delaunay(&[
+            PointI32::new(0, 0),
+            PointI32::new(10, 0),
+            PointI32::new(10, 10),
+            PointI32::new(0, 10),
+        ]);
+
+        assert_eq!(triangles.len(), 2);
+    }
 
-        let edge2 = (Point::new(42.0, 73.0), Point::new(84.0, 146.0));
-        let redge2 = (edge2.1, edge2.0);
+    #[test]
+    fn test_delaunay_mesh_too_few_points_leaves_nothing_after_removing_the_super_triangle() {
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let mut mesh = DelaunayMesh::new(&bbox);
 
-        let edge3 = (Point::new(23.0, 32.0), Point::new(32.0, 23.0));
+        mesh.insert(PointF64::new(5.0, 5.0));
+        mesh.remove_super_triangle();
+
+        assert_eq!(mesh.triangles().count(), 0);
+    }
+
+    #[test]
+    fn test_delaunay_mesh_square_splits_into_2_triangles() {
+        // same property as `test_triangulate_square`, but built up one
+        // `insert` at a time instead of in one batch call.
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let points = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let mut mesh = DelaunayMesh::new(&bbox);
+        for &p in &points {
+            mesh.insert(p);
+        }
+        mesh.remove_super_triangle();
+
+        let triangles = mesh.triangles().collect::<Vec<_>>();
+        assert_eq!(triangles.len(), 2);
+
+        for triangle in &triangles {
+            for p in &triangle.points {
+                assert!(points.contains(p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_delaunay_mesh_matches_triangle_count_of_one_shot_triangulate() {
+        // inserting the same points one at a time through `DelaunayMesh`
+        // should produce a triangulation with as many triangles as
+        // `triangulate`'s one-shot batch call: both implement the same
+        // Bowyer-Watson algorithm over the same point set, and the triangle
+        // count of a Delaunay triangulation (2n - h - 2, by Euler's formula)
+        // doesn't depend on the order points are inserted in.
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let points = vec![
+            PointF64::new(1.0, 1.0),
+            PointF64::new(9.0, 2.0),
+            PointF64::new(5.0, 8.0),
+            PointF64::new(2.0, 6.0),
+            PointF64::new(7.0, 7.0),
+        ];
+
+        let mut mesh = DelaunayMesh::new(&bbox);
+        for &p in &points {
+            mesh.insert(p);
+        }
+        mesh.remove_super_triangle();
 
-        let edges = vec![edge1, edge2, edge1, redge2, edge3, redge1, edge1, redge1];
+        let incremental_count = mesh.triangles().count();
+        let batch_count = triangulate(&bbox, points).len();
 
-        assert_eq!(dedup_edges(&edges), vec![edge3]);
+        assert_eq!(incremental_count, batch_count);
     }
 }
@@ -0,0 +1,182 @@
+//! A simple 2-D [k-d tree](https://en.wikipedia.org/wiki/K-d_tree) for
+//! nearest-neighbor queries over a fixed set of points, e.g. accelerating
+//! `kmeans`'s per-point pivot assignment from an O(k) linear scan down to
+//! roughly O(log k).
+
+use std::fmt::Debug;
+
+use crate::point::Point;
+use crate::utils::ksmallest::ksmallest_by;
+
+struct Node {
+    /// Index into the `KdTree`'s `points`.
+    idx: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A static k-d tree over 2D points, splitting on `x` at even depths and `y`
+/// at odd depths.
+pub struct KdTree<T> {
+    points: Vec<Point<T>>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+impl<T> KdTree<T>
+where
+    T: num::Num + PartialOrd + Copy + Debug,
+{
+    /// Build a tree over `points`. `nearest` later returns indices into this
+    /// same `points` vec.
+    pub fn build(points: Vec<Point<T>>) -> Self {
+        let mut indices = (0..points.len()).collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(points.len());
+        let root = Self::build_rec(&points, &mut indices, 0, &mut nodes);
+
+        KdTree {
+            points,
+            nodes,
+            root,
+        }
+    }
+
+    // recursively partition `indices` on the splitting axis for `depth`,
+    // picking the median along that axis with `ksmallest_by` (an O(n)
+    // selection instead of an O(n log n) sort) so the tree comes out
+    // balanced, then build the two halves as this node's children.
+    fn build_rec(
+        points: &[Point<T>],
+        indices: &mut [usize],
+        depth: usize,
+        nodes: &mut Vec<Node>,
+    ) -> Option<usize> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let axis = depth % 2;
+        let mid = indices.len() / 2;
+
+        ksmallest_by(indices, mid, |&a, &b| {
+            axis_value(&points[a], axis)
+                .partial_cmp(&axis_value(&points[b], axis))
+                .unwrap()
+        });
+
+        let this = indices[mid];
+        let (left_indices, rest) = indices.split_at_mut(mid);
+        let right_indices = &mut rest[1..];
+
+        let left = Self::build_rec(points, left_indices, depth + 1, nodes);
+        let right = Self::build_rec(points, right_indices, depth + 1, nodes);
+
+        nodes.push(Node {
+            idx: this,
+            left,
+            right,
+        });
+
+        Some(nodes.len() - 1)
+    }
+
+    /// Find the index (into the `points` passed to `build`) of the point
+    /// nearest to `query`. Panics if the tree is empty.
+    pub fn nearest(&self, query: &Point<T>) -> usize {
+        self.nearest_rec(self.root, query, 0, None)
+            .expect("KdTree::nearest called on an empty tree")
+            .0
+    }
+
+    // descend to the leaf on `query`'s side of each splitting plane first,
+    // then backtrack up, only descending into the far child when the
+    // squared distance from `query` to the splitting plane is less than the
+    // best squared distance found so far.
+    fn nearest_rec(
+        &self,
+        node: Option<usize>,
+        query: &Point<T>,
+        depth: usize,
+        mut best: Option<(usize, T)>,
+    ) -> Option<(usize, T)> {
+        let node = &self.nodes[node?];
+        let candidate = &self.points[node.idx];
+        let dist = candidate.squared_dist::<T>(query);
+
+        if best.map_or(true, |(_, best_dist)| dist < best_dist) {
+            best = Some((node.idx, dist));
+        }
+
+        let axis = depth % 2;
+        let (q, p) = (axis_value(query, axis), axis_value(candidate, axis));
+        let (near, far) = if q < p {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+
+        best = self.nearest_rec(near, query, depth + 1, best);
+
+        let plane_dist = (q - p) * (q - p);
+        if best.map_or(true, |(_, best_dist)| plane_dist < best_dist) {
+            best = self.nearest_rec(far, query, depth + 1, best);
+        }
+
+        best
+    }
+}
+
+fn axis_value<T: Copy>(p: &Point<T>, axis: usize) -> T {
+    if axis == 0 {
+        p.x
+    } else {
+        p.y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_matches_brute_force_linear_scan() {
+        let points = vec![
+            Point::new(0, 0),
+            Point::new(10, 0),
+            Point::new(0, 10),
+            Point::new(10, 10),
+            Point::new(5, 5),
+            Point::new(-3, 7),
+            Point::new(8, -2),
+        ];
+
+        let tree = KdTree::build(points.clone());
+
+        let queries = [
+            Point::new(1, 1),
+            Point::new(9, 9),
+            Point::new(-3, -3),
+            Point::new(4, 6),
+            Point::new(100, 100),
+        ];
+
+        for query in &queries {
+            let expected = points
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, p)| p.squared_dist::<i64>(query))
+                .unwrap()
+                .0;
+
+            assert_eq!(tree.nearest(query), expected);
+        }
+    }
+
+    #[test]
+    fn nearest_on_a_single_point_tree_always_returns_it() {
+        let tree = KdTree::build(vec![Point::new(42, 42)]);
+
+        assert_eq!(tree.nearest(&Point::new(0, 0)), 0);
+        assert_eq!(tree.nearest(&Point::new(1000, -1000)), 0);
+    }
+}
@@ -0,0 +1,259 @@
+//! A simple [K-Means](https://en.wikipedia.org/wiki/K-means_clustering)
+//! implementation.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::iter;
+
+use num::ToPrimitive;
+use rand::rngs::ThreadRng;
+use rand::Rng;
+
+use crate::kdtree::KdTree;
+use crate::point::Point;
+
+/// How to pick the initial `k` pivots before the iterative refinement loop
+/// starts. The `R` type parameter only matters for `PlusPlus` and defaults to
+/// `ThreadRng` so `KMeansInit::Deterministic` doesn't force callers to
+/// annotate an RNG type they don't use.
+pub enum KMeansInit<'a, R: Rng = ThreadRng> {
+    /// Pick pivots deterministically, evenly spaced across the deduped
+    /// input points. Cheap and reproducible, but frequently collapses to
+    /// fewer than `k` clusters and converges to poor local optima.
+    Deterministic,
+
+    /// Seed the pivots with [k-means++](https://en.wikipedia.org/wiki/K-means%2B%2B):
+    /// pick the first pivot uniformly at random, then repeatedly pick the
+    /// next one with probability proportional to its squared distance to
+    /// the nearest already-chosen pivot. This spreads the initial pivots
+    /// out and dramatically reduces empty clusters.
+    PlusPlus(&'a mut R),
+}
+
+/// Cluster the given set of points in at most k clusters. If k is greater or
+/// equal than the set of unique points then all the input points are returned.
+/// Note that K-Means doesn't return the optimal solution and in fact it's
+/// totally possible that the clusters contain less than k clusters. To avoid
+/// that try to increase the number of max_iterations and/or pick `init` as
+/// `KMeansInit::PlusPlus`.
+pub fn kmeans<T, I, R: Rng>(
+    points: I,
+    k: usize,
+    max_iterations: usize,
+    init: KMeansInit<R>,
+) -> HashMap<Point<T>, Vec<Point<T>>>
+where
+    T: num::Num + num::ToPrimitive + Ord + Copy + Hash + From<u8> + Debug,
+    I: IntoIterator<Item = Point<T>>,
+{
+    if k == 0 {
+        return HashMap::new();
+    }
+
+    // first dedup points in an hashset and then store them in a vec.
+    let points = points
+        .into_iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    if points.len() <= k {
+        return points.into_iter().map(|p| (p, vec![p])).collect();
+    }
+
+    let mut clusters = iter::repeat(vec![]).take(k).collect::<Vec<_>>();
+
+    let mut pivots = match init {
+        KMeansInit::Deterministic => (0..k)
+            .map(|i| points[i * points.len() / k])
+            .collect::<Vec<_>>(),
+        KMeansInit::PlusPlus(rng) => plus_plus_pivots(rng, &points, k),
+    };
+
+    for _ in 0..max_iterations {
+        for cluster in &mut clusters {
+            cluster.clear();
+        }
+
+        // rebuilding a `KdTree` from scratch every iteration is still far
+        // cheaper than the O(n*k) linear scan it replaces once k grows past
+        // a handful of pivots: each of the n points now costs roughly
+        // O(log k) instead of O(k).
+        let pivot_tree = KdTree::build(pivots.clone());
+
+        for point in &points {
+            let closest_i = pivot_tree.nearest(point);
+            clusters[closest_i].push(*point);
+        }
+
+        let pivot_changed = update_pivots(&mut pivots, &clusters, &points, k);
+        if !pivot_changed {
+            break;
+        }
+    }
+
+    pivots
+        .into_iter()
+        .zip(clusters.into_iter())
+        .filter(|(_, c)| !c.is_empty())
+        .collect()
+}
+
+// k-means++ seeding: pick the first pivot uniformly at random, then
+// repeatedly sample the next one with probability proportional to its
+// squared distance to the nearest already-chosen pivot, by building a
+// cumulative weight array (as `f64`, since we only need it for sampling, not
+// for `T`'s own arithmetic) and drawing a uniform value over its total.
+fn plus_plus_pivots<T, R: Rng>(rng: &mut R, points: &[Point<T>], k: usize) -> Vec<Point<T>>
+where
+    T: num::Num + num::ToPrimitive + Ord + Copy + From<u8> + Debug,
+{
+    let mut pivots = vec![points[rng.gen_range(0, points.len())]];
+
+    while pivots.len() < k {
+        let mut cumulative = Vec::with_capacity(points.len());
+        let mut total = 0.0_f64;
+
+        for point in points {
+            let nearest_sq_dist = pivots
+                .iter()
+                .map(|pivot| pivot.squared_dist::<T>(point))
+                .min()
+                .unwrap();
+
+            total += nearest_sq_dist.to_f64().unwrap_or(0.0);
+            cumulative.push(total);
+        }
+
+        if total <= 0.0 {
+            // every remaining point coincides with an already-chosen pivot;
+            // nothing left to prefer, so just pick arbitrarily.
+            pivots.push(points[rng.gen_range(0, points.len())]);
+            continue;
+        }
+
+        let target = rng.gen_range(0.0, total);
+        let next_i = cumulative
+            .iter()
+            .position(|&cum| cum > target)
+            .unwrap_or(points.len() - 1);
+
+        pivots.push(points[next_i]);
+    }
+
+    pivots
+}
+
+fn update_pivots<T>(
+    pivots: &mut [Point<T>],
+    clusters: &[Vec<Point<T>>],
+    points: &[Point<T>],
+    k: usize,
+) -> bool
+where
+    T: num::Num + Copy + From<u8> + Debug,
+{
+    let mut pivot_changed = false;
+
+    for (i, pivot) in pivots.iter_mut().enumerate() {
+        let new_pivot = if clusters[i].is_empty() {
+            // if the cluster for this pivot is empty pickup a point that's
+            // different from the current pivot and hope for the best.
+            let new_pivot_ix = i * points.len() / k;
+            let mut p = points[new_pivot_ix];
+
+            if p == *pivot {
+                // since the points were deduped, if p is the pivot the next
+                // point is definitely not.
+                p = points[(new_pivot_ix + 1) % points.len()];
+                debug_assert_ne!(p, *pivot);
+            }
+
+            p
+        } else {
+            avg_point(&clusters[i])
+        };
+
+        if new_pivot != *pivot {
+            pivot_changed = true;
+        }
+
+        *pivot = new_pivot;
+    }
+
+    pivot_changed
+}
+
+fn avg_point<T>(cluster: &[Point<T>]) -> Point<T>
+where
+    T: num::Num + Copy + From<u8>,
+{
+    let (sum_x, sum_y, len) = cluster.iter().fold(
+        (T::from(0_u8), T::from(0_u8), T::from(0_u8)),
+        |(sum_x, sum_y, len), pt| (sum_x + pt.x, sum_y + pt.y, len + T::from(1)),
+    );
+
+    Point::new(sum_x / len, sum_y / len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+
+    type PointK = Point<i32>;
+
+    #[test]
+    fn deterministic_kmeans_clusters_contains_closest_point() {
+        let points = vec![
+            PointK::new(0, 0),
+            PointK::new(1, 0),
+            PointK::new(0, 1),
+            PointK::new(100, 100),
+            PointK::new(101, 100),
+            PointK::new(100, 101),
+        ];
+
+        let clusters = kmeans(
+            points.clone(),
+            2,
+            usize::max_value(),
+            KMeansInit::Deterministic,
+        );
+        assert!(clusters.len() <= 2);
+
+        for (pivot, cluster) in &clusters {
+            for point in cluster {
+                let closest_pivot = clusters.keys().min_by_key(|p| p.squared_dist::<i64>(point));
+                assert_eq!(
+                    point.squared_dist::<i64>(closest_pivot.unwrap()),
+                    point.squared_dist::<i64>(pivot)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn plus_plus_kmeans_rarely_collapses_well_separated_clusters() {
+        let points = vec![
+            PointK::new(0, 0),
+            PointK::new(1, 0),
+            PointK::new(0, 1),
+            PointK::new(100, 100),
+            PointK::new(101, 100),
+            PointK::new(100, 101),
+        ];
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let clusters = kmeans(
+            points,
+            2,
+            usize::max_value(),
+            KMeansInit::PlusPlus(&mut rng),
+        );
+
+        assert_eq!(clusters.len(), 2);
+    }
+}
@@ -1,6 +1,8 @@
 //! Simple module to work with lines.
 
-use crate::point::Point;
+use crate::bbox::BoundingBox;
+use crate::orientation::PointOrientation;
+use crate::point::{Point, PointI32};
 
 /// Linearly interpolate the point at the given x using the line that goes
 /// between the two points.
@@ -13,6 +15,303 @@ where
     line.y_at(x)
 }
 
+/// Find the intersection point of segment `a.0`→`a.1` with segment
+/// `b.0`→`b.1`, using the sign of the cross product of their direction
+/// vectors as a parametric test (i.e. solve `a.0 + t*r = b.0 + u*s` for `t`
+/// and `u` and check both lie in `[0, 1]`). Returns `None` when the segments
+/// are parallel (the denominator `r×s` is zero) or simply don't cross within
+/// their bounds.
+pub fn line_segment_intersection(
+    a: (Point<f64>, Point<f64>),
+    b: (Point<f64>, Point<f64>),
+) -> Option<Point<f64>> {
+    let r = Point::new(a.1.x - a.0.x, a.1.y - a.0.y);
+    let s = Point::new(b.1.x - b.0.x, b.1.y - b.0.y);
+
+    let denom = r.x * s.y - r.y * s.x;
+    if denom == 0.0 {
+        return None;
+    }
+
+    let qp = Point::new(b.0.x - a.0.x, b.0.y - a.0.y);
+    let t = (qp.x * s.y - qp.y * s.x) / denom;
+    let u = (qp.x * r.y - qp.y * r.x) / denom;
+
+    if t >= 0.0 && t <= 1.0 && u >= 0.0 && u <= 1.0 {
+        Some(Point::new(a.0.x + t * r.x, a.0.y + t * r.y))
+    } else {
+        None
+    }
+}
+
+/// Find every pairwise crossing among `segments`, using a
+/// [Bentley–Ottmann](https://en.wikipedia.org/wiki/Bentley%E2%80%93Ottmann_algorithm)
+/// sweep instead of the `O(n^2)` double loop over `line_segment_intersection`.
+/// A priority queue of events (segment endpoints plus discovered crossings)
+/// drives a sweep from left to right, and a status list tracks which segments
+/// currently cross the sweep line, ordered by their `y` there. The status
+/// list is a plain `Vec`, so each event costs `O(log n)` for the queue plus
+/// up to `O(n)` to update the status list. Each
+/// intersecting pair of indices into `segments` is reported exactly once
+/// (as `(min, max)`), alongside the point where they cross.
+pub fn all_intersections(
+    segments: &[(Point<f64>, Point<f64>)],
+) -> Vec<(usize, usize, Point<f64>)> {
+    use std::cmp::Ordering;
+    use std::collections::{BinaryHeap, HashSet};
+
+    #[derive(Clone, Copy)]
+    struct Seg {
+        left: Point<f64>,
+        right: Point<f64>,
+        line: LineEquation<f64>,
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum EventKind {
+        Left(usize),
+        Right(usize),
+        Intersection(usize, usize),
+    }
+
+    #[derive(Clone, Copy)]
+    struct Event {
+        point: Point<f64>,
+        kind: EventKind,
+    }
+
+    impl PartialEq for Event {
+        fn eq(&self, other: &Self) -> bool {
+            self.point == other.point
+        }
+    }
+
+    impl Eq for Event {}
+
+    impl PartialOrd for Event {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Event {
+        fn cmp(&self, other: &Self) -> Ordering {
+            // reversed, so that `BinaryHeap` (a max-heap) pops the
+            // lexicographically smallest `(x, y)` event first.
+            other
+                .point
+                .x
+                .partial_cmp(&self.point.x)
+                .unwrap()
+                .then_with(|| other.point.y.partial_cmp(&self.point.y).unwrap())
+        }
+    }
+
+    // the `y` a segment's infinite line has at `x`, falling back to its
+    // lower endpoint for the vertical segments `LineEquation` can't resolve.
+    fn y_at(seg: &Seg, x: f64) -> f64 {
+        seg.line.y_at(x).unwrap_or_else(|| seg.left.y.min(seg.right.y))
+    }
+
+    fn queue_intersection(
+        segs: &[Seg],
+        i: usize,
+        j: usize,
+        sweep_x: f64,
+        events: &mut BinaryHeap<Event>,
+    ) {
+        let on_segment = |seg: &Seg, p: Point<f64>| {
+            p.x >= seg.left.x.min(seg.right.x)
+                && p.x <= seg.left.x.max(seg.right.x)
+                && p.y >= seg.left.y.min(seg.right.y)
+                && p.y <= seg.left.y.max(seg.right.y)
+        };
+
+        if let Some(p) = segs[i].line.intersection(&segs[j].line) {
+            // crossings behind the sweep have already been handled (or
+            // would have been, had the segments been adjacent earlier).
+            if p.x >= sweep_x && on_segment(&segs[i], p) && on_segment(&segs[j], p) {
+                events.push(Event {
+                    point: p,
+                    kind: EventKind::Intersection(i, j),
+                });
+            }
+        }
+    }
+
+    let segs = segments
+        .iter()
+        .map(|&(a, b)| {
+            let (left, right) = if (a.x, a.y) <= (b.x, b.y) { (a, b) } else { (b, a) };
+
+            Seg {
+                left,
+                right,
+                line: LineEquation::between(&left, &right),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut events = BinaryHeap::new();
+    for (i, seg) in segs.iter().enumerate() {
+        events.push(Event {
+            point: seg.left,
+            kind: EventKind::Left(i),
+        });
+        events.push(Event {
+            point: seg.right,
+            kind: EventKind::Right(i),
+        });
+    }
+
+    // segments currently crossing the sweep line, ordered by their `y` there.
+    let mut status: Vec<usize> = vec![];
+    let mut seen = HashSet::new();
+    let mut results = vec![];
+
+    while let Some(event) = events.pop() {
+        let x = event.point.x;
+
+        match event.kind {
+            EventKind::Left(i) => {
+                let pos = status
+                    .binary_search_by(|&j| {
+                        y_at(&segs[j], x).partial_cmp(&y_at(&segs[i], x)).unwrap()
+                    })
+                    .unwrap_or_else(|p| p);
+                status.insert(pos, i);
+
+                if pos > 0 {
+                    queue_intersection(&segs, status[pos - 1], i, x, &mut events);
+                }
+                if pos + 1 < status.len() {
+                    queue_intersection(&segs, i, status[pos + 1], x, &mut events);
+                }
+            }
+            EventKind::Right(i) => {
+                if let Some(pos) = status.iter().position(|&j| j == i) {
+                    status.remove(pos);
+
+                    if pos > 0 && pos < status.len() {
+                        queue_intersection(&segs, status[pos - 1], status[pos], x, &mut events);
+                    }
+                }
+            }
+            EventKind::Intersection(i, j) => {
+                let key = (i.min(j), i.max(j));
+                if !seen.insert(key) {
+                    continue;
+                }
+                results.push((key.0, key.1, event.point));
+
+                if let (Some(pi), Some(pj)) = (
+                    status.iter().position(|&s| s == i),
+                    status.iter().position(|&s| s == j),
+                ) {
+                    status.swap(pi, pj);
+
+                    let (lo, hi) = (pi.min(pj), pi.max(pj));
+                    if lo > 0 {
+                        queue_intersection(&segs, status[lo - 1], status[lo], x, &mut events);
+                    }
+                    if hi + 1 < status.len() {
+                        queue_intersection(&segs, status[hi], status[hi + 1], x, &mut events);
+                    }
+                }
+            }
+        }
+    }
+
+    results
+}
+
+/// Whether the polyline `pts` crosses itself anywhere other than at
+/// consecutive segments' shared endpoint, via `all_intersections` over its
+/// segments.
+pub fn is_self_intersecting(pts: &[Point<f64>]) -> bool {
+    if pts.len() < 4 {
+        return false;
+    }
+
+    let segments = pts.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>();
+
+    all_intersections(&segments)
+        .into_iter()
+        .any(|(i, j, _)| j > i + 1)
+}
+
+/// Enumerate every grid cell the segment `from` -> `to` passes through,
+/// including the extra diagonal-adjacent cells that plain Bresenham skips,
+/// so there's never a single-pixel gap between them. Implemented as an
+/// integer DDA: at each step compare `(1 + 2*ix) * ny` against
+/// `(1 + 2*iy) * nx` (the `x`/`y` progress scaled to a common denominator)
+/// to decide whether to step `x`, `y`, or both (the diagonal corner case).
+pub fn supercover_line(from: PointI32, to: PointI32) -> impl Iterator<Item = PointI32> {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+
+    SupercoverLine {
+        x: from.x,
+        y: from.y,
+        nx: dx.abs(),
+        ny: dy.abs(),
+        ix: 0,
+        iy: 0,
+        sx: if dx >= 0 { 1 } else { -1 },
+        sy: if dy >= 0 { 1 } else { -1 },
+        done: false,
+    }
+}
+
+/// Iterator backing `supercover_line`.
+#[derive(Debug)]
+struct SupercoverLine {
+    x: i32,
+    y: i32,
+    nx: i32,
+    ny: i32,
+    ix: i32,
+    iy: i32,
+    sx: i32,
+    sy: i32,
+    done: bool,
+}
+
+impl Iterator for SupercoverLine {
+    type Item = PointI32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let p = PointI32::new(self.x, self.y);
+
+        if self.ix >= self.nx && self.iy >= self.ny {
+            self.done = true;
+            return Some(p);
+        }
+
+        let lhs = (1 + 2 * self.ix) * self.ny;
+        let rhs = (1 + 2 * self.iy) * self.nx;
+
+        if lhs < rhs {
+            self.x += self.sx;
+            self.ix += 1;
+        } else if lhs > rhs {
+            self.y += self.sy;
+            self.iy += 1;
+        } else {
+            self.x += self.sx;
+            self.y += self.sy;
+            self.ix += 1;
+            self.iy += 1;
+        }
+
+        Some(p)
+    }
+}
+
 /// Abstract representation of a line equation.
 #[derive(Clone, Debug, PartialEq)]
 pub enum LineEquation<T> {
@@ -105,7 +404,18 @@ where
     /// Calculate the intersection point between two lines. Returns `None` if
     /// the lines are parallel. **Note**: this method returns `None` even when
     /// `self` and `other` are the same `VerticalLine`.
-    pub fn intersection(&self, other: &Self) -> Option<Point<T>> {
+    ///
+    /// `y` is evaluated through whichever of the two lines has the shallower
+    /// slope rather than always through `self`, so `a.intersection(&b)` and
+    /// `b.intersection(&a)` round to the exact same point even when `T` is a
+    /// lossy type like `f64`. Callers that key on the crossing point between
+    /// 2 independently-computed lines (e.g. `boolean_ops::stitch`) rely on
+    /// that, since they'd otherwise fail to recognize the same point reached
+    /// from 2 different edges.
+    pub fn intersection(&self, other: &Self) -> Option<Point<T>>
+    where
+        T: PartialOrd,
+    {
         // FIXME: might want to return an IntersectionResult enum composed by:
         // - NoIntersection
         // - SameVerticalLine(x)
@@ -130,7 +440,11 @@ where
             ) => {
                 if slope1 != slope2 {
                     let x = (*c2 - *c1) / (*slope1 - *slope2);
-                    let y = self.y_at(x).unwrap();
+                    let y = if abs_diff(*slope1, T::from(0)) <= abs_diff(*slope2, T::from(0)) {
+                        self.y_at(x).unwrap()
+                    } else {
+                        other.y_at(x).unwrap()
+                    };
 
                     Some(Point::new(x, y))
                 } else {
@@ -166,9 +480,263 @@ where
     }
 }
 
+/// A finite segment between two points, as opposed to the infinite line
+/// `LineEquation` models.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineSegment<T> {
+    /// One endpoint of the segment.
+    pub start: Point<T>,
+
+    /// The other endpoint of the segment.
+    pub end: Point<T>,
+}
+
+/// The result of intersecting two `LineSegment`s.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IntersectionResult<T> {
+    /// The segments don't cross within their endpoints, including the case
+    /// where the underlying lines are parallel but not coincident.
+    None,
+
+    /// The segments cross at exactly one point.
+    Point(Point<T>),
+
+    /// The segments are collinear and overlap along a sub-segment.
+    Overlap(LineSegment<T>),
+}
+
+impl<T> LineSegment<T> {
+    /// Build a new segment between `start` and `end`.
+    pub fn new(start: Point<T>, end: Point<T>) -> Self {
+        LineSegment { start, end }
+    }
+}
+
+impl<T> LineSegment<T>
+where
+    T: num::Num + From<u8> + Copy + PartialOrd,
+{
+    /// The infinite-line view of this segment.
+    pub fn to_line_equation(&self) -> LineEquation<T> {
+        LineEquation::between(&self.start, &self.end)
+    }
+
+    /// Like `segment_intersection`, but collapses the result to a single
+    /// point: `None` for parallel segments and for collinear segments that
+    /// overlap along more than one point (use `segment_intersection` if
+    /// that sub-segment is what's needed).
+    pub fn intersection(&self, other: &Self) -> Option<Point<T>> {
+        match self.segment_intersection(other) {
+            IntersectionResult::Point(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// Clip this segment against `bbox`, returning the visible sub-segment,
+    /// or `None` if it falls entirely outside. Implemented as
+    /// [Liang-Barsky](https://en.wikipedia.org/wiki/Liang%E2%80%93Barsky_algorithm):
+    /// parameterize the segment as `p(t) = start + t*(end-start)` for `t ∈
+    /// [0, 1]`, then narrow `[tmin, tmax]` against each of the box's 4 edges
+    /// in turn, rejecting outright if an edge is parallel to the segment and
+    /// entirely on the wrong side of it.
+    pub fn clip(&self, bbox: &BoundingBox<T>) -> Option<Self> {
+        let dx = self.end.x - self.start.x;
+        let dy = self.end.y - self.start.y;
+
+        let p = [T::from(0) - dx, dx, T::from(0) - dy, dy];
+        let q = [
+            self.start.x - bbox.min().x,
+            bbox.max().x - self.start.x,
+            self.start.y - bbox.min().y,
+            bbox.max().y - self.start.y,
+        ];
+
+        let mut tmin = T::from(0);
+        let mut tmax = T::from(1);
+
+        for i in 0..4 {
+            if p[i] == T::from(0) {
+                if q[i] < T::from(0) {
+                    return None;
+                }
+            } else {
+                let r = q[i] / p[i];
+
+                if p[i] < T::from(0) {
+                    if r > tmin {
+                        tmin = r;
+                    }
+                } else if r < tmax {
+                    tmax = r;
+                }
+            }
+        }
+
+        if tmin > tmax {
+            return None;
+        }
+
+        Some(LineSegment::new(
+            Point::new(self.start.x + tmin * dx, self.start.y + tmin * dy),
+            Point::new(self.start.x + tmax * dx, self.start.y + tmax * dy),
+        ))
+    }
+
+    /// Intersect this segment with `other`. Builds the `LineEquation` of
+    /// each segment and, if they're the same line, intersects their 1D
+    /// parameter ranges to find the overlapping sub-segment, if any.
+    /// Otherwise computes the infinite-line intersection and checks that the
+    /// resulting point actually lies within both segments' bounds.
+    pub fn segment_intersection(&self, other: &Self) -> IntersectionResult<T> {
+        let line1 = LineEquation::between(&self.start, &self.end);
+        let line2 = LineEquation::between(&other.start, &other.end);
+
+        if line1 == line2 {
+            return self.overlap(other);
+        }
+
+        match line1.intersection(&line2) {
+            Some(p) if self.contains(&p) && other.contains(&p) => IntersectionResult::Point(p),
+            _ => IntersectionResult::None,
+        }
+    }
+
+    /// Whether `p` lies within this segment's bounding box, i.e. between its
+    /// endpoints on both axes. Assumes `p` already lies on the line through
+    /// `self.start` and `self.end`.
+    fn contains(&self, p: &Point<T>) -> bool {
+        let (min_x, max_x) = min_max(self.start.x, self.end.x);
+        let (min_y, max_y) = min_max(self.start.y, self.end.y);
+
+        p.x >= min_x && p.x <= max_x && p.y >= min_y && p.y <= max_y
+    }
+
+    /// Assuming `self` and `other` are collinear, project every endpoint
+    /// onto whichever axis varies the most along `self` and intersect the
+    /// two resulting 1D intervals to find the overlapping sub-segment.
+    fn overlap(&self, other: &Self) -> IntersectionResult<T> {
+        let use_x = abs_diff(self.start.x, self.end.x) >= abs_diff(self.start.y, self.end.y);
+        let line = LineEquation::between(&self.start, &self.end);
+
+        let to_point = |t: T| -> Point<T> {
+            if use_x {
+                Point::new(t, line.y_at(t).unwrap_or(self.start.y))
+            } else {
+                Point::new(line.x_at(t).unwrap_or(self.start.x), t)
+            }
+        };
+        let param = |p: &Point<T>| if use_x { p.x } else { p.y };
+
+        let (self_lo, self_hi) = min_max(param(&self.start), param(&self.end));
+        let (other_lo, other_hi) = min_max(param(&other.start), param(&other.end));
+
+        let lo = if self_lo > other_lo {
+            self_lo
+        } else {
+            other_lo
+        };
+        let hi = if self_hi < other_hi {
+            self_hi
+        } else {
+            other_hi
+        };
+
+        if lo > hi {
+            IntersectionResult::None
+        } else if lo == hi {
+            IntersectionResult::Point(to_point(lo))
+        } else {
+            IntersectionResult::Overlap(LineSegment::new(to_point(lo), to_point(hi)))
+        }
+    }
+}
+
+impl<T> LineSegment<T>
+where
+    T: num::Signed + Copy,
+{
+    /// Classify which side of this segment's directed line (`start` ->
+    /// `end`) the point `p` falls on, via the sign of the cross product of
+    /// the segment's direction with `p - start`. See `PointOrientation` for
+    /// what each side means; `OnTheLine` doesn't imply `p` lies between
+    /// `start` and `end`, only that it's collinear with the infinite line.
+    pub fn classify(&self, p: &Point<T>) -> PointOrientation {
+        let d = Point::new(self.end.x - self.start.x, self.end.y - self.start.y);
+        let v = Point::new(p.x - self.start.x, p.y - self.start.y);
+
+        let det = d.x * v.y - d.y * v.x;
+
+        if det.is_positive() {
+            PointOrientation::Left
+        } else if det.is_negative() {
+            PointOrientation::Right
+        } else {
+            PointOrientation::OnTheLine
+        }
+    }
+}
+
+impl<T> LineSegment<T>
+where
+    T: num::Signed + From<u8> + Copy + PartialOrd,
+{
+    /// Whether `p` lies on this segment, i.e. it's both collinear with
+    /// `start`/`end` and falls within their bounding box.
+    pub fn contains_point(&self, p: &Point<T>) -> bool {
+        self.classify(p) == PointOrientation::OnTheLine && self.contains(p)
+    }
+
+    /// The length of this segment.
+    pub fn length<O>(&self) -> O
+    where
+        O: num::Float + From<T>,
+    {
+        self.start.dist(&self.end)
+    }
+}
+
+impl<T> LineSegment<T>
+where
+    T: num::Float + From<u8>,
+{
+    /// The point on this segment closest to `p`: project `p` onto the
+    /// segment's direction and clamp the resulting parameter to `[0, 1]` so
+    /// the result never falls outside `start`/`end`.
+    pub fn closest_point_on(&self, p: &Point<T>) -> Point<T> {
+        let d = Point::new(self.end.x - self.start.x, self.end.y - self.start.y);
+        let v = Point::new(p.x - self.start.x, p.y - self.start.y);
+
+        let len_sq = d.dot(&d);
+        if len_sq == T::from(0) {
+            return self.start;
+        }
+
+        let t = (v.dot(&d) / len_sq).max(T::from(0)).min(T::from(1));
+
+        Point::new(self.start.x + d.x * t, self.start.y + d.y * t)
+    }
+}
+
+/// Return `(a, b)` reordered so the first element is `<=` the second.
+fn min_max<T: PartialOrd>(a: T, b: T) -> (T, T) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// The non-negative difference between `a` and `b`, without requiring `T` to
+/// support negation.
+fn abs_diff<T: num::Num + PartialOrd + Copy>(a: T, b: T) -> T {
+    let (lo, hi) = min_max(a, b);
+    hi - lo
+}
+
 #[cfg(test)]
 mod test {
-    use super::LineEquation;
+    use super::{supercover_line, IntersectionResult, LineEquation, LineSegment};
+    use crate::orientation::PointOrientation;
     use geo::PointI32;
 
     #[test]
@@ -294,4 +862,308 @@ mod test {
         assert_eq!(line1.perpendicular(&p), line2);
         assert_eq!(line2.perpendicular(&p), line1);
     }
+
+    #[test]
+    fn test_segment_intersection_point() {
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(4, 4));
+        let b = LineSegment::new(PointI32::new(0, 4), PointI32::new(4, 0));
+
+        assert_eq!(
+            a.segment_intersection(&b),
+            IntersectionResult::Point(PointI32::new(2, 2))
+        );
+    }
+
+    #[test]
+    fn test_segment_intersection_none_out_of_bounds() {
+        // the underlying lines cross, but not within either segment's bounds.
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(1, 1));
+        let b = LineSegment::new(PointI32::new(0, 4), PointI32::new(1, 3));
+
+        assert_eq!(a.segment_intersection(&b), IntersectionResult::None);
+    }
+
+    #[test]
+    fn test_segment_intersection_parallel() {
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(4, 0));
+        let b = LineSegment::new(PointI32::new(0, 1), PointI32::new(4, 1));
+
+        assert_eq!(a.segment_intersection(&b), IntersectionResult::None);
+    }
+
+    #[test]
+    fn test_segment_intersection_overlap() {
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(10, 0));
+        let b = LineSegment::new(PointI32::new(5, 0), PointI32::new(15, 0));
+
+        assert_eq!(
+            a.segment_intersection(&b),
+            IntersectionResult::Overlap(LineSegment::new(
+                PointI32::new(5, 0),
+                PointI32::new(10, 0)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_segment_intersection_collinear_touching_at_a_point() {
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(5, 5));
+        let b = LineSegment::new(PointI32::new(5, 5), PointI32::new(10, 10));
+
+        assert_eq!(
+            a.segment_intersection(&b),
+            IntersectionResult::Point(PointI32::new(5, 5))
+        );
+    }
+
+    #[test]
+    fn test_segment_intersection_collinear_disjoint() {
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(1, 1));
+        let b = LineSegment::new(PointI32::new(5, 5), PointI32::new(6, 6));
+
+        assert_eq!(a.segment_intersection(&b), IntersectionResult::None);
+    }
+
+    #[test]
+    fn test_segment_intersection_vertical_segments() {
+        let a = LineSegment::new(PointI32::new(2, 0), PointI32::new(2, 10));
+        let b = LineSegment::new(PointI32::new(2, 5), PointI32::new(2, 15));
+
+        assert_eq!(
+            a.segment_intersection(&b),
+            IntersectionResult::Overlap(LineSegment::new(
+                PointI32::new(2, 5),
+                PointI32::new(2, 10)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        let segment = LineSegment::new(PointI32::new(0, 0), PointI32::new(4, 4));
+
+        assert_eq!(
+            segment.classify(&PointI32::new(0, 4)),
+            PointOrientation::Left
+        );
+        assert_eq!(
+            segment.classify(&PointI32::new(4, 0)),
+            PointOrientation::Right
+        );
+        assert_eq!(
+            segment.classify(&PointI32::new(2, 2)),
+            PointOrientation::OnTheLine
+        );
+        assert_eq!(
+            segment.classify(&PointI32::new(10, 10)),
+            PointOrientation::OnTheLine
+        );
+    }
+
+    #[test]
+    fn test_supercover_line_single_point() {
+        let p = PointI32::new(3, 3);
+
+        assert_eq!(supercover_line(p, p).collect::<Vec<_>>(), vec![p]);
+    }
+
+    #[test]
+    fn test_supercover_line_horizontal() {
+        let points = supercover_line(PointI32::new(0, 0), PointI32::new(3, 0)).collect::<Vec<_>>();
+
+        assert_eq!(
+            points,
+            vec![
+                PointI32::new(0, 0),
+                PointI32::new(1, 0),
+                PointI32::new(2, 0),
+                PointI32::new(3, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_supercover_line_diagonal_visits_every_touched_cell() {
+        // a shallow diagonal where plain Bresenham would skip the corner
+        // cell between two consecutive steps.
+        let points = supercover_line(PointI32::new(0, 0), PointI32::new(2, 1)).collect::<Vec<_>>();
+
+        assert_eq!(
+            points,
+            vec![
+                PointI32::new(0, 0),
+                PointI32::new(1, 0),
+                PointI32::new(1, 1),
+                PointI32::new(2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_supercover_line_is_symmetric() {
+        let from = PointI32::new(-2, 5);
+        let to = PointI32::new(3, -1);
+
+        let mut forward = supercover_line(from, to).collect::<Vec<_>>();
+        let mut backward = supercover_line(to, from).collect::<Vec<_>>();
+        backward.reverse();
+
+        forward.sort_by_key(|p| (p.x, p.y));
+        backward.sort_by_key(|p| (p.x, p.y));
+
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let segment = LineSegment::new(PointI32::new(0, 0), PointI32::new(4, 4));
+
+        assert!(segment.contains_point(&PointI32::new(2, 2)));
+        assert!(!segment.contains_point(&PointI32::new(6, 6)));
+        assert!(!segment.contains_point(&PointI32::new(2, 3)));
+    }
+
+    #[test]
+    fn test_length() {
+        let segment = LineSegment::new(PointI32::new(0, 0), PointI32::new(3, 4));
+
+        assert_eq!(segment.length::<f64>(), 5.0);
+    }
+
+    #[test]
+    fn test_closest_point_on() {
+        use geo::PointF64;
+
+        let segment = LineSegment::new(PointF64::new(0.0, 0.0), PointF64::new(10.0, 0.0));
+
+        assert_eq!(
+            segment.closest_point_on(&PointF64::new(5.0, 3.0)),
+            PointF64::new(5.0, 0.0)
+        );
+        assert_eq!(
+            segment.closest_point_on(&PointF64::new(-5.0, 0.0)),
+            PointF64::new(0.0, 0.0)
+        );
+        assert_eq!(
+            segment.closest_point_on(&PointF64::new(15.0, 0.0)),
+            PointF64::new(10.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_to_line_equation() {
+        let segment = LineSegment::new(PointI32::new(0, 0), PointI32::new(4, 4));
+
+        assert_eq!(segment.to_line_equation(), LineEquation::line(1, 0));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = LineSegment::new(PointI32::new(0, 0), PointI32::new(4, 4));
+        let b = LineSegment::new(PointI32::new(0, 4), PointI32::new(4, 0));
+
+        assert_eq!(a.intersection(&b), Some(PointI32::new(2, 2)));
+
+        let c = LineSegment::new(PointI32::new(10, 10), PointI32::new(20, 20));
+        assert_eq!(a.intersection(&c), None);
+
+        // collinear overlap: more than one intersection point, so `None`.
+        let d = LineSegment::new(PointI32::new(2, 2), PointI32::new(6, 6));
+        assert_eq!(a.intersection(&d), None);
+    }
+
+    #[test]
+    fn test_clip_segment_crossing_bbox() {
+        use crate::bbox::BoundingBox;
+        use geo::PointF64;
+
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let segment = LineSegment::new(PointF64::new(-5.0, 5.0), PointF64::new(15.0, 5.0));
+
+        assert_eq!(
+            segment.clip(&bbox),
+            Some(LineSegment::new(
+                PointF64::new(0.0, 5.0),
+                PointF64::new(10.0, 5.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_clip_segment_entirely_inside_bbox_is_unchanged() {
+        use crate::bbox::BoundingBox;
+        use geo::PointF64;
+
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let segment = LineSegment::new(PointF64::new(2.0, 2.0), PointF64::new(8.0, 8.0));
+
+        assert_eq!(segment.clip(&bbox), Some(segment));
+    }
+
+    #[test]
+    fn test_clip_segment_entirely_outside_bbox_is_none() {
+        use crate::bbox::BoundingBox;
+        use geo::PointF64;
+
+        let bbox = BoundingBox::from_dimensions(10.0, 10.0);
+        let segment = LineSegment::new(PointF64::new(20.0, 20.0), PointF64::new(30.0, 30.0));
+
+        assert_eq!(segment.clip(&bbox), None);
+    }
+
+    #[test]
+    fn test_all_intersections_finds_every_crossing_pair() {
+        use super::all_intersections;
+        use geo::PointF64;
+
+        // an X made of two crossing diagonals, plus a third segment off to
+        // the side that touches neither.
+        let segments = vec![
+            (PointF64::new(0.0, 0.0), PointF64::new(10.0, 10.0)),
+            (PointF64::new(0.0, 10.0), PointF64::new(10.0, 0.0)),
+            (PointF64::new(20.0, 20.0), PointF64::new(30.0, 30.0)),
+        ];
+
+        let intersections = all_intersections(&segments);
+
+        assert_eq!(intersections, vec![(0, 1, PointF64::new(5.0, 5.0))]);
+    }
+
+    #[test]
+    fn test_all_intersections_handles_vertical_segments() {
+        use super::all_intersections;
+        use geo::PointF64;
+
+        let segments = vec![
+            (PointF64::new(5.0, 0.0), PointF64::new(5.0, 10.0)),
+            (PointF64::new(0.0, 5.0), PointF64::new(10.0, 5.0)),
+        ];
+
+        assert_eq!(
+            all_intersections(&segments),
+            vec![(0, 1, PointF64::new(5.0, 5.0))]
+        );
+    }
+
+    #[test]
+    fn test_is_self_intersecting() {
+        use super::is_self_intersecting;
+        use geo::PointF64;
+
+        let simple = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+        ];
+        assert!(!is_self_intersecting(&simple));
+
+        // a figure-eight: the first and last segments cross in the middle.
+        let figure_eight = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(0.0, 10.0),
+        ];
+        assert!(is_self_intersecting(&figure_eight));
+    }
 }
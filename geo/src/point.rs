@@ -0,0 +1,472 @@
+//! Handy `Point` struct and utility functions.
+
+use std::error::Error;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::angle::Angle;
+
+/// Point specialized for `f64`.
+pub type PointF64 = Point<f64>;
+
+/// Point specialized for `i32`.
+pub type PointI32 = Point<i32>;
+
+/// Point specialized for `u32`.
+pub type PointU32 = Point<u32>;
+
+/// Simple 2d Point struct.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point<T> {
+    /// x coordinate
+    pub x: T,
+
+    /// y coordinate
+    pub y: T,
+}
+
+impl<T> Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    /// Create a new `Point` with the given `x` and `y` coordinates.
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+
+    /// Calculate the midpoint between this point and another.
+    pub fn midpoint(&self, p: &Self) -> Self {
+        Point::new((self.x + p.x) / T::from(2), (self.y + p.y) / T::from(2))
+    }
+
+    /// Calculate the slope between this point and another. Return `None` if
+    /// the slope is undefined, that is when `self` and `p` form a vertical
+    /// line.
+    pub fn slope<O>(&self, p: &Self) -> Option<O>
+    where
+        O: num::Signed + From<T>,
+    {
+        if self.x == p.x {
+            None
+        } else {
+            let dx = O::from(self.x) - O::from(p.x);
+            let dy = O::from(self.y) - O::from(p.y);
+
+            Some(dy / dx)
+        }
+    }
+
+    /// Calculate the y-intercept of the line that has the given `slope` and
+    /// that intersects with this point.
+    pub fn yintercept(&self, slope: T) -> T {
+        self.y - slope * self.x
+    }
+
+    /// Calculate the distance between this point and another.
+    pub fn dist<O>(&self, p: &Self) -> O
+    where
+        O: num::Float + From<T>,
+    {
+        self.squared_dist::<O>(p).sqrt()
+    }
+
+    /// Calculate the squared distance between this point and another.
+    pub fn squared_dist<O>(&self, p: &Self) -> O
+    where
+        O: num::Num + From<T> + Copy,
+    {
+        let dx = O::from(self.x) - O::from(p.x);
+        let dy = O::from(self.y) - O::from(p.y);
+
+        dx * dx + dy * dy
+    }
+
+    /// Return a copy of this point with a different coordinate type.
+    pub fn cast<O>(&self) -> Point<O>
+    where
+        O: num::Num + From<T> + From<u8> + Copy,
+    {
+        Point::new(O::from(self.x), O::from(self.y))
+    }
+
+    /// The dot product of this point (as a vector from the origin) and
+    /// another.
+    pub fn dot(&self, p: &Self) -> T {
+        self.x * p.x + self.y * p.y
+    }
+
+    /// The 2D cross product (aka the `z` component of the 3D cross product of
+    /// the two vectors padded with a 0 `z`), i.e. `x1*y2 - y1*x2`. Its sign
+    /// tells the orientation of `p` relative to this point, and its absolute
+    /// value is the area of the parallelogram spanned by the two vectors.
+    pub fn det(&self, p: &Self) -> T {
+        self.x * p.y - self.y * p.x
+    }
+}
+
+impl<T: crate::orientation::ExactOrientation> Point<T> {
+    /// Classify which side of the directed line `a -> b` this point falls
+    /// on. Thin convenience wrapper over `orientation::point_line_configuration`
+    /// for call sites that already have a `Point` in hand.
+    pub fn classify_against(
+        &self,
+        a: &Self,
+        b: &Self,
+    ) -> crate::orientation::PointOrientation {
+        crate::orientation::point_line_configuration((a, b), self)
+    }
+}
+
+impl<T> Point<T>
+where
+    T: num::Num + From<u8> + Copy + PartialOrd,
+{
+    /// The point made up of the lowest x and y coordinates among this point
+    /// and `other`.
+    pub fn lowest(&self, other: &Self) -> Self {
+        let x = if self.x <= other.x { self.x } else { other.x };
+        let y = if self.y <= other.y { self.y } else { other.y };
+
+        Point::new(x, y)
+    }
+
+    /// The point made up of the highest x and y coordinates among this point
+    /// and `other`.
+    pub fn highest(&self, other: &Self) -> Self {
+        let x = if self.x >= other.x { self.x } else { other.x };
+        let y = if self.y >= other.y { self.y } else { other.y };
+
+        Point::new(x, y)
+    }
+}
+
+impl<T> Add for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T> AddAssign for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T> Sub for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T> SubAssign for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T> Mul<T> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Point::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl<T> MulAssign<T> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    fn mul_assign(&mut self, scalar: T) {
+        *self = *self * scalar;
+    }
+}
+
+impl<T> Div<T> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn div(self, scalar: T) -> Self {
+        Point::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl<T> DivAssign<T> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    fn div_assign(&mut self, scalar: T) {
+        *self = *self / scalar;
+    }
+}
+
+impl<T> Neg for Point<T>
+where
+    T: num::Signed + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Point<f64> {
+    /// The euclidean length of this point, seen as a vector from the origin.
+    pub fn length(&self) -> f64 {
+        self.dist::<f64>(&Point::new(0.0, 0.0))
+    }
+
+    /// The squared euclidean length of this point, seen as a vector from the
+    /// origin. Cheaper than `length` when only relative comparisons matter.
+    pub fn squared_length(&self) -> f64 {
+        self.squared_dist::<f64>(&Point::new(0.0, 0.0))
+    }
+
+    /// Alias for `length`.
+    pub fn norm(&self) -> f64 {
+        self.length()
+    }
+
+    /// Return this vector scaled to unit length. Returns the zero vector if
+    /// this vector is the origin.
+    pub fn normalized(&self) -> Self {
+        let len = self.length();
+
+        if len == 0.0 {
+            return *self;
+        }
+
+        Point::new(self.x / len, self.y / len)
+    }
+
+    /// The angle, in radians, of this point seen as a vector from the origin
+    /// (`atan2(y, x)`).
+    pub fn to_angle(&self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// The polar angle from this point to `other` (`atan2(dy, dx)`). See
+    /// `angle::polar_angle`, which this is equivalent to.
+    pub fn angle_to(&self, other: &Self) -> Angle {
+        Angle::from_radians((other.y - self.y).atan2(other.x - self.x))
+    }
+
+    /// Rotate this point by `angle` radians (counter-clockwise, since `y`
+    /// grows downward this looks clockwise on screen) around `pivot`.
+    pub fn rotate(&self, angle: f64, pivot: &Self) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let d = *self - *pivot;
+
+        Point::new(d.x * cos - d.y * sin, d.x * sin + d.y * cos) + *pivot
+    }
+}
+
+impl<T: FromStr> FromStr for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+    T::Err: Error,
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coords: Result<Vec<T>, T::Err> = s.trim().split(',').map(|c| c.trim().parse()).collect();
+
+        match coords {
+            Err(e) => Err(format!("bad coord number format, {}", e)),
+            Ok(coords) => {
+                if coords.len() != 2 {
+                    Err(
+                        "wrong number of coords, please pass x and y coords separated by ','"
+                            .to_string(),
+                    )
+                } else {
+                    Ok(Point::new(coords[0], coords[1]))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PointF64, PointI32, PointU32};
+    use crate::angle::Angle;
+
+    #[test]
+    fn test_midpoint() {
+        assert_eq!(
+            PointU32::new(0, 0).midpoint(&PointU32::new(6, 6)),
+            PointU32::new(3, 3)
+        );
+
+        assert_eq!(
+            PointF64::new(-4.0, 6.0).midpoint(&PointF64::new(8.0, -8.0)),
+            PointF64::new(2.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn test_slope() {
+        assert_eq!(PointU32::new(1, 1).slope(&PointU32::new(3, 3)), Some(1_i64));
+        assert_eq!(
+            PointU32::new(0, 8).slope(&PointU32::new(8, 0)),
+            Some(-1_i64)
+        );
+
+        // vertical
+        assert_eq!(
+            PointU32::new(7, 0).slope::<i64>(&PointU32::new(7, 53)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_yintercept() {
+        assert_eq!(PointU32::new(0, 0).yintercept(1), 0);
+        assert_eq!(PointI32::new(2, 12).yintercept(-2), 16);
+    }
+
+    #[test]
+    fn test_lowest_highest() {
+        assert_eq!(
+            PointU32::new(0, 12).lowest(&PointU32::new(4, 10)),
+            PointU32::new(0, 10)
+        );
+        assert_eq!(
+            PointU32::new(0, 12).highest(&PointU32::new(4, 10)),
+            PointU32::new(4, 12)
+        );
+    }
+
+    #[test]
+    fn test_dist() {
+        let origin = PointI32::new(0, 0);
+
+        assert_eq!(origin.dist::<f64>(&PointI32::new(0, 4)), 4.0);
+        assert_eq!(origin.dist::<f64>(&PointI32::new(3, 0)), 3.0);
+        assert_eq!(PointI32::new(3, 5).dist::<f64>(&PointI32::new(6, 9)), 5.0);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            PointI32::new(1, 2) + PointI32::new(3, 4),
+            PointI32::new(4, 6)
+        );
+        assert_eq!(
+            PointI32::new(3, 4) - PointI32::new(1, 2),
+            PointI32::new(2, 2)
+        );
+        assert_eq!(PointI32::new(1, 2) * 3, PointI32::new(3, 6));
+        assert_eq!(PointI32::new(6, 4) / 2, PointI32::new(3, 2));
+        assert_eq!(-PointI32::new(1, -2), PointI32::new(-1, 2));
+
+        let mut p = PointI32::new(1, 2);
+        p += PointI32::new(1, 1);
+        assert_eq!(p, PointI32::new(2, 3));
+
+        p -= PointI32::new(1, 1);
+        assert_eq!(p, PointI32::new(1, 2));
+
+        p *= 4;
+        assert_eq!(p, PointI32::new(4, 8));
+
+        p /= 2;
+        assert_eq!(p, PointI32::new(2, 4));
+    }
+
+    #[test]
+    fn test_vector_helpers() {
+        assert_eq!(PointF64::new(3.0, 4.0).length(), 5.0);
+        assert_eq!(PointF64::new(3.0, 4.0).squared_length(), 25.0);
+        assert_eq!(PointF64::new(3.0, 4.0).norm(), 5.0);
+        assert_eq!(
+            PointF64::new(3.0, 4.0).normalized(),
+            PointF64::new(0.6, 0.8)
+        );
+        assert_eq!(
+            PointF64::new(0.0, 0.0).normalized(),
+            PointF64::new(0.0, 0.0)
+        );
+        assert_eq!(PointF64::new(1.0, 0.0).to_angle(), 0.0);
+    }
+
+    #[test]
+    fn test_rotate() {
+        use std::f64::consts::PI;
+
+        let pivot = PointF64::new(0.0, 0.0);
+        let rotated = PointF64::new(1.0, 0.0).rotate(PI / 2.0, &pivot);
+
+        assert!((rotated.x - 0.0).abs() < 1e-9);
+        assert!((rotated.y - 1.0).abs() < 1e-9);
+
+        let pivot = PointF64::new(1.0, 1.0);
+        let rotated = PointF64::new(2.0, 1.0).rotate(PI, &pivot);
+
+        assert!((rotated.x - 0.0).abs() < 1e-9);
+        assert!((rotated.y - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_angle_to() {
+        assert_eq!(
+            PointF64::new(0.0, 0.0).angle_to(&PointF64::new(1.0, 0.0)),
+            Angle::from_radians(0.0)
+        );
+
+        let angle = PointF64::new(1.0, 1.0).angle_to(&PointF64::new(1.0, 2.0));
+        assert!((angle.degrees() - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_classify_against() {
+        use crate::orientation::PointOrientation;
+
+        let a = PointI32::new(0, 0);
+        let b = PointI32::new(10, 0);
+
+        assert_eq!(
+            PointI32::new(5, 5).classify_against(&a, &b),
+            PointOrientation::Left
+        );
+        assert_eq!(
+            PointI32::new(5, -5).classify_against(&a, &b),
+            PointOrientation::Right
+        );
+        assert_eq!(
+            PointI32::new(5, 0).classify_against(&a, &b),
+            PointOrientation::OnTheLine
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("1,2".parse(), Ok(PointI32::new(1, 2)));
+        assert_eq!("1.5, -2.5".parse(), Ok(PointF64::new(1.5, -2.5)));
+
+        assert!("1,2,3".parse::<PointI32>().is_err());
+        assert!("bogus".parse::<PointI32>().is_err());
+    }
+}
@@ -3,10 +3,13 @@
 extern crate num;
 
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 
+use angle::{angle_orientation, AngleOrientation};
 use bbox::BoundingBox;
-use line::LineEquation;
+use line::{IntersectionResult, LineEquation, LineSegment};
 use point::Point;
+use triangle::Triangle;
 use utils::cmp_floats;
 
 /// A simple closed Polygon primitive.
@@ -122,15 +125,305 @@ where
     }
 }
 
+impl<T> Polygon<T>
+where
+    T: num::Num + num::Signed + num::Bounded + From<u8> + Copy + PartialOrd,
+{
+    /// The [signed area](https://en.wikipedia.org/wiki/Shoelace_formula) of
+    /// this polygon via the shoelace formula: positive if the vertices run
+    /// counter-clockwise, negative if clockwise.
+    pub fn signed_area(&self) -> T {
+        let sum = self
+            .points
+            .windows(2)
+            .fold(T::zero(), |acc, e| acc + (e[0].x * e[1].y - e[1].x * e[0].y));
+
+        sum / T::from(2)
+    }
+
+    /// Whether this polygon is convex, i.e. every vertex turns the same way
+    /// (matching the polygon's overall orientation) relative to its 2
+    /// neighbors.
+    pub fn is_convex(&self) -> bool {
+        let ccw = self.signed_area() >= T::zero();
+        let n = self.points.len() - 1; // the last point duplicates the first
+
+        (0..n).all(|i| {
+            let prev = self.points[(i + n - 1) % n];
+            let cur = self.points[i];
+            let next = self.points[(i + 1) % n];
+
+            let turn = turn_direction(&prev, &cur, &next);
+
+            turn == T::zero() || (turn > T::zero()) == ccw
+        })
+    }
+
+    /// Decompose this (possibly concave, but simple, i.e.
+    /// non-self-intersecting) polygon into triangles via [ear
+    /// clipping](https://en.wikipedia.org/wiki/Polygon_triangulation#Ear_clipping_method):
+    /// repeatedly find a convex vertex ("ear") whose triangle with its 2
+    /// neighbors contains no other vertex of the polygon, emit that
+    /// triangle and remove the vertex, until 3 vertices remain. Returns an
+    /// empty `Vec` if a full pass finds no ear, which means the input
+    /// self-intersects or isn't a simple polygon.
+    pub fn triangulate(&self) -> Vec<Triangle<T>> {
+        let ccw = self.signed_area() >= T::zero();
+
+        // drop the closing duplicate of the first point so vertex indices
+        // don't need to special-case wraparound twice.
+        let mut verts = self.points[..self.points.len() - 1].to_vec();
+
+        if verts.len() < 3 {
+            return vec![];
+        }
+
+        let mut triangles = vec![];
+
+        while verts.len() > 3 {
+            let n = verts.len();
+
+            let ear_idx = (0..n).find(|&i| {
+                let prev = verts[(i + n - 1) % n];
+                let cur = verts[i];
+                let next = verts[(i + 1) % n];
+
+                // a reflex or collinear vertex can't be clipped off as a
+                // valid ear.
+                let turn = turn_direction(&prev, &cur, &next);
+                if turn == T::zero() || (turn > T::zero()) != ccw {
+                    return false;
+                }
+
+                let ear = Triangle::new(prev, cur, next);
+
+                (0..n)
+                    .filter(|&j| j != (i + n - 1) % n && j != i && j != (i + 1) % n)
+                    .all(|j| !ear.contains(&verts[j]))
+            });
+
+            match ear_idx {
+                Some(i) => {
+                    let n = verts.len();
+                    let prev = verts[(i + n - 1) % n];
+                    let next = verts[(i + 1) % n];
+
+                    triangles.push(Triangle::new(prev, verts[i], next));
+                    verts.remove(i);
+                }
+                None => return vec![],
+            }
+        }
+
+        triangles.push(Triangle::new(verts[0], verts[1], verts[2]));
+
+        triangles
+    }
+}
+
+// the signed turn at `cur` from `prev` via the cross product of the 2
+// edges meeting there; its sign matches `signed_area`'s (positive for a
+// counter-clockwise/convex turn).
+fn turn_direction<T>(prev: &Point<T>, cur: &Point<T>, next: &Point<T>) -> T
+where
+    T: num::Num + Copy,
+{
+    (cur.x - prev.x) * (next.y - prev.y) - (next.x - prev.x) * (cur.y - prev.y)
+}
+
+impl Polygon<f64> {
+    /// Shoot a ray from `start` in `direction` and bounce it off this
+    /// polygon's edges, reflecting the direction across the hit edge's
+    /// normal (`d' = d - 2*(d.n)*n`) each time, for up to `bounces`
+    /// reflections. Returns the sequence of hit points, in order; stops
+    /// early (returning fewer than `bounces` points) if the ray escapes
+    /// without hitting any edge, which shouldn't happen for a ray starting
+    /// inside a closed polygon but can for one starting outside it.
+    pub fn billiard_path(
+        &self,
+        start: Point<f64>,
+        direction: Point<f64>,
+        bounces: usize,
+    ) -> Vec<Point<f64>> {
+        // long enough to cross the whole polygon from any point inside its
+        // bounding box, regardless of where `pos` actually is.
+        let ray_len = self.bbox.min().dist::<f64>(self.bbox.max()) * 4.0 + 1.0;
+
+        let mut path = Vec::with_capacity(bounces);
+        let mut pos = start;
+        let mut dir = direction.normalized();
+        let mut skip_edge = None;
+
+        for _ in 0..bounces {
+            let ray = LineSegment::new(pos, pos + dir * ray_len);
+
+            let hit = self
+                .edges()
+                .filter(|&(p0, p1)| skip_edge != Some((*p0, *p1)))
+                .filter_map(|(p0, p1)| {
+                    match ray.segment_intersection(&LineSegment::new(*p0, *p1)) {
+                        IntersectionResult::Point(p) => Some((p, *p0, *p1)),
+                        _ => None,
+                    }
+                })
+                .min_by(|a, b| {
+                    cmp_floats(pos.squared_dist::<f64>(&a.0), pos.squared_dist::<f64>(&b.0))
+                });
+
+            match hit {
+                None => break,
+                Some((hit_point, e0, e1)) => {
+                    let edge_dir = (e1 - e0).normalized();
+                    let normal = Point::new(-edge_dir.y, edge_dir.x);
+
+                    dir = dir - normal * (2.0 * dir.dot(&normal));
+                    pos = hit_point;
+                    skip_edge = Some((e0, e1));
+
+                    path.push(hit_point);
+                }
+            }
+        }
+
+        path
+    }
+
+    /// Build a simple (non-self-intersecting) closed polygon out of a
+    /// scattered set of points via [2-opt](https://en.wikipedia.org/wiki/2-opt):
+    /// start from the points in their given order, closed back to the
+    /// first one, then repeatedly scan every pair of non-adjacent edges
+    /// and, whenever 2 of them properly cross, reverse the run of vertices
+    /// between them, which uncrosses that pair. Each reversal replaces the 2
+    /// crossing edges with 2 edges of strictly smaller total length, so the
+    /// closed tour keeps getting shorter and scanning until a full pass finds
+    /// no crossing always terminates. Returns `None` under the same
+    /// conditions as `new`.
+    pub fn simple_from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Point<f64>>,
+    {
+        let mut points = points.into_iter().collect::<Vec<_>>();
+
+        if points.len() < 3 {
+            return None;
+        }
+
+        loop {
+            let n = points.len();
+            let mut found_crossing = false;
+
+            'outer: for i in 0..n {
+                let (p1, p2) = (points[i], points[(i + 1) % n]);
+
+                for j in (i + 2)..n {
+                    if i == 0 && j == n - 1 {
+                        // edge (n - 1, 0) is adjacent to edge (0, 1).
+                        continue;
+                    }
+
+                    let (q1, q2) = (points[j], points[(j + 1) % n]);
+
+                    if segments_properly_cross(p1, p2, q1, q2) {
+                        points[i + 1..=j].reverse();
+                        found_crossing = true;
+                        break 'outer;
+                    }
+                }
+            }
+
+            if !found_crossing {
+                break;
+            }
+        }
+
+        Self::new(points)
+    }
+
+    /// Yield every integer lattice point inside or on this polygon's
+    /// boundary via a scanline sweep, so filling a polygon doesn't have to
+    /// probe every pixel of its bounding box with `contains`. For each
+    /// integer `y` spanning the bounding box, every non-horizontal edge
+    /// crossing that scanline contributes its x intersection (counting the
+    /// edge only while `y` is in its `[min_y, max_y)` range, so a vertex
+    /// shared between 2 edges isn't counted twice); the crossings are
+    /// sorted and every integer x between each successive pair is emitted
+    /// under the even-odd rule. Horizontal edges never "cross" a scanline,
+    /// so their whole span is emitted directly whenever `y` matches them.
+    pub fn interior_points(&self) -> impl Iterator<Item = Point<u32>> + '_ {
+        let y_min = self.bbox.min().y.floor().max(0.0) as u32;
+        let y_max = self.bbox.max().y.ceil().max(0.0) as u32;
+
+        (y_min..=y_max).flat_map(move |y| {
+            let fy = f64::from(y);
+            let mut xs = BTreeSet::new();
+            let mut crossings = vec![];
+
+            for (&p0, &p1) in self.edges() {
+                if p0.y == p1.y {
+                    if p0.y == fy {
+                        let (lo, hi) = if p0.x <= p1.x { (p0.x, p1.x) } else { (p1.x, p0.x) };
+
+                        for x in lo.round().max(0.0) as u32..=hi.round().max(0.0) as u32 {
+                            xs.insert(x);
+                        }
+                    }
+
+                    continue;
+                }
+
+                let (lo, hi) = if p0.y < p1.y { (p0, p1) } else { (p1, p0) };
+                if fy >= lo.y && fy < hi.y {
+                    let t = (fy - lo.y) / (hi.y - lo.y);
+                    crossings.push(lo.x + t * (hi.x - lo.x));
+                }
+            }
+
+            crossings.sort_by(|a, b| cmp_floats(*a, *b));
+
+            for pair in crossings.chunks(2) {
+                if pair.len() < 2 {
+                    continue;
+                }
+
+                let lo = pair[0].ceil().max(0.0) as u32;
+                let hi = pair[1].floor().max(0.0) as u32;
+
+                for x in lo..=hi {
+                    xs.insert(x);
+                }
+            }
+
+            xs.into_iter().map(move |x| Point::new(x, y)).collect::<Vec<_>>()
+        })
+    }
+}
+
 fn in_range<T: PartialOrd>(a: &T, b: &T, v: &T) -> bool {
     let (min, max) = if a < b { (a, b) } else { (b, a) };
 
     min <= v && max >= v
 }
 
+// whether segment `p1`-`p2` crosses segment `q1`-`q2` at a point interior to
+// both, via the standard 4-orientation test; shared or collinear endpoints
+// always yield a `Colinear` orientation and so never count as crossing.
+fn segments_properly_cross(p1: Point<f64>, p2: Point<f64>, q1: Point<f64>, q2: Point<f64>) -> bool {
+    let o1 = angle_orientation(&p1, &p2, &q1);
+    let o2 = angle_orientation(&p1, &p2, &q2);
+    let o3 = angle_orientation(&q1, &q2, &p1);
+    let o4 = angle_orientation(&q1, &q2, &p2);
+
+    o1 != AngleOrientation::Colinear
+        && o2 != AngleOrientation::Colinear
+        && o3 != AngleOrientation::Colinear
+        && o4 != AngleOrientation::Colinear
+        && o1 != o2
+        && o3 != o4
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Polygon;
+    use super::{Polygon, Triangle};
 
     use geo::{BoundingBox, PointF64, PointU32};
 
@@ -298,6 +591,77 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_billiard_path_bounces_between_opposite_walls() {
+        let square = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ])
+        .unwrap();
+
+        let path = square.billiard_path(PointF64::new(5.0, 5.0), PointF64::new(1.0, 0.0), 3);
+
+        assert_eq!(
+            path,
+            vec![
+                PointF64::new(10.0, 5.0),
+                PointF64::new(0.0, 5.0),
+                PointF64::new(10.0, 5.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_billiard_path_stops_if_no_more_hits() {
+        let square = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ])
+        .unwrap();
+
+        // starting outside the polygon and aimed further away: the ray
+        // never reaches any edge.
+        let path = square.billiard_path(PointF64::new(20.0, 20.0), PointF64::new(1.0, 1.0), 5);
+
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn test_simple_from_points_uncrosses_a_bowtie() {
+        // points given in an order that connects them into a self-crossing
+        // bowtie, rather than the square's boundary order.
+        let points = vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let polygon = Polygon::simple_from_points(points).unwrap();
+
+        for (p0, p1) in polygon.edges() {
+            for (q0, q1) in polygon.edges() {
+                if (p0, p1) == (q0, q1) {
+                    continue;
+                }
+
+                assert!(!super::segments_properly_cross(*p0, *p1, *q0, *q1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_simple_from_points_needs_at_least_3_points() {
+        assert_eq!(
+            Polygon::simple_from_points(vec![PointF64::new(0.0, 0.0), PointF64::new(1.0, 1.0)]),
+            None
+        );
+    }
+
     #[test]
     fn test_polygon_square_triangle_contains() {
         let poly = Polygon {
@@ -312,4 +676,125 @@ mod tests {
 
         assert!(poly.contains(&PointF64::new(1.0, 247.0)));
     }
+
+    #[test]
+    fn test_interior_points_of_a_square_is_the_full_grid() {
+        let square = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(3.0, 0.0),
+            PointF64::new(3.0, 2.0),
+            PointF64::new(0.0, 2.0),
+        ])
+        .unwrap();
+
+        let mut points = square.interior_points().collect::<Vec<_>>();
+        points.sort_by_key(|p| (p.y, p.x));
+
+        let mut expected = (0..=2)
+            .flat_map(|y| (0..=3).map(move |x| PointU32::new(x, y)))
+            .collect::<Vec<_>>();
+        expected.sort_by_key(|p| (p.y, p.x));
+
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn test_interior_points_of_a_triangle_agree_with_contains() {
+        let triangle = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(8.0, 0.0),
+            PointF64::new(0.0, 6.0),
+        ])
+        .unwrap();
+
+        for pt in triangle.interior_points() {
+            assert!(triangle.contains(&pt.cast()));
+        }
+    }
+
+    #[test]
+    fn test_polygon_signed_area() {
+        let ccw = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(4.0, 0.0),
+            PointF64::new(4.0, 3.0),
+            PointF64::new(0.0, 3.0),
+        ])
+        .unwrap();
+        assert_eq!(ccw.signed_area(), 12.0);
+
+        let cw = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(0.0, 3.0),
+            PointF64::new(4.0, 3.0),
+            PointF64::new(4.0, 0.0),
+        ])
+        .unwrap();
+        assert_eq!(cw.signed_area(), -12.0);
+    }
+
+    #[test]
+    fn test_polygon_is_convex() {
+        let square = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(4.0, 0.0),
+            PointF64::new(4.0, 4.0),
+            PointF64::new(0.0, 4.0),
+        ])
+        .unwrap();
+        assert!(square.is_convex());
+
+        // an "L" shape is concave
+        let l_shape = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(4.0, 0.0),
+            PointF64::new(4.0, 2.0),
+            PointF64::new(2.0, 2.0),
+            PointF64::new(2.0, 4.0),
+            PointF64::new(0.0, 4.0),
+        ])
+        .unwrap();
+        assert!(!l_shape.is_convex());
+    }
+
+    #[test]
+    fn test_polygon_triangulate_square() {
+        let square = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(4.0, 0.0),
+            PointF64::new(4.0, 4.0),
+            PointF64::new(0.0, 4.0),
+        ])
+        .unwrap();
+
+        let triangles = square.triangulate();
+
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(
+            triangles.iter().map(Triangle::area).sum::<f64>(),
+            square.signed_area().abs()
+        );
+    }
+
+    #[test]
+    fn test_polygon_triangulate_concave_l_shape() {
+        let l_shape = Polygon::new(vec![
+            PointF64::new(0.0, 0.0),
+            PointF64::new(4.0, 0.0),
+            PointF64::new(4.0, 2.0),
+            PointF64::new(2.0, 2.0),
+            PointF64::new(2.0, 4.0),
+            PointF64::new(0.0, 4.0),
+        ])
+        .unwrap();
+
+        let triangles = l_shape.triangulate();
+
+        assert_eq!(triangles.len(), 4);
+        assert!(
+            (triangles.iter().map(Triangle::area).sum::<f64>() - l_shape.signed_area().abs())
+                .abs()
+                < 1e-9
+        );
+    }
 }
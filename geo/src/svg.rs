@@ -0,0 +1,309 @@
+//! Parse SVG path `d` strings into flattened `Polygon<f64>`s, so vector
+//! artwork can be fed into `primify`, the Mondrian subdivision, and the
+//! boolean set operations alongside procedurally generated shapes.
+//!
+//! Only the subset of the path grammar needed to describe polygonal
+//! artwork is supported: the `M`/`L`/`H`/`V`/`Z` line commands and the
+//! `C`/`Q` cubic/quadratic Bézier commands (absolute forms only). Curves
+//! are flattened into line segments by recursively subdividing them with
+//! [de Casteljau's
+//! algorithm](https://en.wikipedia.org/wiki/De_Casteljau%27s_algorithm)
+//! while the control points' maximum perpendicular distance from the
+//! chord exceeds a caller-supplied tolerance, the same approach
+//! pathfinder's `tile-svg` uses to turn SVG paths into polygonal fills.
+
+use crate::point::Point;
+use crate::polygon::Polygon;
+
+/// Parse an SVG path `d` string into its flattened, closed subpaths.
+///
+/// Curves are subdivided until they're within `tolerance` units of their
+/// chord. Only subpaths closed with `Z` are returned, since an open
+/// subpath can't form a `Polygon`.
+pub fn parse_svg_path(d: &str, tolerance: f64) -> Vec<Polygon<f64>> {
+    let tokens = tokenize(d);
+
+    let mut polygons = vec![];
+    let mut subpath: Vec<Point<f64>> = vec![];
+
+    let mut current = Point::new(0.0, 0.0);
+    let mut subpath_start = Point::new(0.0, 0.0);
+
+    let mut cmd = ' ';
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Token::Command(c) = tokens[i] {
+            cmd = c;
+            i += 1;
+        }
+
+        match cmd {
+            'M' => {
+                let p = take_pair(&tokens, &mut i);
+
+                subpath = vec![p];
+                current = p;
+                subpath_start = p;
+
+                // any further coordinate pairs are implicit `L`s.
+                cmd = 'L';
+            }
+            'L' => {
+                let p = take_pair(&tokens, &mut i);
+
+                subpath.push(p);
+                current = p;
+            }
+            'H' => {
+                let x = take_number(&tokens, &mut i);
+
+                current = Point::new(x, current.y);
+                subpath.push(current);
+            }
+            'V' => {
+                let y = take_number(&tokens, &mut i);
+
+                current = Point::new(current.x, y);
+                subpath.push(current);
+            }
+            'C' => {
+                let p1 = take_pair(&tokens, &mut i);
+                let p2 = take_pair(&tokens, &mut i);
+                let p3 = take_pair(&tokens, &mut i);
+
+                flatten_cubic(current, p1, p2, p3, tolerance, &mut subpath);
+                current = p3;
+            }
+            'Q' => {
+                let p1 = take_pair(&tokens, &mut i);
+                let p2 = take_pair(&tokens, &mut i);
+
+                flatten_quadratic(current, p1, p2, tolerance, &mut subpath);
+                current = p2;
+            }
+            'Z' => {
+                if let Some(polygon) = Polygon::new(subpath.clone()) {
+                    polygons.push(polygon);
+                }
+
+                current = subpath_start;
+                subpath = vec![];
+            }
+            // malformed input: bail out rather than looping forever on a
+            // command we don't recognize and can't consume arguments for.
+            _ => break,
+        }
+    }
+
+    polygons
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Command(char),
+    Number(f64),
+}
+
+// split a `d` string into its command letters and numbers, tolerating the
+// punctuation SVG allows between them: commas, whitespace, and numbers
+// glued to a following `-` sign (e.g. `1-2` means `1`, `-2`).
+fn tokenize(d: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+
+    let chars = d.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if c.is_ascii_alphabetic() {
+            tokens.push(Token::Command(c));
+            i += 1;
+        } else {
+            let start = i;
+            i += 1;
+
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+
+            let s = chars[start..i].iter().collect::<String>();
+            if let Ok(n) = s.parse() {
+                tokens.push(Token::Number(n));
+            }
+        }
+    }
+
+    tokens
+}
+
+fn take_number(tokens: &[Token], i: &mut usize) -> f64 {
+    match tokens.get(*i) {
+        Some(Token::Number(n)) => {
+            *i += 1;
+            *n
+        }
+        _ => 0.0,
+    }
+}
+
+fn take_pair(tokens: &[Token], i: &mut usize) -> Point<f64> {
+    let x = take_number(tokens, i);
+    let y = take_number(tokens, i);
+
+    Point::new(x, y)
+}
+
+// recursively subdivide the cubic Bézier `p0 p1 p2 p3` with de Casteljau's
+// algorithm until its control points are within `tolerance` of the chord
+// `p0`-`p3`, then emit its endpoint into `out`.
+fn flatten_cubic(
+    p0: Point<f64>,
+    p1: Point<f64>,
+    p2: Point<f64>,
+    p3: Point<f64>,
+    tolerance: f64,
+    out: &mut Vec<Point<f64>>,
+) {
+    if is_cubic_flat(p0, p1, p2, p3, tolerance) {
+        out.push(p3);
+        return;
+    }
+
+    let p01 = p0.midpoint(&p1);
+    let p12 = p1.midpoint(&p2);
+    let p23 = p2.midpoint(&p3);
+    let p012 = p01.midpoint(&p12);
+    let p123 = p12.midpoint(&p23);
+    let p0123 = p012.midpoint(&p123);
+
+    flatten_cubic(p0, p01, p012, p0123, tolerance, out);
+    flatten_cubic(p0123, p123, p23, p3, tolerance, out);
+}
+
+fn is_cubic_flat(p0: Point<f64>, p1: Point<f64>, p2: Point<f64>, p3: Point<f64>, tolerance: f64) -> bool {
+    perpendicular_distance(p1, p0, p3) <= tolerance
+        && perpendicular_distance(p2, p0, p3) <= tolerance
+}
+
+// recursively subdivide the quadratic Bézier `p0 p1 p2` the same way as
+// `flatten_cubic`, just with one fewer control point to track.
+fn flatten_quadratic(p0: Point<f64>, p1: Point<f64>, p2: Point<f64>, tolerance: f64, out: &mut Vec<Point<f64>>) {
+    if is_quadratic_flat(p0, p1, p2, tolerance) {
+        out.push(p2);
+        return;
+    }
+
+    let p01 = p0.midpoint(&p1);
+    let p12 = p1.midpoint(&p2);
+    let p012 = p01.midpoint(&p12);
+
+    flatten_quadratic(p0, p01, p012, tolerance, out);
+    flatten_quadratic(p012, p12, p2, tolerance, out);
+}
+
+fn is_quadratic_flat(p0: Point<f64>, p1: Point<f64>, p2: Point<f64>, tolerance: f64) -> bool {
+    perpendicular_distance(p1, p0, p2) <= tolerance
+}
+
+// the distance from `pt` to the line through `a` and `b`, or `pt`'s
+// distance to `a` if the chord is degenerate.
+fn perpendicular_distance(pt: Point<f64>, a: Point<f64>, b: Point<f64>) -> f64 {
+    let chord_len: f64 = a.dist(&b);
+
+    if chord_len == 0.0 {
+        return a.dist(&pt);
+    }
+
+    ((b.x - a.x) * (a.y - pt.y) - (a.x - pt.x) * (b.y - a.y)).abs() / chord_len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::point::PointF64;
+
+    #[test]
+    fn parse_closed_rectangle() {
+        let polygons = parse_svg_path("M0,0 L10,0 L10,5 H0 Z", 0.1);
+
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(
+            polygons[0].points(),
+            &[
+                PointF64::new(0.0, 0.0),
+                PointF64::new(10.0, 0.0),
+                PointF64::new(10.0, 5.0),
+                PointF64::new(0.0, 5.0),
+                PointF64::new(0.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_subpath_is_dropped() {
+        let polygons = parse_svg_path("M0,0 L10,0 L10,5", 0.1);
+
+        assert!(polygons.is_empty());
+    }
+
+    #[test]
+    fn multiple_subpaths_produce_multiple_polygons() {
+        let polygons = parse_svg_path("M0,0 L1,0 L1,1 Z M5,5 L6,5 L6,6 Z", 0.1);
+
+        assert_eq!(polygons.len(), 2);
+    }
+
+    #[test]
+    fn cubic_bezier_flattens_within_tolerance() {
+        let polygons = parse_svg_path("M0,0 C0,10 10,10 10,0 Z", 0.01);
+
+        assert_eq!(polygons.len(), 1);
+
+        let points = polygons[0].points();
+        assert!(points.len() > 4);
+
+        for p in points {
+            assert!(p.y <= 7.51);
+        }
+    }
+
+    #[test]
+    fn straight_cubic_flattens_to_its_endpoint() {
+        // control points lie on the chord, so the curve is flat without any
+        // subdivision and only its endpoint is emitted.
+        let mut out = vec![];
+        flatten_cubic(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(3.0, 0.0),
+            PointF64::new(6.0, 0.0),
+            PointF64::new(9.0, 0.0),
+            0.01,
+            &mut out,
+        );
+
+        assert_eq!(out, vec![PointF64::new(9.0, 0.0)]);
+    }
+
+    #[test]
+    fn quadratic_bezier_flattens_within_tolerance() {
+        let polygons = parse_svg_path("M0,0 Q5,10 10,0 Z", 0.01);
+
+        assert_eq!(polygons.len(), 1);
+
+        let points = polygons[0].points();
+        assert!(points.len() > 3);
+    }
+}
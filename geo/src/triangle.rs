@@ -0,0 +1,242 @@
+//! Module to work with triangles.
+
+use crate::line::LineEquation;
+use crate::point::Point;
+
+/// Simple Triangle shape.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Triangle<P> {
+    /// The points of the triangle
+    pub points: [Point<P>; 3],
+}
+
+impl<P> Triangle<P>
+where
+    P: num::Num + From<u8> + Copy,
+{
+    /// Create a new `Triangle` from the given points.
+    pub fn new(p1: Point<P>, p2: Point<P>, p3: Point<P>) -> Triangle<P> {
+        Triangle {
+            points: [p1, p2, p3],
+        }
+    }
+
+    /// Return the [centroid](https://en.wikipedia.org/wiki/Centroid) of the
+    /// triangle.
+    pub fn centroid(&self) -> Point<P> {
+        let (sum_x, sum_y) = self
+            .points
+            .iter()
+            .fold((P::zero(), P::zero()), |(accx, accy), pt| {
+                (accx + pt.x, accy + pt.y)
+            });
+
+        let avg_x = sum_x / P::from(3);
+        let avg_y = sum_y / P::from(3);
+
+        Point::new(avg_x, avg_y)
+    }
+}
+
+impl<P> Triangle<P>
+where
+    P: num::Num + num::Signed + Copy + PartialOrd,
+{
+    /// Return the area for this triangle.
+    pub fn area(&self) -> P {
+        self.signed_area().abs()
+    }
+
+    /// Return the signed area for this triangle. The sign indicates the
+    /// orientation of the points. If it's negative then the vertices are in
+    /// clockwise order, counter clockwise otherwise. Built from the same
+    /// cross product as `angle::robust_orientation`/`orientation`, so this
+    /// is exact over exact coordinate types such as
+    /// `num_rational::Rational64`; over integers the final halving truncates
+    /// toward zero, and over `f64` it rounds like any float arithmetic.
+    pub fn signed_area(&self) -> P {
+        let parallelogram_area = (self.points[1].x - self.points[0].x)
+            * (self.points[2].y - self.points[0].y)
+            - (self.points[2].x - self.points[0].x) * (self.points[1].y - self.points[0].y);
+
+        parallelogram_area / (P::one() + P::one())
+    }
+
+    /// Whether `pt` lies inside (or exactly on the boundary of) this
+    /// triangle: compute, for each edge, the sign of the cross product of
+    /// the edge's direction with the vector from the edge's start to `pt`;
+    /// `pt` is inside iff it's on the same side (or on the line) of all 3
+    /// edges.
+    pub fn contains(&self, pt: &Point<P>) -> bool {
+        let side = |a: &Point<P>, b: &Point<P>| -> P {
+            (b.x - a.x) * (pt.y - a.y) - (pt.x - a.x) * (b.y - a.y)
+        };
+
+        let d1 = side(&self.points[0], &self.points[1]);
+        let d2 = side(&self.points[1], &self.points[2]);
+        let d3 = side(&self.points[2], &self.points[0]);
+
+        let has_neg = d1 < P::zero() || d2 < P::zero() || d3 < P::zero();
+        let has_pos = d1 > P::zero() || d2 > P::zero() || d3 > P::zero();
+
+        !(has_neg && has_pos)
+    }
+}
+
+impl<P> Triangle<P>
+where
+    P: num::Num + num::Signed + From<u8> + Copy + PartialOrd,
+{
+    /// Transform this triangle so that the vertices are always in counter
+    /// clockwise order.
+    pub fn counter_clockwise(self) -> Self {
+        if self.signed_area() >= P::from(0) {
+            self
+        } else {
+            Triangle::new(
+                self.points[1].clone(),
+                self.points[0].clone(),
+                self.points[2].clone(),
+            )
+        }
+    }
+
+    /// Return the circumcenter of the circle that encloses this triangle.
+    ///
+    /// Unlike `signed_area`, this can't be made exact over `Rational64` the
+    /// same way: it goes through `Point`'s `midpoint`/`LineEquation`
+    /// arithmetic, which needs `From<u8>` for division by the literal `2`
+    /// and isn't implemented for `Rational64`. Lifting that would mean
+    /// relaxing `Point<P>`'s arithmetic bound crate-wide, which is its own,
+    /// separate change.
+    pub fn circumcenter(&self) -> Option<Point<P>>
+    where
+        P: ::std::fmt::Debug,
+    {
+        let p0p1 = LineEquation::between(&self.points[0], &self.points[1]);
+        let p0p2 = LineEquation::between(&self.points[0], &self.points[2]);
+
+        let mid_p0p1 = self.points[0].midpoint(&self.points[1]);
+        let mid_p0p2 = self.points[0].midpoint(&self.points[2]);
+
+        let bisec_p0p1 = p0p1.perpendicular(&mid_p0p1);
+        let bisec_p0p2 = p0p2.perpendicular(&mid_p0p2);
+
+        bisec_p0p1.intersection(&bisec_p0p2)
+    }
+
+    /// Return the circumcicle that encloses this triangle as a pair of
+    /// circumcenter and radius _squared_.
+    pub fn squared_circumcircle<O>(&self) -> Option<(Point<P>, O)>
+    where
+        O: num::Num + From<P> + Copy,
+        P: ::std::fmt::Debug,
+    {
+        self.circumcenter().map(|circumcenter| {
+            let squared_radius = circumcenter.squared_dist(&self.points[0]);
+
+            (circumcenter, squared_radius)
+        })
+    }
+}
+
+impl<P> Triangle<P>
+where
+    P: num::Num + num::Signed + From<u8> + Copy + PartialOrd,
+    f64: From<P>,
+{
+    /// Return the circumcicle that encloses this triangle as a pair of
+    /// circumcenter and radius.
+    pub fn circumcircle(&self) -> Option<(Point<P>, f64)>
+    where
+        P: ::std::fmt::Debug,
+    {
+        self.circumcenter().map(|circumcenter| {
+            let radius = circumcenter.dist(&self.points[0]);
+
+            (circumcenter, radius)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Triangle;
+    use crate::point::PointI32;
+
+    #[test]
+    fn test_triangle_circumcircle() {
+        let triangle = Triangle::new(
+            PointI32::new(3, 2),
+            PointI32::new(1, 4),
+            PointI32::new(5, 4),
+        );
+        assert_eq!(triangle.circumcircle(), Some((PointI32::new(3, 4), 2.0)));
+
+        // ensure the algorithm works with vertical lines
+        let triangle = Triangle::new(
+            PointI32::new(3, 2),
+            PointI32::new(5, 4),
+            PointI32::new(1, 4),
+        );
+        assert_eq!(triangle.circumcircle(), Some((PointI32::new(3, 4), 2.0)));
+
+        let triangle = Triangle::new(
+            PointI32::new(3, 2),
+            PointI32::new(5, 2),
+            PointI32::new(4, 2),
+        );
+        assert_eq!(triangle.circumcircle(), None);
+    }
+
+    #[test]
+    fn test_triangle_area() {
+        let triangle = Triangle::new(
+            PointI32::new(6, 0),
+            PointI32::new(0, 0),
+            PointI32::new(3, 3),
+        );
+        assert_eq!(triangle.area(), 9);
+        assert_eq!(triangle.signed_area(), -9);
+
+        let triangle = triangle.counter_clockwise();
+        assert_eq!(triangle.area(), 9);
+        assert_eq!(triangle.signed_area(), 9);
+    }
+
+    #[test]
+    fn test_triangle_signed_area_is_exact_over_rationals() {
+        use num_rational::Rational64;
+
+        let p = |x: i64, y: i64| crate::point::Point::new(Rational64::from_integer(x), Rational64::from_integer(y));
+
+        // the triangle is built directly since `Triangle::new` needs `From<u8>`,
+        // which `Rational64` doesn't implement.
+        let triangle = Triangle {
+            points: [p(6, 0), p(0, 0), p(3, 3)],
+        };
+
+        assert_eq!(triangle.signed_area(), Rational64::from_integer(-9));
+        assert_eq!(triangle.area(), Rational64::from_integer(9));
+    }
+
+    #[test]
+    fn test_triangle_contains() {
+        let triangle = Triangle::new(
+            PointI32::new(0, 0),
+            PointI32::new(6, 0),
+            PointI32::new(0, 6),
+        );
+
+        // vertices and a point on an edge
+        assert!(triangle.contains(&PointI32::new(0, 0)));
+        assert!(triangle.contains(&PointI32::new(3, 0)));
+
+        // an interior point
+        assert!(triangle.contains(&PointI32::new(1, 1)));
+
+        // outside the triangle
+        assert!(!triangle.contains(&PointI32::new(6, 6)));
+        assert!(!triangle.contains(&PointI32::new(-1, -1)));
+    }
+}
@@ -0,0 +1,65 @@
+//! Collect a sequence of still frames and encode them into a single,
+//! looped, animated GIF.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use gif::{Encoder, Frame, Repeat};
+
+/// Accumulates `image::RgbImage` frames and writes them out as a single
+/// looped animated GIF.
+pub struct Animation {
+    frames: Vec<image::RgbImage>,
+    delay_ms: u16,
+}
+
+impl Animation {
+    /// Create an empty animation where each frame is shown for `delay_ms`
+    /// milliseconds before the next one plays.
+    pub fn new(delay_ms: u16) -> Self {
+        Animation {
+            frames: vec![],
+            delay_ms,
+        }
+    }
+
+    /// Append a frame to the animation. Every frame must have the same
+    /// dimensions as the first one.
+    pub fn push(&mut self, frame: image::RgbImage) {
+        self.frames.push(frame);
+    }
+
+    /// Encode every collected frame into a looped GIF at `path`. Does
+    /// nothing if no frame was ever pushed.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let (width, height) = match self.frames.first() {
+            Some(frame) => (frame.width(), frame.height()),
+            None => return Ok(()),
+        };
+
+        let mut file = File::create(path)?;
+        let mut encoder = Encoder::new(&mut file, width as u16, height as u16, &[])
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        // GIF delays are measured in hundredths of a second.
+        let delay = (self.delay_ms / 10).max(1);
+
+        for frame in &self.frames {
+            let mut pixels = frame.clone().into_raw();
+            let mut gif_frame =
+                Frame::from_rgb_speed(width as u16, height as u16, &mut pixels, 10);
+            gif_frame.delay = delay;
+
+            encoder
+                .write_frame(&gif_frame)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,535 @@
+//! Paint an image with every color of a (reduced) RGB cube exactly once,
+//! arranged so that adjacent pixels are perceptually close.
+//!
+//! The image grows from a single seed pixel: a frontier of empty pixels
+//! adjacent to already-filled ones is maintained, and colors are assigned to
+//! whichever frontier pixel they fit best. Two strategies are offered for
+//! how that assignment happens, selectable through [`ColorOrder`]:
+//!
+//! - [`ColorOrder::Hilbert`] walks the color cube along a 3D Hilbert curve,
+//!   so consecutive colors are close together, and greedily scans the
+//!   frontier for the pixel whose already-placed neighbors best match each
+//!   color in turn.
+//! - [`ColorOrder::NearestNeighbor`] builds a k-d tree over every color in
+//!   the cube once and, for each frontier pixel in turn, queries the
+//!   nearest still-unused color to its mean neighbor color. This keeps
+//!   matching close to `O(n log n)` instead of the `O(n^2)` frontier scan
+//!   above.
+
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
+
+use rand::Rng;
+
+use geo::PointU32;
+
+type Rgb = [u8; 3];
+
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+
+/// How colors are produced and matched against the growing frontier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorOrder {
+    /// Walk the color cube along a 3D Hilbert curve, then greedily match
+    /// each color in turn to the best frontier pixel.
+    Hilbert,
+
+    /// Query a k-d tree over every color in the cube for the nearest
+    /// still-unused color to each frontier pixel, in turn.
+    NearestNeighbor,
+}
+
+impl FromStr for ColorOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hilbert" => Ok(ColorOrder::Hilbert),
+            "nearest-neighbor" => Ok(ColorOrder::NearestNeighbor),
+            _ => Err(format!(
+                "unknown color order {:?}, expected `hilbert` or `nearest-neighbor`",
+                s
+            )),
+        }
+    }
+}
+
+/// The order in which frontier pixels are popped for growth, used by
+/// [`ColorOrder::NearestNeighbor`]. Does not affect [`ColorOrder::Hilbert`],
+/// which always scans the whole frontier for the best match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontierOrder {
+    /// Pop frontier pixels in the order they were discovered.
+    Fifo,
+
+    /// Pop the frontier pixel with the most already-filled neighbors first,
+    /// which tends to keep the growing region compact and round.
+    Priority,
+
+    /// Pop a random frontier pixel, for a looser, more organic texture.
+    Random,
+}
+
+impl FromStr for FrontierOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fifo" => Ok(FrontierOrder::Fifo),
+            "priority" => Ok(FrontierOrder::Priority),
+            "random" => Ok(FrontierOrder::Random),
+            _ => Err(format!(
+                "unknown frontier order {:?}, expected `fifo`, `priority` or `random`",
+                s
+            )),
+        }
+    }
+}
+
+/// Fill `img` with every color of an RGB cube, growing out from `start` so
+/// that adjacent pixels are perceptually close. `img` is fully overwritten.
+///
+/// `bits` picks the cube: it has `2^bits` levels per channel, i.e.
+/// `2^(3*bits)` colors total, and `img` must hold exactly that many pixels
+/// so every color is used exactly once. Pass `None` to instead size the cube
+/// to the smallest one with at least `img.width() * img.height()` colors,
+/// which may leave some colors unused if that count isn't a power of 8.
+pub fn allrgb(
+    img: &mut image::RgbImage,
+    order: ColorOrder,
+    frontier_order: FrontierOrder,
+    start: PointU32,
+    bits: Option<u32>,
+) {
+    let side = match bits {
+        Some(bits) => 1 << bits,
+        None => cube_side(img.width() * img.height()),
+    };
+    let colors = cube_colors(side);
+
+    match order {
+        ColorOrder::Hilbert => grow_by_color(img, colors, start),
+        ColorOrder::NearestNeighbor => grow_by_frontier(img, colors, start, frontier_order),
+    }
+}
+
+/// Side length of the smallest cube of colors, a power of two so it can be
+/// walked with a Hilbert curve, that contains at least `n` colors.
+fn cube_side(n: u32) -> u32 {
+    let mut side = 1;
+    while side * side * side < n {
+        side *= 2;
+    }
+    side
+}
+
+/// Every color of a `side` x `side` x `side` cube, in Hilbert-curve order.
+fn cube_colors(side: u32) -> Vec<Rgb> {
+    let bits = side.trailing_zeros();
+    let n = u64::from(side) * u64::from(side) * u64::from(side);
+
+    (0..n)
+        .map(|i| to_rgb(side, hilbert_d2xyz(bits, i)))
+        .collect()
+}
+
+/// Scale a `[0, side)` color cube coordinate up to a `[0, 255]` channel.
+fn to_rgb(side: u32, [x, y, z]: [u32; 3]) -> Rgb {
+    let scale = |c: u32| if side <= 1 { 0 } else { (c * 255 / (side - 1)) as u8 };
+
+    [scale(x), scale(y), scale(z)]
+}
+
+/// Map a Hilbert index to the `(x, y, z)` point it visits on a
+/// `2^bits`-per-side curve, using the standard bit-interleaving, Gray-code
+/// and "undo excess work" recurrence (Skilling, "Programming the Hilbert
+/// Curve").
+fn hilbert_d2xyz(bits: u32, index: u64) -> [u32; 3] {
+    const DIMS: u32 = 3;
+
+    if bits == 0 {
+        return [0, 0, 0];
+    }
+
+    // Distribute the bits of `index`, highest first, across the 3 axes.
+    let mut x = [0u64; 3];
+    for p in 0..bits {
+        let shift = (bits - 1 - p) * DIMS;
+        let chunk = (index >> shift) & 0b111;
+
+        for (i, xi) in x.iter_mut().enumerate() {
+            let bit = (chunk >> (DIMS - 1 - i as u32)) & 1;
+            *xi |= bit << (bits - 1 - p);
+        }
+    }
+
+    // Gray decode.
+    let mut t = x[2] >> 1;
+    for i in (1..3).rev() {
+        x[i] ^= x[i - 1];
+    }
+    x[0] ^= t;
+
+    // Undo excess work.
+    let mut q = 2u64;
+    while q != (1 << bits) {
+        let p = q - 1;
+
+        for i in (0..3).rev() {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+
+        q <<= 1;
+    }
+
+    [x[0] as u32, x[1] as u32, x[2] as u32]
+}
+
+/// Mean color of the already-filled neighbors of `pixel`, or black if it has
+/// none.
+fn mean_neighbor_color(img: &image::RgbImage, filled: &HashSet<PointU32>, pixel: PointU32) -> Rgb {
+    let mut sum = [0u32; 3];
+    let mut count = 0u32;
+
+    for neighbor in neighbors(img, pixel) {
+        if filled.contains(&neighbor) {
+            let p = img.get_pixel(neighbor.x, neighbor.y);
+            for i in 0..3 {
+                sum[i] += u32::from(p.data[i]);
+            }
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return [0, 0, 0];
+    }
+
+    [
+        (sum[0] / count) as u8,
+        (sum[1] / count) as u8,
+        (sum[2] / count) as u8,
+    ]
+}
+
+fn neighbors(img: &image::RgbImage, pixel: PointU32) -> impl Iterator<Item = PointU32> + '_ {
+    NEIGHBOR_OFFSETS.iter().filter_map(move |&(dx, dy)| {
+        let nx = i64::from(pixel.x) + i64::from(dx);
+        let ny = i64::from(pixel.y) + i64::from(dy);
+
+        if nx < 0 || ny < 0 || nx >= i64::from(img.width()) || ny >= i64::from(img.height()) {
+            None
+        } else {
+            Some(PointU32::new(nx as u32, ny as u32))
+        }
+    })
+}
+
+fn squared_color_dist(a: Rgb, b: Rgb) -> i32 {
+    (0..3)
+        .map(|i| {
+            let d = i32::from(a[i]) - i32::from(b[i]);
+            d * d
+        })
+        .sum()
+}
+
+/// A sink that newly-discovered frontier pixels get enqueued into, so
+/// `push_open_neighbors` can feed either `grow_by_color`'s plain `VecDeque`
+/// or `grow_by_frontier`'s order-aware `Frontier`.
+trait FrontierSink {
+    fn enqueue(&mut self, pixel: PointU32);
+}
+
+impl FrontierSink for VecDeque<PointU32> {
+    fn enqueue(&mut self, pixel: PointU32) {
+        self.push_back(pixel);
+    }
+}
+
+fn push_open_neighbors<Q: FrontierSink>(
+    img: &image::RgbImage,
+    filled: &HashSet<PointU32>,
+    on_frontier: &mut HashSet<PointU32>,
+    open: &mut Q,
+    pixel: PointU32,
+) {
+    for neighbor in neighbors(img, pixel) {
+        if !filled.contains(&neighbor) && on_frontier.insert(neighbor) {
+            open.enqueue(neighbor);
+        }
+    }
+}
+
+/// The set of discovered-but-unfilled pixels `grow_by_frontier` draws from,
+/// popped according to a [`FrontierOrder`].
+struct Frontier {
+    order: FrontierOrder,
+    items: VecDeque<PointU32>,
+}
+
+impl Frontier {
+    fn new(order: FrontierOrder) -> Self {
+        Frontier {
+            order,
+            items: VecDeque::new(),
+        }
+    }
+
+    /// Pop the next pixel to fill, according to `self.order`. `filled` is
+    /// only consulted by `FrontierOrder::Priority`, to rank pixels by how
+    /// many already-filled neighbors they have.
+    fn pop(&mut self, img: &image::RgbImage, filled: &HashSet<PointU32>) -> Option<PointU32> {
+        match self.order {
+            FrontierOrder::Fifo => self.items.pop_front(),
+            FrontierOrder::Priority => {
+                let best = self
+                    .items
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|&(_, &pixel)| {
+                        neighbors(img, pixel).filter(|n| filled.contains(n)).count()
+                    })
+                    .map(|(i, _)| i)?;
+
+                self.items.remove(best)
+            }
+            FrontierOrder::Random => {
+                if self.items.is_empty() {
+                    return None;
+                }
+
+                let i = rand::thread_rng().gen_range(0, self.items.len());
+                self.items.remove(i)
+            }
+        }
+    }
+}
+
+impl FrontierSink for Frontier {
+    fn enqueue(&mut self, pixel: PointU32) {
+        self.items.push_back(pixel);
+    }
+}
+
+/// [`ColorOrder::Hilbert`]: visit `colors` in order and, for each one, place
+/// it on whichever open pixel currently adjacent to the filled region best
+/// matches it.
+fn grow_by_color(img: &mut image::RgbImage, colors: Vec<Rgb>, start: PointU32) {
+    let mut filled = HashSet::new();
+    let mut on_frontier = HashSet::new();
+    let mut open = VecDeque::new();
+
+    let mut colors = colors.into_iter();
+
+    if let Some(first) = colors.next() {
+        img.put_pixel(start.x, start.y, image::Rgb { data: first });
+        filled.insert(start);
+        push_open_neighbors(img, &filled, &mut on_frontier, &mut open, start);
+    }
+
+    for color in colors {
+        let best = open
+            .iter()
+            .enumerate()
+            .map(|(i, &pixel)| {
+                let target = mean_neighbor_color(img, &filled, pixel);
+                (i, squared_color_dist(target, color))
+            })
+            .min_by_key(|&(_, dist)| dist)
+            .map(|(i, _)| i);
+
+        let i = match best {
+            Some(i) => i,
+            None => break,
+        };
+
+        let pixel = open.remove(i).unwrap();
+        on_frontier.remove(&pixel);
+        filled.insert(pixel);
+        img.put_pixel(pixel.x, pixel.y, image::Rgb { data: color });
+
+        push_open_neighbors(img, &filled, &mut on_frontier, &mut open, pixel);
+    }
+}
+
+/// [`ColorOrder::NearestNeighbor`]: visit open pixels in `frontier_order` and,
+/// for each one, claim the nearest still-unused color to its mean neighbor
+/// color out of a k-d tree built once over all of `colors`.
+fn grow_by_frontier(
+    img: &mut image::RgbImage,
+    colors: Vec<Rgb>,
+    start: PointU32,
+    frontier_order: FrontierOrder,
+) {
+    let mut tree = ColorKdTree::build(colors);
+
+    let mut filled = HashSet::new();
+    let mut on_frontier = HashSet::new();
+    let mut frontier = Frontier::new(frontier_order);
+
+    if let Some(first) = tree.take_nearest([0, 0, 0]) {
+        img.put_pixel(start.x, start.y, image::Rgb { data: first });
+        filled.insert(start);
+        push_open_neighbors(img, &filled, &mut on_frontier, &mut frontier, start);
+    }
+
+    while let Some(pixel) = frontier.pop(img, &filled) {
+        on_frontier.remove(&pixel);
+
+        let target = mean_neighbor_color(img, &filled, pixel);
+        let color = match tree.take_nearest(target) {
+            Some(color) => color,
+            None => break,
+        };
+
+        filled.insert(pixel);
+        img.put_pixel(pixel.x, pixel.y, image::Rgb { data: color });
+
+        push_open_neighbors(img, &filled, &mut on_frontier, &mut frontier, pixel);
+    }
+}
+
+/// A k-d tree over 3D color points supporting a single operation, finding
+/// and removing the point nearest to a query. Removal is lazy: nodes are
+/// just flagged deleted and skipped by later queries, so the tree never
+/// needs rebalancing.
+struct ColorKdTree {
+    points: Vec<[f64; 3]>,
+    colors: Vec<Rgb>,
+    deleted: Vec<bool>,
+    nodes: Vec<KdNode>,
+    root: Option<usize>,
+}
+
+struct KdNode {
+    /// Index into `points`/`colors`/`deleted`.
+    idx: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl ColorKdTree {
+    fn build(colors: Vec<Rgb>) -> Self {
+        let points: Vec<[f64; 3]> = colors
+            .iter()
+            .map(|c| [f64::from(c[0]), f64::from(c[1]), f64::from(c[2])])
+            .collect();
+        let deleted = vec![false; colors.len()];
+
+        let mut indices: Vec<usize> = (0..colors.len()).collect();
+        let mut nodes = Vec::with_capacity(colors.len());
+        let root = Self::build_rec(&points, &mut indices, 0, &mut nodes);
+
+        ColorKdTree {
+            points,
+            colors,
+            deleted,
+            nodes,
+            root,
+        }
+    }
+
+    fn build_rec(
+        points: &[[f64; 3]],
+        indices: &mut [usize],
+        depth: usize,
+        nodes: &mut Vec<KdNode>,
+    ) -> Option<usize> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let axis = depth % 3;
+        indices.sort_by(|&a, &b| points[a][axis].partial_cmp(&points[b][axis]).unwrap());
+
+        let mid = indices.len() / 2;
+        let idx = indices[mid];
+
+        let node = nodes.len();
+        nodes.push(KdNode {
+            idx,
+            left: None,
+            right: None,
+        });
+
+        let (left_indices, rest) = indices.split_at_mut(mid);
+        let right_indices = &mut rest[1..];
+
+        let left = Self::build_rec(points, left_indices, depth + 1, nodes);
+        let right = Self::build_rec(points, right_indices, depth + 1, nodes);
+
+        nodes[node].left = left;
+        nodes[node].right = right;
+
+        Some(node)
+    }
+
+    /// Find, remove and return the color nearest to `target`, or `None` if
+    /// every color has already been taken.
+    fn take_nearest(&mut self, target: Rgb) -> Option<Rgb> {
+        let t = [f64::from(target[0]), f64::from(target[1]), f64::from(target[2])];
+
+        let root = self.root?;
+        let mut best: Option<(usize, f64)> = None;
+        self.nearest_rec(root, &t, 0, &mut best);
+
+        let (idx, _) = best?;
+        self.deleted[idx] = true;
+        Some(self.colors[idx])
+    }
+
+    fn nearest_rec(
+        &self,
+        node: usize,
+        target: &[f64; 3],
+        depth: usize,
+        best: &mut Option<(usize, f64)>,
+    ) {
+        let idx = self.nodes[node].idx;
+
+        if !self.deleted[idx] {
+            let dist = squared_dist(&self.points[idx], target);
+            if best.map_or(true, |(_, best_dist)| dist < best_dist) {
+                *best = Some((idx, dist));
+            }
+        }
+
+        let axis = depth % 3;
+        let diff = target[axis] - self.points[idx][axis];
+        let (near, far) = if diff < 0.0 {
+            (self.nodes[node].left, self.nodes[node].right)
+        } else {
+            (self.nodes[node].right, self.nodes[node].left)
+        };
+
+        if let Some(near) = near {
+            self.nearest_rec(near, target, depth + 1, best);
+        }
+
+        if best.map_or(true, |(_, best_dist)| diff * diff < best_dist) {
+            if let Some(far) = far {
+                self.nearest_rec(far, target, depth + 1, best);
+            }
+        }
+    }
+}
+
+fn squared_dist(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
@@ -0,0 +1,127 @@
+//! Render a [Barnsley fern](https://en.wikipedia.org/wiki/Barnsley_fern)
+//! using the chaos game: starting at the origin, repeatedly apply one of
+//! four affine maps chosen at random with fixed probabilities, and plot
+//! every point visited.
+
+use geo::PointF64;
+
+/// One of the affine maps `(x, y) -> (ax + by + e, cx + dy + f)` used by the
+/// chaos game, together with the probability it should be picked with.
+struct AffineMap {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+    probability: f64,
+}
+
+impl AffineMap {
+    fn apply(&self, p: PointF64) -> PointF64 {
+        PointF64::new(
+            self.a * p.x + self.b * p.y + self.e,
+            self.c * p.x + self.d * p.y + self.f,
+        )
+    }
+}
+
+/// The four affine maps of the classic Barnsley fern, with their probability
+/// of being picked at each step (0.01, 0.85, 0.07, 0.07).
+fn maps() -> [AffineMap; 4] {
+    [
+        AffineMap {
+            a: 0.0,
+            b: 0.0,
+            c: 0.0,
+            d: 0.16,
+            e: 0.0,
+            f: 0.0,
+            probability: 0.01,
+        },
+        AffineMap {
+            a: 0.85,
+            b: 0.04,
+            c: -0.04,
+            d: 0.85,
+            e: 0.0,
+            f: 1.6,
+            probability: 0.85,
+        },
+        AffineMap {
+            a: 0.2,
+            b: -0.26,
+            c: 0.23,
+            d: 0.22,
+            e: 0.0,
+            f: 1.6,
+            probability: 0.07,
+        },
+        AffineMap {
+            a: -0.15,
+            b: 0.28,
+            c: 0.26,
+            d: 0.24,
+            e: 0.0,
+            f: 0.44,
+            probability: 0.07,
+        },
+    ]
+}
+
+/// Run the chaos game for `iterations` steps starting at `(0, 0)` and return
+/// every point visited, in the fern's native coordinate space (roughly `x`
+/// in `[-2.1820, 2.6558]`, `y` in `[0, 9.9983]`).
+pub fn barnsley_fern<R: ::rand::Rng>(rng: &mut R, iterations: u32) -> Vec<PointF64> {
+    let maps = maps();
+
+    let mut p = PointF64::new(0.0, 0.0);
+    let mut points = Vec::with_capacity(iterations as usize);
+
+    for _ in 0..iterations {
+        let mut r = rng.gen_range(0.0, 1.0);
+
+        let map = maps
+            .iter()
+            .find(|m| {
+                if r < m.probability {
+                    true
+                } else {
+                    r -= m.probability;
+                    false
+                }
+            })
+            .unwrap_or(&maps[maps.len() - 1]);
+
+        p = map.apply(p);
+        points.push(p);
+    }
+
+    points
+}
+
+/// Run the chaos game for `iterations` steps and plot every visited point
+/// onto `img`, mapping the fern's native coordinate space onto the image
+/// dimensions.
+pub fn barnsley_fern_to_image<R: ::rand::Rng>(
+    rng: &mut R,
+    iterations: u32,
+    img: &mut image::RgbImage,
+    pix: image::Rgb<u8>,
+) {
+    const MIN_X: f64 = -2.1820;
+    const MAX_X: f64 = 2.6558;
+    const MAX_Y: f64 = 9.9983;
+
+    let width = f64::from(img.width());
+    let height = f64::from(img.height());
+
+    for p in barnsley_fern(rng, iterations) {
+        let x = (p.x - MIN_X) / (MAX_X - MIN_X) * width;
+        let y = height - p.y / MAX_Y * height;
+
+        if x >= 0.0 && x < width && y >= 0.0 && y < height {
+            img.put_pixel(x as u32, y as u32, pix);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Render the [Buddhabrot](https://en.wikipedia.org/wiki/Buddhabrot): rather
+//! than coloring pixels by their own escape time like the usual Mandelbrot
+//! rendering in `julia`, plot a density histogram of every point visited by
+//! orbits that do escape, sampled from random starting parameters `c`.
+
+use num::complex::Complex64;
+use rand::Rng;
+
+use geo::PointF64;
+
+/// Accumulate `samples` random orbits into a `width x height` density
+/// histogram over the `start`-`end` viewport. `max_iterations` both caps how
+/// long an orbit is followed and decides whether it escaped at all: orbits
+/// that never leave `|z| <= 2` within it are discarded entirely, and only
+/// escaping orbits have every one of their intermediate points plotted. This
+/// asymmetry -- plotting points from escaping orbits only -- is what gives
+/// the Buddhabrot its ghostly structure.
+pub fn buddhabrot<R: Rng>(
+    rng: &mut R,
+    (width, height): (u32, u32),
+    start: PointF64,
+    end: PointF64,
+    samples: u32,
+    max_iterations: u32,
+) -> Vec<u32> {
+    let mut histogram = vec![0_u32; (width * height) as usize];
+
+    let to_bin = |z: Complex64| -> Option<usize> {
+        let x = (z.re - start.x) / (end.x - start.x) * f64::from(width);
+        let y = (z.im - start.y) / (end.y - start.y) * f64::from(height);
+
+        if x >= 0.0 && x < f64::from(width) && y >= 0.0 && y < f64::from(height) {
+            Some(y as usize * width as usize + x as usize)
+        } else {
+            None
+        }
+    };
+
+    for _ in 0..samples {
+        let c = Complex64::new(rng.gen_range(start.x, end.x), rng.gen_range(start.y, end.y));
+
+        let mut z = Complex64::new(0.0, 0.0);
+        let mut orbit = Vec::with_capacity(max_iterations as usize);
+        let mut escaped = false;
+
+        for _ in 0..max_iterations {
+            z = z * z + c;
+            orbit.push(z);
+
+            if z.norm() > 2.0 {
+                escaped = true;
+                break;
+            }
+        }
+
+        if !escaped {
+            continue;
+        }
+
+        for p in orbit {
+            if let Some(bin) = to_bin(p) {
+                histogram[bin] += 1;
+            }
+        }
+    }
+
+    histogram
+}
+
+/// Normalize a `width x height` density histogram (e.g. one returned by
+/// `buddhabrot`) into a grayscale image. `log_scale` compresses the huge
+/// dynamic range between the rarely-visited outer wisps and the densely
+/// visited core so both stay visible; without it, only the core shows up.
+pub fn histogram_to_image(
+    histogram: &[u32],
+    (width, height): (u32, u32),
+    log_scale: bool,
+) -> image::GrayImage {
+    let pixels = normalize_histogram(histogram, log_scale);
+
+    image::ImageBuffer::from_raw(width, height, pixels).expect("histogram buffer size mismatch")
+}
+
+/// Render the "Nebulabrot" color variant: run `buddhabrot` once per entry of
+/// `iteration_thresholds` and map each pass's normalized histogram to one RGB
+/// channel, since passes capped at different iteration counts pick out
+/// different orbit structure (e.g. the classic 50/500/5000 split for
+/// red/green/blue).
+pub fn nebulabrot<R: Rng>(
+    rng: &mut R,
+    (width, height): (u32, u32),
+    start: PointF64,
+    end: PointF64,
+    samples: u32,
+    iteration_thresholds: [u32; 3],
+) -> image::RgbImage {
+    let channels = iteration_thresholds.iter().map(|&max_iterations| {
+        let histogram = buddhabrot(rng, (width, height), start, end, samples, max_iterations);
+        normalize_histogram(&histogram, true)
+    });
+
+    let mut pixels = Vec::with_capacity((width * height * 3) as usize);
+    let channels: Vec<Vec<u8>> = channels.collect();
+    for i in 0..(width * height) as usize {
+        for channel in &channels {
+            pixels.push(channel[i]);
+        }
+    }
+
+    image::ImageBuffer::from_raw(width, height, pixels).expect("pixel buffer size mismatch")
+}
+
+// scale every bin of `histogram` into the `0..=255` range relative to its
+// own maximum, optionally through a log compression first.
+fn normalize_histogram(histogram: &[u32], log_scale: bool) -> Vec<u8> {
+    let max = f64::from(histogram.iter().cloned().max().unwrap_or(0));
+
+    histogram
+        .iter()
+        .map(|&v| {
+            if max <= 0.0 {
+                return 0;
+            }
+
+            let v = f64::from(v);
+            let t = if log_scale {
+                (v + 1.0).ln() / (max + 1.0).ln()
+            } else {
+                v / max
+            };
+
+            (t * 255.0) as u8
+        })
+        .collect()
+}
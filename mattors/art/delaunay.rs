@@ -5,21 +5,30 @@ extern crate rand;
 
 extern crate geo;
 
+use std::collections::VecDeque;
+
 use self::rand::Rng;
 
-use self::geo::{delaunay, BoundingBox, PointF64, PointU32};
+use self::geo::{delaunay, BoundingBox, Point, PointF64, PointU32, Triangle};
 
 use color::{random_color, RandomColorConfig};
 use drawing;
 
+use crate::compose::{self, BlendMode};
+use crate::svg::{self, SvgCanvas};
+
 /// Generate a random triangulation and draws it onto the given image. The
 /// points are generated randomly but the image is divided into a grid and each
-/// point is contained in a cell.
+/// point is contained in a cell. Overlapping triangles are composited using
+/// `blend_mode`, so e.g. `Multiply` lets the darker overlaps of translucent
+/// triangles show through instead of the later one simply overwriting the
+/// earlier one.
 pub fn random_triangulation<R: Rng>(
     img: &mut image::RgbaImage,
     color_config: &mut RandomColorConfig<R>,
     grid_size: u32,
     alpha: u8,
+    blend_mode: BlendMode,
 ) {
     let points = random_points_in_grid(img.width(), img.height(), grid_size);
 
@@ -28,23 +37,358 @@ pub fn random_triangulation<R: Rng>(
         points,
     );
 
-    {
-        let mut drawer = drawing::Drawer::new_with_no_blending(img);
+    for triangle in triangles {
+        let style = compose::Style {
+            fill: image::Rgba {
+                data: random_color(color_config).to_rgba(alpha),
+            },
+            blend_mode,
+            clip: None,
+        };
+
+        rasterize_triangle(img, &triangle, &style);
+    }
+}
+
+/// Blend `style.fill` into every pixel of `img` that falls inside
+/// `triangle`.
+fn rasterize_triangle(
+    img: &mut image::RgbaImage,
+    triangle: &Triangle<f64>,
+    style: &compose::Style,
+) {
+    let [p1, p2, p3] = triangle.points;
 
-        for triangle in triangles {
-            let [ref p1, ref p2, ref p3] = triangle.points;
+    let min_x = p1.x.min(p2.x).min(p3.x).max(0.0) as u32;
+    let max_x = p1.x.max(p2.x).max(p3.x).min(f64::from(img.width() - 1)) as u32;
+    let min_y = p1.y.min(p2.y).min(p3.y).max(0.0) as u32;
+    let max_y = p1.y.max(p2.y).max(p3.y).min(f64::from(img.height() - 1)) as u32;
 
-            let p1 = PointU32::new(p1.x.ceil() as u32, p1.y.ceil() as u32);
-            let p2 = PointU32::new(p2.x.ceil() as u32, p2.y.ceil() as u32);
-            let p3 = PointU32::new(p3.x.ceil() as u32, p3.y.ceil() as u32);
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if point_in_triangle(PointF64::new(f64::from(x), f64::from(y)), triangle) {
+                style.composite(img, x, y);
+            }
+        }
+    }
+}
 
-            let pix = image::Rgba {
-                data: random_color(color_config).to_rgba(alpha),
-            };
+/// Same as `random_triangulation`, but returns the triangulation as an
+/// `SvgCanvas` of filled polygons instead of drawing it onto a raster image.
+pub fn random_triangulation_svg<R: Rng>(
+    width: u32,
+    height: u32,
+    color_config: &mut RandomColorConfig<R>,
+    grid_size: u32,
+    alpha: u8,
+) -> SvgCanvas {
+    let points = random_points_in_grid(width, height, grid_size);
+
+    let triangles = delaunay::triangulate(
+        &BoundingBox::from_dimensions(f64::from(width), f64::from(height)),
+        points,
+    );
+
+    let mut canvas = SvgCanvas::new(f64::from(width), f64::from(height));
+
+    for triangle in triangles {
+        let [ref p1, ref p2, ref p3] = triangle.points;
+        let fill_color = random_color(color_config).to_rgba(alpha);
+
+        canvas.polygon(
+            &[*p1, *p2, *p3],
+            &svg::rgba(fill_color[0], fill_color[1], fill_color[2], fill_color[3]),
+        );
+    }
+
+    canvas
+}
+
+/// Generate a low-poly approximation of `img` by adaptively refining a
+/// Delaunay triangulation wherever the image varies the most, instead of
+/// scattering points uniformly like `random_triangulation` does.
+///
+/// Starting from the two triangles that split the image's bounding box,
+/// repeatedly estimate each triangle's interpolation error against the
+/// underlying luminance field (sampled at the centroid and edge midpoints)
+/// and, if it's above `epsilon`, insert a new vertex at the triangle's
+/// longest edge midpoint and re-triangulate. Stops once every triangle is
+/// under `epsilon`, `max_vertices` have been placed, or a triangle has
+/// shrunk below `min_area` (to avoid infinite subdivision at hard edges).
+/// Each final triangle is filled with the average color of the pixels it
+/// covers.
+pub fn adaptive_triangulation(
+    img: &mut image::RgbaImage,
+    epsilon: f64,
+    max_vertices: usize,
+    min_area: f64,
+) {
+    let bounding_box =
+        BoundingBox::from_dimensions(f64::from(img.width()), f64::from(img.height()));
+    let corners = bounding_box.points();
+
+    let mut points = vec![corners[0], corners[1], corners[2], corners[3]];
+    let mut triangles = vec![
+        Triangle::new(corners[0], corners[1], corners[2]),
+        Triangle::new(corners[0], corners[2], corners[3]),
+    ];
+
+    let mut queue: VecDeque<Triangle<f64>> = triangles.iter().cloned().collect();
+
+    while let Some(triangle) = queue.pop_front() {
+        if points.len() >= max_vertices || triangle.area() <= min_area {
+            continue;
+        }
+
+        if interpolation_error(img, &triangle) <= epsilon {
+            continue;
+        }
+
+        let new_point = longest_edge_midpoint(&triangle);
+        if !bounding_box.contains(&new_point) || points.contains(&new_point) {
+            continue;
+        }
+
+        points.push(new_point);
+
+        // re-triangulating from scratch on every insertion is wasteful, but
+        // `delaunay::triangulate` doesn't support incremental updates.
+        triangles = delaunay::triangulate(&bounding_box, points.clone());
+        queue = triangles.iter().cloned().collect();
+    }
+
+    let filled_triangles: Vec<_> = triangles
+        .iter()
+        .map(|triangle| (triangle.clone(), average_color(img, triangle)))
+        .collect();
+
+    let mut drawer = drawing::Drawer::new_with_no_blending(img);
+
+    for (triangle, pix) in filled_triangles {
+        let [p1, p2, p3] = triangle.points;
+
+        let p1 = PointU32::new(p1.x.max(0.0) as u32, p1.y.max(0.0) as u32);
+        let p2 = PointU32::new(p2.x.max(0.0) as u32, p2.y.max(0.0) as u32);
+        let p3 = PointU32::new(p3.x.max(0.0) as u32, p3.y.max(0.0) as u32);
+
+        drawer.triangle(p1, p2, p3, &pix);
+    }
+}
+
+/// Controls how `triangulate_image` picks the points it triangulates.
+pub struct PointSamplingStrategy {
+    /// How many points to place by importance-sampling the image's Sobel
+    /// edge map, biasing towards high-contrast regions.
+    pub edge_points: usize,
+
+    /// Size of a uniform grid of baseline points (see `random_points_in_grid`)
+    /// added on top of the edge points, so flat regions still get covered.
+    pub grid_size: u32,
+}
+
+/// Generate a low-poly, stained-glass-like rendering of `img` by
+/// triangulating a point set biased towards its edges and filling each
+/// triangle with the average color of the source pixels it covers, instead
+/// of the random colors `random_triangulation` uses.
+pub fn triangulate_image(img: &mut image::RgbaImage, strategy: &PointSamplingStrategy) {
+    let bounding_box =
+        BoundingBox::from_dimensions(f64::from(img.width()), f64::from(img.height()));
+
+    let mut points = random_points_in_grid(img.width(), img.height(), strategy.grid_size);
+    points.extend(sobel_weighted_points(img, strategy.edge_points));
+
+    let triangles = delaunay::triangulate(&bounding_box, points);
 
-            drawer.triangle(p1, p2, p3, &pix);
+    let filled_triangles: Vec<_> = triangles
+        .iter()
+        .map(|triangle| (triangle.clone(), average_color(img, triangle)))
+        .collect();
+
+    let mut drawer = drawing::Drawer::new_with_no_blending(img);
+
+    for (triangle, pix) in filled_triangles {
+        let [p1, p2, p3] = triangle.points;
+
+        let p1 = PointU32::new(p1.x.max(0.0) as u32, p1.y.max(0.0) as u32);
+        let p2 = PointU32::new(p2.x.max(0.0) as u32, p2.y.max(0.0) as u32);
+        let p3 = PointU32::new(p3.x.max(0.0) as u32, p3.y.max(0.0) as u32);
+
+        drawer.triangle(p1, p2, p3, &pix);
+    }
+}
+
+/// Pick `n` points by importance-sampling the image's Sobel edge-magnitude
+/// map: the per-pixel gradient magnitude is turned into a probability
+/// distribution and points are drawn weighted towards high-gradient regions,
+/// so edges and fine detail get more triangulation vertices than flat areas.
+fn sobel_weighted_points(img: &image::RgbaImage, n: usize) -> Vec<PointF64> {
+    let (width, height) = img.dimensions();
+
+    let mut weights = Vec::with_capacity((width * height) as usize);
+    let mut total = 0.0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let magnitude = sobel_magnitude(img, x, y);
+            total += magnitude;
+            weights.push(magnitude);
         }
     }
+
+    if total == 0.0 {
+        return vec![];
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut points = Vec::with_capacity(n);
+
+    for _ in 0..n {
+        // linear scan to find which pixel `target` falls into; not as fast
+        // as a binary search over a prefix sum, but `n` and the image are
+        // both small enough that it doesn't matter.
+        let mut target = rng.gen_range(0.0, total);
+        let mut idx = weights.len() - 1;
+
+        for (i, &w) in weights.iter().enumerate() {
+            if target < w {
+                idx = i;
+                break;
+            }
+            target -= w;
+        }
+
+        let x = idx as u32 % width;
+        let y = idx as u32 / width;
+        points.push(PointF64::new(f64::from(x), f64::from(y)));
+    }
+
+    points
+}
+
+// Sobel gradient magnitude of the luminance field at (x, y), clamping
+// out-of-bounds samples to the image edges instead of padding with zeros.
+fn sobel_magnitude(img: &image::RgbaImage, x: u32, y: u32) -> f64 {
+    let (width, height) = img.dimensions();
+
+    let sample = |dx: i64, dy: i64| -> f64 {
+        let sx = (i64::from(x) + dx).max(0).min(i64::from(width) - 1) as u32;
+        let sy = (i64::from(y) + dy).max(0).min(i64::from(height) - 1) as u32;
+
+        luminance(img, PointF64::new(f64::from(sx), f64::from(sy)))
+    };
+
+    let gx = sample(-1, -1) + 2.0 * sample(-1, 0) + sample(-1, 1)
+        - sample(1, -1)
+        - 2.0 * sample(1, 0)
+        - sample(1, 1);
+    let gy = sample(-1, -1) + 2.0 * sample(0, -1) + sample(1, -1)
+        - sample(-1, 1)
+        - 2.0 * sample(0, 1)
+        - sample(1, 1);
+
+    (gx * gx + gy * gy).sqrt()
+}
+
+/// Estimate the error of linearly interpolating the image's luminance across
+/// `triangle`, by comparing the true luminance at the centroid and edge
+/// midpoints against what a linear interpolation of the triangle's vertices
+/// would predict there.
+fn interpolation_error(img: &image::RgbaImage, triangle: &Triangle<f64>) -> f64 {
+    let [p1, p2, p3] = triangle.points;
+    let lums = [luminance(img, p1), luminance(img, p2), luminance(img, p3)];
+
+    let centroid = triangle.centroid();
+    let centroid_error = (luminance(img, centroid) - (lums[0] + lums[1] + lums[2]) / 3.0).abs();
+
+    let edges = [(p1, p2, 0, 1), (p2, p3, 1, 2), (p3, p1, 2, 0)];
+    edges
+        .iter()
+        .map(|&(a, b, ai, bi)| {
+            let midpoint = a.midpoint(&b);
+            (luminance(img, midpoint) - (lums[ai] + lums[bi]) / 2.0).abs()
+        })
+        .fold(centroid_error, f64::max)
+}
+
+/// Return the midpoint of `triangle`'s longest edge, which is where a new
+/// vertex should be inserted to avoid slivers.
+fn longest_edge_midpoint(triangle: &Triangle<f64>) -> PointF64 {
+    let [p1, p2, p3] = triangle.points;
+
+    [(p1, p2), (p2, p3), (p3, p1)]
+        .iter()
+        .map(|&(a, b)| (a, b, a.squared_dist::<f64>(&b)))
+        .fold(
+            None,
+            |best: Option<(Point<f64>, Point<f64>, f64)>, cur| match best {
+                Some(b) if b.2 >= cur.2 => Some(b),
+                _ => Some(cur),
+            },
+        )
+        .map(|(a, b, _)| a.midpoint(&b))
+        .unwrap()
+}
+
+/// Sample the luminance (in `[0, 255]`) of the pixel closest to `p`, clamping
+/// to the image bounds.
+fn luminance(img: &image::RgbaImage, p: PointF64) -> f64 {
+    let x = (p.x.max(0.0) as u32).min(img.width() - 1);
+    let y = (p.y.max(0.0) as u32).min(img.height() - 1);
+
+    let pix = img.get_pixel(x, y).data;
+
+    0.299 * f64::from(pix[0]) + 0.587 * f64::from(pix[1]) + 0.114 * f64::from(pix[2])
+}
+
+/// Return the average color of the pixels within `triangle`'s bounding box
+/// that actually fall inside it.
+fn average_color(img: &image::RgbaImage, triangle: &Triangle<f64>) -> image::Rgba<u8> {
+    let [p1, p2, p3] = triangle.points;
+
+    let min_x = p1.x.min(p2.x).min(p3.x).max(0.0) as u32;
+    let max_x = p1.x.max(p2.x).max(p3.x).min(f64::from(img.width() - 1)) as u32;
+    let min_y = p1.y.min(p2.y).min(p3.y).max(0.0) as u32;
+    let max_y = p1.y.max(p2.y).max(p3.y).min(f64::from(img.height() - 1)) as u32;
+
+    let (mut r, mut g, mut b, mut a, mut n) = (0u64, 0u64, 0u64, 0u64, 0u64);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if !point_in_triangle(PointF64::new(f64::from(x), f64::from(y)), triangle) {
+                continue;
+            }
+
+            let pix = img.get_pixel(x, y).data;
+            r += u64::from(pix[0]);
+            g += u64::from(pix[1]);
+            b += u64::from(pix[2]);
+            a += u64::from(pix[3]);
+            n += 1;
+        }
+    }
+
+    if n == 0 {
+        return image::Rgba {
+            data: [0, 0, 0, 255],
+        };
+    }
+
+    image::Rgba {
+        data: [(r / n) as u8, (g / n) as u8, (b / n) as u8, (a / n) as u8],
+    }
+}
+
+fn point_in_triangle(p: PointF64, triangle: &Triangle<f64>) -> bool {
+    let [p1, p2, p3] = triangle.points;
+
+    let d1 = (p.x - p2.x) * (p1.y - p2.y) - (p1.x - p2.x) * (p.y - p2.y);
+    let d2 = (p.x - p3.x) * (p2.y - p3.y) - (p2.x - p3.x) * (p.y - p3.y);
+    let d3 = (p.x - p1.x) * (p3.y - p1.y) - (p3.x - p1.x) * (p.y - p1.y);
+
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+
+    !(has_neg && has_pos)
 }
 
 fn random_points_in_grid(width: u32, height: u32, grid_size: u32) -> Vec<PointF64> {
@@ -3,11 +3,107 @@
 use image::{GenericImageView, ImageBuffer, Pixel};
 use num::traits::{AsPrimitive, Bounded};
 
-/// Perform [Floyd–Steinberg_dithering][0] over a binary image.
+/// An error-diffusion kernel: a list of `(dx, dy, weight)` taps, relative to
+/// the pixel just quantized, plus the divisor the weights are taken over.
+/// `dither` pushes `weight / divisor` of the quantization error onto each
+/// tap.
+#[derive(Clone, Copy, Debug)]
+pub struct Kernel {
+    /// The `(dx, dy, weight)` taps to distribute the error to.
+    pub taps: &'static [(i32, i32, f32)],
+
+    /// What the taps' weights are a fraction of.
+    pub divisor: f32,
+}
+
+/// The classic [Floyd–Steinberg][0] stencil.
 ///
 /// 0: https://en.wikipedia.org/wiki/Floyd%E2%80%93Steinberg_dithering
+pub const FLOYD_STEINBERG: Kernel = Kernel {
+    taps: &[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)],
+    divisor: 16.0,
+};
+
+/// Bill Atkinson's stencil, as used by the original Macintosh. Its weights
+/// only add up to 6 out of its divisor of 8, so 1/4 of the error is
+/// deliberately discarded instead of diffused, trading accuracy for a
+/// cleaner, higher-contrast look.
+pub const ATKINSON: Kernel = Kernel {
+    taps: &[
+        (1, 0, 1.0),
+        (2, 0, 1.0),
+        (-1, 1, 1.0),
+        (0, 1, 1.0),
+        (1, 1, 1.0),
+        (0, 2, 1.0),
+    ],
+    divisor: 8.0,
+};
+
+/// The Jarvis, Judice & Ninke stencil: a wider spread than Floyd–Steinberg
+/// that trades some sharpness for smoother gradients.
+pub const JARVIS_JUDICE_NINKE: Kernel = Kernel {
+    taps: &[
+        (1, 0, 7.0),
+        (2, 0, 5.0),
+        (-2, 1, 3.0),
+        (-1, 1, 5.0),
+        (0, 1, 7.0),
+        (1, 1, 5.0),
+        (2, 1, 3.0),
+        (-2, 2, 1.0),
+        (-1, 2, 3.0),
+        (0, 2, 5.0),
+        (1, 2, 3.0),
+        (2, 2, 1.0),
+    ],
+    divisor: 48.0,
+};
+
+/// The three-row member of the Sierra family.
+pub const SIERRA: Kernel = Kernel {
+    taps: &[
+        (1, 0, 5.0),
+        (2, 0, 3.0),
+        (-2, 1, 2.0),
+        (-1, 1, 4.0),
+        (0, 1, 5.0),
+        (1, 1, 4.0),
+        (2, 1, 2.0),
+        (-1, 2, 2.0),
+        (0, 2, 3.0),
+        (1, 2, 2.0),
+    ],
+    divisor: 32.0,
+};
+
+/// A cheaper, two-row member of the Sierra family.
+pub const SIERRA_TWO_ROW: Kernel = Kernel {
+    taps: &[
+        (1, 0, 4.0),
+        (2, 0, 3.0),
+        (-2, 1, 1.0),
+        (-1, 1, 2.0),
+        (0, 1, 3.0),
+        (1, 1, 2.0),
+        (2, 1, 1.0),
+    ],
+    divisor: 16.0,
+};
+
+/// The cheapest member of the Sierra family, only spreading error to its
+/// three closest neighbors.
+pub const SIERRA_LITE: Kernel = Kernel {
+    taps: &[(1, 0, 2.0), (-1, 1, 1.0), (0, 1, 1.0)],
+    divisor: 4.0,
+};
+
+/// Perform error-diffusion dithering over an image, pushing each pixel's
+/// quantization error onto its neighbors according to `kernel` (see
+/// `FLOYD_STEINBERG` and friends above).
 pub fn dither<I: GenericImageView>(
     img: &I,
+    kernel: &Kernel,
     mut closest: impl FnMut(&I::Pixel) -> I::Pixel,
 ) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
 where
@@ -35,12 +131,12 @@ where
                 *e = f32::from(*o) - f32::from(*n);
             }
 
-            let mut distribute_err = |(xx, yy), ratio| {
-                if xx >= new.width() || yy >= new.height() {
+            let mut distribute_err = |(xx, yy): (i64, i64), ratio| {
+                if xx < 0 || yy < 0 || xx as u32 >= new.width() || yy as u32 >= new.height() {
                     return;
                 }
 
-                let p = new.get_pixel_mut(xx, yy);
+                let p = new.get_pixel_mut(xx as u32, yy as u32);
                 for (sp, e) in p.channels_mut().iter_mut().zip(&err) {
                     let nsp: f32 = f32::from(*sp) + e * ratio;
 
@@ -51,18 +147,125 @@ where
                 }
             };
 
-            distribute_err((x + 1, y), 7.0 / 16.0);
+            for &(dx, dy, weight) in kernel.taps {
+                distribute_err(
+                    (i64::from(x) + i64::from(dx), i64::from(y) + i64::from(dy)),
+                    weight / kernel.divisor,
+                );
+            }
+
+            new.put_pixel(x, y, new_pixel);
+        }
+    }
 
-            if x > 0 {
-                distribute_err((x - 1, y + 1), 3.0 / 16.0);
+    new
+}
+
+/// Build the recursive Bayer threshold matrix of order `n`, i.e. of size
+/// `2^n x 2^n`, normalized into `[0, 1)`. `M_2n` is built from the standard
+/// recurrence on the `n x n` matrix `M_n`:
+///
+/// ```text
+/// M_2n = | 4*M_n    4*M_n+2 |
+///        | 4*M_n+3  4*M_n+1 |
+/// ```
+fn bayer_matrix(n: u32) -> Vec<Vec<f32>> {
+    let mut m = vec![vec![0u32]];
+
+    for _ in 0..n {
+        let side = m.len();
+        let mut doubled = vec![vec![0u32; side * 2]; side * 2];
+
+        for (y, row) in m.iter().enumerate() {
+            for (x, &v) in row.iter().enumerate() {
+                doubled[y][x] = 4 * v;
+                doubled[y][x + side] = 4 * v + 2;
+                doubled[y + side][x] = 4 * v + 3;
+                doubled[y + side][x + side] = 4 * v + 1;
             }
+        }
 
-            distribute_err((x, y + 1), 5.0 / 16.0);
-            distribute_err((x + 1, y + 1), 5.0 / 16.0);
+        m = doubled;
+    }
 
-            new.put_pixel(x, y, new_pixel);
+    let area = (m.len() * m.len()) as f32;
+
+    m.into_iter()
+        .map(|row| row.into_iter().map(|v| v as f32 / area).collect())
+        .collect()
+}
+
+/// Perform ordered (Bayer-matrix) dithering: rather than diffusing error
+/// between pixels like `dither` does, nudge each pixel's channels by a
+/// per-position threshold drawn from a precomputed `2^n x 2^n` Bayer matrix,
+/// scaled by `spread`, before quantizing with `closest`. This keeps no
+/// running error buffer, trading `dither`'s smoother gradients for ordered
+/// dithering's characteristic crosshatch look.
+pub fn ordered_dither<I: GenericImageView>(
+    img: &I,
+    n: u32,
+    spread: f32,
+    mut closest: impl FnMut(&I::Pixel) -> I::Pixel,
+) -> ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>>
+where
+    I: GenericImageView,
+    I::Pixel: 'static,
+    <I::Pixel as Pixel>::Subpixel: 'static,
+    f32: From<<I::Pixel as Pixel>::Subpixel> + AsPrimitive<<I::Pixel as Pixel>::Subpixel>,
+{
+    let min_value = f32::from(<I::Pixel as Pixel>::Subpixel::min_value());
+    let max_value = f32::from(<I::Pixel as Pixel>::Subpixel::max_value());
+
+    let bayer = bayer_matrix(n);
+    let side = bayer.len();
+
+    let mut new: ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>> =
+        ImageBuffer::new(img.width(), img.height());
+
+    for y in 0..new.height() {
+        for x in 0..new.width() {
+            let threshold = (bayer[y as usize % side][x as usize % side] - 0.5) * spread;
+
+            let mut pixel = img.get_pixel(x, y);
+            for c in pixel.channels_mut() {
+                let nc: f32 = f32::from(*c) + threshold;
+                *c = nc.max(min_value).min(max_value).as_();
+            }
+
+            new.put_pixel(x, y, closest(&pixel));
         }
     }
 
     new
 }
+
+#[cfg(test)]
+mod tests {
+    use super::bayer_matrix;
+
+    #[test]
+    fn test_bayer_matrix_order_1() {
+        assert_eq!(
+            bayer_matrix(1),
+            vec![vec![0.0, 2.0 / 4.0], vec![3.0 / 4.0, 1.0 / 4.0]]
+        );
+    }
+
+    #[test]
+    fn test_bayer_matrix_order_2() {
+        let m = bayer_matrix(2);
+
+        assert_eq!(m.len(), 4);
+        assert!(m.iter().all(|row| row.len() == 4));
+
+        // every entry should be a distinct multiple of 1/16, since the
+        // matrix is meant to be a permutation of 0..16.
+        let mut values = m
+            .iter()
+            .flat_map(|row| row.iter().map(|v| (v * 16.0).round() as u32))
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+
+        assert_eq!(values, (0..16).collect::<Vec<_>>());
+    }
+}
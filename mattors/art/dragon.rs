@@ -32,6 +32,17 @@ impl Move {
             Move::Up => Move::Right,
         }
     }
+
+    /// return the move that is obtained by rotating the current move in
+    /// counter-clockwise order, i.e. the inverse of `clockwise`.
+    pub fn counter_clockwise(&self) -> Move {
+        match *self {
+            Move::Left => Move::Down,
+            Move::Up => Move::Left,
+            Move::Down => Move::Right,
+            Move::Right => Move::Up,
+        }
+    }
 }
 
 /// A [Dragon Fractal](https://en.wikipedia.org/wiki/Dragon_curve).
@@ -75,6 +86,56 @@ pub fn horns(n: u32, initial: Move) -> Dragon {
     Dragon(moves)
 }
 
+/// Generate an order-`n` [Hilbert
+/// curve](https://en.wikipedia.org/wiki/Hilbert_curve), starting off heading
+/// `initial`, as a flattened `Dragon` that plugs into `dragon_to_image`
+/// unchanged. Built with the standard recursive turtle rule: an order-n
+/// curve is four order-(n-1) sub-curves joined by 3 connector moves, where
+/// the first and last sub-curves are traversed with their notion of
+/// "turn left"/"turn right" swapped relative to the middle two.
+pub fn hilbert(n: u32, initial: Move) -> Dragon {
+    let mut moves = Vec::with_capacity(4_usize.pow(n));
+    let mut heading = initial;
+
+    hilbert_rec(n, false, &mut heading, &mut moves);
+
+    Dragon(moves)
+}
+
+// `flipped` tracks whether "turn right"/"turn left" are swapped for this
+// sub-curve, which is what makes the four quadrants connect into a single
+// continuous, non-self-crossing path instead of 4 separate copies.
+fn hilbert_rec(n: u32, flipped: bool, heading: &mut Move, moves: &mut Vec<Move>) {
+    if n == 0 {
+        return;
+    }
+
+    turn(heading, !flipped);
+    hilbert_rec(n - 1, !flipped, heading, moves);
+
+    moves.push(heading.clone());
+    turn(heading, flipped);
+    hilbert_rec(n - 1, flipped, heading, moves);
+
+    moves.push(heading.clone());
+    hilbert_rec(n - 1, flipped, heading, moves);
+
+    turn(heading, flipped);
+    moves.push(heading.clone());
+    hilbert_rec(n - 1, !flipped, heading, moves);
+
+    turn(heading, !flipped);
+}
+
+// turn `heading` 90° to the right, or to the left if `right` is false.
+fn turn(heading: &mut Move, right: bool) {
+    *heading = if right {
+        heading.clockwise()
+    } else {
+        heading.counter_clockwise()
+    };
+}
+
 /// Generate a [Dragon Fractal](https://en.wikipedia.org/wiki/Dragon_curve) and
 /// dump it to an image with the given color.
 pub fn dragon_to_image(
@@ -85,6 +146,31 @@ pub fn dragon_to_image(
     start_y: u32,
     line_len: u32,
     rgb_color: [u8; 3],
+) -> image::RgbImage {
+    dragon_to_image_styled(
+        drag,
+        width,
+        height,
+        start_x,
+        start_y,
+        line_len,
+        rgb_color,
+        &drawing::StrokeStyle::Solid,
+    )
+}
+
+/// Same as `dragon_to_image`, but strokes each move with `style` instead of a
+/// continuous line, so dashed and dotted fractal traces can be produced
+/// without post-processing.
+pub fn dragon_to_image_styled(
+    drag: &Dragon,
+    width: u32,
+    height: u32,
+    start_x: u32,
+    start_y: u32,
+    line_len: u32,
+    rgb_color: [u8; 3],
+    style: &drawing::StrokeStyle,
 ) -> image::RgbImage {
     // TODO: might be interesting to add [perlin
     // noise](https://en.wikipedia.org/wiki/Perlin_noise)
@@ -98,7 +184,7 @@ pub fn dragon_to_image(
         let mut x = start_x;
         let mut y = start_y;
 
-        for m in &drag.0 {
+        for (i, m) in drag.0.iter().enumerate() {
             let (nx, ny) = {
                 match *m {
                     Move::Down => (x, y.saturating_add(line_len)),
@@ -108,7 +194,10 @@ pub fn dragon_to_image(
                 }
             };
 
-            drawer.line(PointU32::new(x, y), PointU32::new(nx, ny), &pix);
+            // phase-shift every other move so the pattern doesn't visibly
+            // reset at each turn of the fractal.
+            let first_on = i % 2 == 0;
+            drawer.line_styled(PointU32::new(x, y), PointU32::new(nx, ny), &pix, style, first_on);
 
             x = nx;
             y = ny;
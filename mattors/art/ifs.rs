@@ -0,0 +1,136 @@
+//! Render [iterated function system](https://en.wikipedia.org/wiki/Iterated_function_system)
+//! attractors via the chaos game: repeatedly pick one of a handful of affine
+//! maps at random, weighted by probability, and move a point through it,
+//! plotting every point visited after a short burn-in. `barnsley_fern`
+//! hardcodes its own four maps and output coordinate range; this module
+//! generalizes the same chaos-game loop to an arbitrary set of maps whose
+//! attractor's bounding box is auto-fit to the output canvas, so custom
+//! attractors supplied at runtime render just as well as the built-in fern.
+
+use geo::bbox::BoundingBox;
+use geo::PointF64;
+
+/// One of the affine maps `(x, y) -> (ax + by + e, cx + dy + f)` used by the
+/// chaos game, together with the probability it should be picked with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AffineMap {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+    probability: f64,
+}
+
+impl AffineMap {
+    /// Build a map from its six affine coefficients and the probability it
+    /// should be picked with at each step of the chaos game.
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64, probability: f64) -> Self {
+        AffineMap {
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            probability,
+        }
+    }
+
+    fn apply(&self, p: PointF64) -> PointF64 {
+        PointF64::new(
+            self.a * p.x + self.b * p.y + self.e,
+            self.c * p.x + self.d * p.y + self.f,
+        )
+    }
+}
+
+/// The four affine maps of the classic Barnsley fern, with their probability
+/// of being picked at each step (0.01, 0.85, 0.07, 0.07).
+pub fn barnsley_fern_maps() -> Vec<AffineMap> {
+    vec![
+        AffineMap::new(0.0, 0.0, 0.0, 0.16, 0.0, 0.0, 0.01),
+        AffineMap::new(0.85, 0.04, -0.04, 0.85, 0.0, 1.6, 0.85),
+        AffineMap::new(0.2, -0.26, 0.23, 0.22, 0.0, 1.6, 0.07),
+        AffineMap::new(-0.15, 0.28, 0.26, 0.24, 0.0, 0.44, 0.07),
+    ]
+}
+
+/// Run the chaos game for `iterations` steps starting at the origin, picking
+/// a map at each step according to its `probability` (falling back to the
+/// last map if rounding error leaves a tiny residual probability
+/// unaccounted for). The first `burn_in` points are discarded rather than
+/// returned: starting at an arbitrary point, the chaos game needs a few
+/// steps to settle onto the attractor, and plotting those would leave a
+/// stray streak leading into it.
+pub fn run<R: ::rand::Rng>(
+    rng: &mut R,
+    maps: &[AffineMap],
+    iterations: u32,
+    burn_in: u32,
+) -> Vec<PointF64> {
+    assert!(!maps.is_empty(), "run: at least one affine map is needed");
+
+    let mut p = PointF64::new(0.0, 0.0);
+    let mut points = Vec::with_capacity(iterations.saturating_sub(burn_in) as usize);
+
+    for i in 0..iterations {
+        let mut r = rng.gen_range(0.0, 1.0);
+
+        let map = maps
+            .iter()
+            .find(|m| {
+                if r < m.probability {
+                    true
+                } else {
+                    r -= m.probability;
+                    false
+                }
+            })
+            .unwrap_or(&maps[maps.len() - 1]);
+
+        p = map.apply(p);
+
+        if i >= burn_in {
+            points.push(p);
+        }
+    }
+
+    points
+}
+
+/// Plot `points` onto a `width x height` black image, auto-fitting their
+/// bounding box to the canvas (preserving aspect ratio, so the attractor
+/// isn't stretched) with a small margin on every side.
+pub fn to_image(
+    points: &[PointF64],
+    (width, height): (u32, u32),
+    pix: image::Rgb<u8>,
+) -> image::RgbImage {
+    let mut img = image::RgbImage::from_pixel(width, height, image::Rgb { data: [0, 0, 0] });
+
+    let bbox = BoundingBox::from_points(points);
+    let (bbox_width, bbox_height) = match bbox.dimensions() {
+        Some(dims) if dims.0 > 0.0 && dims.1 > 0.0 => dims,
+        _ => return img,
+    };
+
+    const MARGIN: f64 = 0.05;
+    let scale = (1.0 - 2.0 * MARGIN)
+        * (f64::from(width) / bbox_width).min(f64::from(height) / bbox_height);
+
+    let offset_x = (f64::from(width) - bbox_width * scale) / 2.0;
+    let offset_y = (f64::from(height) - bbox_height * scale) / 2.0;
+
+    for p in points {
+        let x = offset_x + (p.x - bbox.min().x) * scale;
+        let y = f64::from(height) - (offset_y + (p.y - bbox.min().y) * scale);
+
+        if x >= 0.0 && x < f64::from(width) && y >= 0.0 && y < f64::from(height) {
+            img.put_pixel(x as u32, y as u32, pix);
+        }
+    }
+
+    img
+}
@@ -2,15 +2,21 @@
 //! Set](https://en.wikipedia.org/wiki/Julia_set). The most famous one is
 //! probably the [Mandelbrot Set](https://en.wikipedia.org/wiki/Mandelbrot_set).
 
+use std::collections::HashMap;
 use std::iter::Iterator;
 
+use indicatif::{ProgressBar, ProgressStyle};
 use num::complex::Complex64;
+use rayon::prelude::*;
 
-use geo::PointF64;
+use geo::{PointF64, PointU32};
+
+use crate::drawing::Drawer;
+use crate::export::stl::{self, Triangle3, Vertex3};
 
 /// This struct is mainly used to pass some data used when converting to raw
 /// pixels.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct FractalPoint {
     is_inside: bool,
     last_value: f64,
@@ -50,6 +56,75 @@ impl FractalPoint {
         }
     }
 
+    /// Calculate if the given `f` (that is, the point) is in the
+    /// [Tricorn](https://en.wikipedia.org/wiki/Tricorn_(mathematics)) set.
+    /// Same escape-time recurrence as `mandelbrot`/`julia`, except the
+    /// imaginary part of `z` is conjugated before squaring:
+    /// `z_{n+1} = conj(z_n)^2 + c`.
+    pub fn tricorn(f: Complex64, iterations: u32) -> FractalPoint {
+        let mut z = f;
+        let mut is_inside = true;
+        let mut i = 0;
+
+        while i < iterations {
+            z = z.conj() * z.conj() + f;
+
+            if z.norm() > 2.0 {
+                is_inside = false;
+                break;
+            }
+
+            i += 1;
+        }
+
+        FractalPoint {
+            last_value: z.norm(),
+            iterations: i,
+            is_inside,
+        }
+    }
+
+    /// Calculate if the given `f` (that is, the point) is in the [Burning
+    /// Ship](https://en.wikipedia.org/wiki/Burning_Ship_fractal) set. Same
+    /// escape-time recurrence as `mandelbrot`/`julia`, except the absolute
+    /// value of each component of `z` is taken before squaring:
+    /// `z_{n+1} = (|Re z_n| + i|Im z_n|)^2 + c`.
+    pub fn burning_ship(f: Complex64, iterations: u32) -> FractalPoint {
+        let mut z = f;
+        let mut is_inside = true;
+        let mut i = 0;
+
+        while i < iterations {
+            let abs_z = Complex64::new(z.re.abs(), z.im.abs());
+            z = abs_z * abs_z + f;
+
+            if z.norm() > 2.0 {
+                is_inside = false;
+                break;
+            }
+
+            i += 1;
+        }
+
+        FractalPoint {
+            last_value: z.norm(),
+            iterations: i,
+            is_inside,
+        }
+    }
+
+    /// A scalar "escape value" for this point, suitable for treating a grid
+    /// of `FractalPoint`s as a scalar field: points inside the set have the
+    /// highest possible value (the iteration limit never reached), points
+    /// outside have the iteration count at which they escaped.
+    pub fn escape_value(&self, max_iterations: u32) -> f64 {
+        if self.is_inside {
+            f64::from(max_iterations)
+        } else {
+            f64::from(self.iterations)
+        }
+    }
+
     fn to_pixels(&self) -> Vec<u8> {
         if self.is_inside {
             vec![
@@ -68,6 +143,117 @@ impl FractalPoint {
             ]
         }
     }
+
+    /// The fractional ("smooth") escape value for this point: `n + 1 -
+    /// ln(ln(|z_n|)) / ln(2)`, which varies continuously across the
+    /// boundary between whole iteration counts instead of jumping by a full
+    /// integer there, removing the banding a raw iteration count produces
+    /// when used to look up a color. Points that never escaped just return
+    /// `max_iterations` like `escape_value`, since there's no orbit left to
+    /// smooth over.
+    pub fn smooth_escape_value(&self, max_iterations: u32) -> f64 {
+        if self.is_inside {
+            return self.escape_value(max_iterations);
+        }
+
+        f64::from(self.iterations) + 1.0 - (self.last_value.ln().ln() / 2.0_f64.ln())
+    }
+
+    // color this point via `palette`, optionally smoothing the lookup
+    // parameter with `smooth_escape_value` instead of the bare iteration
+    // count. Points inside the set are always rendered solid black,
+    // regardless of `palette`, matching the usual convention for the set's
+    // interior.
+    fn to_pixels_styled(&self, max_iterations: u32, smooth: bool, palette: &Palette) -> Vec<u8> {
+        if self.is_inside {
+            return vec![0, 0, 0];
+        }
+
+        let value = if smooth {
+            self.smooth_escape_value(max_iterations)
+        } else {
+            f64::from(self.iterations)
+        };
+
+        let t = (value / f64::from(max_iterations)).max(0.0).min(1.0);
+
+        palette.color_at(t).to_vec()
+    }
+}
+
+/// A gradient of RGB colors used to map a normalized scalar in `[0, 1]`
+/// (typically a fractal's smooth escape value) onto a color, by linearly
+/// interpolating between the two `stops` surrounding it.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    // `(position, color)` pairs, sorted by ascending position.
+    stops: Vec<(f64, [u8; 3])>,
+}
+
+impl Palette {
+    /// Build a palette from its gradient stops, e.g. `(0.0, [0, 0, 0])` for
+    /// black at the start of the gradient. `stops` need not be pre-sorted.
+    pub fn new(mut stops: Vec<(f64, [u8; 3])>) -> Palette {
+        stops.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+        Palette { stops }
+    }
+
+    /// Look up the color at `t`, clamped to the first/last stop's color
+    /// outside `[stops[0].0, stops[last].0]`, by linearly interpolating
+    /// between the two stops surrounding it.
+    pub fn color_at(&self, t: f64) -> [u8; 3] {
+        let last = match self.stops.len() {
+            0 => return [0, 0, 0],
+            n => n - 1,
+        };
+
+        if t <= self.stops[0].0 {
+            return self.stops[0].1;
+        }
+
+        if t >= self.stops[last].0 {
+            return self.stops[last].1;
+        }
+
+        let i = self.stops.iter().position(|&(pos, _)| pos > t).unwrap();
+        let (p0, c0) = self.stops[i - 1];
+        let (p1, c1) = self.stops[i];
+        let frac = (t - p0) / (p1 - p0);
+
+        let mut color = [0; 3];
+        for (ch, out) in color.iter_mut().enumerate() {
+            *out = (f64::from(c0[ch]) + (f64::from(c1[ch]) - f64::from(c0[ch])) * frac) as u8;
+        }
+
+        color
+    }
+
+    /// Black to white.
+    pub fn grayscale() -> Palette {
+        Palette::new(vec![(0.0, [0, 0, 0]), (1.0, [255, 255, 255])])
+    }
+
+    /// Black, through deep red, orange and yellow, to white -- glowing
+    /// embers.
+    pub fn fire() -> Palette {
+        Palette::new(vec![
+            (0.0, [0, 0, 0]),
+            (0.3, [128, 0, 0]),
+            (0.6, [255, 128, 0]),
+            (0.85, [255, 255, 0]),
+            (1.0, [255, 255, 255]),
+        ])
+    }
+
+    /// Deep blue, through cyan, to white -- glacial ice.
+    pub fn ice() -> Palette {
+        Palette::new(vec![
+            (0.0, [0, 0, 40]),
+            (0.4, [0, 80, 160]),
+            (0.75, [120, 220, 255]),
+            (1.0, [255, 255, 255]),
+        ])
+    }
 }
 
 /// Iterator that returns all the `FractalPoint`
@@ -122,6 +308,136 @@ impl<F: Fn(Complex64, u32) -> FractalPoint> JuliaGenIter<F> {
 
         image::ImageBuffer::from_raw(width, height, self.flat_map(|pt| pt.to_pixels()).collect())
     }
+
+    /// Same as `into_image`, but colors each point via `palette` instead of
+    /// the fixed two-tone gradient `into_image` uses, optionally smoothing
+    /// the lookup parameter (see `FractalPoint::smooth_escape_value`) to
+    /// avoid banding at whole iteration-count boundaries.
+    pub fn into_image_styled(
+        self,
+        smooth: bool,
+        palette: &Palette,
+    ) -> Option<image::ImageBuffer<image::Rgb<u8>, Vec<u8>>> {
+        let width = self.xcount;
+        let height = self.ycount;
+        let max_iterations = self.iterations;
+
+        image::ImageBuffer::from_raw(
+            width,
+            height,
+            self.flat_map(|pt| pt.to_pixels_styled(max_iterations, smooth, palette))
+                .collect(),
+        )
+    }
+
+    /// Consume the `JuliaGenIter` and collect its per-pixel escape value
+    /// (see `FractalPoint::escape_value`) into an `xcount x ycount` grid,
+    /// indexed `grid[x][y]`, suitable for treating the fractal as a scalar
+    /// height field.
+    pub fn into_heightmap(self) -> Vec<Vec<f64>> {
+        let width = self.xcount as usize;
+        let height = self.ycount as usize;
+        let max_iterations = self.iterations;
+
+        let mut grid = vec![vec![0.0; height]; width];
+        for (i, pt) in self.enumerate() {
+            grid[i % width][i / width] = pt.escape_value(max_iterations);
+        }
+
+        grid
+    }
+
+    /// Consume the `JuliaGenIter` and collect its `FractalPoint`s into an
+    /// `xcount x ycount` grid, indexed `grid[x][y]`, suitable for passing to
+    /// `march_squares`.
+    pub fn into_grid(self) -> Vec<Vec<FractalPoint>> {
+        let width = self.xcount as usize;
+        let height = self.ycount as usize;
+
+        let mut grid = vec![vec![FractalPoint::default(); height]; width];
+        for (i, pt) in self.enumerate() {
+            grid[i % width][i / width] = pt;
+        }
+
+        grid
+    }
+}
+
+impl<F: Fn(Complex64, u32) -> FractalPoint + Sync> JuliaGenIter<F> {
+    /// Same as `into_image`, but renders each row on a rayon thread pool
+    /// instead of serially on this thread -- every pixel's escape
+    /// computation only reads `self`, so rows can be computed in any order
+    /// -- and reports a row-granularity progress bar while it works, since
+    /// high resolutions/iteration counts can otherwise run for minutes with
+    /// no feedback.
+    pub fn par_into_image(&self) -> Option<image::ImageBuffer<image::Rgb<u8>, Vec<u8>>> {
+        let width = self.xcount;
+        let height = self.ycount;
+
+        let bar = ProgressBar::new(u64::from(height));
+        let style = ProgressStyle::default_bar().template("{bar:40} {pos}/{len} rows ({eta} left)");
+        bar.set_style(style);
+
+        let pixels: Vec<u8> = (0..height)
+            .into_par_iter()
+            .flat_map(|y| {
+                let row: Vec<u8> = (0..width)
+                    .flat_map(|x| {
+                        let cx = self.start.x + f64::from(x) * self.stepx;
+                        let cy = self.start.y + f64::from(y) * self.stepy;
+
+                        (self.gen_fn)(Complex64::new(cx, cy), self.iterations).to_pixels()
+                    })
+                    .collect();
+
+                bar.inc(1);
+                row
+            })
+            .collect();
+
+        bar.finish();
+
+        image::ImageBuffer::from_raw(width, height, pixels)
+    }
+
+    /// Same as `par_into_image`, but colors each point via `palette` (see
+    /// `JuliaGenIter::into_image_styled`) instead of the fixed two-tone
+    /// gradient.
+    pub fn par_into_image_styled(
+        &self,
+        smooth: bool,
+        palette: &Palette,
+    ) -> Option<image::ImageBuffer<image::Rgb<u8>, Vec<u8>>> {
+        let width = self.xcount;
+        let height = self.ycount;
+        let max_iterations = self.iterations;
+
+        let bar = ProgressBar::new(u64::from(height));
+        let style = ProgressStyle::default_bar().template("{bar:40} {pos}/{len} rows ({eta} left)");
+        bar.set_style(style);
+
+        let pixels: Vec<u8> = (0..height)
+            .into_par_iter()
+            .flat_map(|y| {
+                let row: Vec<u8> = (0..width)
+                    .flat_map(|x| {
+                        let cx = self.start.x + f64::from(x) * self.stepx;
+                        let cy = self.start.y + f64::from(y) * self.stepy;
+
+                        (self.gen_fn)(Complex64::new(cx, cy), self.iterations)
+                            .to_pixels_styled(max_iterations, smooth, palette)
+                    })
+                    .collect();
+
+                bar.inc(1);
+                row
+            })
+            .collect();
+
+        bar.finish();
+
+        image::ImageBuffer::from_raw(width, height, pixels)
+    }
 }
 
 impl<F: Fn(Complex64, u32) -> FractalPoint> Iterator for JuliaGenIter<F> {
@@ -147,6 +463,270 @@ impl<F: Fn(Complex64, u32) -> FractalPoint> Iterator for JuliaGenIter<F> {
     }
 }
 
+/// The 4 edges of a marching-squares cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellEdge {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// Extract iso-contours (as chains of `PointF64`) from a scalar field sampled
+/// on an `xcount * ycount` grid, using the classic [marching
+/// squares](https://en.wikipedia.org/wiki/Marching_squares) algorithm. `grid`
+/// is indexed `grid[x][y]`, matching the layout produced by `gen_fractal`.
+/// One set of polylines is returned per value in `iso_values`, in the same
+/// order.
+pub fn march_squares(
+    grid: &[Vec<FractalPoint>],
+    max_iterations: u32,
+    start: PointF64,
+    stepx: f64,
+    stepy: f64,
+    iso_values: &[f64],
+) -> Vec<Vec<Vec<PointF64>>> {
+    let xcount = grid.len();
+    let ycount = if xcount == 0 { 0 } else { grid[0].len() };
+
+    let value = |x: usize, y: usize| grid[x][y].escape_value(max_iterations);
+    let position = |x: usize, y: usize| {
+        PointF64::new(
+            start.x + f64::from(x as u32) * stepx,
+            start.y + f64::from(y as u32) * stepy,
+        )
+    };
+
+    let interpolate = |a: (PointF64, f64), b: (PointF64, f64), iso: f64| -> PointF64 {
+        let t = if (b.1 - a.1).abs() < ::std::f64::EPSILON {
+            0.5
+        } else {
+            (iso - a.1) / (b.1 - a.1)
+        };
+
+        PointF64::new(a.0.x + t * (b.0.x - a.0.x), a.0.y + t * (b.0.y - a.0.y))
+    };
+
+    iso_values
+        .iter()
+        .map(|&iso| {
+            let mut segments = vec![];
+
+            if xcount < 2 || ycount < 2 {
+                return vec![];
+            }
+
+            for x in 0..xcount - 1 {
+                for y in 0..ycount - 1 {
+                    let tl = (position(x, y), value(x, y));
+                    let tr = (position(x + 1, y), value(x + 1, y));
+                    let br = (position(x + 1, y + 1), value(x + 1, y + 1));
+                    let bl = (position(x, y + 1), value(x, y + 1));
+
+                    let case = (tl.1 > iso) as u8
+                        | ((tr.1 > iso) as u8) << 1
+                        | ((br.1 > iso) as u8) << 2
+                        | ((bl.1 > iso) as u8) << 3;
+
+                    let center = (tl.1 + tr.1 + br.1 + bl.1) / 4.0;
+
+                    let edge_point = |edge: CellEdge| match edge {
+                        CellEdge::North => interpolate(tl, tr, iso),
+                        CellEdge::East => interpolate(tr, br, iso),
+                        CellEdge::South => interpolate(bl, br, iso),
+                        CellEdge::West => interpolate(tl, bl, iso),
+                    };
+
+                    use self::CellEdge::*;
+
+                    let pairs: &[(CellEdge, CellEdge)] = match case {
+                        0 | 15 => &[],
+                        1 | 14 => &[(West, North)],
+                        2 | 13 => &[(North, East)],
+                        3 | 12 => &[(West, East)],
+                        4 | 11 => &[(East, South)],
+                        6 | 9 => &[(North, South)],
+                        7 | 8 => &[(West, South)],
+                        5 => {
+                            if center > iso {
+                                &[(West, North), (East, South)]
+                            } else {
+                                &[(West, South), (North, East)]
+                            }
+                        }
+                        10 => {
+                            if center > iso {
+                                &[(North, East), (West, South)]
+                            } else {
+                                &[(West, North), (East, South)]
+                            }
+                        }
+                        _ => unreachable!(),
+                    };
+
+                    for &(e1, e2) in pairs {
+                        segments.push((edge_point(e1), edge_point(e2)));
+                    }
+                }
+            }
+
+            stitch_segments(segments)
+        })
+        .collect()
+}
+
+/// Round a coordinate to a fixed precision so that near-identical floating
+/// point endpoints from two adjacent cells are recognized as the same point.
+fn point_key(p: PointF64) -> (i64, i64) {
+    ((p.x * 1e6).round() as i64, (p.y * 1e6).round() as i64)
+}
+
+/// Stitch a bag of disconnected segments into the longest possible chains by
+/// repeatedly following segments that share an endpoint.
+fn stitch_segments(segments: Vec<(PointF64, PointF64)>) -> Vec<Vec<PointF64>> {
+    let mut by_endpoint: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        by_endpoint.entry(point_key(a)).or_insert_with(Vec::new).push(i);
+        by_endpoint.entry(point_key(b)).or_insert_with(Vec::new).push(i);
+    }
+
+    let mut visited = vec![false; segments.len()];
+    let mut chains = vec![];
+
+    let next_unvisited_at = |visited: &[bool], key: (i64, i64)| -> Option<usize> {
+        by_endpoint
+            .get(&key)
+            .and_then(|ids| ids.iter().cloned().find(|&i| !visited[i]))
+    };
+
+    for start in 0..segments.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let (a, b) = segments[start];
+        let mut chain = vec![a, b];
+
+        // extend forward from the tail
+        loop {
+            let tail = *chain.last().unwrap();
+            match next_unvisited_at(&visited, point_key(tail)) {
+                Some(i) => {
+                    visited[i] = true;
+                    let (sa, sb) = segments[i];
+                    chain.push(if point_key(sa) == point_key(tail) { sb } else { sa });
+                }
+                None => break,
+            }
+        }
+
+        // extend backward from the head
+        loop {
+            let head = chain[0];
+            match next_unvisited_at(&visited, point_key(head)) {
+                Some(i) => {
+                    visited[i] = true;
+                    let (sa, sb) = segments[i];
+                    chain.insert(0, if point_key(sa) == point_key(head) { sb } else { sa });
+                }
+                None => break,
+            }
+        }
+
+        chains.push(chain);
+    }
+
+    chains
+}
+
+/// Stroke the given contour polylines onto `img` using the given `pix`el.
+pub fn draw_contours<I, B>(drawer: &mut Drawer<I, B>, contours: &[Vec<PointF64>], pix: &I::Pixel)
+where
+    I: ::image::GenericImage,
+    I::Pixel: ::std::fmt::Debug,
+    B: crate::drawing::Blender<I::Pixel>,
+{
+    for contour in contours {
+        for window in contour.windows(2) {
+            let start = PointU32::new(window[0].x.max(0.0) as u32, window[0].y.max(0.0) as u32);
+            let end = PointU32::new(window[1].x.max(0.0) as u32, window[1].y.max(0.0) as u32);
+
+            drawer.line(start, end, pix);
+        }
+    }
+}
+
+/// Build a watertight triangle mesh from a `width x height` scalar height
+/// field such as the one returned by `JuliaGenIter::into_heightmap`, indexed
+/// `heights[x][y]`, so an escape-time fractal can be 3D-printed: each grid
+/// cell's corners are extruded from `0` up to `heights[x][y] / max_height *
+/// z_scale`, giving two triangles per cell on top, a flat mirrored bottom,
+/// and side walls around the perimeter so the mesh has no open edges.
+pub fn heightmap_mesh(
+    heights: &[Vec<f64>],
+    max_height: f64,
+    cell_size: f64,
+    z_scale: f64,
+) -> Vec<Triangle3> {
+    let width = heights.len();
+    let depth = if width == 0 { 0 } else { heights[0].len() };
+
+    if width < 2 || depth < 2 {
+        return vec![];
+    }
+
+    let z_of = |x: usize, y: usize| {
+        if max_height <= 0.0 {
+            0.0
+        } else {
+            heights[x][y] / max_height * z_scale
+        }
+    };
+    let top = |x: usize, y: usize| Vertex3::new(x as f64 * cell_size, y as f64 * cell_size, z_of(x, y));
+    let bottom = |x: usize, y: usize| Vertex3::new(x as f64 * cell_size, y as f64 * cell_size, 0.0);
+
+    let mut triangles = vec![];
+
+    for x in 0..width - 1 {
+        for y in 0..depth - 1 {
+            let (tl, tr, bl, br) = (top(x, y), top(x + 1, y), top(x, y + 1), top(x + 1, y + 1));
+            triangles.push(stl::make_triangle(tl, bl, tr));
+            triangles.push(stl::make_triangle(tr, bl, br));
+
+            let (btl, btr, bbl, bbr) = (
+                bottom(x, y),
+                bottom(x + 1, y),
+                bottom(x, y + 1),
+                bottom(x + 1, y + 1),
+            );
+            triangles.push(stl::make_triangle(btl, btr, bbl));
+            triangles.push(stl::make_triangle(btr, bbr, bbl));
+        }
+    }
+
+    // side walls along the 4 edges of the grid, facing outward.
+    for x in 0..width - 1 {
+        triangles.push(stl::make_triangle(top(x, 0), top(x + 1, 0), bottom(x, 0)));
+        triangles.push(stl::make_triangle(top(x + 1, 0), bottom(x + 1, 0), bottom(x, 0)));
+
+        let y = depth - 1;
+        triangles.push(stl::make_triangle(top(x, y), bottom(x, y), top(x + 1, y)));
+        triangles.push(stl::make_triangle(top(x + 1, y), bottom(x, y), bottom(x + 1, y)));
+    }
+    for y in 0..depth - 1 {
+        triangles.push(stl::make_triangle(top(0, y), bottom(0, y), top(0, y + 1)));
+        triangles.push(stl::make_triangle(top(0, y + 1), bottom(0, y), bottom(0, y + 1)));
+
+        let x = width - 1;
+        triangles.push(stl::make_triangle(top(x, y), top(x, y + 1), bottom(x, y)));
+        triangles.push(stl::make_triangle(top(x, y + 1), bottom(x, y + 1), bottom(x, y)));
+    }
+
+    triangles
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,4 +746,18 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn heightmap_mesh_is_empty_for_degenerate_grids() {
+        assert_eq!(heightmap_mesh(&[], 1.0, 1.0, 1.0).len(), 0);
+        assert_eq!(heightmap_mesh(&[vec![0.0]], 1.0, 1.0, 1.0).len(), 0);
+    }
+
+    #[test]
+    fn heightmap_mesh_is_watertight_for_a_single_cell() {
+        let heights = vec![vec![1.0, 1.0], vec![1.0, 1.0]];
+
+        // 2 top + 2 bottom + 4 * 2 side triangles for the single cell's 4 edges.
+        assert_eq!(heightmap_mesh(&heights, 1.0, 1.0, 1.0).len(), 12);
+    }
 }
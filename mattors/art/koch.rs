@@ -0,0 +1,68 @@
+//! Draw a [Koch snowflake](https://en.wikipedia.org/wiki/Koch_snowflake) by
+//! recursively replacing every line segment with four shorter ones, bumping
+//! the middle third out into an equilateral peak.
+
+use std::f64;
+use std::fmt::Debug;
+
+use geo::{PointF64, PointU32};
+
+use crate::drawing;
+
+/// Recursively draw a Koch curve from `start` to `end` onto `img` using
+/// `pix`, replacing the segment with four shorter ones `depth` times before
+/// drawing a plain line.
+pub fn koch_curve<I>(img: &mut I, start: PointF64, end: PointF64, depth: u32, pix: &I::Pixel)
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+{
+    if depth == 0 {
+        let mut drawer = drawing::Drawer::new_with_no_blending(img);
+
+        drawer.antialiased_line(to_point_u32(start), to_point_u32(end), pix);
+        return;
+    }
+
+    let dx = (end.x - start.x) / 3.0;
+    let dy = (end.y - start.y) / 3.0;
+
+    let p1 = PointF64::new(start.x + dx, start.y + dy);
+    let p3 = PointF64::new(start.x + 2.0 * dx, start.y + 2.0 * dy);
+
+    // bump the middle third out into an equilateral peak by rotating it
+    // around p1 by -60 degrees.
+    let angle = dy.atan2(dx) - f64::consts::FRAC_PI_3;
+    let side = (dx * dx + dy * dy).sqrt();
+    let p2 = PointF64::new(p1.x + angle.cos() * side, p1.y + angle.sin() * side);
+
+    koch_curve(img, start, p1, depth - 1, pix);
+    koch_curve(img, p1, p2, depth - 1, pix);
+    koch_curve(img, p2, p3, depth - 1, pix);
+    koch_curve(img, p3, end, depth - 1, pix);
+}
+
+/// Draw a full Koch snowflake, i.e. a Koch curve along each side of an
+/// equilateral triangle centered in a `width` x `height` image.
+pub fn koch_snowflake(img: &mut image::RgbImage, depth: u32, pix: &image::Rgb<u8>) {
+    let width = f64::from(img.width());
+    let height = f64::from(img.height());
+
+    let side = width.min(height) * 0.8;
+    let triangle_height = side * 3f64.sqrt() / 2.0;
+
+    let cx = width / 2.0;
+    let cy = height / 2.0;
+
+    let top = PointF64::new(cx, cy - 2.0 * triangle_height / 3.0);
+    let bottom_right = PointF64::new(cx + side / 2.0, cy + triangle_height / 3.0);
+    let bottom_left = PointF64::new(cx - side / 2.0, cy + triangle_height / 3.0);
+
+    koch_curve(img, top, bottom_right, depth, pix);
+    koch_curve(img, bottom_right, bottom_left, depth, pix);
+    koch_curve(img, bottom_left, top, depth, pix);
+}
+
+fn to_point_u32(p: PointF64) -> PointU32 {
+    PointU32::new(p.x.max(0.0) as u32, p.y.max(0.0) as u32)
+}
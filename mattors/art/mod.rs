@@ -0,0 +1,24 @@
+//! This module contains the code to generate the images.
+
+pub mod allrgb;
+pub mod barnsley_fern;
+pub mod buddhabrot;
+pub mod color_growth;
+pub mod delaunay;
+pub mod dithering;
+pub mod dragon;
+pub mod fractree;
+pub mod ifs;
+pub mod julia;
+pub mod koch;
+pub mod lsystem;
+pub mod mondrian;
+pub mod newton;
+pub mod patchwork;
+pub mod quantize;
+pub mod quaternion_julia;
+pub mod sierpinski;
+pub mod stippling;
+pub mod tangled_web;
+pub mod trimesh;
+pub mod vectorize;
@@ -3,10 +3,11 @@
 
 use rand::Rng;
 
-use geo::{utils::clamp, BoundingBox, PointU32};
+use geo::{utils::clamp, BoundingBox, PointF64, PointU32};
 
 use crate::art::random_bbox_subdivisions;
 use crate::drawing::{Drawer, NoopBlender};
+use crate::svg::{self, SvgCanvas};
 
 /// Generate some Mondrian inspired artwork.
 pub fn generate(
@@ -50,6 +51,117 @@ pub fn generate(
     }
 }
 
+/// Same as `generate`, but returns the composition as an `SvgCanvas` of
+/// filled rects instead of drawing it onto a raster image.
+pub fn generate_svg(
+    width: u32,
+    height: u32,
+    iterations: usize,
+    minimum_area: u32,
+    white: image::Rgb<u8>,
+    fill_palette: &[image::Rgb<u8>],
+    border_thickness: u32,
+) -> SvgCanvas {
+    let mut rng = rand::thread_rng();
+
+    let mut canvas = SvgCanvas::new(f64::from(width), f64::from(height));
+
+    let rects = random_bbox_subdivisions(
+        iterations,
+        BoundingBox::from_dimensions(width, height),
+        minimum_area,
+        &mut rng,
+    ).collect::<Vec<_>>();
+
+    let mut draw_rect = |canvas: &mut SvgCanvas, rect: &BoundingBox<u32>, pix: image::Rgb<u8>| {
+        rect_svg(canvas, rect, pix);
+        draw_borders_svg(canvas, rect, width, height, border_thickness);
+    };
+
+    for rect in &rects {
+        draw_rect(&mut canvas, rect, white);
+    }
+
+    if !rects.is_empty() {
+        let k = rng.gen_range(0, fill_palette.len() + 1);
+
+        for pix in &fill_palette[..k] {
+            let r = rng.gen_range(0, rects.len());
+
+            draw_rect(&mut canvas, &rects[r], *pix);
+        }
+    }
+
+    canvas
+}
+
+fn rect_svg(canvas: &mut SvgCanvas, rect: &BoundingBox<u32>, pix: image::Rgb<u8>) {
+    let (width, height) = rect.dimensions().unwrap();
+
+    canvas.rect(
+        PointF64::new(f64::from(rect.min().x), f64::from(rect.min().y)),
+        f64::from(width),
+        f64::from(height),
+        &svg::rgba(pix.data[0], pix.data[1], pix.data[2], 0xFF),
+    );
+}
+
+fn draw_borders_svg(
+    canvas: &mut SvgCanvas,
+    rect: &BoundingBox<u32>,
+    width: u32,
+    height: u32,
+    border_thickness: u32,
+) {
+    let horizontal_band_width = rect.width().unwrap();
+    let vertical_band_height = clamp(
+        i64::from(rect.height().unwrap()) - i64::from(border_thickness) * 2,
+        0,
+        height,
+    );
+
+    let borders = [
+        BoundingBox::from_dimensions_and_origin(
+            rect.min(),
+            horizontal_band_width,
+            border_thickness,
+        ),
+        BoundingBox::from_dimensions_and_origin(
+            &PointU32::new(rect.min().x, rect.min().y + border_thickness),
+            border_thickness,
+            vertical_band_height,
+        ),
+        BoundingBox::from_dimensions_and_origin(
+            &PointU32::new(
+                clamp(
+                    i64::from(rect.max().x) - i64::from(border_thickness),
+                    0,
+                    width,
+                ),
+                rect.min().y + border_thickness,
+            ),
+            border_thickness,
+            vertical_band_height,
+        ),
+        BoundingBox::from_dimensions_and_origin(
+            &PointU32::new(
+                rect.min().x,
+                clamp(
+                    i64::from(rect.max().y) - i64::from(border_thickness),
+                    0,
+                    height,
+                ),
+            ),
+            horizontal_band_width,
+            border_thickness,
+        ),
+    ];
+
+    for border in &borders {
+        rect_svg(canvas, border, image::Rgb { data: [0, 0, 0] });
+    }
+}
+
 // TODO: drawing borders should be done by the drawing mod.
 fn draw_borders(
     drawer: &mut Drawer<image::RgbImage, NoopBlender>,
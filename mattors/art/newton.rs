@@ -0,0 +1,243 @@
+//! Render the basins of attraction of [Newton's
+//! method](https://en.wikipedia.org/wiki/Newton_fractal) applied to a
+//! complex polynomial, producing "polynomiography" images: each pixel is
+//! colored by which root of the polynomial its starting point converges to.
+
+use std::iter::Iterator;
+
+use num::complex::Complex64;
+
+use geo::PointF64;
+
+/// A monic polynomial `p(z) = Π(z - root)` built directly from its `roots`,
+/// together with the machinery needed to run Newton's method against it.
+#[derive(Debug, Clone)]
+pub struct Polynomial {
+    roots: Vec<Complex64>,
+}
+
+impl Polynomial {
+    /// Build the polynomial whose roots are exactly `roots`.
+    pub fn from_roots(roots: Vec<Complex64>) -> Polynomial {
+        Polynomial { roots }
+    }
+
+    /// Evaluate `p(z) = Π(z - root)`.
+    pub fn eval(&self, z: Complex64) -> Complex64 {
+        self.roots
+            .iter()
+            .fold(Complex64::new(1.0, 0.0), |acc, &root| acc * (z - root))
+    }
+
+    /// Evaluate `p'(z)`, obtained by summing, for each root, `p(z)` with
+    /// that one factor removed.
+    pub fn eval_derivative(&self, z: Complex64) -> Complex64 {
+        (0..self.roots.len())
+            .map(|skip| {
+                self.roots
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != skip)
+                    .fold(Complex64::new(1.0, 0.0), |acc, (_, &root)| {
+                        acc * (z - root)
+                    })
+            })
+            .fold(Complex64::new(0.0, 0.0), |acc, term| acc + term)
+    }
+
+    /// Index, in `roots`, of the root nearest to `z`.
+    fn nearest_root(&self, z: Complex64) -> usize {
+        self.roots
+            .iter()
+            .enumerate()
+            .map(|(i, &root)| (i, (z - root).norm()))
+            .fold((0, ::std::f64::INFINITY), |best, cur| {
+                if cur.1 < best.1 {
+                    cur
+                } else {
+                    best
+                }
+            })
+            .0
+    }
+
+    /// Number of roots, i.e. how many basins of attraction to color.
+    pub fn root_count(&self) -> usize {
+        self.roots.len()
+    }
+}
+
+/// The result of running Newton's method on a single pixel: which root (by
+/// index into `Polynomial::roots`) the iteration converged to, and how many
+/// iterations it took to get there.
+#[derive(Debug, Clone, Copy)]
+pub struct NewtonPoint {
+    root: usize,
+    iterations: u32,
+}
+
+impl NewtonPoint {
+    fn to_pixels(&self, max_iterations: u32, root_count: usize) -> Vec<u8> {
+        let hue = 360.0 * self.root as f64 / (root_count.max(1) as f64);
+        let value = 1.0 - 0.7 * f64::from(self.iterations) / f64::from(max_iterations.max(1));
+        let value = value.max(0.0).min(1.0);
+
+        let [r, g, b] = hue_to_rgb(hue);
+
+        vec![
+            (f64::from(r) * value) as u8,
+            (f64::from(g) * value) as u8,
+            (f64::from(b) * value) as u8,
+        ]
+    }
+}
+
+/// Convert a `hue` in `[0, 360)` (full saturation, full value) to `[r, g, b]`
+/// bytes.
+fn hue_to_rgb(hue: f64) -> [u8; 3] {
+    let hue = hue.rem_euclid(360.0);
+    let c = 255.0;
+    let x = c * (1.0 - (((hue / 60.0) % 2.0) - 1.0).abs());
+
+    let (r, g, b) = match hue as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    [r as u8, g as u8, b as u8]
+}
+
+/// Run Newton's method (`z_{n+1} = z_n - p(z_n) / p'(z_n)`) from `z`,
+/// stopping when `|z_{n+1} - z_n|` drops below `epsilon` or `iterations` is
+/// hit, and classify the result by its nearest root in `poly`.
+pub fn newton(poly: &Polynomial, mut z: Complex64, iterations: u32, epsilon: f64) -> NewtonPoint {
+    let mut i = 0;
+
+    while i < iterations {
+        let next = z - poly.eval(z) / poly.eval_derivative(z);
+
+        if (next - z).norm() < epsilon {
+            z = next;
+            break;
+        }
+
+        z = next;
+        i += 1;
+    }
+
+    NewtonPoint {
+        root: poly.nearest_root(z),
+        iterations: i,
+    }
+}
+
+/// Iterator that returns all the `NewtonPoint`s of the basin-of-attraction
+/// grid for `poly`, mirroring `JuliaGenIter`.
+pub struct NewtonGenIter<'a> {
+    poly: &'a Polynomial,
+    start: PointF64,
+    xcount: u32,
+    ycount: u32,
+    stepx: f64,
+    stepy: f64,
+    iterations: u32,
+    epsilon: f64,
+
+    x: u32,
+    y: u32,
+}
+
+impl<'a> NewtonGenIter<'a> {
+    /// Create a new `NewtonGenIter` that runs Newton's method against
+    /// `poly` from `start`, moving x by `stepx` `xcount` times and y by
+    /// `stepy` `ycount` times. Both `xcount` and `ycount` are exclusive.
+    pub fn new(
+        poly: &'a Polynomial,
+        start: PointF64,
+        xcount: u32,
+        ycount: u32,
+        stepx: f64,
+        stepy: f64,
+        iterations: u32,
+        epsilon: f64,
+    ) -> NewtonGenIter<'a> {
+        NewtonGenIter {
+            poly,
+            start,
+            xcount,
+            ycount,
+            stepx,
+            stepy,
+            iterations,
+            epsilon,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    /// Consume the `NewtonGenIter` and return an image of the
+    /// polynomiography basins it yields.
+    pub fn into_image(self) -> Option<image::ImageBuffer<image::Rgb<u8>, Vec<u8>>> {
+        let width = self.xcount;
+        let height = self.ycount;
+        let iterations = self.iterations;
+        let root_count = self.poly.root_count();
+
+        image::ImageBuffer::from_raw(
+            width,
+            height,
+            self.flat_map(|pt| pt.to_pixels(iterations, root_count))
+                .collect(),
+        )
+    }
+}
+
+impl<'a> Iterator for NewtonGenIter<'a> {
+    type Item = NewtonPoint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y >= self.ycount {
+            return None;
+        }
+
+        let x = self.start.x + f64::from(self.x) * self.stepx;
+        let y = self.start.y + f64::from(self.y) * self.stepy;
+
+        let pt = newton(
+            self.poly,
+            Complex64::new(x, y),
+            self.iterations,
+            self.epsilon,
+        );
+
+        self.x += 1;
+        if self.x >= self.xcount {
+            self.x = 0;
+            self.y += 1;
+        }
+
+        Some(pt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn z_cubed_minus_one_converges_to_a_root() {
+        let poly = Polynomial::from_roots(vec![
+            Complex64::new(1.0, 0.0),
+            Complex64::new(-0.5, 0.866_025_403_784_438_6),
+            Complex64::new(-0.5, -0.866_025_403_784_438_6),
+        ]);
+
+        let pt = newton(&poly, Complex64::new(0.6, 0.6), 64, 1e-6);
+
+        assert_eq!(pt.root, 0);
+    }
+}
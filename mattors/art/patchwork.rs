@@ -6,7 +6,9 @@ use std::collections::HashSet;
 use geo::{convex_hull, kmeans, BoundingBox, Point, Polygon};
 
 use crate::art::random_point_in_bbox;
+use crate::compose::{self, BlendMode};
 use crate::drawing::{Blender, Drawer};
+use crate::svg::{self, SvgCanvas};
 
 const WHITE_EGG: image::Rgb<u8> = image::Rgb {
     data: [0xFD, 0xFD, 0xFF],
@@ -16,13 +18,17 @@ const BLACK_MATTERHORN: image::Rgb<u8> = image::Rgb {
     data: [0x52, 0x4B, 0x4B],
 };
 
-/// Generate random shapes according to the PatchWork algorithm.
+/// Generate random shapes according to the PatchWork algorithm. Leaf
+/// polygons are composited onto the canvas using `blend_mode`, so e.g.
+/// `Multiply` lets overlapping shapes darken each other instead of the
+/// later one simply overwriting the earlier one.
 pub fn random_patchwork(
     img: &mut image::RgbImage,
     npoints: usize,
     k: usize,
     iterations: usize,
     fill_polygons: bool,
+    blend_mode: BlendMode,
 ) {
     let mut generations = vec![vec![Polygon::new(vec![
         Point::new(0.0, 0.0),
@@ -32,9 +38,113 @@ pub fn random_patchwork(
     ])
     .unwrap()]];
 
-    let mut drawer = Drawer::new_with_no_blending(img);
+    {
+        let mut drawer = Drawer::new_with_no_blending(img);
+        drawer.fill(&WHITE_EGG);
+    }
+
+    let mut i = 0;
+
+    while let Some(polygons) = generations.pop() {
+        if i >= iterations {
+            if fill_polygons {
+                for poly in polygons {
+                    let poly =
+                        Polygon::new(poly.points().into_iter().map(|p| p.try_cast().unwrap()))
+                            .unwrap();
+
+                    rasterize_polygon(img, &poly, BLACK_MATTERHORN, blend_mode);
+                }
+            }
+
+            break;
+        }
+
+        i += 1;
+
+        let new_polygons = {
+            let mut drawer = Drawer::new_with_no_blending(img);
+
+            polygons
+                .into_iter()
+                .flat_map(|poly| patchwork_step(&mut drawer, &poly, npoints, k, !fill_polygons))
+                .collect::<Vec<_>>()
+        };
+
+        if !new_polygons.is_empty() {
+            generations.push(new_polygons);
+        }
+    }
+}
+
+/// Blend `fill` into every pixel of `img` that falls inside `polygon`,
+/// using `blend_mode` to combine it with whatever's already there.
+fn rasterize_polygon(
+    img: &mut image::RgbImage,
+    polygon: &Polygon<f64>,
+    fill: image::Rgb<u8>,
+    blend_mode: BlendMode,
+) {
+    let bbox = polygon.bounding_box();
+
+    let min_x = bbox.min().x.max(0.0) as u32;
+    let max_x = bbox.max().x.min(f64::from(img.width() - 1)) as u32;
+    let min_y = bbox.min().y.max(0.0) as u32;
+    let max_y = bbox.max().y.min(f64::from(img.height() - 1)) as u32;
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if polygon.contains(&Point::new(f64::from(x), f64::from(y))) {
+                let dst = *img.get_pixel(x, y);
+                img.put_pixel(x, y, composite_rgb(dst, fill, blend_mode));
+            }
+        }
+    }
+}
+
+/// Blend two opaque RGB colors with `mode`, going through `compose::blend`
+/// at full alpha and dropping the (always-opaque) alpha channel again.
+fn composite_rgb(
+    dst: image::Rgb<u8>,
+    src: image::Rgb<u8>,
+    mode: BlendMode,
+) -> image::Rgb<u8> {
+    let to_rgba = |c: image::Rgb<u8>| image::Rgba {
+        data: [c.data[0], c.data[1], c.data[2], 0xFF],
+    };
+
+    let blended = compose::blend(to_rgba(dst), to_rgba(src), mode);
+
+    image::Rgb {
+        data: [blended.data[0], blended.data[1], blended.data[2]],
+    }
+}
 
-    drawer.fill(&WHITE_EGG);
+/// Same as `random_patchwork`, but returns an `SvgCanvas` of filled/outlined
+/// polygons instead of drawing onto a raster image.
+pub fn random_patchwork_svg(
+    width: u32,
+    height: u32,
+    npoints: usize,
+    k: usize,
+    iterations: usize,
+    fill_polygons: bool,
+) -> SvgCanvas {
+    let mut generations = vec![vec![Polygon::new(vec![
+        Point::new(0.0, 0.0),
+        Point::new(f64::from(width - 1), 0.0),
+        Point::new(f64::from(width - 1), f64::from(height - 1)),
+        Point::new(0.0, f64::from(height - 1)),
+    ])
+    .unwrap()]];
+
+    let mut canvas = SvgCanvas::new(f64::from(width), f64::from(height));
+    canvas.rect(
+        Point::new(0.0, 0.0),
+        f64::from(width),
+        f64::from(height),
+        &svg::rgba(WHITE_EGG.data[0], WHITE_EGG.data[1], WHITE_EGG.data[2], 0xFF),
+    );
 
     let mut i = 0;
 
@@ -46,7 +156,15 @@ pub fn random_patchwork(
                         Polygon::new(poly.points().into_iter().map(|p| p.try_cast().unwrap()))
                             .unwrap();
 
-                    drawer.polygon(&poly, &BLACK_MATTERHORN);
+                    canvas.polygon(
+                        poly.points(),
+                        &svg::rgba(
+                            BLACK_MATTERHORN.data[0],
+                            BLACK_MATTERHORN.data[1],
+                            BLACK_MATTERHORN.data[2],
+                            0xFF,
+                        ),
+                    );
                 }
             }
 
@@ -57,13 +175,90 @@ pub fn random_patchwork(
 
         let new_polygons = polygons
             .into_iter()
-            .flat_map(|poly| patchwork_step(&mut drawer, &poly, npoints, k, !fill_polygons))
+            .flat_map(|poly| patchwork_step_svg(&mut canvas, &poly, npoints, k, !fill_polygons))
             .collect::<Vec<_>>();
 
         if !new_polygons.is_empty() {
             generations.push(new_polygons);
         }
     }
+
+    canvas
+}
+
+fn patchwork_step_svg(
+    canvas: &mut SvgCanvas,
+    polygon: &Polygon<f64>,
+    npoints: usize,
+    k: usize,
+    draw_polygons_boundary: bool,
+) -> Vec<Polygon<f64>> {
+    let mut rng = rand::thread_rng();
+
+    let polygon_bbox = BoundingBox::from_points(&[
+        polygon.bounding_box().min().try_cast().unwrap(),
+        polygon.bounding_box().max().try_cast().unwrap(),
+    ]);
+
+    let mut points = (0..npoints)
+        .map(|_| random_point_in_bbox(&mut rng, &polygon_bbox))
+        .collect::<HashSet<_>>();
+
+    points.retain(|pt| polygon.contains(&pt.cast()));
+
+    if points.len() <= 2 {
+        return vec![];
+    }
+
+    let mut polygons = vec![];
+
+    loop {
+        let clusters = kmeans::kmeans(
+            points.iter().map(|p| p.cast::<i64>()),
+            k,
+            300,
+            kmeans::KMeansInit::PlusPlus(&mut rng),
+        );
+
+        let smallest_cluster = clusters
+            .iter()
+            .filter(|(_, cluster)| cluster.len() > 2)
+            .min_by_key(|(_, cluster)| cluster.len());
+
+        match smallest_cluster {
+            None => break,
+            Some((_pivot, cluster)) => {
+                let hull = convex_hull::convex_hull(
+                    cluster.iter().map(|p| p.try_cast::<u32>().unwrap().cast()),
+                );
+
+                for pt in cluster {
+                    points.remove(&pt.try_cast().unwrap());
+                }
+
+                if draw_polygons_boundary {
+                    let boundary: Vec<_> = hull.iter().map(|p| p.try_cast().unwrap()).collect();
+
+                    canvas.polyline(
+                        &boundary,
+                        &svg::rgba(
+                            BLACK_MATTERHORN.data[0],
+                            BLACK_MATTERHORN.data[1],
+                            BLACK_MATTERHORN.data[2],
+                            0xFF,
+                        ),
+                        1.0,
+                    );
+                }
+
+                if let Some(new_poly) = Polygon::new(hull) {
+                    polygons.push(new_poly);
+                }
+            }
+        }
+    }
+
+    polygons
 }
 
 fn patchwork_step<B: Blender<image::Rgb<u8>>>(
@@ -93,7 +288,12 @@ fn patchwork_step<B: Blender<image::Rgb<u8>>>(
     let mut polygons = vec![];
 
     loop {
-        let clusters = kmeans::kmeans(points.iter().map(|p| p.cast::<i64>()), k, 300);
+        let clusters = kmeans::kmeans(
+            points.iter().map(|p| p.cast::<i64>()),
+            k,
+            300,
+            kmeans::KMeansInit::PlusPlus(&mut rng),
+        );
 
         let smallest_cluster = clusters
             .iter()
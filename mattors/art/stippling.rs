@@ -1,9 +1,15 @@
 //! Generate some stippling art.
 
-use geo::{BoundingBox, PointU32};
+use geo::{BoundingBox, PointF64, PointU32};
 
 use crate::art::{random_bbox_subdivisions, random_point_in_bbox};
 use crate::drawing::{Drawer, NoopBlender};
+use crate::fills::Gradient;
+use crate::svg::{self, SvgCanvas};
+
+/// The radius, in pixels, used to represent a single stippled point when
+/// rendering to SVG.
+const SVG_DOT_RADIUS: f64 = 0.75;
 
 /// The direction of gradient made of stippled points.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -23,12 +29,15 @@ pub enum Direction {
 
 /// Stipple the given image in bands with increasing number of points to
 /// simulate a gradient. Inspired by http://www.tylerlhobbs.com/works/series/st.
+/// Each band's color comes from sampling `gradient` at its position along
+/// the band sequence, so a multi-stop or radial `gradient` makes the
+/// stippled density follow a color ramp instead of a single flat color.
 pub fn gradient(
     img: &mut image::RgbImage,
     bands: u32,
     base_points_per_band: u32,
     grow_coeff: u32,
-    pix: image::Rgb<u8>,
+    gradient: &Gradient,
     dir: Direction,
 ) {
     let mut band = initial_band(dir, img.width(), img.height(), bands);
@@ -37,6 +46,8 @@ pub fn gradient(
     let mut drawer = Drawer::new_with_no_blending(img);
 
     for i in 0..bands {
+        let pix = gradient.color_at(band_t(i, bands));
+
         stipple(&mut drawer, &band, band_npoints, pix);
 
         // prevent overflow when dir is either RightToLeft or BottomToTop,
@@ -84,12 +95,112 @@ pub fn stipple(
     points: u32,
     pix: image::Rgb<u8>,
 ) {
+    for point in stipple_points(bbox, points) {
+        drawer.draw_pixel(point.x, point.y, &pix);
+    }
+}
+
+/// Sample `points` random positions inside `bbox`, shared by both the raster
+/// (`stipple`) and vector (`stipple_svg`) backends.
+fn stipple_points(bbox: &BoundingBox<u32>, points: u32) -> Vec<PointU32> {
     let mut rng = rand::thread_rng();
 
-    for _ in 0..points {
-        let point = random_point_in_bbox(&mut rng, bbox);
+    (0..points)
+        .map(|_| random_point_in_bbox(&mut rng, bbox))
+        .collect()
+}
 
-        drawer.draw_pixel(point.x, point.y, &pix);
+/// Same as `stipple`, but appends each point as a small filled circle to
+/// `canvas` instead of drawing a pixel.
+fn stipple_svg(canvas: &mut SvgCanvas, bbox: &BoundingBox<u32>, points: u32, pix: image::Rgb<u8>) {
+    let fill = svg::rgba(pix.data[0], pix.data[1], pix.data[2], 0xFF);
+
+    for point in stipple_points(bbox, points) {
+        canvas.circle(
+            PointF64::new(f64::from(point.x), f64::from(point.y)),
+            SVG_DOT_RADIUS,
+            &fill,
+        );
+    }
+}
+
+/// Same as `gradient`, but returns an `SvgCanvas` of stippled circles
+/// instead of drawing onto a raster image.
+pub fn gradient_svg(
+    width: u32,
+    height: u32,
+    bands: u32,
+    base_points_per_band: u32,
+    grow_coeff: u32,
+    gradient: &Gradient,
+    dir: Direction,
+) -> SvgCanvas {
+    let mut canvas = SvgCanvas::new(f64::from(width), f64::from(height));
+
+    let mut band = initial_band(dir, width, height, bands);
+    let mut band_npoints = base_points_per_band;
+
+    for i in 0..bands {
+        let pix = gradient.color_at(band_t(i, bands));
+
+        stipple_svg(&mut canvas, &band, band_npoints, pix);
+
+        if i == bands - 1 {
+            continue;
+        }
+
+        band = advance_band(&band, dir);
+        band_npoints += band_npoints * grow_coeff;
+    }
+
+    canvas
+}
+
+/// Same as `rects`, but returns an `SvgCanvas` of stippled circles (with
+/// degenerate rectangles drawn as a single line) instead of drawing onto a
+/// raster image.
+pub fn rects_svg(
+    width: u32,
+    height: u32,
+    iterations: usize,
+    points: u32,
+    minimum_area: u32,
+    pix: image::Rgb<u8>,
+) -> SvgCanvas {
+    let mut rng = rand::thread_rng();
+
+    let mut canvas = SvgCanvas::new(f64::from(width), f64::from(height));
+
+    let bbox = BoundingBox::from_dimensions(width, height);
+    let pieces = random_bbox_subdivisions(iterations, bbox, minimum_area, &mut rng);
+
+    let fill = svg::rgba(pix.data[0], pix.data[1], pix.data[2], 0xFF);
+
+    for piece in pieces {
+        if piece.min().x >= piece.max().x || piece.min().y >= piece.max().y {
+            canvas.polyline(
+                &[
+                    PointF64::new(f64::from(piece.min().x), f64::from(piece.min().y)),
+                    PointF64::new(f64::from(piece.max().x), f64::from(piece.max().y)),
+                ],
+                &fill,
+                1.0,
+            );
+            continue;
+        }
+
+        stipple_svg(&mut canvas, &piece, points, pix);
+    }
+
+    canvas
+}
+
+/// Map the `i`-th of `bands` bands to a gradient position in `[0.0, 1.0]`.
+fn band_t(i: u32, bands: u32) -> f64 {
+    if bands <= 1 {
+        0.0
+    } else {
+        f64::from(i) / f64::from(bands - 1)
     }
 }
 
@@ -1,13 +1,125 @@
 //! Generate 2d tangled webs inspired by https://inconvergent.net/2019/a-tangle-of-webs/
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use rand::Rng;
 
+use geo::bbox::BoundingBox;
+use geo::bezier::CubicBezier;
+use geo::delaunay;
+use geo::line::is_self_intersecting;
 use geo::point::{PointF64, PointU32};
 
 use crate::drawing::Drawer;
 
+/// An edge of the web graph, identified by its two endpoint vertex indices.
+type EdgeId = (usize, usize);
+
+// a node holds at most this many edges before it tries to split into 4
+// quadrants, unless it has already reached `MAX_DEPTH`.
+const MAX_EDGES_PER_NODE: usize = 8;
+const MAX_DEPTH: u32 = 8;
+
+/// A quadtree over the web's edges, used to avoid re-testing every edge
+/// against every swept line `generate_tangled_web` draws. Each node holds
+/// the edges that don't fit entirely within any one of its 4 children,
+/// splitting itself (via `BoundingBox::split_at` on its own center) once it
+/// accumulates more than `MAX_EDGES_PER_NODE`.
+struct QuadTree {
+    bbox: BoundingBox<f64>,
+    edges: Vec<EdgeId>,
+    children: Option<Box<[QuadTree; 4]>>,
+}
+
+impl QuadTree {
+    fn new(bbox: BoundingBox<f64>) -> Self {
+        QuadTree {
+            bbox,
+            edges: vec![],
+            children: None,
+        }
+    }
+
+    fn edge_bbox(vertices: &[Vertex], edge: EdgeId) -> BoundingBox<f64> {
+        BoundingBox::from_points(&[vertices[edge.0].position, vertices[edge.1].position])
+    }
+
+    /// Insert `edge` into this node or, if it fits entirely within one of
+    /// this node's quadrants, recurse into it.
+    fn insert(&mut self, vertices: &[Vertex], edge: EdgeId, depth: u32) {
+        let edge_bbox = Self::edge_bbox(vertices, edge);
+
+        if depth < MAX_DEPTH {
+            if self.children.is_none() && self.edges.len() >= MAX_EDGES_PER_NODE {
+                self.split();
+            }
+
+            if let Some(children) = &mut self.children {
+                if let Some(child) = children
+                    .iter_mut()
+                    .find(|child| child.bbox.contains_bbox(&edge_bbox))
+                {
+                    child.insert(vertices, edge, depth + 1);
+                    return;
+                }
+            }
+        }
+
+        self.edges.push(edge);
+    }
+
+    fn split(&mut self) {
+        if let Some((a, b, c, d)) = self.bbox.split_at(&self.bbox.center()) {
+            self.children = Some(Box::new([
+                QuadTree::new(a),
+                QuadTree::new(b),
+                QuadTree::new(c),
+                QuadTree::new(d),
+            ]));
+        }
+    }
+
+    /// Remove `edge` from wherever in the tree it was inserted. Returns
+    /// whether the edge was found.
+    fn remove(&mut self, vertices: &[Vertex], edge: EdgeId) -> bool {
+        if let Some(pos) = self.edges.iter().position(|e| *e == edge) {
+            self.edges.remove(pos);
+            return true;
+        }
+
+        let edge_bbox = Self::edge_bbox(vertices, edge);
+
+        if let Some(children) = &mut self.children {
+            return children
+                .iter_mut()
+                .filter(|child| child.bbox.intersects(&edge_bbox))
+                .any(|child| child.remove(vertices, edge));
+        }
+
+        false
+    }
+
+    /// Collect every edge whose bounding box overlaps `query_bbox`.
+    fn query(&self, vertices: &[Vertex], query_bbox: &BoundingBox<f64>, out: &mut Vec<EdgeId>) {
+        if !self.bbox.intersects(query_bbox) {
+            return;
+        }
+
+        out.extend(
+            self.edges
+                .iter()
+                .copied()
+                .filter(|&edge| Self::edge_bbox(vertices, edge).intersects(query_bbox)),
+        );
+
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.query(vertices, query_bbox, out);
+            }
+        }
+    }
+}
+
 /// A Vertex of a tangled web. It is a node of the graph.
 #[derive(Debug, Clone)]
 pub struct Vertex {
@@ -72,12 +184,230 @@ pub fn generate_svg(
     )
 }
 
+/// Options controlling `generate_svg_styled`'s output.
+#[derive(Debug, Clone, Copy)]
+pub struct SvgOptions {
+    /// Replace polyline runs between degree-2 vertices with smooth cubic
+    /// Béziers (Catmull-Rom derived control points) instead of straight
+    /// segments.
+    pub smooth: bool,
+
+    /// The stroke width at the start of each chain, in svg units.
+    pub base_width: f64,
+
+    /// Taper the stroke width down towards the end of each chain instead of
+    /// keeping it constant at `base_width`.
+    pub variable_width: bool,
+
+    /// The maximum distance, in svg units, between a smoothed Bézier and the
+    /// polyline approximating it (ignored unless `smooth` is set).
+    pub flatten_tolerance: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            smooth: true,
+            base_width: 2.0,
+            variable_width: true,
+            flatten_tolerance: 0.25,
+        }
+    }
+}
+
+/// Like `generate_svg`, but emits each maximal run of edges between two
+/// degree-2 vertices ("chains") as a single, optionally Bézier-smoothed,
+/// optionally tapered fill rather than as one fixed-width straight stroke
+/// per edge.
+pub fn generate_svg_styled(
+    out: &mut impl std::io::Write,
+    (width, height): (u32, u32),
+    iterations: usize,
+    circle_divisions: u8,
+    options: &SvgOptions,
+) -> std::io::Result<()> {
+    let (vertices, edges) = generate_tangled_web((width, height), iterations, circle_divisions);
+
+    write!(
+        out,
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {w} {h}">
+<path fill="#9a9a9a" stroke="none" d="
+"##,
+        w = width,
+        h = height
+    )?;
+
+    for chain in edge_chains(&vertices, &edges) {
+        let points = chain.iter().map(|&i| vertices[i].position).collect::<Vec<_>>();
+
+        let smoothed = if options.smooth && points.len() > 2 {
+            Some(flatten_chain(&points, options.flatten_tolerance))
+        } else {
+            None
+        };
+
+        // a smoothed chain can overshoot past its original polyline and cross
+        // itself on a tight hairpin, which would turn write_tapered_quads's
+        // fill into a self-overlapping mess; fall back to the unsmoothed,
+        // planar polyline when that happens.
+        let path = match smoothed {
+            Some(ref flattened) if !is_self_intersecting(flattened) => flattened,
+            _ => &points,
+        };
+
+        write_tapered_quads(out, path, options)?;
+    }
+
+    write!(
+        out,
+        r#"" />
+</svg>"#
+    )
+}
+
+// group `edges` into maximal runs ("chains") of edges connecting degree-2
+// vertices, so each can be smoothed/tapered as a single unit instead of as
+// independent segments. A chain's first and last vertex are either a
+// "hub" (degree != 2) or, for a closed loop, the same vertex twice.
+fn edge_chains(vertices: &[Vertex], edges: &HashSet<EdgeId>) -> Vec<Vec<usize>> {
+    let mut visited = HashSet::new();
+    let mut chains = vec![];
+
+    for &(a, b) in edges {
+        let key = (a.min(b), a.max(b));
+        if !visited.insert(key) {
+            continue;
+        }
+
+        let mut chain = vec![a, b];
+
+        let mut prev = a;
+        let mut cur = b;
+        while vertices[cur].neighbors.len() == 2 && cur != a {
+            let next = match vertices[cur].neighbors.iter().find(|&&n| n != prev) {
+                Some(&next) => next,
+                None => break,
+            };
+
+            if !visited.insert((cur.min(next), cur.max(next))) {
+                break;
+            }
+
+            chain.push(next);
+            prev = cur;
+            cur = next;
+        }
+
+        let mut next = b;
+        let mut cur = a;
+        while vertices[cur].neighbors.len() == 2 && cur != *chain.last().unwrap() {
+            let prev = match vertices[cur].neighbors.iter().find(|&&n| n != next) {
+                Some(&prev) => prev,
+                None => break,
+            };
+
+            if !visited.insert((cur.min(prev), cur.max(prev))) {
+                break;
+            }
+
+            chain.insert(0, prev);
+            next = cur;
+            cur = prev;
+        }
+
+        chains.push(chain);
+    }
+
+    chains
+}
+
+// replace the polyline `points` with the flattened approximation of the
+// Catmull-Rom spline running through them, within `tolerance`.
+fn flatten_chain(points: &[PointF64], tolerance: f64) -> Vec<PointF64> {
+    let n = points.len();
+    let at = |i: isize| points[i.clamp(0, n as isize - 1) as usize];
+
+    let mut flattened = vec![points[0]];
+
+    for i in 0..n - 1 {
+        let p0 = at(i as isize - 1);
+        let p1 = at(i as isize);
+        let p2 = at(i as isize + 1);
+        let p3 = at(i as isize + 2);
+
+        let cp1 = PointF64::new(p1.x + (p2.x - p0.x) / 6.0, p1.y + (p2.y - p0.y) / 6.0);
+        let cp2 = PointF64::new(p2.x - (p3.x - p1.x) / 6.0, p2.y - (p3.y - p1.y) / 6.0);
+
+        let mut segment = CubicBezier::new(p1, cp1, cp2, p2).flatten(tolerance);
+        segment.remove(0);
+        flattened.extend(segment);
+    }
+
+    flattened
+}
+
+// emit `points` as a run of filled quads, one per segment, each offset by
+// half of the (possibly tapered) width on either side of the centerline --
+// a stroke-to-fill pass that lets the chain thicken or thin along its
+// length, which a constant-width `stroke` attribute can't do.
+fn write_tapered_quads(
+    out: &mut impl std::io::Write,
+    points: &[PointF64],
+    options: &SvgOptions,
+) -> std::io::Result<()> {
+    if points.len() < 2 {
+        return Ok(());
+    }
+
+    let last = points.len() - 1;
+    let width_at = |i: usize| {
+        if options.variable_width {
+            options.base_width * (1.0 - 0.6 * (i as f64 / last as f64))
+        } else {
+            options.base_width
+        }
+    };
+
+    for i in 0..last {
+        let p0 = points[i];
+        let p1 = points[i + 1];
+
+        let dx = p1.x - p0.x;
+        let dy = p1.y - p0.y;
+        let len = (dx * dx + dy * dy).sqrt();
+
+        if len == 0.0 {
+            continue;
+        }
+
+        let (nx, ny) = (-dy / len, dx / len);
+        let (h0, h1) = (width_at(i) / 2.0, width_at(i + 1) / 2.0);
+
+        writeln!(
+            out,
+            "M {},{} L {},{} L {},{} L {},{} Z",
+            p0.x + nx * h0,
+            p0.y + ny * h0,
+            p1.x + nx * h1,
+            p1.y + ny * h1,
+            p1.x - nx * h1,
+            p1.y - ny * h1,
+            p0.x - nx * h0,
+            p0.y - ny * h0,
+        )?;
+    }
+
+    Ok(())
+}
+
 /// generate a graph of connected points that resemble a spider web.
 pub fn generate_tangled_web(
     (width, height): (u32, u32),
     iterations: usize,
     circle_divisions: u8,
-) -> (Vec<Vertex>, HashSet<(usize, usize)>) {
+) -> (Vec<Vertex>, HashSet<EdgeId>) {
     use std::f64::consts::PI;
     const TWO_PI: f64 = PI * 2.0;
 
@@ -88,6 +418,7 @@ pub fn generate_tangled_web(
     let scale = width.min(height) * 0.5;
 
     let mut edges = HashSet::new();
+    let mut quadtree = QuadTree::new(BoundingBox::from_dimensions(width, height));
     let mut vertices = vec![Vertex::new(PointF64::new(
         width / 2.0 + scale,
         height / 2.0,
@@ -108,7 +439,7 @@ pub fn generate_tangled_web(
         vertices.push(v);
 
         vertices[prev_id].neighbors.insert(id);
-        edges.insert((prev_id, id));
+        add_edge(&vertices, &mut edges, &mut quadtree, (prev_id, id));
     }
     vertices[0]
         .neighbors
@@ -116,7 +447,12 @@ pub fn generate_tangled_web(
     vertices[usize::from(circle_divisions) - 1]
         .neighbors
         .insert(0);
-    edges.insert((vertices.len() - 1, 0));
+    add_edge(
+        &vertices,
+        &mut edges,
+        &mut quadtree,
+        (vertices.len() - 1, 0),
+    );
 
     for _ in 0..iterations {
         let a0 = rng.gen_range(0.0, TWO_PI);
@@ -127,15 +463,17 @@ pub fn generate_tangled_web(
         let d1 = (width.powi(2) + height.powi(2)).sqrt();
         let p1 = PointF64::new(p0.x + a1.cos() * d1, p0.y + a1.sin() * d1);
 
-        let mut intersections = edges
-            .iter()
+        let sweep_bbox = BoundingBox::from_points(&[p0, p1]);
+        let mut candidates = vec![];
+        quadtree.query(&vertices, &sweep_bbox, &mut candidates);
+
+        let mut intersections = candidates
+            .into_iter()
             .filter_map(|(v0, v1)| {
-                let int = segment_intersection(
-                    (p0, p1),
-                    (vertices[*v0].position, vertices[*v1].position),
-                )?;
+                let int =
+                    segment_intersection((p0, p1), (vertices[v0].position, vertices[v1].position))?;
 
-                Some((int, (*v0, *v1)))
+                Some((int, (v0, v1)))
             })
             .collect::<Vec<_>>();
 
@@ -164,20 +502,22 @@ pub fn generate_tangled_web(
             v0.neighbors.remove(&v1_id);
             v0.neighbors.insert(int_v_id);
             edges.remove(&(v0_id, v1_id));
-            edges.insert((v0_id, int_v_id));
+            quadtree.remove(&vertices, (v0_id, v1_id));
+            add_edge(&vertices, &mut edges, &mut quadtree, (v0_id, int_v_id));
 
             let v1 = &mut vertices[v1_id];
             v1.neighbors.remove(&v0_id);
             v1.neighbors.insert(int_v_id);
             edges.remove(&(v1_id, v0_id));
-            edges.insert((int_v_id, v1_id));
+            quadtree.remove(&vertices, (v1_id, v0_id));
+            add_edge(&vertices, &mut edges, &mut quadtree, (int_v_id, v1_id));
         }
 
         let int1_id = vertices.len() - 2;
         let int2_id = vertices.len() - 1;
         vertices[int1_id].neighbors.insert(int2_id);
         vertices[int2_id].neighbors.insert(int1_id);
-        edges.insert((vertices.len() - 2, vertices.len() - 1));
+        add_edge(&vertices, &mut edges, &mut quadtree, (int1_id, int2_id));
 
         let mut new_vertices = vertices.clone();
         for v in &mut new_vertices {
@@ -200,6 +540,104 @@ pub fn generate_tangled_web(
     (vertices, edges)
 }
 
+/// Generate a graph of connected points via Delaunay triangulation instead
+/// of `generate_tangled_web`'s random chord-splicing process, trading
+/// chaotic tangles for an organically regular mesh. `n_sites` points are
+/// scattered uniformly at random across the canvas; running `relax_iters`
+/// rounds of [Lloyd
+/// relaxation](https://en.wikipedia.org/wiki/Lloyd%27s_algorithm) between
+/// triangulations (move each site to the centroid of its Voronoi cell, then
+/// re-triangulate) evens out the mesh before its final triangulation's edges
+/// become the web graph. Returns the same shape as `generate_tangled_web`,
+/// so it plugs straight into `generate_img`/`generate_svg`.
+pub fn generate_delaunay_web(
+    (width, height): (u32, u32),
+    n_sites: usize,
+    relax_iters: usize,
+) -> (Vec<Vertex>, HashSet<EdgeId>) {
+    let mut rng = rand::thread_rng();
+
+    let width = f64::from(width);
+    let height = f64::from(height);
+    let bbox = BoundingBox::from_dimensions(width, height);
+
+    let mut sites = (0..n_sites)
+        .map(|_| PointF64::new(rng.gen_range(0.0, width), rng.gen_range(0.0, height)))
+        .collect::<Vec<_>>();
+
+    for _ in 0..relax_iters {
+        sites = delaunay::voronoi(&bbox, sites)
+            .into_iter()
+            .map(|(site, polygon)| polygon_centroid(&polygon).unwrap_or(site))
+            .collect();
+    }
+
+    // `triangulate` hands back bare `Point<f64>`s rather than indices into
+    // `sites`, so key a lookup off of each coordinate's raw bits: the
+    // triangulation only ever copies or reorders the points we gave it, it
+    // never computes new ones, so exact float equality is safe here.
+    let index_of = sites
+        .iter()
+        .enumerate()
+        .map(|(i, p)| ((p.x.to_bits(), p.y.to_bits()), i))
+        .collect::<HashMap<_, _>>();
+
+    let mut vertices = sites.iter().map(|&p| Vertex::new(p)).collect::<Vec<_>>();
+    let mut edges = HashSet::new();
+    let mut quadtree = QuadTree::new(bbox);
+
+    for triangle in delaunay::triangulate(&bbox, sites.clone()) {
+        let ids = triangle
+            .points
+            .iter()
+            .map(|p| index_of[&(p.x.to_bits(), p.y.to_bits())])
+            .collect::<Vec<_>>();
+
+        for &(a, b) in &[(ids[0], ids[1]), (ids[1], ids[2]), (ids[2], ids[0])] {
+            if edges.contains(&(a, b)) || edges.contains(&(b, a)) {
+                continue;
+            }
+
+            vertices[a].neighbors.insert(b);
+            vertices[b].neighbors.insert(a);
+            add_edge(&vertices, &mut edges, &mut quadtree, (a, b));
+        }
+    }
+
+    (vertices, edges)
+}
+
+// the centroid of `polygon`'s area, via the standard signed-area-weighted
+// shoelace formula. Falls back to `None` for the degenerate (<3 points or
+// zero-area) polygons a sliver Voronoi cell can produce.
+fn polygon_centroid(polygon: &[PointF64]) -> Option<PointF64> {
+    if polygon.len() < 3 {
+        return None;
+    }
+
+    let mut area = 0.0;
+    let mut cx = 0.0;
+    let mut cy = 0.0;
+
+    for i in 0..polygon.len() {
+        let p0 = polygon[i];
+        let p1 = polygon[(i + 1) % polygon.len()];
+
+        let cross = p0.x * p1.y - p1.x * p0.y;
+        area += cross;
+        cx += (p0.x + p1.x) * cross;
+        cy += (p0.y + p1.y) * cross;
+    }
+
+    area /= 2.0;
+
+    if area.abs() < ::std::f64::EPSILON {
+        return None;
+    }
+
+    Some(PointF64::new(cx / (6.0 * area), cy / (6.0 * area)))
+}
+
 impl Vertex {
     fn new(pos: PointF64) -> Self {
         Vertex {
@@ -209,6 +647,18 @@ impl Vertex {
     }
 }
 
+// record `edge` in both the flat `edges` set and the `quadtree` that
+// accelerates spatial queries over it, so the two never drift apart.
+fn add_edge(
+    vertices: &[Vertex],
+    edges: &mut HashSet<EdgeId>,
+    quadtree: &mut QuadTree,
+    edge: EdgeId,
+) {
+    edges.insert(edge);
+    quadtree.insert(vertices, edge, 0);
+}
+
 fn segment_intersection(
     (p0, p1): (PointF64, PointF64),
     (q0, q1): (PointF64, PointF64),
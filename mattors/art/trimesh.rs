@@ -0,0 +1,266 @@
+//! Approximate a scalar field, or an image's luminance, with an adaptively
+//! refined triangle mesh.
+//!
+//! Unlike `delaunay::adaptive_triangulation`, which inserts a vertex and
+//! re-triangulates the whole point set with `delaunay::triangulate`, this
+//! module never re-triangulates: each triangle that's still too coarse is
+//! split into exactly three children around a new vertex (its centroid or
+//! one of its edge midpoints), which is
+//! cheaper and lets it work over any `Fn(f64, f64) -> f64` field instead of
+//! just an image.
+
+extern crate image;
+
+use std::collections::VecDeque;
+
+extern crate geo;
+
+use self::geo::{Point, PointU32, Triangle};
+
+use drawing;
+
+// a triangle paired with the field value sampled at each of its vertices, so
+// the interpolation error can be estimated without resampling the vertices
+// every time.
+#[derive(Debug, Clone)]
+struct FieldTriangle {
+    points: [Point<f64>; 3],
+    values: [f64; 3],
+}
+
+impl FieldTriangle {
+    fn triangle(&self) -> Triangle<f64> {
+        Triangle::new(self.points[0], self.points[1], self.points[2])
+    }
+}
+
+/// Adaptively subdivide the `width` x `height` bounding box into a triangle
+/// mesh that approximates `field`, starting from the 2 triangles that split
+/// the box in half. A triangle is split into 3 children around its worst
+/// deviation point (the one among the centroid and the 3 edge midpoints
+/// whose sampled value most disagrees with the linear interpolation of the
+/// triangle's vertex values) whenever that deviation exceeds `tolerance`,
+/// scaled up by the square root of the triangle's own area so large flat
+/// regions are allowed to stay coarse. Stops once every triangle is within
+/// tolerance or `max_triangles` have been produced.
+///
+/// Returns each final triangle paired with the average of its vertex
+/// values, ready to be used as a fill color.
+pub fn triangulate_field<F>(
+    width: f64,
+    height: f64,
+    field: F,
+    tolerance: f64,
+    max_triangles: usize,
+) -> Vec<(Triangle<f64>, f64)>
+where
+    F: Fn(f64, f64) -> f64,
+{
+    let sample = |p: Point<f64>| field(p.x, p.y);
+
+    let tl = Point::new(0.0, 0.0);
+    let tr = Point::new(width, 0.0);
+    let br = Point::new(width, height);
+    let bl = Point::new(0.0, height);
+
+    let mut queue = VecDeque::new();
+    queue.push_back(FieldTriangle {
+        points: [tl, tr, br],
+        values: [sample(tl), sample(tr), sample(br)],
+    });
+    queue.push_back(FieldTriangle {
+        points: [tl, br, bl],
+        values: [sample(tl), sample(br), sample(bl)],
+    });
+
+    let mut done = vec![];
+    let mut count = queue.len();
+
+    while let Some(t) = queue.pop_front() {
+        if count >= max_triangles {
+            queue.push_front(t);
+            break;
+        }
+
+        let (worst_point, worst_value, error) = worst_deviation(&t, &sample);
+        let weighted_tolerance = tolerance * t.triangle().area().max(1.0).sqrt();
+
+        if error <= weighted_tolerance {
+            done.push(t);
+            continue;
+        }
+
+        let [p1, p2, p3] = t.points;
+        let [v1, v2, v3] = t.values;
+
+        queue.push_back(FieldTriangle {
+            points: [p1, p2, worst_point],
+            values: [v1, v2, worst_value],
+        });
+        queue.push_back(FieldTriangle {
+            points: [p2, p3, worst_point],
+            values: [v2, v3, worst_value],
+        });
+        queue.push_back(FieldTriangle {
+            points: [p3, p1, worst_point],
+            values: [v3, v1, worst_value],
+        });
+        count += 2;
+    }
+
+    done.extend(queue);
+
+    done.into_iter()
+        .map(|t| {
+            let avg = (t.values[0] + t.values[1] + t.values[2]) / 3.0;
+            (t.triangle(), avg)
+        })
+        .collect()
+}
+
+// sample the centroid and each edge midpoint of `t`, compare each against
+// what a linear interpolation of the triangle's vertex values would predict
+// there, and return whichever point/value/error disagrees the most.
+fn worst_deviation<F>(t: &FieldTriangle, sample: &F) -> (Point<f64>, f64, f64)
+where
+    F: Fn(Point<f64>) -> f64,
+{
+    let [p1, p2, p3] = t.points;
+    let [v1, v2, v3] = t.values;
+
+    let candidates = [
+        (t.triangle().centroid(), (v1 + v2 + v3) / 3.0),
+        (p1.midpoint(&p2), (v1 + v2) / 2.0),
+        (p2.midpoint(&p3), (v2 + v3) / 2.0),
+        (p3.midpoint(&p1), (v3 + v1) / 2.0),
+    ];
+
+    candidates
+        .iter()
+        .map(|&(p, interpolated)| {
+            let value = sample(p);
+            (p, value, (value - interpolated).abs())
+        })
+        .fold(None, |best: Option<(Point<f64>, f64, f64)>, cur| {
+            match best {
+                Some(b) if b.2 >= cur.2 => Some(b),
+                _ => Some(cur),
+            }
+        })
+        .unwrap()
+}
+
+/// Replace `img` with a low-poly approximation of itself, built by adaptively
+/// subdividing a triangle mesh over its luminance field (see
+/// `triangulate_field`) and filling each final triangle with the average
+/// color of the source pixels it covers, using `Drawer::fill_triangle`.
+pub fn subdivide_image(img: &mut image::RgbaImage, tolerance: f64, max_triangles: usize) {
+    let (width, height) = img.dimensions();
+    let source = img.clone();
+
+    let triangles = triangulate_field(
+        f64::from(width),
+        f64::from(height),
+        |x, y| luminance(&source, x, y),
+        tolerance,
+        max_triangles,
+    );
+
+    let filled_triangles: Vec<_> = triangles
+        .into_iter()
+        .map(|(triangle, _)| {
+            let color = average_color(&source, &triangle);
+            (triangle, color)
+        })
+        .collect();
+
+    let mut drawer = drawing::Drawer::new_with_no_blending(img);
+
+    for (triangle, pix) in filled_triangles {
+        let [p1, p2, p3] = triangle.points;
+
+        let p1 = PointU32::new(p1.x.max(0.0) as u32, p1.y.max(0.0) as u32);
+        let p2 = PointU32::new(p2.x.max(0.0) as u32, p2.y.max(0.0) as u32);
+        let p3 = PointU32::new(p3.x.max(0.0) as u32, p3.y.max(0.0) as u32);
+
+        drawer.fill_triangle(p1, p2, p3, &pix);
+    }
+}
+
+// luminance (in `[0, 255]`) of the pixel closest to `(x, y)`, clamped to the
+// image bounds.
+fn luminance(img: &image::RgbaImage, x: f64, y: f64) -> f64 {
+    let x = (x.max(0.0) as u32).min(img.width() - 1);
+    let y = (y.max(0.0) as u32).min(img.height() - 1);
+
+    let pix = img.get_pixel(x, y).data;
+
+    0.299 * f64::from(pix[0]) + 0.587 * f64::from(pix[1]) + 0.114 * f64::from(pix[2])
+}
+
+// average color of the pixels within `triangle`'s bounding box that fall
+// inside it.
+fn average_color(img: &image::RgbaImage, triangle: &Triangle<f64>) -> image::Rgba<u8> {
+    let [p1, p2, p3] = triangle.points;
+
+    let min_x = p1.x.min(p2.x).min(p3.x).max(0.0) as u32;
+    let max_x = p1.x.max(p2.x).max(p3.x).min(f64::from(img.width() - 1)) as u32;
+    let min_y = p1.y.min(p2.y).min(p3.y).max(0.0) as u32;
+    let max_y = p1.y.max(p2.y).max(p3.y).min(f64::from(img.height() - 1)) as u32;
+
+    let (mut r, mut g, mut b, mut a, mut n) = (0u64, 0u64, 0u64, 0u64, 0u64);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if !triangle.contains(&Point::new(f64::from(x), f64::from(y))) {
+                continue;
+            }
+
+            let pix = img.get_pixel(x, y).data;
+            r += u64::from(pix[0]);
+            g += u64::from(pix[1]);
+            b += u64::from(pix[2]);
+            a += u64::from(pix[3]);
+            n += 1;
+        }
+    }
+
+    if n == 0 {
+        return image::Rgba {
+            data: [0, 0, 0, 255],
+        };
+    }
+
+    image::Rgba {
+        data: [(r / n) as u8, (g / n) as u8, (b / n) as u8, (a / n) as u8],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangulate_field_stops_under_tolerance_for_a_flat_field() {
+        let triangles = triangulate_field(10.0, 10.0, |_, _| 1.0, 0.01, 1000);
+
+        assert_eq!(triangles.len(), 2);
+        for (_, value) in triangles {
+            assert_eq!(value, 1.0);
+        }
+    }
+
+    #[test]
+    fn test_triangulate_field_refines_towards_a_varying_field() {
+        let triangles = triangulate_field(10.0, 10.0, |x, y| x * y, 0.01, 1000);
+
+        assert!(triangles.len() > 2);
+    }
+
+    #[test]
+    fn test_triangulate_field_respects_max_triangles() {
+        let triangles = triangulate_field(100.0, 100.0, |x, y| (x * y).sin(), 0.01, 8);
+
+        assert!(triangles.len() <= 8);
+    }
+}
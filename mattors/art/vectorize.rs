@@ -0,0 +1,369 @@
+//! Turn a raster image into a posterized vector illustration by clustering
+//! similar colored pixels and tracing the outline of each surviving
+//! cluster, the vector counterpart to the triangle-primitive approach in
+//! `primi`.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use geo::{PointF64, PointU32};
+
+use crate::svg::{self, SvgCanvas};
+
+/// Turn `img` into a layered SVG illustration.
+///
+/// Neighboring pixels are grown into the same cluster with a union-find as
+/// long as their color distance is at most `grow_threshold`. The resulting
+/// clusters are then repeatedly merged, closest-color pair first, as long
+/// as more than `target_clusters` remain and the pair's colors differ by at
+/// most `merge_threshold`. Clusters still smaller than `min_area` pixels
+/// afterwards are folded into their largest neighbor. Each surviving
+/// cluster is traced into a closed outline and emitted back-to-front
+/// (largest area first) as a filled SVG path.
+pub fn vectorize(
+    img: &image::RgbImage,
+    grow_threshold: u32,
+    target_clusters: usize,
+    merge_threshold: u32,
+    min_area: u32,
+) -> SvgCanvas {
+    let width = img.width();
+    let height = img.height();
+
+    let mut uf = UnionFind::new((width as usize) * (height as usize));
+    let idx = |x: u32, y: u32| (y as usize) * (width as usize) + (x as usize);
+
+    for y in 0..height {
+        for x in 0..width {
+            let pix = *img.get_pixel(x, y);
+
+            if x > 0 && color_dist(pix.data, img.get_pixel(x - 1, y).data) <= grow_threshold {
+                uf.union(idx(x, y), idx(x - 1, y));
+            }
+
+            if y > 0 && color_dist(pix.data, img.get_pixel(x, y - 1).data) <= grow_threshold {
+                uf.union(idx(x, y), idx(x, y - 1));
+            }
+        }
+    }
+
+    loop {
+        let (clusters, adjacency) = cluster_state(&mut uf, img, width, height);
+
+        if clusters.len() <= target_clusters {
+            break;
+        }
+
+        let closest = adjacency
+            .iter()
+            .map(|&(a, b)| (color_dist(clusters[&a].color, clusters[&b].color), a, b))
+            .min_by_key(|&(dist, _, _)| dist);
+
+        match closest {
+            Some((dist, a, b)) if dist <= merge_threshold => uf.union(a, b),
+            _ => break,
+        }
+    }
+
+    loop {
+        let (clusters, adjacency) = cluster_state(&mut uf, img, width, height);
+
+        let smallest = clusters
+            .iter()
+            .filter(|(_, cluster)| cluster.pixels.len() < min_area as usize)
+            .min_by_key(|(_, cluster)| cluster.pixels.len())
+            .map(|(&root, _)| root);
+
+        let root = match smallest {
+            Some(root) => root,
+            None => break,
+        };
+
+        let biggest_neighbor = adjacency
+            .iter()
+            .filter_map(|&(a, b)| {
+                if a == root {
+                    Some(b)
+                } else if b == root {
+                    Some(a)
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|other| clusters[other].pixels.len());
+
+        match biggest_neighbor {
+            Some(neighbor) => uf.union(root, neighbor),
+            // an isolated cluster with no neighbors to fold into, e.g. the
+            // whole image: leave it as is rather than looping forever.
+            None => break,
+        }
+    }
+
+    let (clusters, _) = cluster_state(&mut uf, img, width, height);
+
+    let mut clusters: Vec<_> = clusters.into_iter().map(|(_, cluster)| cluster).collect();
+    clusters.sort_by_key(|cluster| Reverse(cluster.pixels.len()));
+
+    let mut canvas = SvgCanvas::new(f64::from(width), f64::from(height));
+
+    for cluster in &clusters {
+        let boundary = trace_boundary(&cluster.pixels, width, height);
+        let boundary = simplify_collinear(&boundary);
+
+        let points: Vec<PointF64> = boundary
+            .iter()
+            .map(|p| PointF64::new(f64::from(p.x), f64::from(p.y)))
+            .collect();
+
+        canvas.path(
+            &points,
+            &svg::rgba(cluster.color[0], cluster.color[1], cluster.color[2], 0xFF),
+        );
+    }
+
+    canvas
+}
+
+/// A connected group of pixels sharing roughly the same color.
+struct Cluster {
+    pixels: Vec<PointU32>,
+    color: [u8; 3],
+}
+
+/// Recompute the current clusters (grouped by union-find root) and the set
+/// of adjacent cluster pairs, given the grid of pixels behind `uf`.
+fn cluster_state(
+    uf: &mut UnionFind,
+    img: &image::RgbImage,
+    width: u32,
+    height: u32,
+) -> (HashMap<usize, Cluster>, HashSet<(usize, usize)>) {
+    let idx = |x: u32, y: u32| (y as usize) * (width as usize) + (x as usize);
+
+    let mut roots = vec![0; (width as usize) * (height as usize)];
+    for (i, root) in roots.iter_mut().enumerate() {
+        *root = uf.find(i);
+    }
+
+    let mut sums: HashMap<usize, ([u64; 3], u64, Vec<PointU32>)> = HashMap::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let root = roots[idx(x, y)];
+            let pix = img.get_pixel(x, y);
+
+            let entry = sums.entry(root).or_insert_with(|| ([0; 3], 0, Vec::new()));
+            for (sum, chan) in entry.0.iter_mut().zip(&pix.data) {
+                *sum += u64::from(*chan);
+            }
+            entry.1 += 1;
+            entry.2.push(PointU32::new(x, y));
+        }
+    }
+
+    let clusters: HashMap<usize, Cluster> = sums
+        .into_iter()
+        .map(|(root, (sum, count, pixels))| {
+            let mut color = [0u8; 3];
+            for (c, s) in color.iter_mut().zip(&sum) {
+                *c = (*s / count) as u8;
+            }
+
+            (root, Cluster { pixels, color })
+        })
+        .collect();
+
+    let mut adjacency = HashSet::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let here = roots[idx(x, y)];
+
+            if x + 1 < width {
+                let right = roots[idx(x + 1, y)];
+                if right != here {
+                    adjacency.insert(ordered_pair(here, right));
+                }
+            }
+
+            if y + 1 < height {
+                let below = roots[idx(x, y + 1)];
+                if below != here {
+                    adjacency.insert(ordered_pair(here, below));
+                }
+            }
+        }
+    }
+
+    (clusters, adjacency)
+}
+
+fn ordered_pair(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Sum of squared per-channel differences between two colors.
+fn color_dist(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(&b)
+        .map(|(&x, &y)| {
+            let d = i32::from(x) - i32::from(y);
+            (d * d) as u32
+        })
+        .sum()
+}
+
+/// Walk the outer boundary of `pixels` into a closed polygon using Moore
+/// neighbor tracing. Holes inside the cluster, if any, aren't traced
+/// separately; only the silhouette is produced.
+fn trace_boundary(pixels: &[PointU32], width: u32, height: u32) -> Vec<PointU32> {
+    // clockwise neighbor offsets, starting due west.
+    const NEIGHBORS: [(i64, i64); 8] = [
+        (-1, 0),
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+    ];
+
+    let set: HashSet<PointU32> = pixels.iter().cloned().collect();
+
+    let start = match pixels.iter().min_by_key(|p| (p.y, p.x)) {
+        Some(&p) => p,
+        None => return Vec::new(),
+    };
+
+    let in_set = |x: i64, y: i64| -> bool {
+        x >= 0
+            && y >= 0
+            && (x as u32) < width
+            && (y as u32) < height
+            && set.contains(&PointU32::new(x as u32, y as u32))
+    };
+
+    let mut boundary = vec![start];
+    let mut current = start;
+    let mut backtrack_dir = 0;
+
+    loop {
+        let mut found = None;
+
+        for step in 0..8 {
+            let dir = (backtrack_dir + 1 + step) % 8;
+            let (dx, dy) = NEIGHBORS[dir];
+            let (nx, ny) = (i64::from(current.x) + dx, i64::from(current.y) + dy);
+
+            if in_set(nx, ny) {
+                found = Some((PointU32::new(nx as u32, ny as u32), dir));
+                break;
+            }
+        }
+
+        let (next, dir) = match found {
+            Some(next) => next,
+            // an isolated pixel with no in-set neighbor at all.
+            None => break,
+        };
+
+        // resume scanning from the direction we just arrived from.
+        backtrack_dir = (dir + 4) % 8;
+        current = next;
+
+        if current == start && boundary.len() > 1 {
+            break;
+        }
+
+        boundary.push(current);
+
+        if boundary.len() > pixels.len() * 8 {
+            // a degenerate cluster shape with no well-defined closed
+            // boundary; bail out instead of looping forever.
+            break;
+        }
+    }
+
+    boundary
+}
+
+/// Drop points that lie exactly on the straight line between their
+/// neighbors, collapsing straight pixel-stair runs into a single segment.
+fn simplify_collinear(points: &[PointU32]) -> Vec<PointU32> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let simplified: Vec<PointU32> = (0..points.len())
+        .filter(|&i| {
+            let prev = points[(i + points.len() - 1) % points.len()];
+            let cur = points[i];
+            let next = points[(i + 1) % points.len()];
+
+            let d1 = (
+                i64::from(cur.x) - i64::from(prev.x),
+                i64::from(cur.y) - i64::from(prev.y),
+            );
+            let d2 = (
+                i64::from(next.x) - i64::from(cur.x),
+                i64::from(next.y) - i64::from(cur.y),
+            );
+
+            d1.0 * d2.1 - d1.1 * d2.0 != 0
+        })
+        .map(|i| points[i])
+        .collect();
+
+    if simplified.is_empty() {
+        points.to_vec()
+    } else {
+        simplified
+    }
+}
+
+/// A union-find (disjoint-set) structure over `0..n`, used both to grow
+/// pixels into clusters and to later merge whole clusters together.
+struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        if self.parent[x] != x {
+            self.parent[x] = self.find(self.parent[x]);
+        }
+
+        self.parent[x]
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+
+        if ra == rb {
+            return;
+        }
+
+        if self.rank[ra] < self.rank[rb] {
+            self.parent[ra] = rb;
+        } else if self.rank[ra] > self.rank[rb] {
+            self.parent[rb] = ra;
+        } else {
+            self.parent[rb] = ra;
+            self.rank[ra] += 1;
+        }
+    }
+}
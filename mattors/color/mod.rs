@@ -0,0 +1,6 @@
+//! Color generation and manipulation helpers shared by the art commands
+//! (random HSV sampling bucketed by named hue/luminosity, RGB conversions,
+//! and this module's palette-ordering helpers).
+
+pub mod order;
+pub mod space;
@@ -0,0 +1,181 @@
+//! Lay out a set of colors so consecutive entries are close together in
+//! color space, so generated palettes and swatches read as a smooth
+//! gradient instead of jumping between unrelated hues.
+
+use super::Hsv;
+
+/// Map a `[0, 255]^3` RGB coordinate to its position along a 3-D
+/// [Hilbert curve](https://en.wikipedia.org/wiki/Hilbert_curve), using
+/// Skilling's iterative "axes to transpose" algorithm: walk the 3
+/// coordinates' bits from most- to least-significant, at each step either
+/// inverting or exchanging them against the first axis depending on which
+/// sub-cube they fall in, then Gray-encode the result and interleave the 3
+/// axes' bits into the final index. Sorting colors by this index keeps
+/// neighboring entries close together in RGB space.
+pub fn hilbert_index(coords: [u8; 3]) -> u32 {
+    const BITS: u32 = 8;
+    const DIMS: usize = 3;
+
+    let mut x = [
+        u32::from(coords[0]),
+        u32::from(coords[1]),
+        u32::from(coords[2]),
+    ];
+
+    // rotate/reflect each axis against the first one, from the
+    // most-significant bit down, undoing the excess work done by the
+    // curve's recursive subdivision.
+    let mut q = 1_u32 << (BITS - 1);
+    while q > 1 {
+        let p = q - 1;
+
+        for i in 0..DIMS {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+
+        q >>= 1;
+    }
+
+    // Gray-encode the result.
+    for i in 1..DIMS {
+        x[i] ^= x[i - 1];
+    }
+
+    let mut t = 0;
+    q = 1_u32 << (BITS - 1);
+    while q > 1 {
+        if x[DIMS - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for v in &mut x {
+        *v ^= t;
+    }
+
+    // interleave the (now Gray-coded) axes' bits, most-significant first,
+    // into a single index.
+    let mut index = 0_u32;
+    for bit in (0..BITS).rev() {
+        for v in &x {
+            index = (index << 1) | ((v >> bit) & 1);
+        }
+    }
+
+    index
+}
+
+/// Sort `colors` by their position along a Hilbert curve through RGB space
+/// (see `hilbert_index`), so the sequence reads as a smooth gradient
+/// instead of jumping between unrelated hues.
+pub fn sort_by_hilbert(colors: &mut [Hsv]) {
+    colors.sort_by_key(|c| hilbert_index(c.to_rgb()));
+}
+
+/// Sort `colors` by hue angle without any trigonometry: project RGB onto
+/// the 2 axes of the standard color hexagon (`x = 2R - G - B`,
+/// `y = G - B`), bucket by which quadrant `(x, y)` falls in, then order
+/// within a bucket by the `y / x` ratio, which is monotonic in angle
+/// across each quadrant.
+pub fn sort_by_hue(colors: &mut [Hsv]) {
+    colors.sort_by(|a, b| {
+        hue_key(a.to_rgb())
+            .partial_cmp(&hue_key(b.to_rgb()))
+            .unwrap()
+    });
+}
+
+fn hue_key(rgb: [u8; 3]) -> f64 {
+    let (r, g, b) = (f64::from(rgb[0]), f64::from(rgb[1]), f64::from(rgb[2]));
+
+    let x = 2.0 * r - g - b;
+    let y = g - b;
+
+    // squash the unbounded `y / x` ratio into (-1, 1) so it can be added
+    // to a per-quadrant bucket offset without the buckets overlapping.
+    let squashed = if x == 0.0 {
+        if y >= 0.0 {
+            1.0
+        } else {
+            -1.0
+        }
+    } else {
+        let ratio = y / x;
+        ratio / (1.0 + ratio.abs())
+    };
+
+    let bucket = if x >= 0.0 {
+        if y >= 0.0 {
+            0.0
+        } else {
+            4.0
+        }
+    } else {
+        2.0
+    };
+
+    bucket + squashed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hilbert_index_is_a_bijection_on_a_small_cube() {
+        let mut indices = (0_u8..8)
+            .flat_map(|x| (0_u8..8).flat_map(move |y| (0_u8..8).map(move |z| [x, y, z])))
+            .map(hilbert_index)
+            .collect::<Vec<_>>();
+
+        indices.sort_unstable();
+        indices.dedup();
+
+        assert_eq!(indices.len(), 8 * 8 * 8);
+    }
+
+    #[test]
+    fn hilbert_index_neighbors_on_the_curve_are_close_in_space() {
+        // walking the curve one step at a time should never jump more than
+        // one unit along any axis.
+        let mut points = (0_u8..16)
+            .flat_map(|x| (0_u8..16).flat_map(move |y| (0_u8..16).map(move |z| [x, y, z])))
+            .collect::<Vec<_>>();
+
+        points.sort_by_key(|&p| hilbert_index(p));
+
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let steps = (0..3)
+                .map(|i| (i64::from(a[i]) - i64::from(b[i])).abs())
+                .sum::<i64>();
+
+            assert_eq!(steps, 1);
+        }
+    }
+
+    #[test]
+    fn hue_key_is_monotonic_around_the_color_wheel() {
+        let rainbow = [
+            [255, 0, 0],
+            [255, 128, 0],
+            [255, 255, 0],
+            [0, 255, 0],
+            [0, 255, 255],
+            [0, 0, 255],
+            [255, 0, 255],
+        ];
+
+        let keys = rainbow.iter().map(|&c| hue_key(c)).collect::<Vec<_>>();
+
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! [CIELAB](https://en.wikipedia.org/wiki/CIELAB_color_space) conversion and
+//! the ΔE76 distance built on it, so colors can be compared and clustered
+//! the way they actually look to a human observer instead of by raw
+//! Euclidean distance in the RGB cube.
+
+use std::collections::HashSet;
+
+use rand::rngs::ThreadRng;
+use rand::Rng;
+
+// D65 reference white, matching the sRGB -> XYZ matrix below.
+const XN: f64 = 0.95047;
+const YN: f64 = 0.2126729 + 0.7151522 + 0.0721750;
+const ZN: f64 = 1.08883;
+
+/// Convert an 8-bit sRGB color to CIE `L*a*b*`: sRGB -> linear light via the
+/// standard 2.4-gamma transfer (with its `0.04045` linear segment near
+/// black), linear -> XYZ via the D65 matrix, then XYZ -> Lab via the
+/// `f(t) = t^(1/3)` kernel (linear below `(6/29)^3`, to avoid an infinite
+/// slope at `t = 0`).
+pub fn rgb_to_lab(rgb: [u8; 3]) -> [f64; 3] {
+    let linearize = |c: u8| -> f64 {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    let (r, g, b) = (linearize(rgb[0]), linearize(rgb[1]), linearize(rgb[2]));
+
+    let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+    const DELTA: f64 = 6.0 / 29.0;
+    let f = |t: f64| -> f64 {
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    };
+
+    let (fx, fy, fz) = (f(x / XN), f(y / YN), f(z / ZN));
+
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// [ΔE76](https://en.wikipedia.org/wiki/Color_difference#CIE76): plain
+/// Euclidean distance between two Lab colors. The simplest perceptual
+/// color-difference metric; later formulas (ΔE94, ΔE2000) correct for its
+/// non-uniformity in some hue ranges, but ΔE76 is accurate enough for
+/// clustering and deduplication.
+pub fn delta_e76(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt()
+}
+
+/// How to pick the initial `k` pivots for `lab_palette`, mirroring
+/// `geo::kmeans::KMeansInit`. Kept separate from it because Lab coordinates
+/// are plain `f64` triples and can't satisfy `geo::kmeans`'s `Ord` bound.
+pub enum LabKMeansInit<'a, R: Rng = ThreadRng> {
+    /// Pick pivots deterministically, evenly spaced across the deduped
+    /// input colors.
+    Deterministic,
+
+    /// Seed the pivots with [k-means++](https://en.wikipedia.org/wiki/K-means%2B%2B),
+    /// weighted by ΔE76 distance to the nearest already-chosen pivot.
+    PlusPlus(&'a mut R),
+}
+
+/// Cluster `pixels` into at most `k` perceptually-distinct colors: unlike
+/// `geo::kmeans`, centroids are computed and colors assigned to their
+/// nearest centroid by ΔE76 distance in Lab space, so the resulting palette
+/// groups colors the way a human would rather than by RGB-cube geometry.
+/// Each returned color is the actual input pixel closest to its cluster's
+/// final Lab centroid.
+pub fn lab_palette<R: Rng>(
+    pixels: impl IntoIterator<Item = image::Rgb<u8>>,
+    k: usize,
+    max_iterations: usize,
+    init: LabKMeansInit<R>,
+) -> Vec<image::Rgb<u8>> {
+    if k == 0 {
+        return vec![];
+    }
+
+    let mut points = pixels
+        .into_iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|pix| (pix, rgb_to_lab(pix.data)))
+        .collect::<Vec<_>>();
+
+    // HashSet iteration order varies between runs; sort so `Deterministic`
+    // picks the same pivots every time.
+    points.sort_by_key(|&(pix, _)| pix.data);
+
+    if points.len() <= k {
+        return points.into_iter().map(|(pix, _)| pix).collect();
+    }
+
+    let mut pivots = match init {
+        LabKMeansInit::Deterministic => (0..k)
+            .map(|i| points[i * points.len() / k].1)
+            .collect::<Vec<_>>(),
+        LabKMeansInit::PlusPlus(rng) => plus_plus_pivots(rng, &points, k),
+    };
+
+    let mut clusters = vec![Vec::new(); k];
+
+    for _ in 0..max_iterations {
+        for cluster in &mut clusters {
+            cluster.clear();
+        }
+
+        for (i, &(_, lab)) in points.iter().enumerate() {
+            let closest = pivots
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    delta_e76(lab, **a).partial_cmp(&delta_e76(lab, **b)).unwrap()
+                })
+                .map(|(i, _)| i)
+                .unwrap();
+
+            clusters[closest].push(i);
+        }
+
+        let mut pivot_changed = false;
+        for (cluster, pivot) in clusters.iter().zip(pivots.iter_mut()) {
+            if cluster.is_empty() {
+                continue;
+            }
+
+            let new_pivot = avg_lab(cluster.iter().map(|&i| points[i].1));
+            if new_pivot != *pivot {
+                pivot_changed = true;
+            }
+
+            *pivot = new_pivot;
+        }
+
+        if !pivot_changed {
+            break;
+        }
+    }
+
+    pivots
+        .iter()
+        .zip(clusters.iter())
+        .filter(|(_, cluster)| !cluster.is_empty())
+        .map(|(pivot, cluster)| {
+            cluster
+                .iter()
+                .map(|&i| points[i])
+                .min_by(|(_, a), (_, b)| {
+                    delta_e76(*pivot, *a).partial_cmp(&delta_e76(*pivot, *b)).unwrap()
+                })
+                .unwrap()
+                .0
+        })
+        .collect()
+}
+
+// k-means++ seeding in Lab space: pick the first pivot uniformly at random,
+// then repeatedly sample the next one with probability proportional to its
+// squared ΔE76 distance to the nearest already-chosen pivot.
+fn plus_plus_pivots<R: Rng>(
+    rng: &mut R,
+    points: &[(image::Rgb<u8>, [f64; 3])],
+    k: usize,
+) -> Vec<[f64; 3]> {
+    let mut pivots = vec![points[rng.gen_range(0, points.len())].1];
+
+    while pivots.len() < k {
+        let mut cumulative = Vec::with_capacity(points.len());
+        let mut total = 0.0_f64;
+
+        for &(_, lab) in points {
+            let nearest_dist = pivots
+                .iter()
+                .map(|&pivot| delta_e76(lab, pivot))
+                .fold(f64::INFINITY, f64::min);
+
+            total += nearest_dist * nearest_dist;
+            cumulative.push(total);
+        }
+
+        if total <= 0.0 {
+            pivots.push(points[rng.gen_range(0, points.len())].1);
+            continue;
+        }
+
+        let target = rng.gen_range(0.0, total);
+        let next_i = cumulative
+            .iter()
+            .position(|&cum| cum > target)
+            .unwrap_or(points.len() - 1);
+
+        pivots.push(points[next_i].1);
+    }
+
+    pivots
+}
+
+fn avg_lab(lab: impl Iterator<Item = [f64; 3]>) -> [f64; 3] {
+    let (mut sum, mut count) = ([0.0_f64; 3], 0_u32);
+
+    for l in lab {
+        for i in 0..3 {
+            sum[i] += l[i];
+        }
+        count += 1;
+    }
+
+    [sum[0] / f64::from(count), sum[1] / f64::from(count), sum[2] / f64::from(count)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn black_and_white_map_to_lab_extremes() {
+        let black = rgb_to_lab([0, 0, 0]);
+        let white = rgb_to_lab([255, 255, 255]);
+
+        assert!((black[0]).abs() < 1e-6);
+        assert!((white[0] - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn delta_e76_of_a_color_with_itself_is_zero() {
+        let lab = rgb_to_lab([123, 45, 67]);
+
+        assert_eq!(delta_e76(lab, lab), 0.0);
+    }
+
+    #[test]
+    fn delta_e76_black_to_white_is_the_full_lightness_range() {
+        let black = rgb_to_lab([0, 0, 0]);
+        let white = rgb_to_lab([255, 255, 255]);
+
+        assert!((delta_e76(black, white) - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lab_palette_with_fewer_colors_than_k_returns_them_all() {
+        let pixels = vec![
+            image::Rgb { data: [0, 0, 0] },
+            image::Rgb {
+                data: [255, 255, 255],
+            },
+        ];
+
+        let mut palette =
+            lab_palette::<ThreadRng>(pixels.clone(), 5, 10, LabKMeansInit::Deterministic);
+        palette.sort_by_key(|p| p.data);
+
+        let mut expected = pixels;
+        expected.sort_by_key(|p| p.data);
+
+        assert_eq!(palette, expected);
+    }
+
+    #[test]
+    fn lab_palette_groups_perceptually_close_colors_together() {
+        let near_black = image::Rgb { data: [5, 5, 5] };
+        let near_white = image::Rgb {
+            data: [250, 250, 250],
+        };
+
+        let pixels = vec![
+            image::Rgb { data: [0, 0, 0] },
+            near_black,
+            image::Rgb {
+                data: [255, 255, 255],
+            },
+            near_white,
+        ];
+
+        let palette =
+            lab_palette::<ThreadRng>(pixels, 2, 50, LabKMeansInit::Deterministic);
+
+        assert_eq!(palette.len(), 2);
+
+        let mut lightness = palette
+            .iter()
+            .map(|p| rgb_to_lab(p.data)[0])
+            .collect::<Vec<_>>();
+        lightness.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // one color from the dark pair and one from the light pair.
+        assert!(lightness[0] < 10.0);
+        assert!(lightness[1] > 90.0);
+    }
+}
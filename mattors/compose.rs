@@ -0,0 +1,171 @@
+//! Runtime-selectable per-pixel compositing for translucent shape fills.
+//!
+//! Generators that layer many overlapping shapes (`delaunay`, `voronoi`,
+//! `patchwork`) used to premix their fill color with a hardcoded alpha and
+//! write it straight into the buffer, so shapes could only ever look like
+//! plain stacked glass. This module adds a small set of separable blend
+//! modes on top of straight-alpha Porter-Duff "source over" compositing, so
+//! overlapping shapes can darken, lighten or otherwise interact with what's
+//! already underneath them.
+
+use std::str::FromStr;
+
+use geo::{BoundingBox, PointU32};
+
+/// How a shape's fill color is combined with whatever's already underneath
+/// it, before the result is composited over the destination with the
+/// fill's own alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlendMode {
+    /// Plain alpha compositing, the behavior every generator had before
+    /// blend modes existed.
+    Over,
+
+    /// Multiplies channels together; always darkens, never lightens.
+    Multiply,
+
+    /// Inverted multiply; always lightens, never darkens.
+    Screen,
+
+    /// Keeps the darker of the two channels.
+    Darken,
+
+    /// Keeps the lighter of the two channels.
+    Lighten,
+
+    /// Multiplies below 50% gray and screens above it, boosting contrast.
+    Overlay,
+
+    /// Adds channels together, clamping at white.
+    Add,
+}
+
+impl Default for BlendMode {
+    /// Plain alpha compositing, so a layer with no explicit blend mode
+    /// behaves the way every generator did before blend modes existed.
+    fn default() -> Self {
+        BlendMode::Over
+    }
+}
+
+impl FromStr for BlendMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "over" => Ok(BlendMode::Over),
+            "multiply" => Ok(BlendMode::Multiply),
+            "screen" => Ok(BlendMode::Screen),
+            "darken" => Ok(BlendMode::Darken),
+            "lighten" => Ok(BlendMode::Lighten),
+            "overlay" => Ok(BlendMode::Overlay),
+            "add" => Ok(BlendMode::Add),
+            _ => Err(format!(
+                "unknown blend mode {:?}, expected one of `over`, `multiply`, `screen`, \
+                 `darken`, `lighten`, `overlay` or `add`",
+                s
+            )),
+        }
+    }
+}
+
+/// A shape's fill: its color, how it's blended with the destination, and
+/// an optional region it's clipped to.
+#[derive(Clone, Copy, Debug)]
+pub struct Style {
+    /// The shape's straight-alpha fill color.
+    pub fill: image::Rgba<u8>,
+
+    /// How `fill` is combined with the destination pixel.
+    pub blend_mode: BlendMode,
+
+    /// If set, only pixels inside this box are touched by `composite`.
+    pub clip: Option<BoundingBox<u32>>,
+}
+
+impl Style {
+    /// A plain `Over`-blended fill with no clip: the same behavior the
+    /// generators had before blend modes and clipping were introduced.
+    pub fn solid(fill: image::Rgba<u8>) -> Self {
+        Style {
+            fill,
+            blend_mode: BlendMode::Over,
+            clip: None,
+        }
+    }
+
+    /// Blend `self.fill` into `img` at `(x, y)`, respecting `self.clip` and
+    /// doing nothing if the coordinates fall outside the image or the clip.
+    pub fn composite(&self, img: &mut image::RgbaImage, x: u32, y: u32) {
+        if x >= img.width() || y >= img.height() {
+            return;
+        }
+
+        if let Some(clip) = &self.clip {
+            if !clip.contains(&PointU32::new(x, y)) {
+                return;
+            }
+        }
+
+        let dst = *img.get_pixel(x, y);
+        img.put_pixel(x, y, blend(dst, self.fill, self.blend_mode));
+    }
+}
+
+/// Blend `src` with `dst` using `mode`, then composite the result over
+/// `dst` with straight-alpha Porter-Duff "source over" at `src`'s alpha, as
+/// described by the CSS Compositing and Blending spec. Channels are
+/// clamped and rounded to the nearest `u8`.
+pub fn blend(dst: image::Rgba<u8>, src: image::Rgba<u8>, mode: BlendMode) -> image::Rgba<u8> {
+    let to_unit = |c: u8| f64::from(c) / 255.0;
+    let from_unit = |c: f64| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+
+    let src_a = to_unit(src.data[3]);
+    let dst_a = to_unit(dst.data[3]);
+    let out_a = src_a + dst_a * (1.0 - src_a);
+
+    let mut out = [0u8; 4];
+
+    for i in 0..3 {
+        let cb = to_unit(dst.data[i]);
+        let cs = to_unit(src.data[i]);
+
+        // mix the blend-mode result with the plain source color based on
+        // how opaque the backdrop is, then composite the mixed color over
+        // the backdrop with "source over".
+        let mixed = (1.0 - dst_a) * cs + dst_a * blend_channel(cb, cs, mode);
+
+        let straight = if out_a == 0.0 {
+            0.0
+        } else {
+            (src_a * mixed + dst_a * (1.0 - src_a) * cb) / out_a
+        };
+
+        out[i] = from_unit(straight);
+    }
+
+    out[3] = from_unit(out_a);
+
+    image::Rgba { data: out }
+}
+
+/// Apply a single separable blend mode to a pair of channels, each in
+/// `[0.0, 1.0]`.
+fn blend_channel(dst: f64, src: f64, mode: BlendMode) -> f64 {
+    match mode {
+        BlendMode::Over => src,
+        BlendMode::Multiply => dst * src,
+        BlendMode::Screen => 1.0 - (1.0 - dst) * (1.0 - src),
+        BlendMode::Darken => dst.min(src),
+        BlendMode::Lighten => dst.max(src),
+        BlendMode::Overlay => {
+            if dst <= 0.5 {
+                2.0 * dst * src
+            } else {
+                1.0 - 2.0 * (1.0 - dst) * (1.0 - src)
+            }
+        }
+        BlendMode::Add => (dst + src).min(1.0),
+    }
+}
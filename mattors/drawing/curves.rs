@@ -0,0 +1,216 @@
+//! Bézier curve primitives, flattened into polylines for rasterization.
+
+extern crate geo;
+
+use self::geo::PointF64;
+
+/// A quadratic (3 control point) Bézier curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuadBezier {
+    /// The curve's control points, in order.
+    pub points: [PointF64; 3],
+}
+
+/// A cubic (4 control point) Bézier curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubicBezier {
+    /// The curve's control points, in order.
+    pub points: [PointF64; 4],
+}
+
+/// A curve that can be approximated by a polyline via recursive subdivision.
+pub trait Flatten {
+    /// Flatten this curve into a sequence of points that approximate it
+    /// within `tolerance` pixels, by recursively subdividing via [de
+    /// Casteljau's
+    /// algorithm](https://en.wikipedia.org/wiki/De_Casteljau%27s_algorithm)
+    /// while the control points stray further than `tolerance` from the
+    /// chord connecting the curve's endpoints.
+    fn flatten(&self, tolerance: f64) -> Vec<PointF64>;
+}
+
+fn lerp(a: PointF64, b: PointF64, t: f64) -> PointF64 {
+    PointF64::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+// perpendicular distance of `p` from the (infinite) line through `a` and `b`.
+fn distance_from_chord(p: PointF64, a: PointF64, b: PointF64) -> f64 {
+    let chord_len = a.dist::<f64>(&b);
+
+    if chord_len == 0.0 {
+        return p.dist::<f64>(&a);
+    }
+
+    ((b.x - a.x) * (a.y - p.y) - (a.x - p.x) * (b.y - a.y)).abs() / chord_len
+}
+
+impl QuadBezier {
+    /// Create a new `QuadBezier` from its 3 control points.
+    pub fn new(p0: PointF64, p1: PointF64, p2: PointF64) -> Self {
+        QuadBezier {
+            points: [p0, p1, p2],
+        }
+    }
+
+    fn split(&self) -> (Self, Self) {
+        let [p0, p1, p2] = self.points;
+
+        let p01 = lerp(p0, p1, 0.5);
+        let p12 = lerp(p1, p2, 0.5);
+        let mid = lerp(p01, p12, 0.5);
+
+        (QuadBezier::new(p0, p01, mid), QuadBezier::new(mid, p12, p2))
+    }
+
+    fn is_flat(&self, tolerance: f64) -> bool {
+        distance_from_chord(self.points[1], self.points[0], self.points[2]) <= tolerance
+    }
+
+    /// Solve for the parameter `t` at which this curve's `x` coordinate
+    /// equals `x`, so the curve can be sampled as a function of `x` (e.g.
+    /// for scanline rendering). Expands `x(t)` into `a*t^2 + b*t + c = 0`
+    /// and solves it without the catastrophic cancellation the textbook
+    /// formula suffers from when `b` is large: with
+    /// `q = -(b + sign(b) * sqrt(b^2 - 4ac)) / 2` the two roots are the
+    /// [Citardauq form](https://en.wikipedia.org/wiki/Loss_of_significance)
+    /// `c / q` and `q / a`. Returns the first of them that lands in
+    /// `[0, 1]`, or `None` if neither does.
+    pub fn solve_t_for_x(&self, x: f64) -> Option<f64> {
+        let [p0, p1, p2] = self.points;
+
+        let a = p0.x - 2.0 * p1.x + p2.x;
+        let b = 2.0 * (p1.x - p0.x);
+        let c = p0.x - x;
+
+        let in_range = |t: f64| t.is_finite() && t >= 0.0 && t <= 1.0;
+
+        if a == 0.0 {
+            if b == 0.0 {
+                return None;
+            }
+
+            return Some(-c / b).filter(|&t| in_range(t));
+        }
+
+        let q = -0.5 * (b + b.signum() * (b * b - 4.0 * a * c).sqrt());
+
+        [c / q, q / a].iter().cloned().find(|&t| in_range(t))
+    }
+}
+
+impl Flatten for QuadBezier {
+    fn flatten(&self, tolerance: f64) -> Vec<PointF64> {
+        if self.is_flat(tolerance) {
+            return vec![self.points[0], self.points[2]];
+        }
+
+        let (left, right) = self.split();
+
+        let mut points = left.flatten(tolerance);
+        points.pop();
+        points.extend(right.flatten(tolerance));
+
+        points
+    }
+}
+
+impl CubicBezier {
+    /// Create a new `CubicBezier` from its 4 control points.
+    pub fn new(p0: PointF64, p1: PointF64, p2: PointF64, p3: PointF64) -> Self {
+        CubicBezier {
+            points: [p0, p1, p2, p3],
+        }
+    }
+
+    // split this curve into two curves at t=0.5 via de Casteljau's algorithm.
+    fn split(&self) -> (Self, Self) {
+        let [p0, p1, p2, p3] = self.points;
+
+        let p01 = lerp(p0, p1, 0.5);
+        let p12 = lerp(p1, p2, 0.5);
+        let p23 = lerp(p2, p3, 0.5);
+
+        let p012 = lerp(p01, p12, 0.5);
+        let p123 = lerp(p12, p23, 0.5);
+
+        let mid = lerp(p012, p123, 0.5);
+
+        (
+            CubicBezier::new(p0, p01, p012, mid),
+            CubicBezier::new(mid, p123, p23, p3),
+        )
+    }
+
+    fn is_flat(&self, tolerance: f64) -> bool {
+        let [p0, p1, p2, p3] = self.points;
+
+        distance_from_chord(p1, p0, p3).max(distance_from_chord(p2, p0, p3)) <= tolerance
+    }
+}
+
+impl Flatten for CubicBezier {
+    fn flatten(&self, tolerance: f64) -> Vec<PointF64> {
+        if self.is_flat(tolerance) {
+            return vec![self.points[0], self.points[3]];
+        }
+
+        let (left, right) = self.split();
+
+        let mut points = left.flatten(tolerance);
+        points.pop();
+        points.extend(right.flatten(tolerance));
+
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattening_a_straight_cubic_keeps_only_the_endpoints() {
+        let curve = CubicBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(1.0, 0.0),
+            PointF64::new(2.0, 0.0),
+            PointF64::new(3.0, 0.0),
+        );
+
+        assert_eq!(
+            curve.flatten(0.005),
+            vec![PointF64::new(0.0, 0.0), PointF64::new(3.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn flattening_a_curved_cubic_adds_intermediate_points() {
+        let curve = CubicBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(0.0, 100.0),
+            PointF64::new(100.0, 100.0),
+            PointF64::new(100.0, 0.0),
+        );
+
+        let flattened = curve.flatten(0.005);
+
+        assert!(flattened.len() > 2);
+        assert_eq!(flattened[0], PointF64::new(0.0, 0.0));
+        assert_eq!(flattened[flattened.len() - 1], PointF64::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn solve_t_for_x_on_a_straight_quad() {
+        let curve = QuadBezier::new(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(5.0, 1.0),
+            PointF64::new(10.0, 0.0),
+        );
+
+        let t = curve.solve_t_for_x(5.0).unwrap();
+        assert!((t - 0.5).abs() < 1e-9);
+
+        assert_eq!(curve.solve_t_for_x(-1.0), None);
+        assert_eq!(curve.solve_t_for_x(11.0), None);
+    }
+}
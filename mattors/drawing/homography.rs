@@ -0,0 +1,215 @@
+//! Projective (homography) coordinate warping: maps any quadrilateral onto
+//! any other quadrilateral via a 3x3 matrix, so art built for a plain
+//! rectangle can be pre-distorted to fit an arbitrary skewed region — the
+//! same keystone/trapezoid-to-rectangle correction used to calibrate a
+//! projector onto a tilted screen.
+
+extern crate geo;
+
+use self::geo::PointF64;
+
+/// A 3x3 projective transform matrix, stored row-major.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Homography {
+    h: [[f64; 3]; 3],
+}
+
+impl Homography {
+    /// The identity homography: leaves every point unchanged.
+    pub fn identity() -> Self {
+        Homography {
+            h: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+
+    /// Build the homography mapping each `src[i]` to `dst[i]`. Each
+    /// correspondence `(x, y) -> (x', y')` must satisfy
+    ///
+    /// ```text
+    /// x' = (h00*x + h01*y + h02) / (h20*x + h21*y + h22)
+    /// y' = (h10*x + h11*y + h12) / (h20*x + h21*y + h22)
+    /// ```
+    ///
+    /// which, fixing `h22 = 1` (a homography is only defined up to scale),
+    /// rearranges into two linear equations per correspondence — 8
+    /// equations for the 8 remaining unknowns across the 4 point pairs.
+    /// Returns `None` if the 4 source (or destination) points are such that
+    /// the resulting system has no unique solution, e.g. 3 of them are
+    /// collinear.
+    pub fn from_quad(src: [PointF64; 4], dst: [PointF64; 4]) -> Option<Self> {
+        let mut a = vec![vec![0.0; 8]; 8];
+        let mut b = vec![0.0; 8];
+
+        for i in 0..4 {
+            let (x, y) = (src[i].x, src[i].y);
+            let (xp, yp) = (dst[i].x, dst[i].y);
+
+            a[2 * i] = vec![x, y, 1.0, 0.0, 0.0, 0.0, -x * xp, -y * xp];
+            b[2 * i] = xp;
+
+            a[2 * i + 1] = vec![0.0, 0.0, 0.0, x, y, 1.0, -x * yp, -y * yp];
+            b[2 * i + 1] = yp;
+        }
+
+        let sol = solve_linear_system(a, b)?;
+
+        Some(Homography {
+            h: [
+                [sol[0], sol[1], sol[2]],
+                [sol[3], sol[4], sol[5]],
+                [sol[6], sol[7], 1.0],
+            ],
+        })
+    }
+
+    /// Apply this homography to `p`: `(x', y', w') = H * (x, y, 1)`, then
+    /// perspective-divide by `w'`.
+    pub fn apply(&self, p: PointF64) -> PointF64 {
+        let x = self.h[0][0] * p.x + self.h[0][1] * p.y + self.h[0][2];
+        let y = self.h[1][0] * p.x + self.h[1][1] * p.y + self.h[1][2];
+        let w = self.h[2][0] * p.x + self.h[2][1] * p.y + self.h[2][2];
+
+        PointF64::new(x / w, y / w)
+    }
+
+    /// The inverse homography, useful for back-mapping a warped region's
+    /// pixels to the coordinates they came from. `None` if this homography
+    /// is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        invert3x3(&self.h).map(|h| Homography { h })
+    }
+}
+
+// solve the `a * x = b` linear system via Gaussian elimination with partial
+// pivoting. `None` if `a` is singular.
+fn solve_linear_system(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = a.len();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| {
+            a[i][col]
+                .abs()
+                .partial_cmp(&a[j][col].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+
+        if a[pivot][col].abs() < std::f64::EPSILON {
+            return None;
+        }
+
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in (col + 1)..n {
+            let factor = a[row][col] / a[col][col];
+
+            for k in col..n {
+                a[row][k] -= factor * a[col][k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    Some(x)
+}
+
+fn invert3x3(m: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+
+    if det.abs() < std::f64::EPSILON {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+
+    Some([
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv_det,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv_det,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv_det,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+        ],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_leaves_points_unchanged() {
+        let h = Homography::identity();
+        let p = PointF64::new(12.0, 34.0);
+
+        assert_eq!(h.apply(p), p);
+    }
+
+    #[test]
+    fn test_from_quad_maps_corners_exactly() {
+        let src = [
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        // a trapezoid: the top edge is narrower than the bottom one.
+        let dst = [
+            PointF64::new(2.0, 0.0),
+            PointF64::new(8.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+
+        let h = Homography::from_quad(src, dst).unwrap();
+
+        for (s, d) in src.iter().zip(&dst) {
+            let mapped = h.apply(*s);
+
+            assert!((mapped.x - d.x).abs() < 1e-9);
+            assert!((mapped.y - d.y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_inverse_undoes_the_homography() {
+        let src = [
+            PointF64::new(0.0, 0.0),
+            PointF64::new(10.0, 0.0),
+            PointF64::new(10.0, 10.0),
+            PointF64::new(0.0, 10.0),
+        ];
+        let dst = [
+            PointF64::new(2.0, 1.0),
+            PointF64::new(9.0, 0.0),
+            PointF64::new(10.0, 11.0),
+            PointF64::new(1.0, 9.0),
+        ];
+
+        let h = Homography::from_quad(src, dst).unwrap();
+        let inv = h.inverse().unwrap();
+
+        let p = PointF64::new(4.0, 6.0);
+        let round_tripped = inv.apply(h.apply(p));
+
+        assert!((round_tripped.x - p.x).abs() < 1e-9);
+        assert!((round_tripped.y - p.y).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,368 @@
+//! Low level implementation details of line drawing algorithms.
+
+extern crate geo;
+extern crate image;
+
+use std::mem;
+
+use self::geo::{Point, PointU32};
+
+/// Iterator that returns all the points that compose the line from start to
+/// end. It uses the [Bresenham's line
+/// algorithm](https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm) to
+/// interpolate the points in the line. Note that the points are returned in
+/// order that is if start is higher than end(i.e. start.y < end.y) then the
+/// points will be returned by starting from the top falling down.
+#[derive(Debug)]
+pub struct BresenhamLineIter {
+    // this struct is designed to work for non steep lines. In case we actually
+    // want to iterate over a steep line then the `new` function swaps x with y,
+    // sets `is_steep` that is then checked in `next` and swaps x with y again
+    // if the flag is set. It also assumes that `start` is the more "bottom
+    // left" than `end`(this invariant is also ensured by `new`).
+    start: Point<i64>,
+    end: PointU32,
+    is_steep: bool,
+    d: i64,
+    dx: i64,
+    dy: i64,
+    xstep: i64,
+    ystep: i64,
+}
+
+impl BresenhamLineIter {
+    /// Creates a new `BresenhamLineIter` iterator to return all points between
+    /// `start` and `end` both included.
+    pub fn new(mut start: PointU32, mut end: PointU32) -> BresenhamLineIter {
+        let mut dx = (i64::from(end.x) - i64::from(start.x)).abs();
+        let mut dy = (i64::from(end.y) - i64::from(start.y)).abs();
+
+        let is_steep;
+
+        if dx >= dy {
+            is_steep = false;
+        } else {
+            is_steep = true;
+
+            mem::swap(&mut start.x, &mut start.y);
+            mem::swap(&mut end.x, &mut end.y);
+            mem::swap(&mut dx, &mut dy);
+        }
+
+        let xstep = if start.x > end.x { -1 } else { 1 };
+        let ystep = if start.y > end.y { -1 } else { 1 };
+
+        let start = Point {
+            x: i64::from(start.x),
+            y: i64::from(start.y),
+        };
+
+        BresenhamLineIter {
+            start,
+            end,
+            is_steep,
+            dx,
+            dy,
+            d: 2 * dy - dx,
+            ystep,
+            xstep,
+        }
+    }
+
+    // calculate next non steep point in the line
+    fn next_non_steep_point(&mut self) -> Option<PointU32> {
+        if (self.start.x > i64::from(self.end.x) && self.xstep > 0)
+            || (self.start.x < i64::from(self.end.x) && self.xstep < 0)
+        {
+            return None;
+        }
+
+        if self.start.x < 0 || self.start.y < 0 {
+            return None;
+        }
+
+        let old = PointU32 {
+            x: self.start.x as u32,
+            y: self.start.y as u32,
+        };
+
+        if self.d > 0 {
+            self.start.y += self.ystep;
+            self.d -= 2 * self.dx;
+        }
+
+        self.d += 2 * self.dy;
+
+        self.start.x += self.xstep;
+
+        Some(old)
+    }
+}
+
+impl Iterator for BresenhamLineIter {
+    type Item = PointU32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_non_steep_point().map(|mut res| {
+            if self.is_steep {
+                mem::swap(&mut res.x, &mut res.y);
+            }
+            res
+        })
+    }
+}
+
+/// Iterator that yields the points of an anti-aliased line using a variation
+/// of [Xiaolin Wu's line
+/// algorithm](https://en.wikipedia.org/wiki/Xiaolin_Wu%27s_line_algorithm),
+/// paired with how much of that pixel the line actually covers, in `[0, 1]`.
+/// Unlike `BresenhamLineIter`, a single x (or y, for steep lines) step yields
+/// the two pixels straddling the ideal line, each with its own coverage.
+#[derive(Debug)]
+pub struct WuLineIter {
+    start: PointU32,
+    end: PointU32,
+    is_steep: bool,
+    gradient: f64,
+    intery: f64,
+    x: u32,
+    endpoint_done: bool,
+    done: bool,
+    pending: Option<(PointU32, f64)>,
+}
+
+impl WuLineIter {
+    /// Creates a new `WuLineIter` to return the anti-aliased points between
+    /// `start` and `end`, both included.
+    pub fn new(mut start: PointU32, mut end: PointU32) -> WuLineIter {
+        let mut dx = (i64::from(end.x) - i64::from(start.x)).abs();
+        let mut dy = (i64::from(end.y) - i64::from(start.y)).abs();
+
+        let is_steep = dy > dx;
+        if is_steep {
+            mem::swap(&mut start.x, &mut start.y);
+            mem::swap(&mut end.x, &mut end.y);
+            mem::swap(&mut dx, &mut dy);
+        }
+
+        if start.x > end.x {
+            mem::swap(&mut start, &mut end);
+        }
+
+        let gradient = if dx == 0 { 1.0 } else { dy as f64 / dx as f64 };
+        let gradient = if start.y > end.y { -gradient } else { gradient };
+
+        WuLineIter {
+            start,
+            end,
+            is_steep,
+            gradient,
+            intery: f64::from(start.y),
+            x: start.x,
+            endpoint_done: false,
+            done: false,
+            pending: None,
+        }
+    }
+
+    fn restore(&self, x: u32, y: u32) -> PointU32 {
+        if self.is_steep {
+            PointU32::new(y, x)
+        } else {
+            PointU32::new(x, y)
+        }
+    }
+}
+
+impl Iterator for WuLineIter {
+    type Item = (PointU32, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if let Some(second) = self.pending.take() {
+            return Some(second);
+        }
+
+        // since the points are `u32` there is no fractional part, so the
+        // endpoints are fully covered and don't need the two straddling
+        // pixels like every other step does.
+        if !self.endpoint_done {
+            self.endpoint_done = true;
+            return Some((self.restore(self.start.x, self.start.y), 1.0));
+        }
+
+        if self.x >= self.end.x {
+            self.done = true;
+            return Some((self.restore(self.end.x, self.end.y), 1.0));
+        }
+
+        self.x += 1;
+        self.intery += self.gradient;
+
+        let y = self.intery.floor();
+        let coverage = 1.0 - self.intery.fract();
+
+        // the pixel on the other side of the ideal line gets the rest.
+        self.pending = Some((self.restore(self.x, y as u32 + 1), self.intery.fract()));
+
+        Some((self.restore(self.x, y as u32), coverage))
+    }
+}
+
+/// A value that can be linearly interpolated, so `BresenhamInterpIter` can
+/// walk a line's pixels while also interpolating an arbitrary attribute
+/// (a color, a depth/z value, ...) between its endpoints.
+pub trait Lerp {
+    /// Return the point `t` of the way from `self` to `other`. `t` is
+    /// typically in `[0, 1]`.
+    fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for self::image::Rgb<u8> {
+    fn lerp(&self, other: &Self, t: f64) -> Self {
+        let mut data = [0; 3];
+
+        for i in 0..3 {
+            let from = f64::from(self.data[i]);
+            let to = f64::from(other.data[i]);
+
+            data[i] = (from + (to - from) * t).round() as u8;
+        }
+
+        self::image::Rgb { data }
+    }
+}
+
+/// Like `BresenhamLineIter`, but also linearly interpolates an arbitrary
+/// `Lerp` attribute (e.g. a color or a depth value) between `start_attr` and
+/// `end_attr` as it walks the line's pixels, so callers can draw
+/// color-gradient strokes or depth-sort polygon edges without a separate
+/// interpolation pass.
+#[derive(Debug)]
+pub struct BresenhamInterpIter<A> {
+    points: BresenhamLineIter,
+    start_attr: A,
+    end_attr: A,
+    t: f64,
+    t_step: f64,
+}
+
+impl<A> BresenhamInterpIter<A> {
+    /// Creates a new `BresenhamInterpIter` that walks from `start` to `end`,
+    /// interpolating from `start_attr` to `end_attr` along the way.
+    pub fn new(start: PointU32, end: PointU32, start_attr: A, end_attr: A) -> Self {
+        let dx = (i64::from(end.x) - i64::from(start.x)).abs();
+        let dy = (i64::from(end.y) - i64::from(start.y)).abs();
+        let steps = dx.max(dy);
+
+        let t_step = if steps == 0 { 0.0 } else { 1.0 / steps as f64 };
+
+        BresenhamInterpIter {
+            points: BresenhamLineIter::new(start, end),
+            start_attr,
+            end_attr,
+            t: 0.0,
+            t_step,
+        }
+    }
+}
+
+impl<A> Iterator for BresenhamInterpIter<A>
+where
+    A: Lerp,
+{
+    type Item = (PointU32, A);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pt = self.points.next()?;
+
+        let attr = self.start_attr.lerp(&self.end_attr, self.t.min(1.0));
+        self.t += self.t_step;
+
+        Some((pt, attr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _test_line_bresenham(start: PointU32, end: PointU32, exp_points: Vec<PointU32>) {
+        assert_eq!(
+            BresenhamLineIter::new(start, end).collect::<Vec<_>>(),
+            exp_points,
+            "line from start {:?} to end {:?}",
+            start,
+            end,
+        );
+
+        assert_eq!(
+            BresenhamLineIter::new(end, start).collect::<Vec<_>>(),
+            exp_points.iter().cloned().rev().collect::<Vec<_>>(),
+            "line from end {:?} to start {:?}",
+            end,
+            start,
+        );
+    }
+
+    #[test]
+    fn test_bresenham_line_basic() {
+        let origin = Point { x: 0, y: 0 };
+
+        _test_line_bresenham(origin, origin, vec![origin]);
+
+        let bis = Point { x: 3, y: 3 };
+        let bis_exp_points = vec![origin, Point { x: 1, y: 1 }, Point { x: 2, y: 2 }, bis];
+
+        _test_line_bresenham(origin, bis, bis_exp_points);
+    }
+
+    #[test]
+    fn test_wu_line_endpoints_are_fully_covered() {
+        let start = PointU32::new(0, 0);
+        let end = PointU32::new(4, 0);
+
+        let points = WuLineIter::new(start, end).collect::<Vec<_>>();
+
+        assert_eq!(points[0], (start, 1.0));
+        assert_eq!(points[points.len() - 1], (end, 1.0));
+    }
+
+    #[test]
+    fn test_f64_lerp() {
+        assert_eq!(0.0_f64.lerp(&10.0, 0.0), 0.0);
+        assert_eq!(0.0_f64.lerp(&10.0, 1.0), 10.0);
+        assert_eq!(0.0_f64.lerp(&10.0, 0.5), 5.0);
+    }
+
+    #[test]
+    fn test_rgb_lerp() {
+        let black = image::Rgb { data: [0, 0, 0] };
+        let white = image::Rgb { data: [255, 255, 255] };
+
+        assert_eq!(black.lerp(&white, 0.0), black);
+        assert_eq!(black.lerp(&white, 1.0), white);
+    }
+
+    #[test]
+    fn test_bresenham_interp_iter_endpoints_match_input_attrs() {
+        let start = PointU32::new(0, 0);
+        let end = PointU32::new(4, 0);
+
+        let points =
+            BresenhamInterpIter::new(start, end, 0.0_f64, 4.0_f64).collect::<Vec<_>>();
+
+        assert_eq!(points.first(), Some(&(start, 0.0)));
+        assert_eq!(points.last(), Some(&(end, 4.0)));
+        assert_eq!(points.len(), 5);
+    }
+}
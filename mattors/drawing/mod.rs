@@ -0,0 +1,643 @@
+//! Simple module to draw basic shapes on an image.
+
+pub mod curves;
+pub mod homography;
+pub mod line;
+pub mod svg;
+pub mod triangle;
+
+extern crate geo;
+extern crate image;
+extern crate num;
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use self::curves::Flatten;
+use self::geo::{supercover_line, Point, PointF64, PointI32, PointU32};
+use self::homography::Homography;
+use self::image::Pixel;
+use self::line::{BresenhamLineIter, WuLineIter};
+use self::triangle::{FlatTriangleIter, TriangleIter};
+
+fn to_point_u32(p: self::geo::PointF64) -> PointU32 {
+    PointU32::new(p.x.max(0.0) as u32, p.y.max(0.0) as u32)
+}
+
+// make sure `points` ends with a duplicate of its first point, so it forms a
+// closed ring for `scanline_crossings` regardless of how the caller built it.
+fn close_polygon<P: IntoIterator<Item = PointU32>>(points: P) -> Vec<PointU32> {
+    let mut points = points.into_iter().collect::<Vec<_>>();
+
+    if !points.is_empty() && points[0] != points[points.len() - 1] {
+        let p = points[0];
+        points.push(p);
+    }
+
+    points
+}
+
+// an edge's entry in the active-edge table: the scanline it drops out at,
+// its current x intersection (updated incrementally as y advances) and the
+// per-scanline x increment (the inverse slope).
+struct Edge {
+    y_max: u32,
+    x: f64,
+    dxdy: f64,
+}
+
+// sweep `points` (a closed ring) scanline by scanline using a classic
+// active-edge-table algorithm: edges are bucketed by their starting (minimum)
+// y, brought into the active set as y reaches them, dropped once y reaches
+// their ymax, and their x intersection is updated by adding `dxdy` each step
+// instead of being recomputed from scratch. Horizontal edges never cross a
+// scanline and are skipped. Returns, for every y from the lowest vertex up
+// to (but not including) the highest one, the sorted x intersections of all
+// active edges; consecutive pairs
+// bound the filled spans under the even-odd rule.
+fn scanline_crossings(points: &[PointU32]) -> Vec<(u32, Vec<f64>)> {
+    use std::collections::BTreeMap;
+
+    let mut edge_table: BTreeMap<u32, Vec<Edge>> = BTreeMap::new();
+    let mut ymin = u32::max_value();
+    let mut ymax = u32::min_value();
+
+    for edge in points.windows(2) {
+        let (p0, p1) = (edge[0], edge[1]);
+        if p0.y == p1.y {
+            continue;
+        }
+
+        let (lo, hi) = if p0.y < p1.y { (p0, p1) } else { (p1, p0) };
+        let dxdy = (f64::from(hi.x) - f64::from(lo.x)) / (f64::from(hi.y) - f64::from(lo.y));
+
+        ymin = ymin.min(lo.y);
+        ymax = ymax.max(hi.y);
+
+        edge_table.entry(lo.y).or_insert_with(Vec::new).push(Edge {
+            y_max: hi.y,
+            x: f64::from(lo.x),
+            dxdy,
+        });
+    }
+
+    if edge_table.is_empty() {
+        return vec![];
+    }
+
+    let mut active: Vec<Edge> = vec![];
+    let mut rows = vec![];
+
+    for y in ymin..ymax {
+        if let Some(mut entering) = edge_table.remove(&y) {
+            active.append(&mut entering);
+        }
+
+        active.retain(|e| e.y_max > y);
+
+        let mut xs = active.iter().map(|e| e.x).collect::<Vec<_>>();
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        rows.push((y, xs));
+
+        for e in &mut active {
+            e.x += e.dxdy;
+        }
+    }
+
+    rows
+}
+
+/// The `Blender` is the function that decides how to merge two pixels together.
+pub trait Blender<P: image::Pixel> {
+    /// The first param is the old value of the pixel and it's meant to be modified
+    /// with the blended value. The second parameter is the new pixel.
+    fn blend(dst: &mut P, src: &P);
+}
+
+/// Simple struct to easily write common geometric primitives onto a given image
+/// using the given `Blender`.
+pub struct Drawer<'a, I: 'a, B>
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+    B: Blender<I::Pixel>,
+{
+    img: &'a mut I,
+    _blender: PhantomData<B>,
+}
+
+impl<'a, I> Drawer<'a, I, NoopBlender>
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+{
+    /// Create a new `Drawer` that does not perform any blending, but just
+    /// copies the new pixel.
+    pub fn new_with_no_blending(img: &'a mut I) -> Self {
+        Drawer::new(img)
+    }
+}
+
+impl<'a, I> Drawer<'a, I, DefaultBlender>
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+{
+    /// Create a new `Drawer` that performs pixel blending.
+    pub fn new_with_default_blending(img: &'a mut I) -> Self {
+        Drawer::new(img)
+    }
+}
+
+impl<'a, I, B> Drawer<'a, I, B>
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+    B: Blender<I::Pixel>,
+{
+    /// Create a new `Drawer` on the given `img` with the given `blender`.
+    pub fn new(img: &'a mut I) -> Self {
+        Drawer {
+            img,
+            _blender: PhantomData,
+        }
+    }
+
+    /// Returns the inner image dimensions as (width, height).
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.img.dimensions()
+    }
+
+    /// Draw the given `pix`el at `x` and `y`. It does nothing if the
+    /// coordinates are out of bounds.
+    pub fn draw_pixel(&mut self, x: u32, y: u32, pix: &I::Pixel) {
+        if x >= self.img.width() || y >= self.img.height() {
+            return;
+        }
+
+        let old_pix = self.img.get_pixel_mut(x, y);
+        B::blend(old_pix, pix);
+    }
+
+    /// Draw a line on the given image using [Bresenham's line
+    /// algorithm](https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm).
+    pub fn line(&mut self, start: PointU32, end: PointU32, pix: &I::Pixel) {
+        let it = BresenhamLineIter::new(start, end);
+        for pt in it {
+            self.draw_pixel(pt.x, pt.y, pix);
+        }
+    }
+
+    /// Draw a line using `supercover_line` instead of Bresenham, so every
+    /// grid cell the line passes through gets painted, including the extra
+    /// diagonal-adjacent cells Bresenham skips. Useful for gap-free fills
+    /// and strokes where a single missed pixel would leave a hole.
+    pub fn draw_thick_line(&mut self, start: PointU32, end: PointU32, pix: &I::Pixel) {
+        let from = PointI32::new(start.x as i32, start.y as i32);
+        let to = PointI32::new(end.x as i32, end.y as i32);
+
+        for pt in supercover_line(from, to) {
+            if pt.x >= 0 && pt.y >= 0 {
+                self.draw_pixel(pt.x as u32, pt.y as u32, pix);
+            }
+        }
+    }
+
+    /// Draw a line following the given `StrokeStyle`, walking the
+    /// `BresenhamLineIter` while tracking how many pixels have been
+    /// traversed so far to decide whether the current pixel falls in an
+    /// "on" span of the pattern. `first_on` picks which span of the pattern
+    /// starts the line, so callers can phase-shift it.
+    pub fn line_styled(
+        &mut self,
+        start: PointU32,
+        end: PointU32,
+        pix: &I::Pixel,
+        style: &StrokeStyle,
+        first_on: bool,
+    ) {
+        let it = BresenhamLineIter::new(start, end);
+        for (travelled, pt) in it.enumerate() {
+            if style.is_on(travelled as u32, first_on) {
+                self.draw_pixel(pt.x, pt.y, pix);
+            }
+        }
+    }
+
+    /// Draw a line following an arbitrary dash `pattern` of alternating
+    /// on/off run lengths in pixels (e.g. `[10, 5]` for dash-gap or `[1, 1]`
+    /// for dotted), resuming from `phase` and returning the phase the dash
+    /// pattern ended on. Unlike `line_styled`, which always restarts its
+    /// pattern at the beginning of the line, feeding the returned phase back
+    /// into the next call lets a multi-segment polyline dash continuously
+    /// across its vertices instead of resetting at each one.
+    pub fn draw_dashed_line(
+        &mut self,
+        start: PointU32,
+        end: PointU32,
+        pix: &I::Pixel,
+        pattern: &[u32],
+        mut phase: DashPhase,
+    ) -> DashPhase {
+        if pattern.is_empty() {
+            self.line(start, end, pix);
+            return phase;
+        }
+
+        if phase.remaining == 0 {
+            phase.remaining = pattern[phase.run];
+        }
+
+        for pt in BresenhamLineIter::new(start, end) {
+            if phase.on {
+                self.draw_pixel(pt.x, pt.y, pix);
+            }
+
+            phase.remaining -= 1;
+            if phase.remaining == 0 {
+                phase.run = (phase.run + 1) % pattern.len();
+                phase.remaining = pattern[phase.run];
+                phase.on = !phase.on;
+            }
+        }
+
+        phase
+    }
+
+    /// Draw a hollow triangle on the given image, with each edge stroked
+    /// according to `style`. Consecutive edges alternate their starting
+    /// phase so the pattern doesn't visibly reset at every vertex.
+    pub fn hollow_triangle_styled(
+        &mut self,
+        p1: PointU32,
+        p2: PointU32,
+        p3: PointU32,
+        pix: &I::Pixel,
+        style: &StrokeStyle,
+    ) {
+        self.line_styled(p1, p2, pix, style, true);
+        self.line_styled(p1, p3, pix, style, false);
+        self.line_styled(p2, p3, pix, style, true);
+    }
+
+    /// Draw a hollow triangle on the given image.
+    pub fn hollow_triangle(&mut self, p1: PointU32, p2: PointU32, p3: PointU32, pix: &I::Pixel) {
+        self.line(p1, p2, pix);
+        self.line(p1, p3, pix);
+        self.line(p2, p3, pix);
+    }
+
+    /// Draw a triangle on the given image filled with the given `pix`.
+    pub fn triangle(&mut self, p1: PointU32, p2: PointU32, p3: PointU32, pix: &I::Pixel) {
+        let (tl, mid, br) = {
+            let mut tmp = [p1, p2, p3];
+            tmp.sort_by_key(|p| (p.y, p.x));
+
+            (tmp[0], tmp[1], tmp[2])
+        };
+
+        let mid_y = f64::from(mid.y);
+        let tl_y = f64::from(tl.y);
+        let br_y = f64::from(br.y);
+        let br_x = f64::from(br.x);
+        let tl_x = f64::from(tl.x);
+
+        let break_point = Point::new(
+            (tl_x + (mid_y - tl_y) / (br_y - tl_y) * (br_x - tl_x)) as u32,
+            mid.y,
+        );
+
+        let upper_triangle = FlatTriangleIter::new(tl, mid, break_point);
+        for (start, end) in upper_triangle {
+            self.line(start, end, pix);
+        }
+
+        let mut bottom_triangle = FlatTriangleIter::new(br, break_point, mid).peekable();
+        loop {
+            let mpoints = bottom_triangle.next();
+
+            match mpoints {
+                Some((start, end)) => {
+                    let are_last_points = bottom_triangle.peek().is_none();
+
+                    if !are_last_points {
+                        self.line(start, end, pix);
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Draw a triangle on the given image filled with the given `pix`, by
+    /// directly filling every `TriangleIter` span instead of stroking it
+    /// with `line` like `triangle` does; unlike `triangle`, degenerate
+    /// (flat or zero-area) triangles are handled without producing
+    /// duplicate or missing scanlines.
+    pub fn fill_triangle(&mut self, p1: PointU32, p2: PointU32, p3: PointU32, pix: &I::Pixel) {
+        for (left, right) in TriangleIter::new(p1, p2, p3) {
+            for x in left.x..=right.x {
+                self.draw_pixel(x, left.y, pix);
+            }
+        }
+    }
+
+    /// Flatten `curve` into a polyline (see `curves::Flatten`) and draw it
+    /// with solid segments.
+    pub fn bezier(&mut self, curve: &impl Flatten, tolerance: f64, pix: &I::Pixel) {
+        let points = curve.flatten(tolerance);
+
+        for edge in points.windows(2) {
+            self.line(to_point_u32(edge[0]), to_point_u32(edge[1]), pix);
+        }
+    }
+
+    /// Draw a hollow polygon.
+    pub fn hollow_polygon<P: IntoIterator<Item = PointU32>>(&mut self, points: P, pix: &I::Pixel) {
+        let mut points = points.into_iter();
+
+        points.next().map(|first| {
+            points.fold(first, |prev, cur| {
+                self.line(prev, cur, pix);
+
+                cur
+            });
+        });
+    }
+
+    /// Draw a polygon filled with the given pixel, using an active-edge-table
+    /// scanline sweep with the even-odd fill rule (see `scanline_crossings`).
+    pub fn polygon<P: IntoIterator<Item = PointU32>>(&mut self, points: P, pix: &I::Pixel) {
+        let points = close_polygon(points);
+
+        for (y, xs) in scanline_crossings(&points) {
+            for span in xs.chunks(2) {
+                if span.len() < 2 {
+                    break;
+                }
+
+                let (x0, x1) = (span[0].round() as u32, span[1].round() as u32);
+                for x in x0..x1 {
+                    self.draw_pixel(x, y, pix);
+                }
+            }
+        }
+    }
+
+    /// Same as `line`, but first pushes `start` and `end` through `h`,
+    /// letting a `Homography` pre-distort a straight edge to fit a skewed
+    /// quadrilateral before rasterizing.
+    pub fn warped_line(&mut self, h: &Homography, start: PointF64, end: PointF64, pix: &I::Pixel) {
+        self.line(to_point_u32(h.apply(start)), to_point_u32(h.apply(end)), pix);
+    }
+
+    /// Same as `polygon`, but first pushes every vertex through `h`.
+    pub fn warped_polygon<P: IntoIterator<Item = PointF64>>(
+        &mut self,
+        h: &Homography,
+        points: P,
+        pix: &I::Pixel,
+    ) {
+        self.polygon(points.into_iter().map(|p| to_point_u32(h.apply(p))), pix);
+    }
+
+    /// Parse `d` as SVG path data (see `svg::parse`) and fill every closed
+    /// subpath with `polygon`. Open subpaths have no well-defined interior
+    /// and are skipped.
+    pub fn svg_path_fill(&mut self, d: &str, tolerance: f64, pix: &I::Pixel) {
+        for subpath in svg::parse(d, tolerance) {
+            if subpath.closed {
+                self.polygon(subpath.points.into_iter().map(to_point_u32), pix);
+            }
+        }
+    }
+
+    /// Parse `d` as SVG path data (see `svg::parse`) and stroke every
+    /// subpath with `hollow_polygon`.
+    pub fn svg_path_stroke(&mut self, d: &str, tolerance: f64, pix: &I::Pixel) {
+        for subpath in svg::parse(d, tolerance) {
+            self.hollow_polygon(subpath.points.into_iter().map(to_point_u32), pix);
+        }
+    }
+}
+
+impl<'a, I, B> Drawer<'a, I, B>
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+    B: Blender<I::Pixel>,
+    f64: From<<I::Pixel as image::Pixel>::Subpixel>,
+{
+    /// Draw an anti-aliased line using a variation of [Xiaolin Wu's line
+    /// algorithm](https://en.wikipedia.org/wiki/Xiaolin_Wu%27s_line_algorithm),
+    /// by scaling `pix`'s channels by how much of each straddling pixel the
+    /// ideal line actually covers before blending it in. Callers that want
+    /// crisp, solid output should keep using `line`. This is the
+    /// coverage-weighted blend that consumes `WuLineIter`.
+    pub fn line_antialiased(&mut self, start: PointU32, end: PointU32, pix: &I::Pixel) {
+        use self::num::traits::cast::NumCast;
+
+        for (pt, coverage) in WuLineIter::new(start, end) {
+            let scaled_pix = pix.map(|c| {
+                <<I::Pixel as image::Pixel>::Subpixel as NumCast>::from(
+                    <f64 as From<_>>::from(c) * coverage,
+                )
+                .unwrap()
+            });
+
+            self.draw_pixel(pt.x, pt.y, &scaled_pix);
+        }
+    }
+
+    /// Same as `bezier`, but using `line_antialiased` for its segments.
+    pub fn bezier_antialiased(&mut self, curve: &impl Flatten, tolerance: f64, pix: &I::Pixel) {
+        let points = curve.flatten(tolerance);
+
+        for edge in points.windows(2) {
+            self.line_antialiased(to_point_u32(edge[0]), to_point_u32(edge[1]), pix);
+        }
+    }
+
+    /// Draw a hollow, anti-aliased triangle. Same as `hollow_triangle` but
+    /// using `line_antialiased` for its edges.
+    pub fn hollow_triangle_antialiased(
+        &mut self,
+        p1: PointU32,
+        p2: PointU32,
+        p3: PointU32,
+        pix: &I::Pixel,
+    ) {
+        self.line_antialiased(p1, p2, pix);
+        self.line_antialiased(p1, p3, pix);
+        self.line_antialiased(p2, p3, pix);
+    }
+
+    /// Same as `polygon`, but blends fractional horizontal coverage at each
+    /// scanline span's left and right edge pixel instead of hard on/off,
+    /// similar to how `line_antialiased` scales `pix` by how much of a
+    /// pixel the ideal line covers.
+    pub fn polygon_antialiased<P: IntoIterator<Item = PointU32>>(
+        &mut self,
+        points: P,
+        pix: &I::Pixel,
+    ) {
+        use self::num::traits::cast::NumCast;
+
+        let scaled = |coverage: f64| {
+            pix.map(|c| {
+                <<I::Pixel as image::Pixel>::Subpixel as NumCast>::from(
+                    <f64 as From<_>>::from(c) * coverage,
+                )
+                .unwrap()
+            })
+        };
+
+        let points = close_polygon(points);
+
+        for (y, xs) in scanline_crossings(&points) {
+            for span in xs.chunks(2) {
+                if span.len() < 2 {
+                    break;
+                }
+
+                let (x0, x1) = (span[0], span[1]);
+                if x1 <= x0 {
+                    continue;
+                }
+
+                let left = x0.floor() as u32;
+                let right = x1.ceil() as u32;
+
+                if left + 1 >= right {
+                    self.draw_pixel(left, y, &scaled(x1 - x0));
+                    continue;
+                }
+
+                self.draw_pixel(left, y, &scaled(f64::from(left + 1) - x0));
+
+                for x in (left + 1)..(right - 1) {
+                    self.draw_pixel(x, y, pix);
+                }
+
+                self.draw_pixel(right - 1, y, &scaled(x1 - f64::from(right - 1)));
+            }
+        }
+    }
+
+    /// Same as `svg_path_stroke`, but using `line_antialiased` for its
+    /// segments.
+    pub fn svg_path_stroke_antialiased(&mut self, d: &str, tolerance: f64, pix: &I::Pixel) {
+        for subpath in svg::parse(d, tolerance) {
+            let points = subpath
+                .points
+                .into_iter()
+                .map(to_point_u32)
+                .collect::<Vec<_>>();
+
+            for edge in points.windows(2) {
+                self.line_antialiased(edge[0], edge[1], pix);
+            }
+        }
+    }
+}
+
+/// The pattern a stroked line or polygon edge should follow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrokeStyle {
+    /// A continuous, unbroken stroke.
+    Solid,
+
+    /// Alternates `on` pixels drawn with `off` pixels skipped.
+    Dashed {
+        /// How many consecutive pixels to draw.
+        on: u32,
+        /// How many consecutive pixels to skip.
+        off: u32,
+    },
+
+    /// Plots a single pixel every `spacing` pixels.
+    Dotted {
+        /// The distance, in pixels walked along the line, between dots.
+        spacing: u32,
+    },
+}
+
+/// How far into a `draw_dashed_line` pattern the previous call left off, so
+/// the next segment of a polyline can resume the dash from there instead of
+/// restarting it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DashPhase {
+    run: usize,
+    remaining: u32,
+    on: bool,
+}
+
+impl DashPhase {
+    /// Start a fresh dash pattern. `first_on` picks whether the first run
+    /// is drawn or skipped.
+    pub fn start(first_on: bool) -> Self {
+        DashPhase {
+            run: 0,
+            remaining: 0,
+            on: first_on,
+        }
+    }
+}
+
+impl StrokeStyle {
+    // whether the pixel at the given distance travelled along the line
+    // should be plotted. `first_on` picks which span of the pattern comes
+    // first, so callers can phase-shift it (e.g. to make alternating edges
+    // of a shape not share the same dash phase).
+    fn is_on(self, travelled: u32, first_on: bool) -> bool {
+        match self {
+            StrokeStyle::Solid => true,
+            StrokeStyle::Dashed { on, off } => {
+                let period = on + off;
+                if period == 0 {
+                    return first_on;
+                }
+
+                let phase = travelled % period;
+
+                if first_on {
+                    phase < on
+                } else {
+                    phase >= off
+                }
+            }
+            StrokeStyle::Dotted { spacing } => {
+                if spacing == 0 {
+                    return first_on;
+                }
+
+                let half_period = spacing / 2;
+                let phase = if first_on {
+                    travelled % spacing
+                } else {
+                    (travelled + half_period) % spacing
+                };
+
+                phase == 0
+            }
+        }
+    }
+}
+
+/// Noop Blender
+pub struct NoopBlender;
+
+impl<P: image::Pixel> Blender<P> for NoopBlender {
+    fn blend(dst: &mut P, src: &P) {
+        *dst = *src;
+    }
+}
+
+/// Default Blender
+pub struct DefaultBlender;
+
+impl<P: image::Pixel> Blender<P> for DefaultBlender {
+    fn blend(dst: &mut P, src: &P) {
+        dst.blend(src);
+    }
+}
@@ -0,0 +1,287 @@
+//! Parses a subset of [SVG path
+//! data](https://www.w3.org/TR/SVG/paths.html#PathData) (`M`/`L`/`C`/`Q`/`Z`,
+//! absolute and relative, with implicit repeated coordinate pairs) into
+//! flattened polyline subpaths, ready to feed into `Drawer`'s fill/stroke
+//! primitives. This lets callers drop arbitrary vector glyphs and shapes
+//! into generative pieces instead of hand-building `PointU32` lists.
+
+extern crate geo;
+
+use self::geo::PointF64;
+
+use super::curves::{CubicBezier, Flatten, QuadBezier};
+
+/// A single subpath parsed out of a path's `d` attribute, already flattened
+/// into a polyline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubPath {
+    /// The subpath's points, in order. For a closed subpath the last point
+    /// is equal to the first, so it can be fed straight into
+    /// `Drawer::hollow_polygon` without any special-casing.
+    pub points: Vec<PointF64>,
+
+    /// Whether the subpath was closed with a `Z`/`z` command.
+    pub closed: bool,
+}
+
+/// Parse `d` into its subpaths, flattening any `C`/`Q` curves into polylines
+/// within `tolerance` pixels of the true curve (see `curves::Flatten`).
+/// Unknown commands stop parsing early rather than erroring out, since this
+/// is meant for dropping hand-authored shapes into generative pieces, not
+/// for validating arbitrary SVG.
+pub fn parse(d: &str, tolerance: f64) -> Vec<SubPath> {
+    let mut tokens = Tokenizer::new(d);
+
+    let mut subpaths = vec![];
+    let mut current = vec![];
+    let mut closed = false;
+
+    let mut pos = PointF64::new(0.0, 0.0);
+    let mut subpath_start = pos;
+
+    while let Some(cmd) = tokens.next_command() {
+        let relative = cmd.is_lowercase();
+
+        match cmd.to_ascii_uppercase() {
+            'M' => {
+                flush(&mut subpaths, &mut current, &mut closed);
+
+                pos = tokens.read_point(relative, pos);
+                subpath_start = pos;
+                current.push(pos);
+            }
+            'L' => {
+                pos = tokens.read_point(relative, pos);
+                current.push(pos);
+            }
+            'C' => {
+                let p1 = tokens.read_point(relative, pos);
+                let p2 = tokens.read_point(relative, pos);
+                let p3 = tokens.read_point(relative, pos);
+
+                current.extend(
+                    CubicBezier::new(pos, p1, p2, p3)
+                        .flatten(tolerance)
+                        .into_iter()
+                        .skip(1),
+                );
+                pos = p3;
+            }
+            'Q' => {
+                let p1 = tokens.read_point(relative, pos);
+                let p2 = tokens.read_point(relative, pos);
+
+                current.extend(
+                    QuadBezier::new(pos, p1, p2)
+                        .flatten(tolerance)
+                        .into_iter()
+                        .skip(1),
+                );
+                pos = p2;
+            }
+            'Z' => {
+                current.push(subpath_start);
+                closed = true;
+                pos = subpath_start;
+
+                flush(&mut subpaths, &mut current, &mut closed);
+            }
+            _ => break,
+        }
+    }
+
+    flush(&mut subpaths, &mut current, &mut closed);
+
+    subpaths
+}
+
+// push `current` as a new subpath if it has at least 2 points, then reset it
+// for the next one.
+fn flush(subpaths: &mut Vec<SubPath>, current: &mut Vec<PointF64>, closed: &mut bool) {
+    if current.len() > 1 {
+        subpaths.push(SubPath {
+            points: current.clone(),
+            closed: *closed,
+        });
+    }
+
+    current.clear();
+    *closed = false;
+}
+
+// a minimal scanner over path data: command letters and the (whitespace-
+// and/or comma-separated, sign-prefixable) floats that follow them.
+struct Tokenizer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    last_command: Option<char>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(d: &'a str) -> Self {
+        Tokenizer {
+            chars: d.chars().peekable(),
+            last_command: None,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == ',' {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // the next command letter, or an implicit repeat of the last one (`M`
+    // repeats as `L`, everything else repeats as itself) if another
+    // coordinate pair follows instead of a letter, per the SVG spec.
+    fn next_command(&mut self) -> Option<char> {
+        self.skip_separators();
+
+        match self.chars.peek() {
+            Some(&c) if c.is_ascii_alphabetic() => {
+                self.chars.next();
+                self.last_command = Some(c);
+                Some(c)
+            }
+            Some(&c) if is_number_start(c) => match self.last_command {
+                Some('M') => Some('L'),
+                Some('m') => Some('l'),
+                other => other,
+            },
+            _ => None,
+        }
+    }
+
+    fn read_number(&mut self) -> f64 {
+        self.skip_separators();
+
+        let mut s = String::new();
+
+        if let Some(&c) = self.chars.peek() {
+            if c == '-' || c == '+' {
+                s.push(c);
+                self.chars.next();
+            }
+        }
+
+        let mut seen_dot = false;
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || (c == '.' && !seen_dot) {
+                seen_dot = seen_dot || c == '.';
+                s.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+
+        s.parse().unwrap_or(0.0)
+    }
+
+    fn read_point(&mut self, relative: bool, origin: PointF64) -> PointF64 {
+        let x = self.read_number();
+        let y = self.read_number();
+
+        if relative {
+            PointF64::new(origin.x + x, origin.y + y)
+        } else {
+            PointF64::new(x, y)
+        }
+    }
+}
+
+fn is_number_start(c: char) -> bool {
+    c.is_ascii_digit() || c == '-' || c == '+' || c == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_a_simple_polyline() {
+        let subpaths = parse("M 0 0 L 10 0 L 10 10", 0.1);
+
+        assert_eq!(subpaths.len(), 1);
+        assert_eq!(
+            subpaths[0].points,
+            vec![
+                PointF64::new(0.0, 0.0),
+                PointF64::new(10.0, 0.0),
+                PointF64::new(10.0, 10.0),
+            ]
+        );
+        assert!(!subpaths[0].closed);
+    }
+
+    #[test]
+    fn parsing_implicit_repeated_line_coordinates() {
+        let subpaths = parse("M0,0L10,0 20,0 30,0", 0.1);
+
+        assert_eq!(
+            subpaths[0].points,
+            vec![
+                PointF64::new(0.0, 0.0),
+                PointF64::new(10.0, 0.0),
+                PointF64::new(20.0, 0.0),
+                PointF64::new(30.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parsing_a_closed_triangle() {
+        let subpaths = parse("M0 0 L10 0 L10 10 Z", 0.1);
+
+        assert_eq!(subpaths.len(), 1);
+        assert!(subpaths[0].closed);
+        assert_eq!(
+            subpaths[0].points,
+            vec![
+                PointF64::new(0.0, 0.0),
+                PointF64::new(10.0, 0.0),
+                PointF64::new(10.0, 10.0),
+                PointF64::new(0.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parsing_relative_commands() {
+        let subpaths = parse("m10 10 l5 0 l0 5 z", 0.1);
+
+        assert_eq!(
+            subpaths[0].points,
+            vec![
+                PointF64::new(10.0, 10.0),
+                PointF64::new(15.0, 10.0),
+                PointF64::new(15.0, 15.0),
+                PointF64::new(10.0, 10.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parsing_a_cubic_curve_flattens_it() {
+        let subpaths = parse("M0 0 C0 100 100 100 100 0", 0.005);
+
+        assert!(subpaths[0].points.len() > 2);
+        assert_eq!(subpaths[0].points[0], PointF64::new(0.0, 0.0));
+        assert_eq!(
+            subpaths[0].points[subpaths[0].points.len() - 1],
+            PointF64::new(100.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn parsing_multiple_subpaths() {
+        let subpaths = parse("M0 0 L10 0 Z M20 20 L30 20", 0.1);
+
+        assert_eq!(subpaths.len(), 2);
+        assert!(subpaths[0].closed);
+        assert!(!subpaths[1].closed);
+    }
+}
@@ -77,6 +77,102 @@ impl Iterator for FlatTriangleIter {
     }
 }
 
+/// Iterator that returns the left/right edge points of an arbitrary
+/// triangle, scanline by scanline, built out of up to 2 `FlatTriangleIter`s.
+/// Unlike `FlatTriangleIter`, the 3 vertices don't need to share a y: the
+/// vertices are sorted by y, the point where the long edge crosses the
+/// middle vertex's y is found, and the resulting flat-bottom and flat-top
+/// halves are stitched into one continuous iterator. Degenerate inputs are
+/// handled directly: 2 vertices sharing a y collapse to a single
+/// `FlatTriangleIter`, and all 3 sharing a y (a zero-area triangle) yield a
+/// single horizontal span.
+pub struct TriangleIter {
+    degenerate: Option<(PointU32, PointU32)>,
+    top: Option<FlatTriangleIter>,
+    bottom: Option<std::vec::IntoIter<(PointU32, PointU32)>>,
+    // the seam scanline at the split point is the last point of `top` and
+    // would also be the first point of `bottom`; skip that duplicate.
+    skip_first_bottom: bool,
+}
+
+impl TriangleIter {
+    /// Create a new `TriangleIter` over the triangle `(a, b, c)`, in any
+    /// order.
+    pub fn new(a: PointU32, b: PointU32, c: PointU32) -> TriangleIter {
+        let mut vertices = [a, b, c];
+        vertices.sort_by_key(|p| (p.y, p.x));
+        let (top, mid, bottom) = (vertices[0], vertices[1], vertices[2]);
+
+        if top.y == bottom.y {
+            let mut xs = [top.x, mid.x, bottom.x];
+            xs.sort_unstable();
+
+            return TriangleIter {
+                degenerate: Some((PointU32::new(xs[0], top.y), PointU32::new(xs[2], top.y))),
+                top: None,
+                bottom: None,
+                skip_first_bottom: false,
+            };
+        }
+
+        if mid.y == bottom.y {
+            return TriangleIter {
+                degenerate: None,
+                top: Some(FlatTriangleIter::new(top, mid, bottom)),
+                bottom: None,
+                skip_first_bottom: false,
+            };
+        }
+
+        if top.y == mid.y {
+            return TriangleIter {
+                degenerate: None,
+                top: Some(FlatTriangleIter::new(bottom, top, mid)),
+                bottom: None,
+                skip_first_bottom: false,
+            };
+        }
+
+        let t = (f64::from(mid.y) - f64::from(top.y)) / (f64::from(bottom.y) - f64::from(top.y));
+        let split_x = f64::from(top.x) + t * (f64::from(bottom.x) - f64::from(top.x));
+        let split = PointU32::new(split_x.round().max(0.0) as u32, mid.y);
+        let (left, right) = if mid.x <= split.x { (mid, split) } else { (split, mid) };
+
+        // `FlatTriangleIter` starts at its apex, so the bottom half is
+        // collected and reversed to run from the seam down to `bottom`.
+        let mut bottom_spans = FlatTriangleIter::new(bottom, left, right).collect::<Vec<_>>();
+        bottom_spans.reverse();
+
+        TriangleIter {
+            degenerate: None,
+            top: Some(FlatTriangleIter::new(top, left, right)),
+            bottom: Some(bottom_spans.into_iter()),
+            skip_first_bottom: true,
+        }
+    }
+}
+
+impl Iterator for TriangleIter {
+    type Item = (PointU32, PointU32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(span) = self.degenerate.take() {
+            return Some(span);
+        }
+
+        if let Some(item) = self.top.as_mut().and_then(Iterator::next) {
+            return Some(item);
+        }
+
+        if self.skip_first_bottom {
+            self.skip_first_bottom = false;
+            self.bottom.as_mut().and_then(Iterator::next);
+        }
+
+        self.bottom.as_mut().and_then(Iterator::next)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use self::geo::Point;
@@ -117,4 +213,55 @@ mod tests {
             exp_points
         );
     }
+
+    #[test]
+    fn test_triangle_iter_general() {
+        let top = Point::new(4, 0);
+        let mid = Point::new(0, 4);
+        let bottom = Point::new(8, 8);
+
+        let spans = TriangleIter::new(top, mid, bottom).collect::<Vec<_>>();
+
+        assert_eq!(spans.first(), Some(&(top, top)));
+        assert_eq!(spans.last(), Some(&(bottom, bottom)));
+
+        for (left, right) in &spans {
+            assert!(left.x <= right.x);
+        }
+    }
+
+    #[test]
+    fn test_triangle_iter_flat_bottom() {
+        let p1 = Point::new(2, 0);
+        let p2 = Point::new(6, 0);
+        let p3 = Point::new(4, 2);
+
+        assert_eq!(
+            TriangleIter::new(p3, p1, p2).collect::<Vec<_>>(),
+            FlatTriangleIter::new(p3, p1, p2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_triangle_iter_flat_top() {
+        let p1 = Point::new(4, 0);
+        let p2 = Point::new(2, 2);
+        let p3 = Point::new(8, 2);
+
+        assert_eq!(
+            TriangleIter::new(p1, p2, p3).collect::<Vec<_>>(),
+            FlatTriangleIter::new(p1, p2, p3).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_triangle_iter_zero_height() {
+        let p1 = Point::new(2, 3);
+        let p2 = Point::new(6, 3);
+        let p3 = Point::new(4, 3);
+
+        let spans = TriangleIter::new(p1, p2, p3).collect::<Vec<_>>();
+
+        assert_eq!(spans, vec![(PointU32::new(2, 3), PointU32::new(6, 3))]);
+    }
 }
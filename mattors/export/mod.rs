@@ -0,0 +1,3 @@
+//! File format exporters shared by the 3D-mesh-producing generators.
+
+pub mod stl;
@@ -0,0 +1,85 @@
+//! A minimal binary [STL](https://en.wikipedia.org/wiki/STL_(file_format))
+//! writer, so any generator that can produce a triangle soup can be sent
+//! straight to a 3D printer or renderer.
+
+use std::io::{self, Write};
+
+/// A 3D vertex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex3 {
+    /// x coordinate.
+    pub x: f64,
+    /// y coordinate.
+    pub y: f64,
+    /// z coordinate.
+    pub z: f64,
+}
+
+impl Vertex3 {
+    /// Create a new vertex.
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Vertex3 { x, y, z }
+    }
+
+    fn sub(self, o: Self) -> Self {
+        Vertex3::new(self.x - o.x, self.y - o.y, self.z - o.z)
+    }
+
+    fn cross(self, o: Self) -> Self {
+        Vertex3::new(
+            self.y * o.z - self.z * o.y,
+            self.z * o.x - self.x * o.z,
+            self.x * o.y - self.y * o.x,
+        )
+    }
+
+    fn normalized(self) -> Self {
+        let len = (self.x * self.x + self.y * self.y + self.z * self.z)
+            .sqrt()
+            .max(::std::f64::EPSILON);
+
+        Vertex3::new(self.x / len, self.y / len, self.z / len)
+    }
+}
+
+/// A triangle of a mesh, with a precomputed face normal.
+#[derive(Debug, Clone, Copy)]
+pub struct Triangle3 {
+    /// The 3 vertices of the triangle.
+    pub vertices: [Vertex3; 3],
+    /// The face normal.
+    pub normal: Vertex3,
+}
+
+/// Build a triangle from 3 vertices, computing its face normal from the
+/// `a -> b -> c` winding via the cross product.
+pub fn make_triangle(a: Vertex3, b: Vertex3, c: Vertex3) -> Triangle3 {
+    let normal = b.sub(a).cross(c.sub(a)).normalized();
+
+    Triangle3 {
+        vertices: [a, b, c],
+        normal,
+    }
+}
+
+/// Serialize a triangle soup to binary STL: an ignored 80-byte header, a
+/// little-endian `u32` triangle count, then per triangle the normal and its
+/// 3 vertices as `f32` triples, followed by a 2-byte attribute count left at
+/// 0.
+pub fn write_stl<W: Write>(mut w: W, triangles: &[Triangle3]) -> io::Result<()> {
+    let header = [0u8; 80];
+    w.write_all(&header)?;
+    w.write_all(&(triangles.len() as u32).to_le_bytes())?;
+
+    for tri in triangles {
+        for v in &[tri.normal, tri.vertices[0], tri.vertices[1], tri.vertices[2]] {
+            w.write_all(&(v.x as f32).to_le_bytes())?;
+            w.write_all(&(v.y as f32).to_le_bytes())?;
+            w.write_all(&(v.z as f32).to_le_bytes())?;
+        }
+
+        w.write_all(&0u16.to_le_bytes())?;
+    }
+
+    Ok(())
+}
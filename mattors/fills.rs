@@ -0,0 +1,301 @@
+//! Multi-stop gradient fills shared by generators that paint a color ramp
+//! across cells or stipple density (`voronoi`, `stippling`), rather than a
+//! flat color or a single linear lerp between exactly two colors.
+
+use std::str::FromStr;
+
+use geo::PointF64;
+
+/// A single color reached at position `t` along a `Gradient`.
+#[derive(Clone, Copy, Debug)]
+pub struct Stop {
+    /// Where along the gradient this stop sits, in `[0.0, 1.0]`.
+    pub t: f64,
+
+    /// The color at this stop.
+    pub color: image::Rgb<u8>,
+}
+
+impl Stop {
+    /// Build a new stop, reaching `color` at position `t`.
+    pub fn new(t: f64, color: image::Rgb<u8>) -> Self {
+        Stop { t, color }
+    }
+}
+
+/// Deserialize a stop from the same `t:RRGGBB` string `FromStr` accepts, so
+/// scene files can write stops the same way the CLI does.
+impl<'de> serde::Deserialize<'de> for Stop {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        Stop::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse a `t:RRGGBB` stop, e.g. `0.5:ff7f00`.
+impl FromStr for Stop {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+
+        let t = parts
+            .next()
+            .ok_or_else(|| format!("missing `t:RRGGBB` stop in {:?}", s))?
+            .parse::<f64>()
+            .map_err(|err| format!("invalid stop position in {:?}: {}", s, err))?;
+
+        let hex = parts
+            .next()
+            .ok_or_else(|| format!("missing `RRGGBB` color in stop {:?}", s))?;
+
+        if hex.len() != 6 {
+            return Err(format!("color {:?} is not in `RRGGBB` form", hex));
+        }
+
+        let mut channels = [0u8; 3];
+        for (channel, chunk) in channels.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *channel = u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 16)
+                .map_err(|err| format!("invalid color {:?}: {}", hex, err))?;
+        }
+
+        Ok(Stop::new(t, image::Rgb { data: channels }))
+    }
+}
+
+/// Where a `Gradient`'s `t` parameter comes from when it's sampled at a
+/// point, rather than given directly.
+#[derive(Clone, Debug)]
+enum Layout {
+    /// `t` grows linearly along `direction` starting at `origin`, reaching
+    /// `1.0` at `origin + direction`.
+    Linear {
+        origin: PointF64,
+        direction: PointF64,
+    },
+
+    /// `t` is the distance from `center`, normalized so it reaches `1.0` at
+    /// `radius`.
+    Radial { center: PointF64, radius: f64 },
+}
+
+/// A color ramp made of N stops at arbitrary positions, sampled either by a
+/// raw parameter (`color_at`) or by a point in space (`color_at_point`)
+/// according to a linear or radial layout.
+#[derive(Clone, Debug)]
+pub struct Gradient {
+    layout: Layout,
+    stops: Vec<Stop>,
+}
+
+impl Gradient {
+    /// A gradient whose `t` grows linearly along `direction` starting at
+    /// `origin`, reaching `1.0` at `origin + direction`. `stops` needn't be
+    /// sorted; fewer than 2 stops degenerates to a solid fill.
+    pub fn linear(origin: PointF64, direction: PointF64, stops: Vec<Stop>) -> Self {
+        Gradient {
+            layout: Layout::Linear { origin, direction },
+            stops: sorted_stops(stops),
+        }
+    }
+
+    /// A gradient whose `t` is the distance from `center`, reaching `1.0` at
+    /// `radius`. `stops` needn't be sorted; fewer than 2 stops degenerates to
+    /// a solid fill.
+    pub fn radial(center: PointF64, radius: f64, stops: Vec<Stop>) -> Self {
+        Gradient {
+            layout: Layout::Radial { center, radius },
+            stops: sorted_stops(stops),
+        }
+    }
+
+    /// Sample the color at the given point, mapping it to a `t` according to
+    /// this gradient's layout before delegating to `color_at`.
+    pub fn color_at_point(&self, p: PointF64) -> image::Rgb<u8> {
+        let t = match self.layout {
+            Layout::Linear { origin, direction } => {
+                let to_p = PointF64::new(p.x - origin.x, p.y - origin.y);
+                let len2 = direction.x * direction.x + direction.y * direction.y;
+
+                if len2 == 0.0 {
+                    0.0
+                } else {
+                    (to_p.x * direction.x + to_p.y * direction.y) / len2
+                }
+            }
+            Layout::Radial { center, radius } => {
+                let dx = p.x - center.x;
+                let dy = p.y - center.y;
+
+                if radius <= 0.0 {
+                    0.0
+                } else {
+                    (dx * dx + dy * dy).sqrt() / radius
+                }
+            }
+        };
+
+        self.color_at(t)
+    }
+
+    /// Sample the color at `t`, clamping to the outermost stops and
+    /// linearly interpolating between the two stops bracketing `t`.
+    pub fn color_at(&self, t: f64) -> image::Rgb<u8> {
+        match self.stops.len() {
+            0 => image::Rgb { data: [0, 0, 0] },
+            1 => self.stops[0].color,
+            _ => {
+                let t = t
+                    .max(self.stops[0].t)
+                    .min(self.stops[self.stops.len() - 1].t);
+
+                let next = self
+                    .stops
+                    .iter()
+                    .position(|stop| stop.t >= t)
+                    .unwrap_or(self.stops.len() - 1)
+                    .max(1);
+
+                let lo = &self.stops[next - 1];
+                let hi = &self.stops[next];
+
+                let span = hi.t - lo.t;
+                let frac = if span == 0.0 { 0.0 } else { (t - lo.t) / span };
+
+                lerp_rgb(lo.color, hi.color, frac)
+            }
+        }
+    }
+}
+
+fn sorted_stops(mut stops: Vec<Stop>) -> Vec<Stop> {
+    stops.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+    stops
+}
+
+fn lerp_rgb(from: image::Rgb<u8>, to: image::Rgb<u8>, t: f64) -> image::Rgb<u8> {
+    let mut data = [0u8; 3];
+
+    for i in 0..3 {
+        let a = f64::from(from.data[i]);
+        let b = f64::from(to.data[i]);
+
+        data[i] = (a + (b - a) * t).round() as u8;
+    }
+
+    image::Rgb { data }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_color_at_two_stops() {
+        let gradient = Gradient::linear(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(1.0, 0.0),
+            vec![
+                Stop::new(0.0, image::Rgb { data: [0, 0, 0] }),
+                Stop::new(
+                    1.0,
+                    image::Rgb {
+                        data: [255, 255, 255],
+                    },
+                ),
+            ],
+        );
+
+        assert_eq!(gradient.color_at(0.0), image::Rgb { data: [0, 0, 0] });
+        assert_eq!(
+            gradient.color_at(0.5),
+            image::Rgb {
+                data: [128, 128, 128]
+            }
+        );
+        assert_eq!(
+            gradient.color_at(1.0),
+            image::Rgb {
+                data: [255, 255, 255]
+            }
+        );
+
+        // clamped outside [0, 1]
+        assert_eq!(gradient.color_at(-1.0), image::Rgb { data: [0, 0, 0] });
+        assert_eq!(
+            gradient.color_at(2.0),
+            image::Rgb {
+                data: [255, 255, 255]
+            }
+        );
+    }
+
+    #[test]
+    fn test_color_at_multi_stop() {
+        let gradient = Gradient::linear(
+            PointF64::new(0.0, 0.0),
+            PointF64::new(1.0, 0.0),
+            vec![
+                Stop::new(0.0, image::Rgb { data: [255, 0, 0] }),
+                Stop::new(0.5, image::Rgb { data: [0, 255, 0] }),
+                Stop::new(1.0, image::Rgb { data: [0, 0, 255] }),
+            ],
+        );
+
+        assert_eq!(gradient.color_at(0.5), image::Rgb { data: [0, 255, 0] });
+        assert_eq!(
+            gradient.color_at(0.25),
+            image::Rgb {
+                data: [128, 128, 0]
+            }
+        );
+    }
+
+    #[test]
+    fn test_color_at_point_radial() {
+        let gradient = Gradient::radial(
+            PointF64::new(0.0, 0.0),
+            10.0,
+            vec![
+                Stop::new(0.0, image::Rgb { data: [0, 0, 0] }),
+                Stop::new(
+                    1.0,
+                    image::Rgb {
+                        data: [255, 255, 255],
+                    },
+                ),
+            ],
+        );
+
+        assert_eq!(
+            gradient.color_at_point(PointF64::new(0.0, 0.0)),
+            image::Rgb { data: [0, 0, 0] }
+        );
+        assert_eq!(
+            gradient.color_at_point(PointF64::new(10.0, 0.0)),
+            image::Rgb {
+                data: [255, 255, 255]
+            }
+        );
+    }
+
+    #[test]
+    fn test_stop_from_str() {
+        let stop: Stop = "0.5:ff7f00".parse().unwrap();
+
+        assert!((stop.t - 0.5).abs() < f64::EPSILON);
+        assert_eq!(
+            stop.color,
+            image::Rgb {
+                data: [0xff, 0x7f, 0x00]
+            }
+        );
+
+        assert!("bogus".parse::<Stop>().is_err());
+        assert!("0.5:notacolor".parse::<Stop>().is_err());
+    }
+}
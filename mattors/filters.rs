@@ -0,0 +1,355 @@
+//! SVG-style raster post-processing filters that can be composed into a
+//! pipeline and applied to a final `image::RgbImage` before it's saved.
+//!
+//! Pass a comma-separated [`FilterChain`] to [`apply_filters`], e.g.
+//! `turbulence,displace,blur` to fill a turbulence field, warp the source
+//! image by it and then soften the result.
+
+use std::str::FromStr;
+
+/// A single post-processing filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    /// Separable Gaussian blur with the given standard deviation.
+    Blur {
+        /// Standard deviation of the Gaussian kernel, in pixels.
+        stddev: f64,
+    },
+
+    /// Fill the image with a fractal turbulence field made of octaves of
+    /// lattice value noise (not Perlin gradient noise), as a grayscale
+    /// pattern, discarding whatever was there before.
+    Turbulence {
+        /// How many cycles of noise fit across one pixel; higher values
+        /// give a finer, noisier pattern.
+        frequency: f64,
+    },
+
+    /// Offset every pixel's sample coordinates in the *original* image by
+    /// the current image (expected to be a turbulence field) scaled by
+    /// `strength`, the same way SVG's `feDisplacementMap` uses a separate
+    /// `feTurbulence` output as its displacement map.
+    Displace {
+        /// How far, in pixels, a fully saturated displacement channel
+        /// shifts a sample.
+        strength: f64,
+    },
+
+    /// Multiply every pixel's `[r, g, b, a]` (`a` implicitly 255) by a 4x5
+    /// color matrix, in `feColorMatrix` row order (`r, g, b, a, bias` per
+    /// row), and write the first 3 rows back as the new RGB channels.
+    ColorMatrix {
+        /// 4 rows of 5 coefficients each.
+        matrix: [f64; 20],
+    },
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap_or("");
+        let param = parts.next();
+
+        match name {
+            "blur" => Ok(Filter::Blur {
+                stddev: parse_param(param, 3.0)?,
+            }),
+            "turbulence" => Ok(Filter::Turbulence {
+                frequency: parse_param(param, 0.05)?,
+            }),
+            "displace" => Ok(Filter::Displace {
+                strength: parse_param(param, 16.0)?,
+            }),
+            "saturate" => Ok(Filter::ColorMatrix {
+                matrix: saturate_matrix(parse_param(param, 1.5)?),
+            }),
+            _ => Err(format!(
+                "unknown filter {:?}, expected one of `blur`, `turbulence`, `displace`, `saturate`",
+                name
+            )),
+        }
+    }
+}
+
+fn parse_param(param: Option<&str>, default: f64) -> Result<f64, String> {
+    match param {
+        Some(p) => p
+            .parse()
+            .map_err(|_| format!("invalid filter parameter {:?}", p)),
+        None => Ok(default),
+    }
+}
+
+/// A `--post`-style, comma-separated list of filters applied in order, e.g.
+/// `turbulence:0.08,displace:24,blur:2`.
+#[derive(Clone, Debug, Default)]
+pub struct FilterChain(pub Vec<Filter>);
+
+impl FromStr for FilterChain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(Filter::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map(FilterChain)
+    }
+}
+
+/// Run `source` through every filter in `chain`, in order, and return the
+/// final image. `source` is untouched and stays available to filters (like
+/// [`Filter::Displace`]) that need to sample the original image.
+pub fn apply_filters(source: &image::RgbImage, chain: &[Filter]) -> image::RgbImage {
+    let mut current = source.clone();
+
+    for filter in chain {
+        current = match *filter {
+            Filter::Blur { stddev } => gaussian_blur(&current, stddev),
+            Filter::Turbulence { frequency } => {
+                turbulence(current.width(), current.height(), frequency)
+            }
+            Filter::Displace { strength } => displace(source, &current, strength),
+            Filter::ColorMatrix { matrix } => apply_color_matrix(&current, &matrix),
+        };
+    }
+
+    current
+}
+
+fn gaussian_blur(img: &image::RgbImage, stddev: f64) -> image::RgbImage {
+    if stddev <= 0.0 {
+        return img.clone();
+    }
+
+    let kernel = gaussian_kernel(stddev);
+    let horizontal = convolve_1d(img, &kernel, true);
+    convolve_1d(&horizontal, &kernel, false)
+}
+
+fn gaussian_kernel(stddev: f64) -> Vec<f64> {
+    let radius = (stddev * 3.0).ceil().max(1.0) as i32;
+
+    let mut kernel: Vec<f64> = (-radius..=radius)
+        .map(|i| {
+            let x = f64::from(i);
+            (-x * x / (2.0 * stddev * stddev)).exp()
+        })
+        .collect();
+
+    let sum: f64 = kernel.iter().sum();
+    for k in &mut kernel {
+        *k /= sum;
+    }
+
+    kernel
+}
+
+/// Convolve `img` with the 1D `kernel` along either the x axis
+/// (`horizontal`) or the y axis, clamping samples to the image edges.
+fn convolve_1d(img: &image::RgbImage, kernel: &[f64], horizontal: bool) -> image::RgbImage {
+    let (width, height) = img.dimensions();
+    let radius = (kernel.len() / 2) as i32;
+
+    let mut out = image::RgbImage::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f64; 3];
+
+            for (i, &weight) in kernel.iter().enumerate() {
+                let offset = i as i32 - radius;
+
+                let (sx, sy) = if horizontal {
+                    (clamp_coord(x as i32 + offset, width), y)
+                } else {
+                    (x, clamp_coord(y as i32 + offset, height))
+                };
+
+                let p = img.get_pixel(sx, sy);
+                for (c, s) in sum.iter_mut().enumerate() {
+                    *s += f64::from(p.data[c]) * weight;
+                }
+            }
+
+            out.put_pixel(
+                x,
+                y,
+                image::Rgb {
+                    data: [
+                        sum[0].round() as u8,
+                        sum[1].round() as u8,
+                        sum[2].round() as u8,
+                    ],
+                },
+            );
+        }
+    }
+
+    out
+}
+
+fn clamp_coord(v: i32, bound: u32) -> u32 {
+    v.max(0).min(bound as i32 - 1) as u32
+}
+
+/// Render a `width` x `height` grayscale turbulence pattern by summing
+/// several octaves of value noise (fractal Brownian motion).
+fn turbulence(width: u32, height: u32, frequency: f64) -> image::RgbImage {
+    const OCTAVES: u32 = 4;
+
+    let mut img = image::RgbImage::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = fractal_noise(f64::from(x) * frequency, f64::from(y) * frequency, OCTAVES);
+            let v = ((value * 0.5 + 0.5).max(0.0).min(1.0) * 255.0).round() as u8;
+
+            img.put_pixel(x, y, image::Rgb { data: [v, v, v] });
+        }
+    }
+
+    img
+}
+
+/// Sum `octaves` doublings of frequency/halvings of amplitude of
+/// [`value_noise`], normalized back to `[-1, 1]`.
+fn fractal_noise(x: f64, y: f64, octaves: u32) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_amplitude = 0.0;
+
+    for _ in 0..octaves {
+        total += value_noise(x * frequency, y * frequency) * amplitude;
+        max_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+
+    total / max_amplitude
+}
+
+/// Bilinearly-interpolated lattice value noise in `[-1, 1]`, smoothed with
+/// a cubic easing curve at the cell edges.
+fn value_noise(x: f64, y: f64) -> f64 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+
+    let sx = smoothstep(x - x0);
+    let sy = smoothstep(y - y0);
+
+    let n00 = lattice_value(x0 as i64, y0 as i64);
+    let n10 = lattice_value(x0 as i64 + 1, y0 as i64);
+    let n01 = lattice_value(x0 as i64, y0 as i64 + 1);
+    let n11 = lattice_value(x0 as i64 + 1, y0 as i64 + 1);
+
+    let ix0 = lerp(n00, n10, sx);
+    let ix1 = lerp(n01, n11, sx);
+
+    lerp(ix0, ix1, sy)
+}
+
+/// Pseudo-random value in `[-1, 1]` for an integer lattice point, hashed
+/// with a cheap integer mixing function so the noise is deterministic.
+fn lattice_value(x: i64, y: i64) -> f64 {
+    let mut h = (x.wrapping_mul(374_761_393)).wrapping_add(y.wrapping_mul(668_265_263)) as u64;
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+
+    (h as f64 / u64::max_value() as f64) * 2.0 - 1.0
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Offset every pixel of `source` by `displacement_map`'s red/green
+/// channels at that position, scaled by `strength`, and sample the result.
+fn displace(
+    source: &image::RgbImage,
+    displacement_map: &image::RgbImage,
+    strength: f64,
+) -> image::RgbImage {
+    let (width, height) = source.dimensions();
+    let mut out = image::RgbImage::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let map_x = x.min(displacement_map.width() - 1);
+            let map_y = y.min(displacement_map.height() - 1);
+            let m = displacement_map.get_pixel(map_x, map_y);
+
+            let dx = (f64::from(m.data[0]) / 255.0 - 0.5) * 2.0 * strength;
+            let dy = (f64::from(m.data[1]) / 255.0 - 0.5) * 2.0 * strength;
+
+            let sx = clamp_coord((f64::from(x) + dx).round() as i32, width);
+            let sy = clamp_coord((f64::from(y) + dy).round() as i32, height);
+
+            out.put_pixel(x, y, *source.get_pixel(sx, sy));
+        }
+    }
+
+    out
+}
+
+fn apply_color_matrix(img: &image::RgbImage, matrix: &[f64; 20]) -> image::RgbImage {
+    let (width, height) = img.dimensions();
+    let mut out = image::RgbImage::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let p = img.get_pixel(x, y);
+            let rgba = [
+                f64::from(p.data[0]),
+                f64::from(p.data[1]),
+                f64::from(p.data[2]),
+                255.0,
+            ];
+
+            let channel = |row: usize| {
+                let m = &matrix[row * 5..row * 5 + 5];
+                m[0] * rgba[0] + m[1] * rgba[1] + m[2] * rgba[2] + m[3] * rgba[3] + m[4] * 255.0
+            };
+
+            out.put_pixel(
+                x,
+                y,
+                image::Rgb {
+                    data: [
+                        clamp_u8(channel(0)),
+                        clamp_u8(channel(1)),
+                        clamp_u8(channel(2)),
+                    ],
+                },
+            );
+        }
+    }
+
+    out
+}
+
+fn clamp_u8(v: f64) -> u8 {
+    v.round().max(0.0).min(255.0) as u8
+}
+
+/// The standard SVG `feColorMatrix type="saturate"` matrix for the given
+/// saturation `amount` (`0` desaturates to grayscale, `1` is the identity).
+fn saturate_matrix(amount: f64) -> [f64; 20] {
+    #[rustfmt::skip]
+    let matrix = [
+        0.213 + 0.787 * amount, 0.715 - 0.715 * amount, 0.072 - 0.072 * amount, 0.0, 0.0,
+        0.213 - 0.213 * amount, 0.715 + 0.285 * amount, 0.072 - 0.072 * amount, 0.0, 0.0,
+        0.213 - 0.213 * amount, 0.715 - 0.715 * amount, 0.072 + 0.928 * amount, 0.0, 0.0,
+        0.0, 0.0, 0.0, 1.0, 0.0,
+    ];
+
+    matrix
+}
@@ -5,6 +5,16 @@
 #[macro_use]
 extern crate maplit;
 
+pub mod animation;
 pub mod art;
 pub mod color;
+pub mod compose;
 pub mod drawing;
+pub mod export;
+pub mod fills;
+pub mod filters;
+pub mod noise;
+pub mod quantize;
+pub mod remap;
+pub mod scene;
+pub mod svg;
@@ -2,6 +2,7 @@
 #![deny(missing_docs, warnings)]
 
 use std::f64;
+use std::fs;
 use std::num::ParseFloatError;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -14,20 +15,45 @@ use structopt::StructOpt;
 
 use geo::{PointF64, PointU32};
 
+use matto::animation;
+use matto::art::allrgb::{self, ColorOrder, FrontierOrder};
+use matto::art::barnsley_fern;
+use matto::art::buddhabrot;
+use matto::art::color_growth;
 use matto::art::delaunay;
 use matto::art::dithering;
 use matto::art::dragon;
 use matto::art::fractree;
-use matto::art::julia::{FractalPoint, JuliaGenIter};
+use matto::art::ifs;
+use matto::art::julia::{self, FractalPoint, JuliaGenIter, Palette};
+use matto::art::koch;
+use matto::art::lsystem::{self, Turtle};
 use matto::art::mondrian;
+use matto::art::newton::{NewtonGenIter, Polynomial};
 use matto::art::patchwork;
 use matto::art::primi;
 use matto::art::primi::Shape;
-use matto::art::quantize;
+use matto::art::quantize::{self, ColorSpace};
+use matto::art::quaternion_julia;
 use matto::art::runes;
 use matto::art::sierpinski;
 use matto::art::stippling;
+use matto::art::trimesh;
+use matto::art::vectorize;
 use matto::art::voronoi;
+use matto::compose::BlendMode;
+use matto::drawing::Drawer;
+use matto::export::stl;
+use matto::fills;
+use matto::filters::{self, Filter, FilterChain};
+use matto::scene;
+
+/// Geometric shrink factor applied to the viewport on every `--animate`
+/// frame of a Julia/Mandelbrot zoom.
+const ZOOM_FACTOR: f64 = 0.95;
+
+/// Delay, in milliseconds, each frame of a generated animation is shown for.
+const ANIMATION_FRAME_DELAY_MS: u16 = 50;
 
 const LIGHT_GREEN: [u8; 3] = [0x17, 0xB9, 0x78];
 const RED: [u8; 3] = [0xF6, 0x72, 0x80];
@@ -37,6 +63,26 @@ fn parse_complex(s: &str) -> Result<Complex64, ParseComplexError<ParseFloatError
     Complex64::from_str(s.trim())
 }
 
+fn parse_stop(s: &str) -> Result<fills::Stop, String> {
+    fills::Stop::from_str(s.trim())
+}
+
+/// Have fun with some generative art
+#[derive(StructOpt, Debug)]
+#[structopt(name = "matto")]
+pub struct Opt {
+    /// Which generative art to produce.
+    #[structopt(subcommand)]
+    command: Command,
+
+    /// Comma-separated chain of SVG-style post-processing filters (`blur`,
+    /// `turbulence`, `displace`, `saturate`, each taking an optional
+    /// `:param`) applied in order to the final RGB image before it's saved,
+    /// e.g. `--post turbulence:0.08,displace:24,blur:2`.
+    #[structopt(long = "post")]
+    post: Option<FilterChain>,
+}
+
 /// Have fun with some generative art
 #[derive(StructOpt, Debug)]
 #[structopt(name = "matto")]
@@ -47,6 +93,12 @@ pub enum Command {
         /// How many iterations the algorithm should perform before creating the image.
         #[structopt(short = "i", long = "iterations", default_value = "17")]
         iterations: u32,
+
+        /// Instead of the final images, render one frame per iteration
+        /// count from 1 up to `iterations` and encode them into an
+        /// animated GIF so the fractal appears to unfold.
+        #[structopt(short = "a", long = "animate")]
+        animate: bool,
     },
 
     /// Generate the horns fractals which are invented by me(really?) which are
@@ -56,6 +108,12 @@ pub enum Command {
         /// How many iterations the algorithm should perform before creating the image.
         #[structopt(short = "i", long = "iterations", default_value = "16")]
         iterations: u32,
+
+        /// Instead of the final images, render one frame per iteration
+        /// count from 1 up to `iterations` and encode them into an
+        /// animated GIF so the fractal appears to unfold.
+        #[structopt(short = "a", long = "animate")]
+        animate: bool,
     },
 
     /// Generate some julia fractals. The Mandelbrot set is one of those.
@@ -74,6 +132,11 @@ pub enum Command {
     #[structopt(name = "primirs")]
     Primirs(Primirs),
 
+    /// Turn an image into a posterized vector illustration by clustering
+    /// similar colored pixels and tracing their outlines.
+    #[structopt(name = "vectorize")]
+    Vectorize(Vectorize),
+
     /// Generate a Fractal Tree.
     #[structopt(name = "fractal-tree")]
     FractalTree(FractalTree),
@@ -109,6 +172,462 @@ pub enum Command {
     /// Generate some spider web likes shapes.
     #[structopt(name = "tangled-web")]
     TangledWeb(TangledWeb),
+
+    /// Generate a low-poly approximation of an image, with detail
+    /// concentrated where the image varies the most.
+    #[structopt(name = "adaptive-triangulation")]
+    AdaptiveTriangulation(AdaptiveTriangulation),
+
+    /// Generate a stained-glass-like low-poly rendering of an image, colored
+    /// by sampling the source photo instead of randomly.
+    #[structopt(name = "triangulate-image")]
+    TriangulateImage(TriangulateImage),
+
+    /// Generate a low-poly approximation of an image by splitting triangles
+    /// into 3 around their worst deviation point instead of re-triangulating
+    /// the whole point set.
+    #[structopt(name = "trimesh")]
+    Trimesh(Trimesh),
+
+    /// Render the basins of attraction of Newton's method on a complex
+    /// polynomial, a.k.a. polynomiography.
+    #[structopt(name = "newton")]
+    Newton(Newton),
+
+    /// Draw a Koch snowflake.
+    #[structopt(name = "koch")]
+    Koch(Koch),
+
+    /// Render a Barnsley fern using the chaos game.
+    #[structopt(name = "barnsley-fern")]
+    BarnsleyFern(BarnsleyFern),
+
+    /// Render an iterated function system attractor using the chaos game.
+    #[structopt(name = "ifs")]
+    Ifs(Ifs),
+
+    /// Paint an image using every color of a reduced RGB cube exactly once.
+    #[structopt(name = "all-colors")]
+    AllColors(AllColors),
+
+    /// Fill an image by placing every color of a palette exactly once, each
+    /// one next to the already-placed colors it looks most natural beside.
+    #[structopt(name = "color-growth")]
+    ColorGrowth(ColorGrowth),
+
+    /// Draw a shape produced by the generic L-system / turtle-graphics
+    /// engine: a Koch snowflake, a dragon curve, a Sierpinski triangle (via
+    /// the arrowhead rule), a Hilbert/Peano space-filling curve, or a
+    /// branching plant.
+    #[structopt(name = "lsystem")]
+    LSystem(LSystem),
+
+    /// Render a declarative multi-layer scene description (YAML or RON) onto
+    /// a single shared canvas.
+    #[structopt(name = "scene")]
+    Scene(SceneConfig),
+
+    /// Render the Buddhabrot: a density histogram of escaping Mandelbrot
+    /// orbits, rather than the usual per-pixel escape-time image.
+    #[structopt(name = "buddhabrot")]
+    Buddhabrot(Buddhabrot),
+
+    /// Render a quaternion Julia set as a 3D-printable mesh, exported as
+    /// binary STL instead of a 2D image.
+    #[structopt(name = "quaternion-julia")]
+    QuaternionJulia(QuaternionJulia),
+}
+
+/// Buddhabrot/Nebulabrot settings.
+#[derive(StructOpt, Debug)]
+pub struct Buddhabrot {
+    /// Number of random orbits to sample.
+    #[structopt(short = "n", long = "samples", default_value = "10000000")]
+    samples: u32,
+
+    /// Maximum number of iterations an orbit is followed for before it's
+    /// considered to not escape.
+    #[structopt(short = "i", long = "iterations", default_value = "500")]
+    iterations: u32,
+
+    /// Render the "Nebulabrot" color variant instead, compositing three
+    /// passes at 50/500/5000 iterations into the red/green/blue channels.
+    #[structopt(long = "nebula")]
+    nebula: bool,
+
+    /// Top left point where to start sampling.
+    #[structopt(short = "s", long = "start", default_value = "-2,-1.2")]
+    start: PointF64,
+
+    /// Bottom right point where to end sampling.
+    #[structopt(long = "end", default_value = "1,1.2")]
+    end: PointF64,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "1920")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "1080")]
+    height: u32,
+
+    /// Where to write the image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "buddhabrot.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// Quaternion Julia set settings, rendered as a 3D mesh instead of a 2D
+/// image.
+#[derive(StructOpt, Debug)]
+pub struct QuaternionJulia {
+    /// The quaternion constant `c` in `q -> q*q + c`, as 4 comma-separated
+    /// numbers `w,x,y,z`. Defaults to a well-known quaternion Julia constant
+    /// that produces a richly detailed surface.
+    #[structopt(
+        short = "c",
+        long = "constant",
+        default_value = "-1,0.2,0,0",
+        parse(try_from_str = "parse_quaternion")
+    )]
+    c: quaternion_julia::Quaternion,
+
+    /// The 4th quaternion component held fixed while slicing a 3D
+    /// cross-section out of the full 4D set.
+    #[structopt(short = "w", long = "slice-w", default_value = "0.0")]
+    w: f64,
+
+    /// Resolution of the sampling grid along each axis; the mesh is
+    /// extracted from an `n * n * n` grid, so runtime grows with its cube.
+    #[structopt(short = "n", long = "resolution", default_value = "64")]
+    resolution: usize,
+
+    /// Half-extent of the sampling grid, centered at the origin.
+    #[structopt(long = "half-extent", default_value = "1.5")]
+    half_extent: f64,
+
+    /// Number of escape-time iterations to run per sampled voxel.
+    #[structopt(short = "i", long = "iterations", default_value = "20")]
+    iterations: u32,
+
+    /// Isosurface threshold the marching tetrahedra extract the mesh at.
+    /// `0` traces the boundary between escaping and non-escaping voxels.
+    #[structopt(long = "threshold", default_value = "0.0")]
+    threshold: f64,
+
+    /// Where to write the binary STL mesh.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "quaternion-julia.stl",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// Newton-fractal/polynomiography settings.
+#[derive(StructOpt, Debug)]
+pub struct Newton {
+    /// Roots of the polynomial whose Newton basins to render, e.g. `-r
+    /// 1,0 -r -0.5,0.866 -r -0.5,-0.866` for z^3 - 1.
+    #[structopt(short = "r", long = "root", parse(try_from_str = "parse_complex"))]
+    roots: Vec<Complex64>,
+
+    /// Number of iterations to run Newton's method for before giving up on
+    /// convergence.
+    #[structopt(short = "i", long = "iterations", default_value = "64")]
+    iterations: u32,
+
+    /// Convergence tolerance: Newton's method stops once two consecutive
+    /// iterates are closer than this.
+    #[structopt(short = "e", long = "epsilon", default_value = "0.00001")]
+    epsilon: f64,
+
+    /// Top left point where to start the generation.
+    #[structopt(short = "s", long = "start", default_value = "-2,-2")]
+    start: PointF64,
+
+    /// Bottom right point where to end the generation.
+    #[structopt(long = "end", default_value = "2,2")]
+    end: PointF64,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "1920")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "1080")]
+    height: u32,
+}
+
+/// Koch snowflake settings.
+#[derive(StructOpt, Debug)]
+pub struct Koch {
+    /// How many times each segment should be replaced with four shorter
+    /// ones. The higher the more detailed the snowflake.
+    #[structopt(short = "d", long = "depth", default_value = "5")]
+    depth: u32,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "1920")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "1080")]
+    height: u32,
+
+    /// Where to write the snowflake image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "koch.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// Barnsley fern settings.
+#[derive(StructOpt, Debug)]
+pub struct BarnsleyFern {
+    /// Number of points to plot in the chaos game.
+    #[structopt(short = "i", long = "iterations", default_value = "1000000")]
+    iterations: u32,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "1080")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "1920")]
+    height: u32,
+
+    /// Where to write the fern image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "barnsley-fern.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// Iterated function system settings.
+#[derive(StructOpt, Debug)]
+pub struct Ifs {
+    /// Affine map to add to the chaos game, as 7 space-separated numbers
+    /// `a b c d e f p` for `(x, y) -> (ax + by + e, cx + dy + f)`, picked
+    /// with probability `p`. Repeat for every map; every map's `p` should
+    /// sum to 1 across the whole set. Defaults to the classic Barnsley
+    /// fern's four maps if none are given.
+    #[structopt(long = "map", parse(try_from_str = "parse_affine_map"))]
+    maps: Vec<ifs::AffineMap>,
+
+    /// Number of points to plot in the chaos game.
+    #[structopt(short = "i", long = "iterations", default_value = "1000000")]
+    iterations: u32,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "1080")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "1920")]
+    height: u32,
+
+    /// Where to write the rendered image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "ifs.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// All-RGB-colors settings.
+#[derive(StructOpt, Debug)]
+pub struct AllColors {
+    /// How colors are produced and matched to the growing frontier: either
+    /// `hilbert` or `nearest-neighbor`.
+    #[structopt(short = "r", long = "order", default_value = "hilbert")]
+    order: ColorOrder,
+
+    /// Frontier pixel visiting order used by `nearest-neighbor`: `fifo`
+    /// (discovery order), `priority` (most already-filled neighbors first)
+    /// or `random`. Ignored by `hilbert`.
+    #[structopt(short = "f", long = "frontier", default_value = "fifo")]
+    frontier: FrontierOrder,
+
+    /// Bits per channel of the color cube to use, so the cube has exactly
+    /// `2^(3*bits)` colors. If set, width * height must equal that count
+    /// exactly so every color is used exactly once; if unset, the smallest
+    /// cube with at least width * height colors is used instead.
+    #[structopt(long = "bits")]
+    bits: Option<u32>,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "256")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "256")]
+    height: u32,
+
+    /// Where to write the image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "all-colors.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// "Kd-forest" style color-growth settings.
+#[derive(StructOpt, Debug)]
+pub struct ColorGrowth {
+    /// Order the palette colors are offered to the growing frontier in:
+    /// `hue`, `random` or `hilbert`.
+    #[structopt(short = "r", long = "order", default_value = "hilbert")]
+    order: color_growth::ColorOrder,
+
+    /// Which neighbors of a pixel count towards its frontier membership and
+    /// target color: `four` or `eight`.
+    #[structopt(short = "n", long = "neighborhood", default_value = "eight")]
+    neighborhood: color_growth::Neighborhood,
+
+    /// Bits per channel of the color cube to use, so the cube has exactly
+    /// `2^(3*bits)` colors. If set, width * height must equal that count
+    /// exactly so every color is used exactly once; if unset, the smallest
+    /// cube with at least width * height colors is used instead.
+    #[structopt(long = "bits")]
+    bits: Option<u32>,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "256")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "256")]
+    height: u32,
+
+    /// Where to write the output image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "color-growth.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+/// L-system / turtle-graphics settings.
+#[derive(StructOpt, Debug)]
+pub struct LSystem {
+    /// Number of times to expand the L-system's axiom before interpreting
+    /// it. Higher depths make for more detailed (and much slower to draw)
+    /// curves.
+    #[structopt(short = "i", long = "iterations", default_value = "5")]
+    iterations: u32,
+
+    /// Length, in pixels, of a single forward step, before any decay is
+    /// applied.
+    #[structopt(short = "s", long = "step", default_value = "10.0")]
+    step: f64,
+
+    /// How much a step shrinks after every forward move. Only meaningful
+    /// for `branching-plant`, where a value below `1.0` makes branches get
+    /// thinner towards the tips.
+    #[structopt(long = "step-decay", default_value = "1.0")]
+    step_decay: f64,
+
+    /// Jitter the turning angle randomly instead of turning by a fixed
+    /// amount, for a less rigid looking plant. Only meaningful for
+    /// `branching-plant`.
+    #[structopt(long = "jitter")]
+    jitter: bool,
+
+    /// Width of the output image.
+    #[structopt(short = "w", long = "width", default_value = "1600")]
+    width: u32,
+
+    /// Height of the output image.
+    #[structopt(short = "h", long = "height", default_value = "1600")]
+    height: u32,
+
+    /// Where to write the output image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "lsystem.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+
+    /// Which preset to draw.
+    #[structopt(subcommand)]
+    preset: LSystemPreset,
+}
+
+/// All the available L-system presets.
+#[derive(StructOpt, Debug)]
+pub enum LSystemPreset {
+    /// A Koch snowflake, a 60 degree angle.
+    #[structopt(name = "koch-snowflake")]
+    KochSnowflake,
+
+    /// A dragon curve, a 90 degree angle.
+    #[structopt(name = "dragon-curve")]
+    DragonCurve,
+
+    /// A Sierpinski triangle via the classic arrowhead rule, a 60 degree
+    /// angle.
+    #[structopt(name = "sierpinski-arrowhead")]
+    SierpinskiArrowhead,
+
+    /// A Hilbert space-filling curve, a 90 degree angle.
+    #[structopt(name = "hilbert-curve")]
+    HilbertCurve,
+
+    /// A Peano space-filling curve, a 90 degree angle.
+    #[structopt(name = "peano-curve")]
+    PeanoCurve,
+
+    /// A branching plant, a 22.5 degree angle. Combine with `--step-decay`
+    /// and `--jitter` for a less rigid looking plant than the fixed-shape
+    /// `fractal-tree` command produces.
+    #[structopt(name = "branching-plant")]
+    BranchingPlant,
+}
+
+/// Declarative multi-layer scene rendering settings.
+#[derive(StructOpt, Debug)]
+pub struct SceneConfig {
+    /// Path to the scene description file (`.yaml`/`.yml` or `.ron`; any
+    /// other extension is parsed as YAML).
+    #[structopt(parse(from_os_str))]
+    scene_path: PathBuf,
+
+    /// Width of the shared canvas.
+    #[structopt(short = "w", long = "width", default_value = "1920")]
+    width: u32,
+
+    /// Height of the shared canvas.
+    #[structopt(short = "h", long = "height", default_value = "1080")]
+    height: u32,
+
+    /// Where to write the final image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "scene.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
 }
 
 /// Julia Set settings.
@@ -131,6 +650,161 @@ pub struct Julia {
     /// Which Julia set to generate.
     #[structopt(subcommand)]
     set_type: Option<JuliaSet>,
+
+    /// Instead of a single PNG, render this many frames progressively
+    /// zooming towards `zoom_target` and encode them into an animated GIF.
+    #[structopt(long = "animate")]
+    animate: Option<u32>,
+
+    /// Point the viewport zooms towards when `--animate` is given. Defaults
+    /// to the center of the viewport.
+    #[structopt(long = "zoom-target", parse(try_from_str = "parse_complex"))]
+    zoom_target: Option<Complex64>,
+
+    /// When used together with `--animate`, write each frame as a numbered
+    /// `name_0001.png`, `name_0002.png`, etc. into this directory (created
+    /// if missing) instead of encoding them into a `{name}.gif` animation.
+    /// Handy for assembling a deep zoom into video externally, especially
+    /// together with `--parallel`.
+    #[structopt(long = "frames-dir", parse(from_os_str))]
+    frames_dir: Option<PathBuf>,
+
+    /// In addition to the PNG, write a binary STL mesh built by treating
+    /// the per-pixel iteration count as a height field and extruding it
+    /// into a thin 3D-printable solid. Ignored together with `--animate`.
+    #[structopt(long = "stl", parse(from_os_str))]
+    stl_output: Option<PathBuf>,
+
+    /// Render across a rayon thread pool, with a progress bar, instead of
+    /// serially. Most useful at high resolutions/iteration counts.
+    #[structopt(long = "parallel")]
+    parallel: bool,
+
+    /// Number of worker threads to use when `--parallel` is given. Defaults
+    /// to rayon's own choice (one per logical CPU).
+    #[structopt(long = "threads")]
+    threads: Option<usize>,
+
+    /// Color escaped points via a named gradient (`fire`, `ice` or
+    /// `grayscale`) instead of the default two-tone coloring.
+    #[structopt(long = "palette", parse(try_from_str = "parse_palette"))]
+    palette: Option<Palette>,
+
+    /// Use continuous ("smooth") coloring instead of banding by raw
+    /// iteration count. Only has an effect together with `--palette`.
+    #[structopt(long = "smooth")]
+    smooth: bool,
+
+    /// In addition to the PNG, extract the escape-time field's iso-contours
+    /// via marching squares and stroke them onto their own image, written
+    /// here. Ignored together with `--animate`.
+    #[structopt(long = "contour", parse(from_os_str))]
+    contour_output: Option<PathBuf>,
+
+    /// Iso-values to extract contours at, as a comma-separated list. Only
+    /// has an effect together with `--contour`.
+    #[structopt(
+        long = "iso-values",
+        default_value = "8,16,32,48,56",
+        parse(try_from_str = "parse_iso_values")
+    )]
+    iso_values: Vec<f64>,
+}
+
+fn parse_palette(s: &str) -> Result<Palette, String> {
+    match s {
+        "fire" => Ok(Palette::fire()),
+        "ice" => Ok(Palette::ice()),
+        "grayscale" => Ok(Palette::grayscale()),
+        _ => Err(format!(
+            "unknown palette {:?}, expected one of: fire, ice, grayscale",
+            s
+        )),
+    }
+}
+
+fn parse_affine_map(s: &str) -> Result<ifs::AffineMap, String> {
+    let coeffs = s
+        .split_whitespace()
+        .map(|tok| tok.parse::<f64>().map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match coeffs.as_slice() {
+        &[a, b, c, d, e, f, p] => Ok(ifs::AffineMap::new(a, b, c, d, e, f, p)),
+        _ => Err(format!(
+            "expected 7 space-separated numbers \"a b c d e f p\", got {:?}",
+            s
+        )),
+    }
+}
+
+fn parse_iso_values(s: &str) -> Result<Vec<f64>, String> {
+    s.split(',')
+        .map(|tok| tok.parse::<f64>().map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn parse_quaternion(s: &str) -> Result<quaternion_julia::Quaternion, String> {
+    let coeffs = s
+        .split(',')
+        .map(|tok| tok.parse::<f64>().map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match coeffs.as_slice() {
+        &[w, x, y, z] => Ok(quaternion_julia::Quaternion::new(w, x, y, z)),
+        _ => Err(format!(
+            "expected 4 comma-separated numbers \"w,x,y,z\", got {:?}",
+            s
+        )),
+    }
+}
+
+fn parse_color_space(s: &str) -> Result<ColorSpace, String> {
+    match s {
+        "rgb" => Ok(ColorSpace::Rgb),
+        "lab" => Ok(ColorSpace::Lab),
+        "luv" => Ok(ColorSpace::Luv),
+        _ => Err(format!(
+            "unknown color space {:?}, expected one of: rgb, lab, luv",
+            s
+        )),
+    }
+}
+
+/// Which quantization algorithm `quantize_image` should run; see `Quantize`'s
+/// `--method` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantizeMethod {
+    /// Median Cut, in `--color-space`, stopping after `--divide-steps`
+    /// splits.
+    MedianCut,
+
+    /// Octree quantization down to `--colors` leaves.
+    Octree,
+
+    /// Median Cut followed by `--refine-iterations` passes of k-means.
+    Refined,
+
+    /// Error-driven adaptive box splitting down to `--colors` boxes.
+    Adaptive,
+
+    /// NeuQuant self-organizing-map quantization down to `--colors`
+    /// neurons.
+    NeuQuant,
+}
+
+fn parse_quantize_method(s: &str) -> Result<QuantizeMethod, String> {
+    match s {
+        "median-cut" => Ok(QuantizeMethod::MedianCut),
+        "octree" => Ok(QuantizeMethod::Octree),
+        "refined" => Ok(QuantizeMethod::Refined),
+        "adaptive" => Ok(QuantizeMethod::Adaptive),
+        "neuquant" => Ok(QuantizeMethod::NeuQuant),
+        _ => Err(format!(
+            "unknown quantization method {:?}, expected one of: median-cut, octree, refined, adaptive, neuquant",
+            s
+        )),
+    }
 }
 
 /// All the available Julia sets.
@@ -156,6 +830,14 @@ pub enum JuliaSet {
     #[structopt(name = "black-holes")]
     BlackHoles,
 
+    /// Generate a Tricorn (a.k.a. Mandelbar) fractal.
+    #[structopt(name = "tricorn")]
+    Tricorn,
+
+    /// Generate a Burning Ship fractal.
+    #[structopt(name = "burning-ship")]
+    BurningShip,
+
     /// Generate custom fractal by specifying its parameters.
     #[structopt(name = "custom")]
     Custom {
@@ -186,6 +868,69 @@ pub struct Quantize {
     #[structopt(short = "d", long = "divide-steps", default_value = "4")]
     divide_steps: u32,
 
+    /// Color space to run Median Cut's channel-range, split and averaging
+    /// steps in (`rgb`, `lab` or `luv`). `lab`/`luv` convert every pixel
+    /// through XYZ into a perceptually uniform space first and the final
+    /// palette back to sRGB, which tends to give noticeably better palettes
+    /// on photographic input than splitting directly on sRGB bytes.
+    #[structopt(
+        long = "color-space",
+        default_value = "rgb",
+        parse(try_from_str = "parse_color_space")
+    )]
+    color_space: ColorSpace,
+
+    /// Quantization algorithm to use: `median-cut` (the default, see
+    /// `--color-space`/`--divide-steps`), `octree`, which builds an octree
+    /// of per-channel bit planes and merges its smallest leaves until
+    /// `--colors` remain, preserving small high-contrast regions that
+    /// median cut's frequency-sorted splits tend to wash out, `refined`,
+    /// which follows median cut with `--refine-iterations` passes of
+    /// k-means to reduce its quantization error for the same palette size,
+    /// `adaptive`, which greedily splits the worst (highest
+    /// variance-times-population) box first instead of bisecting every box
+    /// `--divide-steps` times, so it works for arbitrary `--colors` counts,
+    /// or `neuquant`, which trains a self-organizing map of `--colors`
+    /// neurons on the image, tending to preserve photographic gradients
+    /// better than the clustering-based methods above.
+    #[structopt(
+        long = "method",
+        default_value = "median-cut",
+        parse(try_from_str = "parse_quantize_method")
+    )]
+    method: QuantizeMethod,
+
+    /// Number of output colors for `--method octree`/`adaptive`/`neuquant`.
+    /// Ignored by `median-cut`/`refined`, which derive their color count
+    /// from `--divide-steps` instead.
+    #[structopt(long = "colors", default_value = "16")]
+    colors: usize,
+
+    /// Train `--method neuquant` on every `sample-factor`-th pixel instead
+    /// of the whole image, trading palette quality for speed on larger
+    /// images. Ignored by every other method.
+    #[structopt(long = "sample-factor", default_value = "1")]
+    sample_factor: usize,
+
+    /// Number of k-means refinement passes for `--method refined`. Ignored
+    /// by every other method.
+    #[structopt(long = "refine-iterations", default_value = "4")]
+    refine_iterations: usize,
+
+    /// Remap pixels to the palette with Floyd–Steinberg error diffusion
+    /// instead of flat nearest-color mapping, which reproduces
+    /// smoothly-varying gradients much better at low palette sizes.
+    #[structopt(long = "dither")]
+    dither: bool,
+
+    /// Also write an indexed representation: the palette as an `N`x`1` PNG
+    /// at this path, and one little-endian `u16` palette index per pixel,
+    /// in row-major order, at the same path with an `.indices` extension.
+    /// This is what a real paletted format (GIF, indexed PNG, ...) needs,
+    /// unlike `--output`'s color-to-color remap.
+    #[structopt(long = "indexed-output", parse(from_os_str))]
+    indexed_output_path: Option<PathBuf>,
+
     /// Where to write the quantized image.
     #[structopt(
         short = "o",
@@ -267,6 +1012,44 @@ pub struct Primirs {
     img_path: PathBuf,
 }
 
+/// Turn a bitmap into layered colored SVG paths via hierarchical color
+/// clustering, the vector counterpart to `primirs`.
+#[derive(StructOpt, Debug)]
+pub struct Vectorize {
+    /// Maximum color distance (squared, per channel) between neighboring
+    /// pixels for them to be grown into the same cluster.
+    #[structopt(short = "g", long = "grow-threshold", default_value = "900")]
+    grow_threshold: u32,
+
+    /// Keep merging the two most similar adjacent clusters until at most
+    /// this many are left, as long as `merge-threshold` allows it.
+    #[structopt(short = "c", long = "clusters", default_value = "32")]
+    target_clusters: usize,
+
+    /// Maximum color distance (squared, per channel) allowed between two
+    /// clusters for them to be merged.
+    #[structopt(short = "m", long = "merge-threshold", default_value = "2500")]
+    merge_threshold: u32,
+
+    /// Clusters smaller than this many pixels are folded into their
+    /// largest neighbor instead of being traced on their own.
+    #[structopt(short = "a", long = "min-area", default_value = "16")]
+    min_area: u32,
+
+    /// Where to write the vectorized SVG illustration.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "vectorized.svg",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+
+    /// Image to vectorize.
+    #[structopt(name = "FILE", parse(from_os_str))]
+    img_path: PathBuf,
+}
+
 /// Generate some awesome Fractal Trees.
 #[derive(StructOpt, Debug)]
 pub struct FractalTree {
@@ -345,6 +1128,16 @@ pub struct Delaunay {
     #[structopt(short = "h", long = "height", default_value = "1080")]
     height: u32,
 
+    /// Whether to save the triangulation as a resolution-independent SVG of
+    /// filled polygons instead of a raster image.
+    #[structopt(long = "svg")]
+    svg: bool,
+
+    /// How overlapping triangles are composited: `over`, `multiply`,
+    /// `screen`, `darken`, `lighten`, `overlay` or `add`. Ignored by `--svg`.
+    #[structopt(long = "blend-mode", default_value = "over")]
+    blend_mode: BlendMode,
+
     /// Where to write the final image.
     #[structopt(
         short = "o",
@@ -367,6 +1160,18 @@ pub struct Voronoi {
     #[structopt(short = "g", long = "gradient-background")]
     gradient_background: bool,
 
+    /// Whether the background gradient radiates out from the center instead
+    /// of running left to right. Only used with `--gradient-background`.
+    #[structopt(long = "radial-gradient")]
+    radial_gradient: bool,
+
+    /// Color stops the background gradient ramps through, each `t:RRGGBB`
+    /// with `t` in `[0, 1]`, e.g. `-s 0:ff0000 -s 1:0000ff`. Needs at least
+    /// two; defaults to two random colors if none are given. Only used with
+    /// `--gradient-background`.
+    #[structopt(short = "s", long = "stop", parse(try_from_str = "parse_stop"))]
+    gradient_stops: Vec<fills::Stop>,
+
     /// Width of the image.
     #[structopt(short = "w", long = "width", default_value = "1920")]
     width: u32,
@@ -375,6 +1180,28 @@ pub struct Voronoi {
     #[structopt(short = "h", long = "height", default_value = "1080")]
     height: u32,
 
+    /// Distance metric used to assign each pixel to its closest point. One of
+    /// "euclidean", "manhattan" or "chebyshev".
+    #[structopt(short = "m", long = "metric", default_value = "euclidean")]
+    metric: String,
+
+    /// Whether the cells should wrap around the image edges, making the
+    /// output seamlessly tileable.
+    #[structopt(short = "t", long = "toroidal")]
+    toroidal: bool,
+
+    /// Whether to save the diagram as a resolution-independent SVG instead
+    /// of a raster image. Cells are true polygons computed from the Delaunay
+    /// dual, rather than a per-pixel nearest neighbor fill.
+    #[structopt(long = "svg")]
+    svg: bool,
+
+    /// How each cell is composited onto the background: `over`,
+    /// `multiply`, `screen`, `darken`, `lighten`, `overlay` or `add`.
+    /// Ignored by `--svg`.
+    #[structopt(long = "blend-mode", default_value = "over")]
+    blend_mode: BlendMode,
+
     /// Where to write the final image.
     #[structopt(
         short = "o",
@@ -385,6 +1212,90 @@ pub struct Voronoi {
     output_path: PathBuf,
 }
 
+/// Adaptive low-poly triangulation settings.
+#[derive(StructOpt, Debug)]
+pub struct AdaptiveTriangulation {
+    /// Maximum allowed interpolation error before a triangle gets subdivided.
+    /// Lower values produce more, smaller triangles.
+    #[structopt(short = "e", long = "epsilon", default_value = "10")]
+    epsilon: f64,
+
+    /// Maximum number of vertices to insert.
+    #[structopt(short = "n", long = "max-vertices", default_value = "5000")]
+    max_vertices: usize,
+
+    /// Minimum area a triangle must have to still be considered for
+    /// subdivision, to prevent infinite subdivision at hard edges.
+    #[structopt(long = "min-area", default_value = "8")]
+    min_area: f64,
+
+    /// Where to write the final image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "adaptive-triangulation.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+
+    /// Image to approximate.
+    #[structopt(name = "FILE", parse(from_os_str))]
+    img_path: PathBuf,
+}
+
+/// Color-sampled low-poly triangulation settings.
+#[derive(StructOpt, Debug)]
+pub struct TriangulateImage {
+    /// How many points to place by importance-sampling the image's Sobel
+    /// edge map, biasing towards high-contrast regions.
+    #[structopt(short = "e", long = "edge-points", default_value = "2000")]
+    edge_points: usize,
+
+    /// Size of the uniform grid of baseline points added on top of the edge
+    /// points, so flat regions still get covered.
+    #[structopt(short = "g", long = "grid-size", default_value = "10")]
+    grid_size: u32,
+
+    /// Where to write the final image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "triangulate-image.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+
+    /// Image to stylize.
+    #[structopt(name = "FILE", parse(from_os_str))]
+    img_path: PathBuf,
+}
+
+/// Triangle-splitting low-poly triangulation settings.
+#[derive(StructOpt, Debug)]
+pub struct Trimesh {
+    /// Maximum allowed deviation, scaled by a triangle's own size, before it
+    /// gets split. Lower values produce more, smaller triangles.
+    #[structopt(short = "e", long = "tolerance", default_value = "10")]
+    tolerance: f64,
+
+    /// Maximum number of triangles to produce.
+    #[structopt(short = "n", long = "max-triangles", default_value = "5000")]
+    max_triangles: usize,
+
+    /// Where to write the final image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "trimesh.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+
+    /// Image to approximate.
+    #[structopt(name = "FILE", parse(from_os_str))]
+    img_path: PathBuf,
+}
+
 /// Generate some art according to the PatchWork algorithm.
 #[derive(StructOpt, Debug)]
 pub struct Patchwork {
@@ -413,6 +1324,16 @@ pub struct Patchwork {
     #[structopt(short = "h", long = "height", default_value = "1080")]
     height: u32,
 
+    /// Whether to save the result as a resolution-independent SVG of filled
+    /// or outlined polygons instead of a raster image.
+    #[structopt(long = "svg")]
+    svg: bool,
+
+    /// How overlapping leaf polygons are composited: `over`, `multiply`,
+    /// `screen`, `darken`, `lighten`, `overlay` or `add`. Ignored by `--svg`.
+    #[structopt(long = "blend-mode", default_value = "over")]
+    blend_mode: BlendMode,
+
     /// Where to write the final image.
     #[structopt(
         short = "o",
@@ -437,6 +1358,11 @@ pub struct Stippling {
     #[structopt(short = "h", long = "height", default_value = "1080")]
     height: u32,
 
+    /// Whether to save the result as a resolution-independent SVG of
+    /// stippled circles instead of a raster image.
+    #[structopt(long = "svg")]
+    svg: bool,
+
     /// Where to write the final image.
     #[structopt(
         short = "o",
@@ -474,6 +1400,12 @@ pub struct StipplingGradient {
     /// in the next band. In particular npoints = prev_points + prev_points * k.
     #[structopt(short = "k", long = "grow-coefficient", default_value = "2")]
     grow_coeff: u32,
+
+    /// Color stops the bands ramp through, each `t:RRGGBB` with `t` in
+    /// `[0, 1]`, e.g. `-s 0:ff0000 -s 1:0000ff` for a red-to-blue ramp.
+    /// Defaults to solid black if none are given.
+    #[structopt(short = "s", long = "stop", parse(try_from_str = "parse_stop"))]
+    stops: Vec<fills::Stop>,
 }
 
 /// Stippling some rectangles.
@@ -512,6 +1444,11 @@ pub struct Mondrian {
     #[structopt(short = "h", long = "height", default_value = "1080")]
     height: u32,
 
+    /// Whether to save the composition as a resolution-independent SVG of
+    /// filled rects instead of a raster image.
+    #[structopt(long = "svg")]
+    svg: bool,
+
     /// Where to write the final image.
     #[structopt(
         short = "o",
@@ -566,72 +1503,350 @@ pub struct TangledWeb {
     #[structopt(short = "d", long = "circle-divisions", default_value = "30")]
     circle_divisions: u8,
 
-    /// Whether to save the image as an svg or png.
-    #[structopt(long = "svg")]
-    svg: bool,
+    /// Whether to save the image as an svg or png.
+    #[structopt(long = "svg")]
+    svg: bool,
+
+    /// Where to write the dithered image.
+    #[structopt(
+        short = "o",
+        long = "output",
+        default_value = "tangled-web.png",
+        parse(from_os_str)
+    )]
+    output_path: PathBuf,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    let post = opt.post.unwrap_or_default().0;
+
+    match opt.command {
+        Command::Dragons {
+            iterations,
+            animate,
+        } => spawn_dragons(iterations, animate, &post),
+        Command::Horns {
+            iterations,
+            animate,
+        } => spawn_horns(iterations, animate, &post),
+        Command::Julia(ref config) => match config.set_type {
+            None | Some(JuliaSet::All) => {
+                mandelbrot(config, &post);
+                planets(config, &post);
+                dragon_like(config, &post);
+                black_holes(config, &post);
+            }
+            Some(JuliaSet::Mandelbrot) => mandelbrot(config, &post),
+            Some(JuliaSet::Planets) => planets(config, &post),
+            Some(JuliaSet::DragonLikeSpiral) => dragon_like(config, &post),
+            Some(JuliaSet::BlackHoles) => black_holes(config, &post),
+            Some(JuliaSet::Tricorn) => tricorn(config, &post),
+            Some(JuliaSet::BurningShip) => burning_ship(config, &post),
+            Some(JuliaSet::Custom {
+                ref start,
+                ref end,
+                ref c,
+                ref name,
+            }) => create_julia_set(config, name, start, end, &post, |f, it| {
+                FractalPoint::julia(f, *c, it)
+            }),
+        },
+        Command::Quantize(ref config) => quantize_image(config, &post),
+        Command::Sierpinski(ref config) => spawn_sierpinski(config, &post),
+        Command::Primirs(ref config) => primirs(config),
+        Command::Vectorize(ref config) => vectorize(config),
+        Command::FractalTree(ref config) => fractal_tree(config),
+        Command::Runes(ref config) => runes(config),
+        Command::Delaunay(ref config) => delaunay(config),
+        Command::Voronoi(ref config) => voronoi(config, &post),
+        Command::Patchwork(ref config) => patchwork(config, &post),
+        Command::Stippling(ref config) => stippling(config, &post),
+        Command::Mondrian(ref config) => mondrian(config, &post),
+        Command::Dither(ref config) => dither(config, &post),
+        Command::TangledWeb(ref config) => tangled_web(config, &post),
+        Command::AdaptiveTriangulation(ref config) => adaptive_triangulation(config),
+        Command::TriangulateImage(ref config) => triangulate_image(config),
+        Command::Trimesh(ref config) => trimesh(config),
+        Command::Newton(ref config) => newton(config, &post),
+        Command::Koch(ref config) => koch(config, &post),
+        Command::BarnsleyFern(ref config) => barnsley_fern(config, &post),
+        Command::Ifs(ref config) => ifs(config, &post),
+        Command::AllColors(ref config) => all_colors(config, &post),
+        Command::ColorGrowth(ref config) => color_growth(config, &post),
+        Command::LSystem(ref config) => lsystem(config),
+        Command::Scene(ref config) => scene(config),
+        Command::Buddhabrot(ref config) => buddhabrot(config),
+        Command::QuaternionJulia(ref config) => quaternion_julia(config),
+    }
+}
+
+/// Apply the `--post` filter chain (if any) to `img` and return the result.
+fn postprocess(img: image::RgbImage, post: &[Filter]) -> image::RgbImage {
+    if post.is_empty() {
+        img
+    } else {
+        filters::apply_filters(&img, post)
+    }
+}
+
+fn newton(config: &Newton, post: &[Filter]) {
+    let poly = Polynomial::from_roots(config.roots.clone());
+
+    let stepx = (config.end.x - config.start.x) / f64::from(config.width);
+    let stepy = (config.end.y - config.start.y) / f64::from(config.height);
+
+    let frac_it = NewtonGenIter::new(
+        &poly,
+        config.start,
+        config.width,
+        config.height,
+        stepx,
+        stepy,
+        config.iterations,
+        config.epsilon,
+    );
+
+    let imgbuf = frac_it.into_image().expect("error while generating fractal");
+    let img = image::ImageRgb8(postprocess(imgbuf, post));
+
+    img.save("newton.png").expect("cannot save output image");
+}
+
+fn koch(config: &Koch, post: &[Filter]) {
+    let mut img = image::RgbImage::from_pixel(
+        config.width,
+        config.height,
+        image::Rgb { data: [0, 0, 0] },
+    );
+
+    koch::koch_snowflake(
+        &mut img,
+        config.depth,
+        &image::Rgb {
+            data: [0xFF, 0xFF, 0xFF],
+        },
+    );
+
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
+}
+
+fn barnsley_fern(config: &BarnsleyFern, post: &[Filter]) {
+    let mut rng = rand::thread_rng();
+
+    let mut img = image::RgbImage::from_pixel(
+        config.width,
+        config.height,
+        image::Rgb { data: [0, 0, 0] },
+    );
+
+    barnsley_fern::barnsley_fern_to_image(
+        &mut rng,
+        config.iterations,
+        &mut img,
+        image::Rgb {
+            data: [0x17, 0xB9, 0x78],
+        },
+    );
+
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
+}
+
+fn ifs(config: &Ifs, post: &[Filter]) {
+    let mut rng = rand::thread_rng();
+
+    let maps = if config.maps.is_empty() {
+        ifs::barnsley_fern_maps()
+    } else {
+        config.maps.clone()
+    };
+
+    const BURN_IN: u32 = 100;
+    let points = ifs::run(&mut rng, &maps, config.iterations, BURN_IN);
+
+    let img = ifs::to_image(
+        &points,
+        (config.width, config.height),
+        image::Rgb {
+            data: [0x17, 0xB9, 0x78],
+        },
+    );
+
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
+}
+
+fn all_colors(config: &AllColors, post: &[Filter]) {
+    if let Some(bits) = config.bits {
+        let expected_pixels = 1u64 << (3 * bits);
+        let actual_pixels = u64::from(config.width) * u64::from(config.height);
+
+        assert_eq!(
+            actual_pixels, expected_pixels,
+            "--bits {} needs an image of exactly {} pixels to use every color exactly once, but {}x{} is {}",
+            bits, expected_pixels, config.width, config.height, actual_pixels
+        );
+    }
+
+    let mut img = image::RgbImage::new(config.width, config.height);
+
+    allrgb::allrgb(
+        &mut img,
+        config.order,
+        config.frontier,
+        PointU32::new(config.width / 2, config.height / 2),
+        config.bits,
+    );
+
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
+}
+
+fn color_growth(config: &ColorGrowth) {
+    let side = match config.bits {
+        Some(bits) => {
+            let side = 1 << bits;
+            let expected_pixels = side * side * side;
+            let actual_pixels = config.width * config.height;
+
+            assert_eq!(
+                actual_pixels, expected_pixels,
+                "--bits {} needs an image of exactly {} pixels to use every color exactly once, \
+                 but {}x{} is {}",
+                bits, expected_pixels, config.width, config.height, actual_pixels
+            );
+
+            side
+        }
+        None => color_growth::cube_side(config.width * config.height),
+    };
+
+    let mut rng = rand::thread_rng();
+    let palette = color_growth::order_palette(color_growth::cube_colors(side), config.order, &mut rng);
+
+    let mut img = image::RgbImage::from_pixel(config.width, config.height, image::Rgb { data: [0, 0, 0] });
+
+    color_growth::grow(
+        &mut img,
+        palette,
+        PointU32::new(config.width / 2, config.height / 2),
+        config.neighborhood,
+    );
+
+    img.save(&config.output_path)
+        .expect("cannot save color-growth image");
+}
+
+fn lsystem(config: &LSystem) {
+    let (system, angle) = match config.preset {
+        LSystemPreset::KochSnowflake => (lsystem::koch_snowflake(), 60f64.to_radians()),
+        LSystemPreset::DragonCurve => (lsystem::dragon_curve(), 90f64.to_radians()),
+        LSystemPreset::SierpinskiArrowhead => (lsystem::sierpinski_arrowhead(), 60f64.to_radians()),
+        LSystemPreset::HilbertCurve => (lsystem::hilbert_curve(), 90f64.to_radians()),
+        LSystemPreset::PeanoCurve => (lsystem::peano_curve(), 90f64.to_radians()),
+        LSystemPreset::BranchingPlant => (lsystem::branching_plant(), 22.5f64.to_radians()),
+    };
+
+    let start = PointF64::new(f64::from(config.width) / 2.0, f64::from(config.height) - 1.0);
+
+    let mut turtle = Turtle::new(start, config.step, angle);
+    turtle.step_decay = config.step_decay;
+    if config.jitter {
+        turtle.rng = Some(rand::thread_rng());
+    }
+
+    let commands = system.expand(config.iterations);
+    let segments = turtle.interpret(&commands);
+
+    let mut img = image::GrayImage::from_pixel(config.width, config.height, image::Luma { data: [0] });
+    lsystem::draw(
+        &mut Drawer::new_with_no_blending(&mut img),
+        &segments,
+        &image::Luma { data: [0xFF] },
+    );
+
+    img.save(&config.output_path)
+        .expect("cannot save lsystem image");
+}
+
+fn scene(config: &SceneConfig) {
+    let contents = fs::read_to_string(&config.scene_path).expect("cannot read scene file");
+
+    let ext = config
+        .scene_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+
+    let parsed = scene::parse(ext, &contents).expect("invalid scene description");
+
+    let canvas = scene::render(config.width, config.height, &parsed);
+
+    canvas.save(&config.output_path).expect("cannot save image");
+}
+
+fn buddhabrot(config: &Buddhabrot) {
+    let mut rng = rand::thread_rng();
+
+    let img = if config.nebula {
+        buddhabrot::nebulabrot(
+            &mut rng,
+            (config.width, config.height),
+            config.start,
+            config.end,
+            config.samples,
+            [50, 500, 5000],
+        )
+        .save(&config.output_path)
+    } else {
+        let histogram = buddhabrot::buddhabrot(
+            &mut rng,
+            (config.width, config.height),
+            config.start,
+            config.end,
+            config.samples,
+            config.iterations,
+        );
+
+        buddhabrot::histogram_to_image(&histogram, (config.width, config.height), true)
+            .save(&config.output_path)
+    };
 
-    /// Where to write the dithered image.
-    #[structopt(
-        short = "o",
-        long = "output",
-        default_value = "tangled-web.png",
-        parse(from_os_str)
-    )]
-    output_path: PathBuf,
+    img.expect("cannot save image");
 }
 
-fn main() {
-    let command = Command::from_args();
+fn quaternion_julia(config: &QuaternionJulia) {
+    let triangles = quaternion_julia::march(
+        config.c,
+        config.w,
+        config.resolution,
+        config.half_extent,
+        config.iterations,
+        config.threshold,
+    );
 
-    match command {
-        Command::Dragons { iterations } => spawn_dragons(iterations),
-        Command::Horns { iterations } => spawn_horns(iterations),
-        Command::Julia(ref config) => match config.set_type {
-            None | Some(JuliaSet::All) => {
-                mandelbrot(config);
-                planets(config);
-                dragon_like(config);
-                black_holes(config);
-            }
-            Some(JuliaSet::Mandelbrot) => mandelbrot(config),
-            Some(JuliaSet::Planets) => planets(config),
-            Some(JuliaSet::DragonLikeSpiral) => dragon_like(config),
-            Some(JuliaSet::BlackHoles) => black_holes(config),
-            Some(JuliaSet::Custom {
-                ref start,
-                ref end,
-                ref c,
-                ref name,
-            }) => create_julia_set(config, name, start, end, |f, it| {
-                FractalPoint::julia(f, *c, it)
-            }),
-        },
-        Command::Quantize(ref config) => quantize_image(config),
-        Command::Sierpinski(ref config) => spawn_sierpinski(config),
-        Command::Primirs(ref config) => primirs(config),
-        Command::FractalTree(ref config) => fractal_tree(config),
-        Command::Runes(ref config) => runes(config),
-        Command::Delaunay(ref config) => delaunay(config),
-        Command::Voronoi(ref config) => voronoi(config),
-        Command::Patchwork(ref config) => patchwork(config),
-        Command::Stippling(ref config) => stippling(config),
-        Command::Mondrian(ref config) => mondrian(config),
-        Command::Dither(ref config) => dither(config),
-        Command::TangledWeb(ref config) => tangled_web(config),
-    }
+    let file = fs::File::create(&config.output_path).expect("cannot create stl file");
+    stl::write_stl(file, &triangles).expect("cannot write stl file");
 }
 
-fn mandelbrot(config: &Julia) {
+fn mandelbrot(config: &Julia, post: &[Filter]) {
     create_julia_set(
         config,
         "mandelbrot",
         &PointF64::new(-3.0, -1.2),
         &PointF64::new(1.0, 1.2),
+        post,
         FractalPoint::mandelbrot,
     );
 }
 
-fn planets(config: &Julia) {
+fn planets(config: &Julia, post: &[Filter]) {
     let c = Complex64::new(-0.4, 0.6);
 
     create_julia_set(
@@ -639,11 +1854,12 @@ fn planets(config: &Julia) {
         "planets",
         &PointF64::new(-3.0, -1.2),
         &PointF64::new(2.0, 1.2),
+        post,
         |f, it| FractalPoint::julia(f, c, it),
     );
 }
 
-fn dragon_like(config: &Julia) {
+fn dragon_like(config: &Julia, post: &[Filter]) {
     let c = Complex64::new(-0.8, 0.156);
 
     create_julia_set(
@@ -651,11 +1867,12 @@ fn dragon_like(config: &Julia) {
         "dragon_like",
         &PointF64::new(-3.0, -1.2),
         &PointF64::new(2.0, 1.2),
+        post,
         |f, it| FractalPoint::julia(f, c, it),
     );
 }
 
-fn black_holes(config: &Julia) {
+fn black_holes(config: &Julia, post: &[Filter]) {
     let c = Complex64::new(0.285, 0.01);
 
     create_julia_set(
@@ -663,11 +1880,73 @@ fn black_holes(config: &Julia) {
         "black_holes",
         &PointF64::new(-1.2, -1.2),
         &PointF64::new(1.2, 1.0),
+        post,
         |f, it| FractalPoint::julia(f, c, it),
     );
 }
 
-fn create_julia_set<F>(config: &Julia, name: &str, start: &PointF64, end: &PointF64, gen: F)
+fn tricorn(config: &Julia, post: &[Filter]) {
+    create_julia_set(
+        config,
+        "tricorn",
+        &PointF64::new(-2.5, -2.0),
+        &PointF64::new(1.5, 2.0),
+        post,
+        FractalPoint::tricorn,
+    );
+}
+
+fn burning_ship(config: &Julia, post: &[Filter]) {
+    // the interesting detail of the Burning Ship sits around its lower
+    // antenna rather than at the origin, so zoom the default viewport there
+    // instead of framing the whole set like the other presets do.
+    create_julia_set(
+        config,
+        "burning_ship",
+        &PointF64::new(-1.8, -0.08),
+        &PointF64::new(-1.7, 0.01),
+        post,
+        FractalPoint::burning_ship,
+    );
+}
+
+fn create_julia_set<F>(
+    config: &Julia,
+    name: &str,
+    start: &PointF64,
+    end: &PointF64,
+    post: &[Filter],
+    gen: F,
+) where
+    F: Fn(Complex64, u32) -> FractalPoint + Copy + Sync,
+{
+    println!("Fractal: {}", name);
+
+    match config.animate {
+        Some(frames) => animate_julia_set(config, name, start, end, frames, post, gen),
+        None => {
+            let imgbuf = render_julia_frame(config, start, end, gen);
+            let img = image::ImageRgb8(postprocess(imgbuf, post));
+
+            // let img = img.resize_exact(width, height, image::Lanczos3);
+
+            img.save(&format!("{}.png", name))
+                .expect("cannot save output image");
+
+            if let Some(ref stl_path) = config.stl_output {
+                write_julia_stl(config, start, end, gen, stl_path);
+            }
+
+            if let Some(ref contour_path) = config.contour_output {
+                write_julia_contours(config, start, end, gen, contour_path);
+            }
+        }
+    }
+}
+
+/// Re-render the fractal's escape-time field as a heightmap and write it
+/// out as a binary STL mesh to `path`.
+fn write_julia_stl<F>(config: &Julia, start: &PointF64, end: &PointF64, gen: F, path: &PathBuf)
 where
     F: Fn(Complex64, u32) -> FractalPoint,
 {
@@ -684,22 +1963,189 @@ where
         gen,
     );
 
-    println!("Fractal: {}", name);
+    let heights = frac_it.into_heightmap();
+    let z_scale = f64::from(config.width.min(config.height)) * 0.1;
+    let triangles = julia::heightmap_mesh(&heights, f64::from(config.iterations), 1.0, z_scale);
+
+    let file = fs::File::create(path).expect("cannot create stl file");
+    stl::write_stl(file, &triangles).expect("cannot write stl file");
+}
+
+/// Extract the escape-time field's iso-contours via marching squares at
+/// `config.iso_values` and stroke them onto their own image, written out to
+/// `path`.
+fn write_julia_contours<F>(config: &Julia, start: &PointF64, end: &PointF64, gen: F, path: &PathBuf)
+where
+    F: Fn(Complex64, u32) -> FractalPoint,
+{
+    let stepx = (end.x - start.x) / f64::from(config.width);
+    let stepy = (end.y - start.y) / f64::from(config.height);
+
+    let frac_it = JuliaGenIter::new(
+        *start,
+        config.width,
+        config.height,
+        stepx,
+        stepy,
+        config.iterations,
+        gen,
+    );
+
+    let grid = frac_it.into_grid();
+    // positions are in pixel space here (not the complex-plane start/step
+    // used to sample the field above), since the contours get drawn
+    // straight onto a `width x height` image.
+    let contour_sets = julia::march_squares(
+        &grid,
+        config.iterations,
+        PointF64::new(0.0, 0.0),
+        1.0,
+        1.0,
+        &config.iso_values,
+    );
+
+    let mut img = image::RgbImage::from_pixel(
+        config.width,
+        config.height,
+        image::Rgb { data: [0, 0, 0] },
+    );
+
+    {
+        let mut drawer = Drawer::new_with_no_blending(&mut img);
+        for contours in &contour_sets {
+            julia::draw_contours(
+                &mut drawer,
+                contours,
+                &image::Rgb {
+                    data: [0xFF, 0xFF, 0xFF],
+                },
+            );
+        }
+    }
+
+    img.save(path).expect("cannot save contour image");
+}
+
+/// Render `frames` frames, each progressively zooming the `start`/`end`
+/// viewport towards `config.zoom_target` (or its center) by a fixed
+/// geometric factor, and either encode them into a `{name}.gif` animation or,
+/// if `config.frames_dir` is set, write each one out as a numbered
+/// `name_NNNN.png` into that directory instead.
+fn animate_julia_set<F>(
+    config: &Julia,
+    name: &str,
+    start: &PointF64,
+    end: &PointF64,
+    frames: u32,
+    post: &[Filter],
+    gen: F,
+) where
+    F: Fn(Complex64, u32) -> FractalPoint + Copy + Sync,
+{
+    let target = config
+        .zoom_target
+        .map(|c| PointF64::new(c.re, c.im))
+        .unwrap_or_else(|| PointF64::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0));
+
+    if let Some(ref frames_dir) = config.frames_dir {
+        fs::create_dir_all(frames_dir).expect("cannot create frames directory");
+    }
+
+    let mut anim = animation::Animation::new(ANIMATION_FRAME_DELAY_MS);
+
+    let mut cur_start = *start;
+    let mut cur_end = *end;
 
-    let imgbuf = frac_it
-        .into_image()
-        .expect("error while generating fractal");
-    let img = image::ImageRgb8(imgbuf);
+    for i in 0..frames {
+        let frame = postprocess(render_julia_frame(config, &cur_start, &cur_end, gen), post);
 
-    // let img = img.resize_exact(width, height, image::Lanczos3);
+        match &config.frames_dir {
+            Some(frames_dir) => {
+                let frame_path = frames_dir.join(format!("{}_{:04}.png", name, i + 1));
+                frame
+                    .save(&frame_path)
+                    .expect("cannot save animation frame");
+            }
+            None => anim.push(frame),
+        }
+
+        cur_start = zoom_toward(cur_start, target, ZOOM_FACTOR);
+        cur_end = zoom_toward(cur_end, target, ZOOM_FACTOR);
+    }
+
+    if config.frames_dir.is_none() {
+        anim.save(format!("{}.gif", name))
+            .expect("cannot save animation");
+    }
+}
+
+fn render_julia_frame<F>(
+    config: &Julia,
+    start: &PointF64,
+    end: &PointF64,
+    gen: F,
+) -> image::RgbImage
+where
+    F: Fn(Complex64, u32) -> FractalPoint + Sync,
+{
+    let stepx = (end.x - start.x) / f64::from(config.width);
+    let stepy = (end.y - start.y) / f64::from(config.height);
+
+    let frac_it = JuliaGenIter::new(
+        *start,
+        config.width,
+        config.height,
+        stepx,
+        stepy,
+        config.iterations,
+        gen,
+    );
+
+    if config.parallel {
+        let render = || match &config.palette {
+            Some(palette) => frac_it
+                .par_into_image_styled(config.smooth, palette)
+                .expect("error while generating fractal"),
+            None => frac_it
+                .par_into_image()
+                .expect("error while generating fractal"),
+        };
+
+        match config.threads {
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("cannot build thread pool")
+                .install(render),
+            None => render(),
+        }
+    } else {
+        match &config.palette {
+            Some(palette) => frac_it
+                .into_image_styled(config.smooth, palette)
+                .expect("error while generating fractal"),
+            None => frac_it.into_image().expect("error while generating fractal"),
+        }
+    }
+}
 
-    img.save(&format!("{}.png", name))
-        .expect("cannot save output image");
+/// Scale `point`'s distance from `target` by `factor` (`factor < 1` moves it
+/// a `1 - factor` fraction of the way towards `target`, zooming in).
+fn zoom_toward(point: PointF64, target: PointF64, factor: f64) -> PointF64 {
+    PointF64::new(
+        target.x + (point.x - target.x) * factor,
+        target.y + (point.y - target.y) * factor,
+    )
 }
 
-fn spawn_dragons(iterations: u32) {
+fn spawn_dragons(iterations: u32, animate: bool, post: &[Filter]) {
     println!("Dragons!");
 
+    if animate {
+        animate_dragons(iterations, dragon::dragon, "dragons", post);
+        return;
+    }
+
     let red = dragon::dragon(iterations, dragon::Move::Left);
     let red_img = dragon::dragon_to_image(&red, 1920, 1080, 1480, 730, 2, [255, 0, 0]);
 
@@ -712,16 +2158,25 @@ fn spawn_dragons(iterations: u32) {
     let redblue_img = overlap_images(&red_img, &blue_img).unwrap();
     let rgb_img = overlap_images(&redblue_img, &green_img).unwrap();
 
-    red_img.save("red-dragon.png").unwrap();
-    blue_img.save("blue-dragon.png").unwrap();
-    green_img.save("green-dragon.png").unwrap();
-    redblue_img.save("redblue-dragon.png").unwrap();
-    rgb_img.save("rgb-dragon.png").unwrap();
+    postprocess(red_img, post).save("red-dragon.png").unwrap();
+    postprocess(blue_img, post).save("blue-dragon.png").unwrap();
+    postprocess(green_img, post)
+        .save("green-dragon.png")
+        .unwrap();
+    postprocess(redblue_img, post)
+        .save("redblue-dragon.png")
+        .unwrap();
+    postprocess(rgb_img, post).save("rgb-dragon.png").unwrap();
 }
 
-fn spawn_horns(iterations: u32) {
+fn spawn_horns(iterations: u32, animate: bool, post: &[Filter]) {
     println!("Horns!");
 
+    if animate {
+        animate_dragons(iterations, dragon::horns, "horns", post);
+        return;
+    }
+
     let red = dragon::horns(iterations, dragon::Move::Left);
     let red_img = dragon::dragon_to_image(&red, 1920, 1080, 1480, 530, 2, RED);
 
@@ -731,9 +2186,40 @@ fn spawn_horns(iterations: u32) {
     let green = dragon::horns(iterations, dragon::Move::Right);
     let green_img = dragon::dragon_to_image(&green, 1920, 1080, 960, 550, 2, LIGHT_GREEN);
 
-    red_img.save("red-horns.png").unwrap();
-    blue_img.save("blue-horns.png").unwrap();
-    green_img.save("green-horns.png").unwrap();
+    postprocess(red_img, post).save("red-horns.png").unwrap();
+    postprocess(blue_img, post).save("blue-horns.png").unwrap();
+    postprocess(green_img, post).save("green-horns.png").unwrap();
+}
+
+/// Render one frame per iteration count from 1 to `iterations`, combining
+/// the red/blue/green variants of `gen` (`dragon::dragon` or
+/// `dragon::horns`) the same way `spawn_dragons` combines its stills, and
+/// encode them into a `{name}.gif` animation so the fractal appears to
+/// unfold.
+fn animate_dragons<F>(iterations: u32, gen: F, name: &str, post: &[Filter])
+where
+    F: Fn(u32, dragon::Move) -> dragon::Dragon,
+{
+    let mut anim = animation::Animation::new(ANIMATION_FRAME_DELAY_MS);
+
+    for i in 1..=iterations {
+        let red = gen(i, dragon::Move::Left);
+        let red_img = dragon::dragon_to_image(&red, 1920, 1080, 1480, 730, 2, [255, 0, 0]);
+
+        let blue = gen(i, dragon::Move::Up);
+        let blue_img = dragon::dragon_to_image(&blue, 1920, 1080, 500, 730, 2, [0, 0, 255]);
+
+        let green = gen(i, dragon::Move::Right);
+        let green_img = dragon::dragon_to_image(&green, 1920, 1080, 500, 350, 2, [0, 255, 0]);
+
+        let redblue_img = overlap_images(&red_img, &blue_img).unwrap();
+        let rgb_img = overlap_images(&redblue_img, &green_img).unwrap();
+
+        anim.push(postprocess(rgb_img, post));
+    }
+
+    anim.save(format!("{}.gif", name))
+        .expect("cannot save animation");
 }
 
 fn overlap_images(lhs: &image::RgbImage, rhs: &image::RgbImage) -> Option<image::RgbImage> {
@@ -761,25 +2247,59 @@ fn overlap_images(lhs: &image::RgbImage, rhs: &image::RgbImage) -> Option<image:
     Some(res)
 }
 
-fn quantize_image(config: &Quantize) {
+fn quantize_image(config: &Quantize, post: &[Filter]) {
     let img = image::open(&config.img_path).expect("cannot open source image file");
     let rgb = img
         .as_rgb8()
         .expect("cannot convert source image to rgb8 image");
 
-    let res = quantize::quantize(rgb.pixels().cloned(), config.divide_steps);
+    let res = match config.method {
+        QuantizeMethod::MedianCut => {
+            quantize::quantize_in_space(rgb.pixels().cloned(), config.divide_steps, config.color_space)
+        }
+        QuantizeMethod::Octree => quantize::octree(rgb.pixels().cloned(), config.colors),
+        QuantizeMethod::Refined => {
+            quantize::quantize_refined(rgb.pixels().cloned(), config.divide_steps, config.refine_iterations)
+        }
+        QuantizeMethod::Adaptive => quantize::quantize_adaptive(rgb.pixels().cloned(), config.colors),
+        QuantizeMethod::NeuQuant => {
+            quantize::neuquant(rgb.pixels().cloned(), config.colors, config.sample_factor)
+        }
+    };
 
-    let mut quantized = rgb.clone();
-    for pixel in quantized.pixels_mut() {
-        *pixel = res.quantized_pixels[pixel];
+    if let Some(ref indexed_output_path) = config.indexed_output_path {
+        save_indexed_image(&res.indexed(rgb), indexed_output_path);
     }
 
-    quantized
+    let quantized = if config.dither {
+        quantize::dither(rgb, &res)
+    } else {
+        res.remap(rgb, false)
+    };
+
+    postprocess(quantized, post)
         .save(&config.output_path)
         .expect("cannot save quantized file");
 }
 
-fn spawn_sierpinski(config: &Sierpinski) {
+/// Write `indexed`'s palette as an `N`x`1` PNG at `path`, and its
+/// row-major, little-endian `u16` indices to the same path with an
+/// `.indices` extension.
+fn save_indexed_image(indexed: &quantize::IndexedImage<image::Rgb<u8>>, path: &PathBuf) {
+    let mut palette_img = image::RgbImage::new(indexed.palette.len() as u32, 1);
+    for (i, color) in indexed.palette.iter().enumerate() {
+        palette_img.put_pixel(i as u32, 0, *color);
+    }
+    palette_img.save(path).expect("cannot save indexed palette");
+
+    let mut indices_bytes = Vec::with_capacity(indexed.indices.len() * 2);
+    for index in &indexed.indices {
+        indices_bytes.extend_from_slice(&index.to_le_bytes());
+    }
+    fs::write(path.with_extension("indices"), indices_bytes).expect("cannot save indexed data");
+}
+
+fn spawn_sierpinski(config: &Sierpinski, post: &[Filter]) {
     let mut img = image::RgbImage::from_pixel(
         config.width,
         config.height,
@@ -819,7 +2339,8 @@ fn spawn_sierpinski(config: &Sierpinski) {
         );
     }
 
-    img.save(&config.output_path)
+    postprocess(img, post)
+        .save(&config.output_path)
         .expect("cannot save sierpinski triangle");
 }
 
@@ -874,6 +2395,23 @@ fn primirs(config: &Primirs) {
         .expect("cannot save primitized file");
 }
 
+fn vectorize(config: &Vectorize) {
+    let img = image::open(&config.img_path)
+        .expect("cannot open source image file")
+        .to_rgb();
+
+    let canvas = vectorize::vectorize(
+        &img,
+        config.grow_threshold,
+        config.target_clusters,
+        config.merge_threshold,
+        config.min_area,
+    );
+
+    fs::write(config.output_path.with_extension("svg"), canvas.to_svg())
+        .expect("cannot save svg");
+}
+
 fn fractal_tree(config: &FractalTree) {
     let mut img =
         image::GrayImage::from_pixel(config.width, config.height, image::Luma { data: [0] });
@@ -914,6 +2452,20 @@ fn delaunay(config: &Delaunay) {
 
     let alpha = 0xd6;
 
+    if config.svg {
+        let canvas = delaunay::random_triangulation_svg(
+            config.width,
+            config.height,
+            &mut color_config,
+            config.grid_size,
+            alpha,
+        );
+
+        fs::write(config.output_path.with_extension("svg"), canvas.to_svg())
+            .expect("cannot save svg");
+        return;
+    }
+
     let mut img = image::RgbaImage::from_pixel(
         config.width,
         config.height,
@@ -922,35 +2474,169 @@ fn delaunay(config: &Delaunay) {
         },
     );
 
-    delaunay::random_triangulation(&mut img, &mut color_config, config.grid_size, alpha);
+    delaunay::random_triangulation(
+        &mut img,
+        &mut color_config,
+        config.grid_size,
+        alpha,
+        config.blend_mode,
+    );
+
+    img.save(&config.output_path).expect("cannot save image");
+}
+
+fn adaptive_triangulation(config: &AdaptiveTriangulation) {
+    let mut img = image::open(&config.img_path)
+        .expect("cannot open image")
+        .to_rgba();
+
+    delaunay::adaptive_triangulation(
+        &mut img,
+        config.epsilon,
+        config.max_vertices,
+        config.min_area,
+    );
+
+    img.save(&config.output_path).expect("cannot save image");
+}
+
+fn triangulate_image(config: &TriangulateImage) {
+    let mut img = image::open(&config.img_path)
+        .expect("cannot open image")
+        .to_rgba();
+
+    let strategy = delaunay::PointSamplingStrategy {
+        edge_points: config.edge_points,
+        grid_size: config.grid_size,
+    };
+
+    delaunay::triangulate_image(&mut img, &strategy);
+
+    img.save(&config.output_path).expect("cannot save image");
+}
+
+fn trimesh(config: &Trimesh) {
+    let mut img = image::open(&config.img_path)
+        .expect("cannot open image")
+        .to_rgba();
+
+    trimesh::subdivide_image(&mut img, config.tolerance, config.max_triangles);
 
     img.save(&config.output_path).expect("cannot save image");
 }
 
-fn voronoi(config: &Voronoi) {
+fn parse_distance_metric(s: &str) -> matto::geo::kdtree::DistanceMetric {
+    match s {
+        "manhattan" => matto::geo::kdtree::DistanceMetric::Manhattan,
+        "chebyshev" => matto::geo::kdtree::DistanceMetric::Chebyshev,
+        _ => matto::geo::kdtree::DistanceMetric::Euclidean,
+    }
+}
+
+fn voronoi(config: &Voronoi, post: &[Filter]) {
     let mut color_config =
         matto::color::RandomColorConfig::new().luminosity(matto::color::Luminosity::Bright);
 
+    if config.svg {
+        let canvas = voronoi::random_voronoi_svg(
+            config.width,
+            config.height,
+            &mut color_config,
+            config.npoints,
+        );
+
+        fs::write(config.output_path.with_extension("svg"), canvas.to_svg())
+            .expect("cannot save svg");
+
+        return;
+    }
+
     let mut img = image::RgbImage::new(config.width, config.height);
 
+    let metric = parse_distance_metric(&config.metric);
+    let wrap = if config.toroidal {
+        voronoi::Wrap::Toroidal
+    } else {
+        voronoi::Wrap::None
+    };
+
     if config.gradient_background {
-        let color1 = matto::color::random_color(&mut color_config).to_rgb();
-        let color2 = matto::color::random_color(&mut color_config).to_rgb();
+        let stops = if config.gradient_stops.len() >= 2 {
+            config.gradient_stops.clone()
+        } else {
+            vec![
+                fills::Stop::new(
+                    0.0,
+                    image::Rgb {
+                        data: matto::color::random_color(&mut color_config).to_rgb(),
+                    },
+                ),
+                fills::Stop::new(
+                    1.0,
+                    image::Rgb {
+                        data: matto::color::random_color(&mut color_config).to_rgb(),
+                    },
+                ),
+            ]
+        };
+
+        let gradient = if config.radial_gradient {
+            fills::Gradient::radial(
+                PointF64::new(
+                    f64::from(config.width) / 2.0,
+                    f64::from(config.height) / 2.0,
+                ),
+                (f64::from(config.width).powi(2) + f64::from(config.height).powi(2)).sqrt() / 2.0,
+                stops,
+            )
+        } else {
+            fills::Gradient::linear(
+                PointF64::new(0.0, 0.0),
+                PointF64::new(f64::from(config.width), 0.0),
+                stops,
+            )
+        };
 
         voronoi::gradient_voronoi(
             &mut img,
-            image::Rgb { data: color1 },
-            image::Rgb { data: color2 },
+            &gradient,
             config.npoints,
+            metric,
+            wrap,
+            config.blend_mode,
         )
     } else {
-        voronoi::random_voronoi(&mut img, &mut color_config, config.npoints);
+        voronoi::random_voronoi(
+            &mut img,
+            &mut color_config,
+            config.npoints,
+            metric,
+            wrap,
+            config.blend_mode,
+        );
     }
 
-    img.save(&config.output_path).expect("cannot save image");
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
 }
 
-fn patchwork(config: &Patchwork) {
+fn patchwork(config: &Patchwork, post: &[Filter]) {
+    if config.svg {
+        let canvas = patchwork::random_patchwork_svg(
+            config.width,
+            config.height,
+            config.npoints,
+            config.clusters,
+            config.iterations,
+            config.fill_polygons,
+        );
+
+        fs::write(config.output_path.with_extension("svg"), canvas.to_svg())
+            .expect("cannot save svg");
+        return;
+    }
+
     let mut img = image::RgbImage::new(config.width, config.height);
 
     patchwork::random_patchwork(
@@ -959,12 +2645,56 @@ fn patchwork(config: &Patchwork) {
         config.clusters,
         config.iterations,
         config.fill_polygons,
+        config.blend_mode,
     );
 
-    img.save(&config.output_path).expect("cannot save image");
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
+}
+
+/// Build the color ramp a `stippling gradient` command's bands sample from,
+/// falling back to solid black if fewer than 2 stops were given.
+fn stippling_gradient(config: &StipplingGradient) -> fills::Gradient {
+    let stops = if config.stops.len() >= 2 {
+        config.stops.clone()
+    } else {
+        vec![
+            fills::Stop::new(0.0, image::Rgb { data: [0, 0, 0] }),
+            fills::Stop::new(1.0, image::Rgb { data: [0, 0, 0] }),
+        ]
+    };
+
+    fills::Gradient::linear(PointF64::new(0.0, 0.0), PointF64::new(0.0, 1.0), stops)
 }
 
-fn stippling(config: &Stippling) {
+fn stippling(config: &Stippling, post: &[Filter]) {
+    if config.svg {
+        let canvas = match config.command {
+            StipplingCommand::Gradient(ref gradient_config) => stippling::gradient_svg(
+                config.width,
+                config.height,
+                gradient_config.bands,
+                gradient_config.first_band_points,
+                gradient_config.grow_coeff,
+                &stippling_gradient(gradient_config),
+                stippling::Direction::TopToBottom,
+            ),
+            StipplingCommand::StipplingRects(ref rects_config) => stippling::rects_svg(
+                config.width,
+                config.height,
+                rects_config.iterations,
+                rects_config.points,
+                rects_config.minimum_area,
+                image::Rgb { data: [0, 0, 0] },
+            ),
+        };
+
+        fs::write(config.output_path.with_extension("svg"), canvas.to_svg())
+            .expect("cannot save svg");
+        return;
+    }
+
     let mut img = image::RgbImage::from_pixel(
         config.width,
         config.height,
@@ -980,7 +2710,7 @@ fn stippling(config: &Stippling) {
                 gradient_config.bands,
                 gradient_config.first_band_points,
                 gradient_config.grow_coeff,
-                image::Rgb { data: [0, 0, 0] },
+                &stippling_gradient(gradient_config),
                 stippling::Direction::TopToBottom,
             );
         }
@@ -995,60 +2725,84 @@ fn stippling(config: &Stippling) {
         }
     }
 
-    img.save(&config.output_path).expect("cannot save image");
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
 }
 
-fn mondrian(config: &Mondrian) {
-    let mut img = image::RgbImage::new(config.width, config.height);
+const MONDRIAN_FILL_PALETTE: [image::Rgb<u8>; 3] = [
+    image::Rgb {
+        data: [0x8d, 0x22, 0x02],
+    },
+    image::Rgb {
+        data: [0x0b, 0x18, 0x3b],
+    },
+    image::Rgb {
+        data: [0xd0, 0x95, 0x02],
+    },
+];
+
+const MONDRIAN_WHITE: image::Rgb<u8> = image::Rgb {
+    data: [0xe6, 0xeb, 0xc3],
+};
+
+fn mondrian(config: &Mondrian, post: &[Filter]) {
+    if config.svg {
+        let canvas = mondrian::generate_svg(
+            config.width,
+            config.height,
+            config.iterations,
+            config.minimum_area,
+            MONDRIAN_WHITE,
+            &MONDRIAN_FILL_PALETTE,
+            10,
+        );
 
-    let fill_palette = [
-        image::Rgb {
-            data: [0x8d, 0x22, 0x02],
-        },
-        image::Rgb {
-            data: [0x0b, 0x18, 0x3b],
-        },
-        image::Rgb {
-            data: [0xd0, 0x95, 0x02],
-        },
-    ];
+        fs::write(config.output_path.with_extension("svg"), canvas.to_svg())
+            .expect("cannot save svg");
+        return;
+    }
+
+    let mut img = image::RgbImage::new(config.width, config.height);
 
     mondrian::generate(
         &mut img,
         config.iterations,
         config.minimum_area,
-        image::Rgb {
-            data: [0xe6, 0xeb, 0xc3],
-        },
-        &fill_palette,
+        MONDRIAN_WHITE,
+        &MONDRIAN_FILL_PALETTE,
         10,
     );
 
-    img.save(&config.output_path).expect("cannot save image");
+    postprocess(img, post)
+        .save(&config.output_path)
+        .expect("cannot save image");
 }
 
-fn dither(config: &Dither) {
+fn dither(config: &Dither, post: &[Filter]) {
     let img = image::open(&config.img_path).expect("cannot load image file");
 
     let step = u8::max_value() / config.levels;
 
     if config.rgb {
-        let dithered = dithering::dither(&img.to_rgb(), |l| image::Rgb {
-            data: {
-                [
+        let dithered = dithering::dither(&img.to_rgb(), &dithering::FLOYD_STEINBERG, |l| {
+            image::Rgb {
+                data: [
                     l.data[0] / step * step,
                     l.data[1] / step * step,
                     l.data[2] / step * step,
-                ]
-            },
+                ],
+            }
         });
 
-        dithered
+        postprocess(dithered, post)
             .save(&config.output_path)
             .expect("cannot save image");
     } else {
-        let dithered = dithering::dither(&img.to_luma(), |l| image::Luma {
-            data: { [l.data[0] / step * step] },
+        let dithered = dithering::dither(&img.to_luma(), &dithering::FLOYD_STEINBERG, |l| {
+            image::Luma {
+                data: [l.data[0] / step * step],
+            }
         });
 
         dithered
@@ -1057,13 +2811,15 @@ fn dither(config: &Dither) {
     }
 }
 
-fn tangled_web(config: &TangledWeb) {
+fn tangled_web(config: &TangledWeb, post: &[Filter]) {
     if !config.svg {
         let mut img = image::RgbImage::new(config.width, config.height);
 
         matto::art::tangled_web::generate_img(&mut img, config.iterations, config.circle_divisions);
 
-        img.save(&config.output_path).expect("cannot save image");
+        postprocess(img, post)
+            .save(&config.output_path)
+            .expect("cannot save image");
         return;
     }
 
@@ -0,0 +1,262 @@
+//! [Perlin gradient noise](https://en.wikipedia.org/wiki/Perlin_noise) and
+//! fractal turbulence built on top of it, for modulating strokes, color
+//! fields and backgrounds with organic, non-repeating texture instead of
+//! pure randomness.
+
+use std::fmt::Debug;
+
+use geo::{PointF64, PointU32};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::drawing::{Blender, Drawer};
+
+// 8 evenly-spaced unit gradient vectors; classic Perlin noise only needs
+// the *direction* of the gradient at each lattice point, not its magnitude.
+const GRADIENTS: [[f64; 2]; 8] = [
+    [1.0, 0.0],
+    [-1.0, 0.0],
+    [0.0, 1.0],
+    [0.0, -1.0],
+    [
+        std::f64::consts::FRAC_1_SQRT_2,
+        std::f64::consts::FRAC_1_SQRT_2,
+    ],
+    [
+        -std::f64::consts::FRAC_1_SQRT_2,
+        std::f64::consts::FRAC_1_SQRT_2,
+    ],
+    [
+        std::f64::consts::FRAC_1_SQRT_2,
+        -std::f64::consts::FRAC_1_SQRT_2,
+    ],
+    [
+        -std::f64::consts::FRAC_1_SQRT_2,
+        -std::f64::consts::FRAC_1_SQRT_2,
+    ],
+];
+
+/// A 2D [Perlin noise](https://en.wikipedia.org/wiki/Perlin_noise)
+/// generator, built from a randomly shuffled permutation table.
+pub struct Perlin {
+    // the standard 256-entry permutation table, duplicated to 512 entries
+    // so a lattice cell's `+1` neighbor can be looked up without wrapping
+    // the index by hand.
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    /// Build a generator whose permutation table is shuffled by `rng`.
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
+        let mut table: [u8; 256] = [0; 256];
+        for (i, t) in table.iter_mut().enumerate() {
+            *t = i as u8;
+        }
+        table.shuffle(rng);
+
+        let mut permutation = [0_u8; 512];
+        for (i, p) in permutation.iter_mut().enumerate() {
+            *p = table[i % 256];
+        }
+
+        Perlin { permutation }
+    }
+
+    /// Sample the noise field at `(x, y)`, roughly in `[-1, 1]`: find the
+    /// lattice cell containing `(x, y)`, take the dot product of each of
+    /// its 4 corners' gradient with the offset vector from that corner to
+    /// `(x, y)`, then interpolate the 4 results with the quintic fade curve
+    /// `6t⁵ − 15t⁴ + 10t³` (smoother at the cell boundaries than a linear
+    /// blend, avoiding visible seams between lattice cells).
+    pub fn noise2(&self, x: f64, y: f64) -> f64 {
+        let xi = (x.floor() as i64 & 255) as usize;
+        let yi = (y.floor() as i64 & 255) as usize;
+
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+
+        let u = fade(xf);
+        let v = fade(yf);
+
+        let gradient_dot = |gx: usize, gy: usize, dx: f64, dy: f64| -> f64 {
+            let hash = self.permutation[self.permutation[gx] as usize + gy] as usize % 8;
+            GRADIENTS[hash][0] * dx + GRADIENTS[hash][1] * dy
+        };
+
+        let n00 = gradient_dot(xi, yi, xf, yf);
+        let n10 = gradient_dot(xi + 1, yi, xf - 1.0, yf);
+        let n01 = gradient_dot(xi, yi + 1, xf, yf - 1.0);
+        let n11 = gradient_dot(xi + 1, yi + 1, xf - 1.0, yf - 1.0);
+
+        let nx0 = lerp(n00, n10, u);
+        let nx1 = lerp(n01, n11, u);
+
+        lerp(nx0, nx1, v)
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Sum `octaves` layers of `noise2`, each doubling in frequency while
+/// halving in amplitude (a [fractal Brownian
+/// motion](https://en.wikipedia.org/wiki/Fractional_Brownian_motion)), so
+/// the result looks like cloud/marble-like turbulence instead of a single
+/// smooth wave.
+pub fn turbulence(perlin: &Perlin, x: f64, y: f64, octaves: u32) -> f64 {
+    let mut total = 0.0;
+
+    for i in 0..octaves {
+        let frequency = 2_f64.powi(i as i32);
+        total += perlin.noise2(x * frequency, y * frequency).abs() / frequency;
+    }
+
+    total
+}
+
+/// Fill every pixel of `img` by mapping a turbulence field through
+/// `palette`: `scale` controls how many cycles of noise fit across the
+/// image, with smaller values giving broader, smoother blotches. Gives a
+/// marble/cloud-like fill instead of flat color or uniform noise.
+pub fn fill_noise_field(img: &mut image::RgbImage, palette: &[image::Rgb<u8>], scale: f64) {
+    if palette.is_empty() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let perlin = Perlin::new(&mut rng);
+
+    let (width, height) = img.dimensions();
+
+    for y in 0..height {
+        for x in 0..width {
+            // `turbulence`'s octave sum is roughly in `[0, 2)`; rescale it
+            // into a palette index.
+            let t = (turbulence(&perlin, f64::from(x) * scale, f64::from(y) * scale, 4) / 2.0)
+                .min(1.0)
+                .max(0.0);
+
+            let idx = (t * (palette.len() - 1) as f64).round() as usize;
+
+            img.put_pixel(x, y, palette[idx]);
+        }
+    }
+}
+
+/// Draw a "rune": a connected stroke of `npoints` segments whose direction
+/// at each step comes from the noise field rather than a pure random walk,
+/// so the stroke looks organic but smoothly curving instead of jittery.
+pub fn draw_noise_rune<I, B>(drawer: &mut Drawer<I, B>, npoints: usize, pix: &I::Pixel)
+where
+    I: image::GenericImage,
+    I::Pixel: Debug,
+    B: Blender<I::Pixel>,
+{
+    let (width, height) = drawer.dimensions();
+    if width == 0 || height == 0 || npoints == 0 {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let perlin = Perlin::new(&mut rng);
+
+    let step = f64::from(width.min(height)) / npoints as f64;
+    let mut pos = PointF64::new(f64::from(width) / 2.0, f64::from(height) / 2.0);
+
+    let clamp = |v: f64, max: u32| v.max(0.0).min(f64::from(max - 1)) as u32;
+
+    for _ in 0..npoints {
+        let angle = perlin.noise2(pos.x * 0.01, pos.y * 0.01) * std::f64::consts::PI * 2.0;
+        let next = PointF64::new(pos.x + angle.cos() * step, pos.y + angle.sin() * step);
+
+        let start = PointU32::new(clamp(pos.x, width), clamp(pos.y, height));
+        let end = PointU32::new(clamp(next.x, width), clamp(next.y, height));
+
+        drawer.line(start, end, pix);
+
+        pos = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+
+    #[test]
+    fn noise2_is_deterministic_for_a_given_permutation_table() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let perlin = Perlin::new(&mut rng);
+
+        assert_eq!(perlin.noise2(1.5, 2.5), perlin.noise2(1.5, 2.5));
+    }
+
+    #[test]
+    fn noise2_stays_in_expected_range() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let perlin = Perlin::new(&mut rng);
+
+        for i in 0..100 {
+            let x = f64::from(i) * 0.37;
+            let y = f64::from(i) * 0.61;
+
+            assert!(perlin.noise2(x, y).abs() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn turbulence_is_never_negative() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let perlin = Perlin::new(&mut rng);
+
+        for i in 0..100 {
+            let x = f64::from(i) * 0.37;
+            let y = f64::from(i) * 0.61;
+
+            assert!(turbulence(&perlin, x, y, 4) >= 0.0);
+        }
+    }
+
+    #[test]
+    fn fill_noise_field_with_empty_palette_leaves_image_untouched() {
+        let mut img = image::RgbImage::from_pixel(4, 4, image::Rgb { data: [10, 20, 30] });
+        let before = img.clone();
+
+        fill_noise_field(&mut img, &[], 0.1);
+
+        assert_eq!(img, before);
+    }
+
+    #[test]
+    fn fill_noise_field_only_uses_palette_colors() {
+        let black = image::Rgb { data: [0, 0, 0] };
+        let white = image::Rgb {
+            data: [255, 255, 255],
+        };
+        let palette = [black, white];
+
+        let mut img = image::RgbImage::new(16, 16);
+        fill_noise_field(&mut img, &palette, 0.1);
+
+        for pix in img.pixels() {
+            assert!(*pix == black || *pix == white);
+        }
+    }
+
+    #[test]
+    fn draw_noise_rune_with_zero_npoints_is_a_no_op() {
+        let mut img = image::GrayImage::from_pixel(8, 8, image::Luma { data: [0xFF] });
+        let before = img.clone();
+
+        let mut drawer = crate::drawing::Drawer::new_with_no_blending(&mut img);
+        draw_noise_rune(&mut drawer, 0, &image::Luma { data: [0] });
+
+        assert_eq!(img, before);
+    }
+}
@@ -0,0 +1,176 @@
+//! Median-cut palette quantization: reduce an arbitrary set of RGB colors
+//! to at most `k` representative colors, complementing `geo::kmeans` with a
+//! deterministic, non-iterative alternative for when k-means is overkill or
+//! too slow.
+
+use geo::utils::ksmallest::ksmallest_by;
+
+/// The result of `quantize`: the extracted palette and, for each input
+/// color (in the same order as the `colors` slice `quantize` was called
+/// with), the index into `palette` it was mapped to.
+#[derive(Debug, PartialEq)]
+pub struct Quantized {
+    /// the extracted representative colors
+    pub palette: Vec<[u8; 3]>,
+
+    /// `membership[i]` is the index into `palette` that `colors[i]` was
+    /// assigned to
+    pub membership: Vec<usize>,
+}
+
+/// Reduce `colors` to at most `k` representative colors using [median
+/// cut](https://en.wikipedia.org/wiki/Median_cut): start with a single box
+/// holding every color, then repeatedly split the box with the greatest
+/// extent along any channel (max minus min of R, G or B) at the median of
+/// that channel — found with `ksmallest_by` instead of a full sort — until
+/// there are `k` boxes or no box can be split further. Each box's
+/// representative color is the per-channel average of the colors it holds.
+pub fn quantize(colors: &[[u8; 3]], k: usize) -> Quantized {
+    if colors.is_empty() || k == 0 {
+        return Quantized {
+            palette: vec![],
+            membership: vec![0; colors.len()],
+        };
+    }
+
+    let mut boxes = vec![colors.iter().copied().enumerate().collect::<Vec<_>>()];
+
+    while boxes.len() < k {
+        let widest_box = boxes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| widest_channel(b).map(|(channel, range)| (i, channel, range)))
+            .max_by_key(|&(_, _, range)| range);
+
+        let (i, channel, _) = match widest_box {
+            Some(w) => w,
+            None => break,
+        };
+
+        let mut items = boxes.swap_remove(i);
+        let mid = items.len() / 2;
+
+        ksmallest_by(&mut items, mid, |a, b| a.1[channel].cmp(&b.1[channel]));
+
+        // split on the median's value rather than its position, so identical
+        // colors always land in the same box. Values equal to the median go
+        // with the lower half only when the median is also the minimum; the
+        // channel isn't constant, so both halves are non-empty either way.
+        let median = items[mid].1[channel];
+        let min = items.iter().map(|c| c.1[channel]).min().unwrap_or(median);
+        let (lower, upper): (Vec<_>, Vec<_>) = items
+            .into_iter()
+            .partition(|c| c.1[channel] < median || (median == min && c.1[channel] == median));
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    let palette = boxes.iter().map(|b| average_color(b)).collect::<Vec<_>>();
+
+    let mut membership = vec![0; colors.len()];
+    for (palette_i, items) in boxes.iter().enumerate() {
+        for &(orig_i, _) in items {
+            membership[orig_i] = palette_i;
+        }
+    }
+
+    Quantized {
+        palette,
+        membership,
+    }
+}
+
+// the channel (0 = R, 1 = G, 2 = B) with the greatest max-minus-min spread
+// across `items`, and that spread. `None` if `items` has at most one color
+// or every channel is constant, i.e. the box can't be split any further.
+fn widest_channel(items: &[(usize, [u8; 3])]) -> Option<(usize, u8)> {
+    if items.len() <= 1 {
+        return None;
+    }
+
+    (0..3)
+        .map(|channel| {
+            let (lo, hi) = items
+                .iter()
+                .fold((u8::max_value(), u8::min_value()), |(lo, hi), (_, c)| {
+                    (lo.min(c[channel]), hi.max(c[channel]))
+                });
+
+            (channel, hi - lo)
+        })
+        .max_by_key(|&(_, range)| range)
+        .filter(|&(_, range)| range > 0)
+}
+
+fn average_color(items: &[(usize, [u8; 3])]) -> [u8; 3] {
+    let mut sums = [0_u64; 3];
+
+    for (_, c) in items {
+        for (channel, sum) in sums.iter_mut().enumerate() {
+            *sum += u64::from(c[channel]);
+        }
+    }
+
+    let n = items.len() as u64;
+    [
+        (sums[0] / n) as u8,
+        (sums[1] / n) as u8,
+        (sums[2] / n) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_colors() {
+        let quantized = quantize(&[], 4);
+
+        assert_eq!(quantized.palette, Vec::<[u8; 3]>::new());
+        assert_eq!(quantized.membership, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_fewer_unique_colors_than_k() {
+        let colors = [[0, 0, 0], [0, 0, 0], [255, 0, 0]];
+
+        let quantized = quantize(&colors, 8);
+
+        assert_eq!(quantized.palette.len(), 2);
+        assert_eq!(quantized.membership[0], quantized.membership[1]);
+        assert_ne!(quantized.membership[0], quantized.membership[2]);
+    }
+
+    #[test]
+    fn test_splits_along_the_widest_channel() {
+        let colors = [[0, 10, 10], [255, 10, 10], [0, 10, 10], [255, 10, 10]];
+
+        let quantized = quantize(&colors, 2);
+
+        assert_eq!(quantized.palette.len(), 2);
+        assert!(quantized
+            .palette
+            .iter()
+            .any(|&[r, g, b]| r == 0 && g == 10 && b == 10));
+        assert!(quantized
+            .palette
+            .iter()
+            .any(|&[r, g, b]| r == 255 && g == 10 && b == 10));
+    }
+
+    #[test]
+    fn test_membership_assigns_every_color() {
+        let colors = [[10, 20, 30], [200, 20, 30], [10, 220, 30], [10, 20, 230]];
+
+        let quantized = quantize(&colors, 4);
+
+        for (i, membership) in quantized.membership.iter().enumerate() {
+            assert!(
+                *membership < quantized.palette.len(),
+                "color {} unassigned",
+                i
+            );
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Render an image using only the colors of a given palette (e.g. from
+//! `geo::kmeans` or `quantize`) while avoiding visible banding, by
+//! scattering each pixel's quantization error onto its not-yet-visited
+//! neighbors.
+
+/// Replace every pixel of `img` with its nearest color (Euclidean distance
+/// in RGB) from `palette`, diffusing the per-pixel error with [Floyd–Steinberg
+/// dithering](https://en.wikipedia.org/wiki/Floyd%E2%80%93Steinberg_dithering):
+/// pixels are visited in serpentine order (left-to-right on even rows,
+/// right-to-left on odd rows, so the diffusion doesn't develop a
+/// directional bias), and each pixel's error is distributed to its
+/// not-yet-visited neighbors with weights 7/16 ahead, 3/16
+/// behind-and-below, 5/16 below and 1/16 ahead-and-below (mirrored on
+/// reverse rows), scaled by `strength`. `strength` of `0.0` disables
+/// diffusion entirely (plain nearest-color remapping); `1.0` is the
+/// standard Floyd–Steinberg weights.
+pub fn remap_dithered(img: &mut image::RgbImage, palette: &[image::Rgb<u8>], strength: f64) {
+    if palette.is_empty() {
+        return;
+    }
+
+    let (width, height) = img.dimensions();
+    let mut errors = vec![[0.0_f64; 3]; (width * height) as usize];
+
+    for y in 0..height {
+        let reverse = y % 2 == 1;
+        let xs: Box<dyn Iterator<Item = u32>> = if reverse {
+            Box::new((0..width).rev())
+        } else {
+            Box::new(0..width)
+        };
+
+        for x in xs {
+            let idx = (y * width + x) as usize;
+            let original = img.get_pixel(x, y).data;
+
+            let mut sample = [0.0_f64; 3];
+            for c in 0..3 {
+                sample[c] = (f64::from(original[c]) + errors[idx][c])
+                    .max(0.0)
+                    .min(255.0);
+            }
+
+            let nearest = nearest_color(palette, sample);
+            img.put_pixel(x, y, nearest);
+
+            let error = [
+                sample[0] - f64::from(nearest.data[0]),
+                sample[1] - f64::from(nearest.data[1]),
+                sample[2] - f64::from(nearest.data[2]),
+            ];
+
+            let ahead = if reverse { -1_i64 } else { 1_i64 };
+
+            diffuse(
+                &mut errors,
+                width,
+                height,
+                x,
+                y,
+                ahead,
+                0,
+                7.0 / 16.0,
+                strength,
+                error,
+            );
+            diffuse(
+                &mut errors,
+                width,
+                height,
+                x,
+                y,
+                -ahead,
+                1,
+                3.0 / 16.0,
+                strength,
+                error,
+            );
+            diffuse(
+                &mut errors,
+                width,
+                height,
+                x,
+                y,
+                0,
+                1,
+                5.0 / 16.0,
+                strength,
+                error,
+            );
+            diffuse(
+                &mut errors,
+                width,
+                height,
+                x,
+                y,
+                ahead,
+                1,
+                1.0 / 16.0,
+                strength,
+                error,
+            );
+        }
+    }
+}
+
+// add `error * weight * strength` to the accumulated error of the pixel at
+// `(x as i64 + dx, y as i64 + dy)`, unless that falls outside the image.
+#[allow(clippy::too_many_arguments)]
+fn diffuse(
+    errors: &mut [[f64; 3]],
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    dx: i64,
+    dy: i64,
+    weight: f64,
+    strength: f64,
+    error: [f64; 3],
+) {
+    let nx = i64::from(x) + dx;
+    let ny = i64::from(y) + dy;
+
+    if nx < 0 || ny < 0 || nx >= i64::from(width) || ny >= i64::from(height) {
+        return;
+    }
+
+    let idx = (ny as u32 * width + nx as u32) as usize;
+    for c in 0..3 {
+        errors[idx][c] += error[c] * weight * strength;
+    }
+}
+
+fn nearest_color(palette: &[image::Rgb<u8>], target: [f64; 3]) -> image::Rgb<u8> {
+    *palette
+        .iter()
+        .min_by(|a, b| {
+            squared_dist(a.data, target)
+                .partial_cmp(&squared_dist(b.data, target))
+                .unwrap()
+        })
+        .unwrap()
+}
+
+fn squared_dist(c: [u8; 3], target: [f64; 3]) -> f64 {
+    (0..3)
+        .map(|i| {
+            let d = f64::from(c[i]) - target[i];
+            d * d
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_palette_leaves_image_untouched() {
+        let mut img = image::RgbImage::from_pixel(2, 2, image::Rgb { data: [10, 20, 30] });
+        let before = img.clone();
+
+        remap_dithered(&mut img, &[], 1.0);
+
+        assert_eq!(img, before);
+    }
+
+    #[test]
+    fn test_remaps_to_only_palette_colors() {
+        let black = image::Rgb { data: [0, 0, 0] };
+        let white = image::Rgb {
+            data: [255, 255, 255],
+        };
+        let palette = [black, white];
+
+        let mut img = image::RgbImage::new(8, 8);
+        for (x, y, pix) in img.enumerate_pixels_mut() {
+            let v = ((x + y) * 16) as u8;
+            *pix = image::Rgb { data: [v, v, v] };
+        }
+
+        remap_dithered(&mut img, &palette, 1.0);
+
+        for pix in img.pixels() {
+            assert!(*pix == black || *pix == white);
+        }
+    }
+
+    #[test]
+    fn test_zero_strength_is_plain_nearest_color_remapping() {
+        let black = image::Rgb { data: [0, 0, 0] };
+        let white = image::Rgb {
+            data: [255, 255, 255],
+        };
+        let palette = [black, white];
+
+        let mut img = image::RgbImage::new(4, 4);
+        for (x, y, pix) in img.enumerate_pixels_mut() {
+            let v = if (x + y) % 2 == 0 { 10 } else { 240 };
+            *pix = image::Rgb { data: [v, v, v] };
+        }
+
+        let expected = {
+            let mut expected = image::RgbImage::new(4, 4);
+            for (x, y, pix) in expected.enumerate_pixels_mut() {
+                *pix = if (x + y) % 2 == 0 { black } else { white };
+            }
+            expected
+        };
+
+        remap_dithered(&mut img, &palette, 0.0);
+
+        assert_eq!(img, expected);
+    }
+}
@@ -0,0 +1,338 @@
+//! Declarative multi-layer scenes: an ordered list of layers, each naming
+//! one of the crate's generators plus how it's composited, rendered onto a
+//! single shared canvas instead of each generator producing its own
+//! standalone image. Scene files are plain YAML or RON, so a composition
+//! becomes a reproducible, shareable recipe rather than a one-off CLI
+//! invocation.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::art::{delaunay, dithering, fractree, mondrian, stippling, voronoi};
+use crate::color::{self, RandomColorConfig};
+use crate::compose::{self, BlendMode};
+use crate::fills::Stop;
+
+/// An ordered list of layers, rendered back to front onto a single canvas.
+#[derive(Debug, Deserialize)]
+pub struct Scene {
+    /// The layers making up the scene, in rendering order.
+    pub layers: Vec<Layer>,
+}
+
+/// A rectangular region of the canvas, used to restrict where a layer paints.
+#[derive(Debug, Deserialize)]
+pub struct Rect {
+    /// Left edge, in pixels.
+    pub x: u32,
+    /// Top edge, in pixels.
+    pub y: u32,
+    /// Width, in pixels.
+    pub width: u32,
+    /// Height, in pixels.
+    pub height: u32,
+}
+
+impl Rect {
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// One generator's contribution to a `Scene`: what to render, how
+/// translucent it is as a whole, how it's blended with the layers beneath
+/// it, and where on the canvas it's allowed to paint.
+#[derive(Debug, Deserialize)]
+pub struct Layer {
+    /// Which generator produces this layer's pixels, and its parameters.
+    pub generator: Generator,
+
+    /// How opaque this layer is as a whole, in `[0.0, 1.0]`, independent of
+    /// any translucency the generator itself produces.
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+
+    /// How this layer is composited onto the layers beneath it.
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+
+    /// If set, only pixels inside this region are painted by this layer.
+    #[serde(default)]
+    pub rect: Option<Rect>,
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+const MONDRIAN_FILL_PALETTE: [image::Rgb<u8>; 3] = [
+    image::Rgb {
+        data: [0x8d, 0x22, 0x02],
+    },
+    image::Rgb {
+        data: [0x0b, 0x18, 0x3b],
+    },
+    image::Rgb {
+        data: [0xd0, 0x95, 0x02],
+    },
+];
+
+const MONDRIAN_WHITE: image::Rgb<u8> = image::Rgb {
+    data: [0xe6, 0xeb, 0xc3],
+};
+
+/// A single generator and the parameters it needs to render one layer. Each
+/// variant mirrors the equivalent CLI subcommand, minus the options (SVG
+/// output, where to save a file, ...) that don't make sense for a layer
+/// rendered into a shared, in-memory canvas.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Generator {
+    /// A Voronoi diagram, see `art::voronoi::random_voronoi`.
+    Voronoi {
+        /// Number of points used to generate the diagram.
+        npoints: usize,
+        /// Whether the cells should wrap around the canvas edges.
+        #[serde(default)]
+        toroidal: bool,
+    },
+
+    /// A randomly colored triangulation, see
+    /// `art::delaunay::random_triangulation`.
+    Delaunay {
+        /// Size of the grid where to put points.
+        grid_size: u32,
+    },
+
+    /// A Mondrian-inspired composition, see `art::mondrian::generate`.
+    Mondrian {
+        /// Number of iterations to subdivide the canvas for.
+        iterations: usize,
+        /// The minimum area a rectangle must have in order to recurse into it.
+        minimum_area: u32,
+    },
+
+    /// Gradient stippling, see `art::stippling::gradient`.
+    Stippling {
+        /// Number of bands in the gradient.
+        bands: u32,
+        /// The number of points for the first band.
+        first_band_points: u32,
+        /// The factor by which each band's point count grows over the last.
+        grow_coeff: u32,
+        /// Color stops the bands ramp through. Needs at least two stops;
+        /// defaults to solid black if fewer are given.
+        #[serde(default)]
+        stops: Vec<Stop>,
+    },
+
+    /// Posterized dithering of a source image, see `art::dithering::dither`.
+    Dither {
+        /// Path to the source image to dither.
+        img_path: PathBuf,
+        /// Number of levels per channel to quantize down to.
+        levels: u8,
+    },
+
+    /// A fractal tree, see `art::fractree::fractal_tree`.
+    FractalTree {
+        /// How many times each branch forks.
+        nbranches: u32,
+        /// Angle added and subtracted from a branch's angle at each fork.
+        branching_angle_step: f64,
+        /// Factor applied to a branch's length at each fork.
+        branch_len_factor: f64,
+    },
+}
+
+/// Parse a scene description. `ext` is the source file's extension
+/// (case-insensitively): `"ron"` parses `s` as RON, anything else parses it
+/// as YAML.
+pub fn parse(ext: &str, s: &str) -> Result<Scene, String> {
+    if ext.eq_ignore_ascii_case("ron") {
+        ron::de::from_str(s).map_err(|err| format!("invalid RON scene: {}", err))
+    } else {
+        serde_yaml::from_str(s).map_err(|err| format!("invalid YAML scene: {}", err))
+    }
+}
+
+/// Render every layer of `scene` onto a `width`x`height` canvas, compositing
+/// them back to front in order.
+pub fn render(width: u32, height: u32, scene: &Scene) -> image::RgbaImage {
+    let mut canvas = image::RgbaImage::from_pixel(width, height, image::Rgba { data: [0; 4] });
+
+    for layer in &scene.layers {
+        let layer_img = render_generator(&layer.generator, width, height);
+
+        composite_layer(&mut canvas, &layer_img, layer);
+    }
+
+    canvas
+}
+
+/// Render a single generator, at full canvas size, as an opaque `RgbaImage`
+/// ready to be composited by `composite_layer`.
+fn render_generator(generator: &Generator, width: u32, height: u32) -> image::RgbaImage {
+    match *generator {
+        Generator::Voronoi { npoints, toroidal } => {
+            let mut img = image::RgbImage::new(width, height);
+            let mut color_config = RandomColorConfig::new().luminosity(color::Luminosity::Bright);
+            let wrap = if toroidal {
+                voronoi::Wrap::Toroidal
+            } else {
+                voronoi::Wrap::None
+            };
+
+            voronoi::random_voronoi(
+                &mut img,
+                &mut color_config,
+                npoints,
+                crate::geo::kdtree::DistanceMetric::Euclidean,
+                wrap,
+                BlendMode::Over,
+            );
+
+            image::ImageRgb8(img).to_rgba()
+        }
+        Generator::Delaunay { grid_size } => {
+            let mut color_config = RandomColorConfig::new()
+                .hue(color::KnownHue::Blue)
+                .luminosity(color::Luminosity::Light);
+            let alpha = 0xd6;
+
+            let mut img = image::RgbaImage::from_pixel(
+                width,
+                height,
+                image::Rgba {
+                    data: color::random_color(&mut color_config).to_rgba(alpha),
+                },
+            );
+
+            delaunay::random_triangulation(
+                &mut img,
+                &mut color_config,
+                grid_size,
+                alpha,
+                BlendMode::Over,
+            );
+
+            img
+        }
+        Generator::Mondrian {
+            iterations,
+            minimum_area,
+        } => {
+            let mut img = image::RgbImage::new(width, height);
+
+            mondrian::generate(
+                &mut img,
+                iterations,
+                minimum_area,
+                MONDRIAN_WHITE,
+                &MONDRIAN_FILL_PALETTE,
+                10,
+            );
+
+            image::ImageRgb8(img).to_rgba()
+        }
+        Generator::Stippling {
+            bands,
+            first_band_points,
+            grow_coeff,
+            ref stops,
+        } => {
+            let mut img =
+                image::RgbImage::from_pixel(width, height, image::Rgb { data: [0xFF; 3] });
+
+            let stops = if stops.len() >= 2 {
+                stops.clone()
+            } else {
+                vec![
+                    Stop::new(0.0, image::Rgb { data: [0, 0, 0] }),
+                    Stop::new(1.0, image::Rgb { data: [0, 0, 0] }),
+                ]
+            };
+
+            let gradient = crate::fills::Gradient::linear(
+                geo::PointF64::new(0.0, 0.0),
+                geo::PointF64::new(0.0, 1.0),
+                stops,
+            );
+
+            stippling::gradient(
+                &mut img,
+                bands,
+                first_band_points,
+                grow_coeff,
+                &gradient,
+                stippling::Direction::TopToBottom,
+            );
+
+            image::ImageRgb8(img).to_rgba()
+        }
+        Generator::Dither {
+            ref img_path,
+            levels,
+        } => {
+            let img = image::open(img_path).expect("cannot open source image file");
+            let step = u8::max_value() / levels;
+
+            let dithered =
+                dithering::dither(&img.to_rgb(), &dithering::FLOYD_STEINBERG, |l| image::Rgb {
+                    data: [
+                        l.data[0] / step * step,
+                        l.data[1] / step * step,
+                        l.data[2] / step * step,
+                    ],
+                });
+
+            image::ImageRgb8(dithered).to_rgba()
+        }
+        Generator::FractalTree {
+            nbranches,
+            branching_angle_step,
+            branch_len_factor,
+        } => {
+            let mut img = image::RgbaImage::from_pixel(width, height, image::Rgba { data: [0; 4] });
+
+            fractree::fractal_tree(
+                &mut img,
+                nbranches,
+                geo::PointU32::new(width / 2, height - 1),
+                -std::f64::consts::PI / 2.0,
+                branching_angle_step,
+                f64::from(height) / 3.0,
+                branch_len_factor,
+                &image::Rgba {
+                    data: [0, 0, 0, 0xFF],
+                },
+            );
+
+            img
+        }
+    }
+}
+
+/// Blend `layer_img` onto `canvas`, clipped to `layer.rect` and scaling
+/// every pixel's alpha by `layer.opacity` before compositing with
+/// `layer.blend_mode`.
+fn composite_layer(canvas: &mut image::RgbaImage, layer_img: &image::RgbaImage, layer: &Layer) {
+    let opacity = layer.opacity.max(0.0).min(1.0);
+
+    for y in 0..canvas.height() {
+        for x in 0..canvas.width() {
+            if let Some(ref rect) = layer.rect {
+                if !rect.contains(x, y) {
+                    continue;
+                }
+            }
+
+            let mut src = *layer_img.get_pixel(x, y);
+            src.data[3] = (f64::from(src.data[3]) * opacity).round() as u8;
+
+            let dst = *canvas.get_pixel(x, y);
+            canvas.put_pixel(x, y, compose::blend(dst, src, layer.blend_mode));
+        }
+    }
+}
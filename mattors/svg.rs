@@ -0,0 +1,130 @@
+//! A minimal SVG document builder, shared by every generator that can emit
+//! resolution-independent vector output alongside (or instead of) a raster
+//! image.
+//!
+//! This intentionally doesn't try to be a general purpose SVG library: it
+//! only knows how to append the handful of shapes (filled polygons,
+//! polylines, circles, rects) that the generators in `art` actually produce,
+//! in the order they're pushed.
+
+use std::fmt::Write as _;
+
+use geo::PointF64;
+
+/// An SVG document under construction: a fixed-size viewport plus the shape
+/// elements appended so far, in drawing order.
+#[derive(Debug, Clone)]
+pub struct SvgCanvas {
+    width: f64,
+    height: f64,
+    elements: String,
+}
+
+impl SvgCanvas {
+    /// Create a new, empty canvas with the given pixel dimensions.
+    pub fn new(width: f64, height: f64) -> Self {
+        SvgCanvas {
+            width,
+            height,
+            elements: String::new(),
+        }
+    }
+
+    /// Append a filled polygon. `points` is automatically closed back to its
+    /// first point; fewer than 3 points are ignored.
+    pub fn polygon(&mut self, points: &[PointF64], fill: &str) {
+        if points.len() < 3 {
+            return;
+        }
+
+        write!(self.elements, r#"<polygon fill="{}" points=""#, fill).unwrap();
+        write_points(&mut self.elements, points);
+        writeln!(self.elements, r#""/>"#).unwrap();
+    }
+
+    /// Append an open polyline stroked with `stroke`, `stroke_width` pixels
+    /// wide; fewer than 2 points are ignored.
+    pub fn polyline(&mut self, points: &[PointF64], stroke: &str, stroke_width: f64) {
+        if points.len() < 2 {
+            return;
+        }
+
+        write!(
+            self.elements,
+            r#"<polyline fill="none" stroke="{}" stroke-width="{}" points=""#,
+            stroke, stroke_width
+        )
+        .unwrap();
+        write_points(&mut self.elements, points);
+        writeln!(self.elements, r#""/>"#).unwrap();
+    }
+
+    /// Append a filled circle.
+    pub fn circle(&mut self, center: PointF64, radius: f64, fill: &str) {
+        writeln!(
+            self.elements,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            center.x, center.y, radius, fill
+        )
+        .unwrap();
+    }
+
+    /// Append a filled, axis-aligned rectangle.
+    pub fn rect(&mut self, top_left: PointF64, width: f64, height: f64, fill: &str) {
+        writeln!(
+            self.elements,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            top_left.x, top_left.y, width, height, fill
+        )
+        .unwrap();
+    }
+
+    /// Append a filled, closed path through `points` joined by straight line
+    /// segments; fewer than 3 points are ignored. Unlike `polygon`, this
+    /// emits a `<path>` element, so callers that need to mix in curve
+    /// commands can build on the same `M`/`L`/`Z` shape this produces.
+    pub fn path(&mut self, points: &[PointF64], fill: &str) {
+        if points.len() < 3 {
+            return;
+        }
+
+        write!(
+            self.elements,
+            r#"<path fill="{}" d="M{},{} "#,
+            fill, points[0].x, points[0].y
+        )
+        .unwrap();
+
+        for p in &points[1..] {
+            write!(self.elements, "L{},{} ", p.x, p.y).unwrap();
+        }
+
+        writeln!(self.elements, r#"Z"/>"#).unwrap();
+    }
+
+    /// Render the accumulated elements as a complete, standalone SVG
+    /// document.
+    pub fn to_svg(&self) -> String {
+        format!(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {w} {h}">
+{body}</svg>"##,
+            w = self.width,
+            h = self.height,
+            body = self.elements,
+        )
+    }
+}
+
+fn write_points(out: &mut String, points: &[PointF64]) {
+    for p in points {
+        write!(out, "{},{} ", p.x, p.y).unwrap();
+    }
+}
+
+/// Format an 8-bit RGB color plus an alpha in `[0, 255]` as a CSS
+/// `rgba(...)` fill/stroke value.
+pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> String {
+    format!("rgba({},{},{},{})", r, g, b, f64::from(a) / 255.0)
+}
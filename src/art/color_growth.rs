@@ -0,0 +1,339 @@
+//! "Kd-forest" style image synthesis: fill an image by placing every color of
+//! a palette exactly once, always choosing the pixel whose neighborhood makes
+//! it look most natural for the color being placed. This produces the classic
+//! "every color once" organic gradients.
+//!
+//! The growth keeps a *frontier* of empty pixels that are adjacent to at
+//! least one already filled pixel. For every palette color (visited in some
+//! configurable order) the frontier pixel whose target color (the mean of its
+//! filled neighbors) is closest to it is picked. Because the mean of a
+//! frontier pixel changes as more of its neighbors get filled, frontier
+//! entries are lazily revalidated: a pixel is popped, its current mean is
+//! recomputed and, if it became stale, the pixel is pushed back with the
+//! fresh target and skipped.
+
+extern crate image;
+extern crate rand;
+
+use std::collections::HashSet;
+
+use geo::PointU32;
+
+/// Which neighbors of a pixel are considered when growing the frontier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Only the 4 orthogonal neighbors.
+    Four,
+
+    /// The 4 orthogonal neighbors plus the 4 diagonal ones.
+    Eight,
+}
+
+impl Neighborhood {
+    fn offsets(self) -> &'static [(i32, i32)] {
+        match self {
+            Neighborhood::Four => &[(0, -1), (0, 1), (-1, 0), (1, 0)],
+            Neighborhood::Eight => &[
+                (0, -1),
+                (0, 1),
+                (-1, 0),
+                (1, 0),
+                (-1, -1),
+                (-1, 1),
+                (1, -1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// The order in which palette colors are offered to the frontier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorOrder {
+    /// Sort the palette by hue.
+    Hue,
+
+    /// Shuffle the palette randomly.
+    Random,
+
+    /// Walk the palette following a Hilbert curve through the RGB cube.
+    Hilbert,
+}
+
+type Rgb = [u8; 3];
+
+/// A pixel on the frontier, together with the mean color of its already
+/// filled neighbors at the time it was last (re)computed.
+struct FrontierEntry {
+    pixel: PointU32,
+    target: Rgb,
+}
+
+fn mean_of_filled_neighbors(
+    img: &image::RgbImage,
+    filled: &HashSet<PointU32>,
+    pixel: PointU32,
+    neighborhood: Neighborhood,
+) -> Rgb {
+    let mut sum = [0u32; 3];
+    let mut count = 0u32;
+
+    for &(dx, dy) in neighborhood.offsets() {
+        let nx = pixel.x as i64 + i64::from(dx);
+        let ny = pixel.y as i64 + i64::from(dy);
+
+        if nx < 0 || ny < 0 {
+            continue;
+        }
+
+        let neighbor = PointU32::new(nx as u32, ny as u32);
+        if filled.contains(&neighbor) {
+            let p = img.get_pixel(neighbor.x, neighbor.y);
+            for i in 0..3 {
+                sum[i] += u32::from(p.data[i]);
+            }
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return [0, 0, 0];
+    }
+
+    [
+        (sum[0] / count) as u8,
+        (sum[1] / count) as u8,
+        (sum[2] / count) as u8,
+    ]
+}
+
+fn squared_color_dist(a: Rgb, b: Rgb) -> i32 {
+    (0..3)
+        .map(|i| {
+            let d = i32::from(a[i]) - i32::from(b[i]);
+            d * d
+        })
+        .sum()
+}
+
+fn push_neighbors(
+    img: &image::RgbImage,
+    filled: &HashSet<PointU32>,
+    on_frontier: &mut HashSet<PointU32>,
+    frontier: &mut Vec<FrontierEntry>,
+    pixel: PointU32,
+    neighborhood: Neighborhood,
+) {
+    for &(dx, dy) in neighborhood.offsets() {
+        let nx = pixel.x as i64 + i64::from(dx);
+        let ny = pixel.y as i64 + i64::from(dy);
+
+        if nx < 0 || ny < 0 || nx >= i64::from(img.width()) || ny >= i64::from(img.height()) {
+            continue;
+        }
+
+        let neighbor = PointU32::new(nx as u32, ny as u32);
+        if filled.contains(&neighbor) || on_frontier.contains(&neighbor) {
+            continue;
+        }
+
+        let target = mean_of_filled_neighbors(img, filled, neighbor, neighborhood);
+        on_frontier.insert(neighbor);
+        frontier.push(FrontierEntry {
+            pixel: neighbor,
+            target,
+        });
+    }
+}
+
+fn hue(rgb: Rgb) -> f64 {
+    let [r, g, b] = [
+        f64::from(rgb[0]) / 255.0,
+        f64::from(rgb[1]) / 255.0,
+        f64::from(rgb[2]) / 255.0,
+    ];
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    if delta == 0.0 {
+        return 0.0;
+    }
+
+    let hue = if (max - r).abs() < ::std::f64::EPSILON {
+        60.0 * (((g - b) / delta) % 6.0)
+    } else if (max - g).abs() < ::std::f64::EPSILON {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    if hue < 0.0 {
+        hue + 360.0
+    } else {
+        hue
+    }
+}
+
+/// Interleave the bits of the 3 color channels (Morton/Z-order code). This is
+/// a cheap stand-in for a proper Hilbert-curve walk of the RGB cube: it's not
+/// as locality-preserving, but it's good enough to get smoothly varying
+/// neighboring colors out of a simple sort.
+fn morton_code(rgb: Rgb) -> u32 {
+    fn spread(mut x: u32) -> u32 {
+        x &= 0x0000_00ff;
+        x = (x | (x << 16)) & 0x0300_00ff;
+        x = (x | (x << 8)) & 0x030c_30c3;
+        x = (x | (x << 4)) & 0x0924_9249;
+        x
+    }
+
+    spread(u32::from(rgb[0])) | (spread(u32::from(rgb[1])) << 1) | (spread(u32::from(rgb[2])) << 2)
+}
+
+/// Order the given palette according to `order`.
+pub fn order_palette<R: self::rand::Rng>(
+    mut palette: Vec<Rgb>,
+    order: ColorOrder,
+    rng: &mut R,
+) -> Vec<Rgb> {
+    match order {
+        ColorOrder::Hue => {
+            palette.sort_by(|a, b| hue(*a).partial_cmp(&hue(*b)).unwrap());
+            palette
+        }
+        ColorOrder::Random => {
+            rng.shuffle(&mut palette);
+            palette
+        }
+        ColorOrder::Hilbert => {
+            palette.sort_by_key(|&rgb| morton_code(rgb));
+            palette
+        }
+    }
+}
+
+/// Side length of the smallest cube of colors that contains at least `n`
+/// colors.
+pub fn cube_side(n: u32) -> u32 {
+    let mut side = 1;
+    while side * side * side < n {
+        side += 1;
+    }
+    side
+}
+
+/// Every color of a `side` x `side` x `side` cube of the RGB space, evenly
+/// spaced so `side == 256` yields every 24-bit color exactly once.
+pub fn cube_colors(side: u32) -> Vec<Rgb> {
+    let scale = |c: u32| if side <= 1 { 0 } else { (c * 255 / (side - 1)) as u8 };
+
+    let mut colors = Vec::with_capacity((side * side * side) as usize);
+    for r in 0..side {
+        for g in 0..side {
+            for b in 0..side {
+                colors.push([scale(r), scale(g), scale(b)]);
+            }
+        }
+    }
+
+    colors
+}
+
+/// Fill `img` by placing every color in `palette` exactly once, starting the
+/// growth at `start`. `img` must be big enough to hold `palette.len()`
+/// pixels, otherwise the growth stops once either the image is full or the
+/// palette is exhausted.
+pub fn grow(
+    img: &mut image::RgbImage,
+    palette: Vec<Rgb>,
+    start: PointU32,
+    neighborhood: Neighborhood,
+) {
+    let mut filled = HashSet::new();
+    let mut on_frontier = HashSet::new();
+    let mut frontier: Vec<FrontierEntry> = vec![];
+
+    let mut palette = palette.into_iter();
+
+    if let Some(first) = palette.next() {
+        img.put_pixel(
+            start.x,
+            start.y,
+            image::Rgb {
+                data: first,
+            },
+        );
+        filled.insert(start);
+        push_neighbors(
+            img,
+            &filled,
+            &mut on_frontier,
+            &mut frontier,
+            start,
+            neighborhood,
+        );
+    }
+
+    for color in palette {
+        if frontier.is_empty() {
+            break;
+        }
+
+        // find, and lazily revalidate, the best frontier entry for `color`.
+        let mut best_idx = None;
+        let mut best_dist = i32::max_value();
+
+        loop {
+            if frontier.is_empty() {
+                break;
+            }
+
+            for (i, entry) in frontier.iter().enumerate() {
+                let fresh_target =
+                    mean_of_filled_neighbors(img, &filled, entry.pixel, neighborhood);
+
+                if fresh_target != entry.target {
+                    continue;
+                }
+
+                let dist = squared_color_dist(fresh_target, color);
+                if best_idx.is_none() || dist < best_dist {
+                    best_idx = Some(i);
+                    best_dist = dist;
+                }
+            }
+
+            // refresh any stale entries found along the way, then retry if we
+            // still didn't manage to pick anything (shouldn't normally
+            // happen, but keeps the loop well defined).
+            for entry in &mut frontier {
+                entry.target = mean_of_filled_neighbors(img, &filled, entry.pixel, neighborhood);
+            }
+
+            if best_idx.is_some() {
+                break;
+            }
+        }
+
+        let idx = match best_idx {
+            Some(i) => i,
+            None => break,
+        };
+
+        let entry = frontier.swap_remove(idx);
+        on_frontier.remove(&entry.pixel);
+        filled.insert(entry.pixel);
+        img.put_pixel(entry.pixel.x, entry.pixel.y, image::Rgb { data: color });
+
+        push_neighbors(
+            img,
+            &filled,
+            &mut on_frontier,
+            &mut frontier,
+            entry.pixel,
+            neighborhood,
+        );
+    }
+}
@@ -78,10 +78,12 @@ pub fn random_point_in_bbox<R: Rng>(rng: &mut R, bbox: &BoundingBox<u32>) -> Poi
     PointU32::new(x, y)
 }
 
+pub mod color_growth;
 pub mod delaunay;
 pub mod dragon;
 pub mod fractree;
 pub mod julia;
+pub mod lsystem;
 pub mod mondrian;
 pub mod patchwork;
 pub mod primi;
@@ -12,6 +12,7 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::iter::Iterator;
 
+use self::image::Pixel;
 use self::rand::Rng;
 
 use art::quantize;
@@ -27,14 +28,71 @@ pub trait Shape {
     /// Create a new version of `Shape` that's slightly changed.
     fn mutate(&self, width: u32, height: u32, dx: u32, dy: u32) -> Self;
 
-    /// Draw the `Shape` onto `dst`.
-    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>)
+    /// Draw the `Shape` onto `dst`, blended with `alpha` (in `[0, 255]`).
+    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>, alpha: u8)
     where
         P: 'static + image::Pixel + Debug,
         P::Subpixel: From<u8>;
 
     /// Upscale the shape by the given `factor`.
     fn upscale(&self, factor: u32) -> Self;
+
+    /// The axis-aligned bounding box (inclusive on both ends, clamped to
+    /// `width`x`height`) that `draw` can possibly touch. Only this region
+    /// needs to be re-scored against the target image after drawing the
+    /// shape.
+    fn bbox(&self, width: u32, height: u32) -> (u32, u32, u32, u32);
+}
+
+/// Which `Shape` implementation `primify` should approximate the image
+/// with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeKind {
+    /// Plain triangles, the original (and default) shape.
+    Triangle,
+
+    /// Axis-aligned rectangles.
+    Rect,
+
+    /// Rectangles free to rotate around their center.
+    RotatedRect,
+
+    /// Axis-aligned ellipses.
+    Ellipse,
+}
+
+impl std::str::FromStr for ShapeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "triangle" => Ok(ShapeKind::Triangle),
+            "rect" => Ok(ShapeKind::Rect),
+            "rotated-rect" => Ok(ShapeKind::RotatedRect),
+            "ellipse" => Ok(ShapeKind::Ellipse),
+            _ => Err(format!("unknown shape kind: {}", s)),
+        }
+    }
+}
+
+/// Configuration shared by every `Shape` implementation `primify` drives.
+#[derive(Clone, Debug)]
+pub struct PrimifyConfig {
+    /// Alpha every shape is blended with when drawn, in `[0, 255]`.
+    pub alpha: u8,
+
+    /// Which kind of shape to approximate the image with.
+    pub shape_kind: ShapeKind,
+
+    /// delta in x that determines how big the shapes will be.
+    pub dx: u32,
+
+    /// delta in y that determines how big the shapes will be.
+    pub dy: u32,
+
+    /// Number of mutations to try while hill-climbing a single shape
+    /// before moving on to the next one.
+    pub nmutations: u32,
 }
 
 /// The result of `primitify`.
@@ -60,9 +118,7 @@ pub type PrimifyImage<P> = image::ImageBuffer<P, Vec<<P as image::Pixel>::Subpix
 pub fn primify<P, S>(
     img: &PrimifyImage<P>,
     nshapes: usize,
-    nmutations: u32,
-    dx: u32,
-    dy: u32,
+    config: &PrimifyConfig,
 ) -> Option<Primitized<P, S>>
 where
     P: 'static + Eq + Hash + image::Pixel + Debug,
@@ -73,19 +129,26 @@ where
     if let Some(dominant) = get_dominant_color(img) {
         let initial_image = image::ImageBuffer::from_pixel(img.width(), img.height(), dominant);
 
+        // a running total squared-error accumulator: every shape only
+        // touches a small region of the image, so it's cheaper to patch
+        // this in place than to recompute it from scratch on every
+        // mutation.
+        let mut sq_error = squared_error(img.iter(), initial_image.iter());
+
         let mut res = Primitized {
-            best_error: get_error(img.iter(), initial_image.iter()),
+            best_error: sq_error.sqrt(),
             shapes: Vec::with_capacity(nshapes),
             best_image: initial_image,
             dominant_color: dominant,
         };
 
         for _ in 0..nshapes {
-            let (new_primified, new_error, shape) =
-                generate_shape::<P, S>(img, &res.best_image, nmutations, dx, dy);
+            let (new_primified, new_sq_error, shape) =
+                generate_shape::<P, S>(img, &res.best_image, sq_error, config);
 
-            if new_error < res.best_error {
-                res.best_error = new_error;
+            if new_sq_error < sq_error {
+                sq_error = new_sq_error;
+                res.best_error = sq_error.sqrt();
                 res.best_image = new_primified;
                 res.shapes.push(shape);
             }
@@ -102,9 +165,8 @@ where
 fn generate_shape<P, S>(
     origin: &PrimifyImage<P>,
     best_primified: &PrimifyImage<P>,
-    nmutations: u32,
-    dx: u32,
-    dy: u32,
+    best_sq_error: f64,
+    config: &PrimifyConfig,
 ) -> (PrimifyImage<P>, f64, S)
 where
     P: 'static + image::Pixel + Debug,
@@ -112,20 +174,29 @@ where
     f64: From<P::Subpixel>,
     S: Shape,
 {
+    let mut shape = S::random(origin.width(), origin.height(), config.dx, config.dy);
     let mut primified = best_primified.clone();
-
-    let mut shape = S::random(origin.width(), origin.height(), dx, dy);
-    shape.draw(origin, &mut primified);
-
-    let mut error = get_error(origin.iter(), primified.iter());
-
-    for _ in 0..nmutations {
-        let new_shape = shape.mutate(origin.width(), origin.height(), dx, dy);
+    let mut error = draw_and_score(
+        origin,
+        best_primified,
+        &mut primified,
+        &shape,
+        best_sq_error,
+        config.alpha,
+    );
+
+    for _ in 0..config.nmutations {
+        let new_shape = shape.mutate(origin.width(), origin.height(), config.dx, config.dy);
 
         let mut new_primified = best_primified.clone();
-        new_shape.draw(origin, &mut new_primified);
-
-        let mut new_error = get_error(origin.iter(), new_primified.iter());
+        let new_error = draw_and_score(
+            origin,
+            best_primified,
+            &mut new_primified,
+            &new_shape,
+            best_sq_error,
+            config.alpha,
+        );
 
         // println!("error: {:?} new_error: {:?}", error, new_error);
 
@@ -139,6 +210,35 @@ where
     (primified, error, shape)
 }
 
+// draw `shape` onto `dst` (a clone of `best_primified`) and return the
+// resulting total squared error, by only re-scoring `shape.bbox()` against
+// `origin` and patching `best_sq_error` with the delta rather than
+// recomputing it over the whole image.
+fn draw_and_score<P, S>(
+    origin: &PrimifyImage<P>,
+    best_primified: &PrimifyImage<P>,
+    dst: &mut PrimifyImage<P>,
+    shape: &S,
+    best_sq_error: f64,
+    alpha: u8,
+) -> f64
+where
+    P: 'static + image::Pixel + Debug,
+    P::Subpixel: From<u8> + std::fmt::Debug,
+    f64: From<P::Subpixel>,
+    S: Shape,
+{
+    let bbox = shape.bbox(origin.width(), origin.height());
+
+    let old_region_error = region_squared_error(origin, best_primified, bbox);
+
+    shape.draw(origin, dst, alpha);
+
+    let new_region_error = region_squared_error(origin, dst, bbox);
+
+    best_sq_error - old_region_error + new_region_error
+}
+
 fn get_dominant_color<I>(img: &I) -> Option<I::Pixel>
 where
     I: image::GenericImageView,
@@ -150,17 +250,46 @@ where
     quantize::quantize(pixels_it, 0).map(|res| res.colors[0])
 }
 
-fn get_error<'a, I, D>(it1: I, it2: I) -> f64
+fn squared_error<'a, I, D>(it1: I, it2: I) -> f64
 where
     I: Iterator<Item = &'a D>,
     D: 'a + Clone,
     f64: From<D>,
 {
-    // root mean square deviation
     it1.zip(it2)
         .map(|(x, y)| (f64::from(x.clone()), f64::from(y.clone())))
         .fold(0.0, |acc, (x, y)| acc + (x - y).powi(2))
-        .sqrt()
+}
+
+// sum the squared per-subpixel difference between `origin` and `img` over
+// just the `(x0, y0, x1, y1)` region (inclusive), rather than the whole
+// image.
+fn region_squared_error<P>(
+    origin: &PrimifyImage<P>,
+    img: &PrimifyImage<P>,
+    bbox: (u32, u32, u32, u32),
+) -> f64
+where
+    P: image::Pixel,
+    P::Subpixel: Clone,
+    f64: From<P::Subpixel>,
+{
+    let (x0, y0, x1, y1) = bbox;
+
+    let mut sum = 0.0;
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let a = origin.get_pixel(x, y).channels();
+            let b = img.get_pixel(x, y).channels();
+
+            for (ca, cb) in a.iter().zip(b.iter()) {
+                sum += (f64::from(ca.clone()) - f64::from(cb.clone())).powi(2);
+            }
+        }
+    }
+
+    sum
 }
 
 impl Shape for geo::Triangle<u32> {
@@ -211,17 +340,16 @@ impl Shape for geo::Triangle<u32> {
         tri
     }
 
-    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>)
+    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>, alpha: u8)
     where
         P: 'static + image::Pixel + Debug,
         P::Subpixel: From<u8>,
     {
         let triangle_center = self.centroid();
 
-        // FIXME: take opacity from config
         let pix = origin
             .get_pixel(triangle_center.x, triangle_center.y)
-            .map_with_alpha(|c| c, |_| From::from(0x7F));
+            .map_with_alpha(|c| c, |_| From::from(alpha));
 
         let mut drawer = drawing::Drawer::new_with_default_blending(dst);
         drawer.triangle(self.points[0], self.points[1], self.points[2], &pix);
@@ -236,6 +364,272 @@ impl Shape for geo::Triangle<u32> {
             ],
         }
     }
+
+    fn bbox(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let xs = self.points.iter().map(|p| p.x);
+        let ys = self.points.iter().map(|p| p.y);
+
+        let x0 = xs.clone().min().unwrap();
+        let x1 = xs.max().unwrap();
+        let y0 = ys.clone().min().unwrap();
+        let y1 = ys.max().unwrap();
+
+        (x0, y0, clamp(x1, 0, width - 1), clamp(y1, 0, height - 1))
+    }
+}
+
+impl Shape for geo::Rect<u32> {
+    fn random(width: u32, height: u32, dx: u32, dy: u32) -> Self {
+        let mut rng = rand::thread_rng();
+
+        let dx = i64::from(dx);
+        let dy = i64::from(dy);
+
+        let x0 = rng.gen_range(0, width);
+        let y0 = rng.gen_range(0, height);
+
+        let x1 = clamp(i64::from(x0) + rng.gen_range(-dx, dx), 0, width);
+        let y1 = clamp(i64::from(y0) + rng.gen_range(-dy, dy), 0, height);
+
+        let (x_min, x_max) = if x0 < x1 { (x0, x1) } else { (x1, x0) };
+        let (y_min, y_max) = if y0 < y1 { (y0, y1) } else { (y1, y0) };
+
+        geo::Rect::new(
+            geo::PointU32::new(x_min, y_min),
+            x_max - x_min,
+            y_max - y_min,
+        )
+    }
+
+    fn mutate(&self, width: u32, height: u32, dx: u32, dy: u32) -> Self {
+        let dx = i64::from(dx);
+        let dy = i64::from(dy);
+
+        let mut rng = rand::thread_rng();
+
+        // mutate either the origin or one of the sides, so a single
+        // mutation never reshuffles the whole rectangle at once.
+        match rng.gen_range(0, 4) {
+            0 => {
+                let x = i64::from(self.origin.x) + rng.gen_range(-dx, dx);
+                geo::Rect::new(
+                    geo::PointU32::new(clamp(x, 0, width - 1), self.origin.y),
+                    self.width,
+                    self.height,
+                )
+            }
+            1 => {
+                let y = i64::from(self.origin.y) + rng.gen_range(-dy, dy);
+                geo::Rect::new(
+                    geo::PointU32::new(self.origin.x, clamp(y, 0, height - 1)),
+                    self.width,
+                    self.height,
+                )
+            }
+            2 => {
+                let w = i64::from(self.width) + rng.gen_range(-dx, dx);
+                geo::Rect::new(
+                    self.origin.clone(),
+                    clamp(w, 1, width - self.origin.x),
+                    self.height,
+                )
+            }
+            _ => {
+                let h = i64::from(self.height) + rng.gen_range(-dy, dy);
+                geo::Rect::new(
+                    self.origin.clone(),
+                    self.width,
+                    clamp(h, 1, height - self.origin.y),
+                )
+            }
+        }
+    }
+
+    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>, alpha: u8)
+    where
+        P: 'static + image::Pixel + Debug,
+        P::Subpixel: From<u8>,
+    {
+        let center = self.center();
+
+        let pix = origin
+            .get_pixel(center.x, center.y)
+            .map_with_alpha(|c| c, |_| From::from(alpha));
+
+        let mut drawer = drawing::Drawer::new_with_default_blending(dst);
+        drawer.polygon(self.points().to_vec(), &pix);
+    }
+
+    fn upscale(&self, factor: u32) -> Self {
+        geo::Rect::new(
+            geo::PointU32::new(self.origin.x * factor, self.origin.y * factor),
+            self.width * factor,
+            self.height * factor,
+        )
+    }
+
+    fn bbox(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        (
+            self.origin.x,
+            self.origin.y,
+            clamp(self.origin.x + self.width, 0, width - 1),
+            clamp(self.origin.y + self.height, 0, height - 1),
+        )
+    }
+}
+
+/// An axis-aligned rectangle free to rotate around its center, for more
+/// varied `primify` output than a plain `geo::Rect`.
+#[derive(Clone, Debug)]
+pub struct RotatedRect {
+    rect: geo::Rect<u32>,
+    angle: f64,
+}
+
+impl RotatedRect {
+    // the 4 corners of `self.rect`, rotated around its center by `self.angle`.
+    fn points(&self) -> [geo::PointU32; 4] {
+        let center = self.rect.center().cast::<f64>();
+
+        let mut points = self.rect.points();
+        for p in &mut points {
+            let rotated = p.cast::<f64>().rotate_around(self.angle, &center);
+            *p = geo::PointU32::new(rotated.x.max(0.0) as u32, rotated.y.max(0.0) as u32);
+        }
+
+        points
+    }
+}
+
+impl Shape for RotatedRect {
+    fn random(width: u32, height: u32, dx: u32, dy: u32) -> Self {
+        let mut rng = rand::thread_rng();
+
+        RotatedRect {
+            rect: geo::Rect::random(width, height, dx, dy),
+            angle: rng.gen_range(0.0, ::std::f64::consts::PI),
+        }
+    }
+
+    fn mutate(&self, width: u32, height: u32, dx: u32, dy: u32) -> Self {
+        let mut rng = rand::thread_rng();
+
+        if rng.gen_range(0, 5) == 0 {
+            RotatedRect {
+                rect: self.rect.clone(),
+                angle: self.angle + rng.gen_range(-0.2, 0.2),
+            }
+        } else {
+            RotatedRect {
+                rect: self.rect.mutate(width, height, dx, dy),
+                angle: self.angle,
+            }
+        }
+    }
+
+    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>, alpha: u8)
+    where
+        P: 'static + image::Pixel + Debug,
+        P::Subpixel: From<u8>,
+    {
+        let center = self.rect.center();
+
+        let pix = origin
+            .get_pixel(center.x, center.y)
+            .map_with_alpha(|c| c, |_| From::from(alpha));
+
+        let mut drawer = drawing::Drawer::new_with_default_blending(dst);
+        drawer.polygon(self.points().to_vec(), &pix);
+    }
+
+    fn upscale(&self, factor: u32) -> Self {
+        RotatedRect {
+            rect: self.rect.upscale(factor),
+            angle: self.angle,
+        }
+    }
+
+    fn bbox(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let points = self.points();
+
+        let xs = points.iter().map(|p| p.x);
+        let ys = points.iter().map(|p| p.y);
+
+        let x0 = xs.clone().min().unwrap();
+        let x1 = xs.max().unwrap();
+        let y0 = ys.clone().min().unwrap();
+        let y1 = ys.max().unwrap();
+
+        (x0, y0, clamp(x1, 0, width - 1), clamp(y1, 0, height - 1))
+    }
+}
+
+/// An axis-aligned ellipse, represented by its bounding `geo::Rect`.
+#[derive(Clone, Debug)]
+pub struct Ellipse {
+    rect: geo::Rect<u32>,
+}
+
+impl Ellipse {
+    const NPOINTS: usize = 24;
+
+    // sample `NPOINTS` points around the ellipse's boundary so it can be
+    // filled with the existing scanline polygon fill.
+    fn points(&self) -> Vec<geo::PointU32> {
+        let center = self.rect.center();
+        let rx = f64::from(self.rect.width) / 2.0;
+        let ry = f64::from(self.rect.height) / 2.0;
+
+        (0..Self::NPOINTS)
+            .map(|i| {
+                let t = 2.0 * ::std::f64::consts::PI * (i as f64) / (Self::NPOINTS as f64);
+
+                let x = f64::from(center.x) + rx * t.cos();
+                let y = f64::from(center.y) + ry * t.sin();
+
+                geo::PointU32::new(x.max(0.0) as u32, y.max(0.0) as u32)
+            })
+            .collect()
+    }
+}
+
+impl Shape for Ellipse {
+    fn random(width: u32, height: u32, dx: u32, dy: u32) -> Self {
+        Ellipse {
+            rect: geo::Rect::random(width, height, dx, dy),
+        }
+    }
+
+    fn mutate(&self, width: u32, height: u32, dx: u32, dy: u32) -> Self {
+        Ellipse {
+            rect: self.rect.mutate(width, height, dx, dy),
+        }
+    }
+
+    fn draw<P>(&self, origin: &PrimifyImage<P>, dst: &mut PrimifyImage<P>, alpha: u8)
+    where
+        P: 'static + image::Pixel + Debug,
+        P::Subpixel: From<u8>,
+    {
+        let center = self.rect.center();
+
+        let pix = origin
+            .get_pixel(center.x, center.y)
+            .map_with_alpha(|c| c, |_| From::from(alpha));
+
+        let mut drawer = drawing::Drawer::new_with_default_blending(dst);
+        drawer.polygon(self.points(), &pix);
+    }
+
+    fn upscale(&self, factor: u32) -> Self {
+        Ellipse {
+            rect: self.rect.upscale(factor),
+        }
+    }
+
+    fn bbox(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        self.rect.bbox(width, height)
+    }
 }
 
 #[cfg(test)]
@@ -244,11 +638,14 @@ mod tests {
 
     #[test]
     fn test_error() {
-        assert_eq!(get_error(([] as [u8; 0]).iter(), [].iter()), 0.0);
-        assert_eq!(get_error([0_u8].iter(), [2_u8].iter()), 2.0);
-        assert_eq!(get_error([3_u8, 1, 3].iter(), [3_u8, 4, 7].iter()), 5.0);
-
-        let err = get_error([3_u8, 1, 3].iter(), [3_u8, 4, 5].iter());
+        assert_eq!(squared_error(([] as [u8; 0]).iter(), [].iter()).sqrt(), 0.0);
+        assert_eq!(squared_error([0_u8].iter(), [2_u8].iter()).sqrt(), 2.0);
+        assert_eq!(
+            squared_error([3_u8, 1, 3].iter(), [3_u8, 4, 7].iter()).sqrt(),
+            5.0
+        );
+
+        let err = squared_error([3_u8, 1, 3].iter(), [3_u8, 4, 5].iter()).sqrt();
 
         // round err to two decimal digits to avoid float issues
         let err = (err * 100.0).trunc() / 100.0;
@@ -6,10 +6,33 @@ extern crate rand;
 use std::collections::HashSet;
 
 use color::{random_color, RandomColorConfig};
-use geo::{kdtree, BoundingBox, PointU32};
+use drawing::svg::VectorCanvas;
+use geo::{delaunay, kdtree, BoundingBox, PointF64, PointU32, Rect};
 
 use self::rand::Rng;
 
+/// Whether the Voronoi cells wrap around the image edges, making it
+/// seamlessly tileable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    /// The image edges don't connect, cells near the border are clipped as
+    /// usual.
+    None,
+
+    /// The image edges connect, so a cell can straddle the border and the
+    /// resulting texture tiles seamlessly.
+    Toroidal,
+}
+
+impl Wrap {
+    fn dimensions(self, img_width: u32, img_height: u32) -> Option<(u32, u32)> {
+        match self {
+            Wrap::None => None,
+            Wrap::Toroidal => Some((img_width, img_height)),
+        }
+    }
+}
+
 /// Generate a voronoi diagram where the colors are taken from the gradient
 /// going from color1 to color2.
 pub fn gradient_voronoi(
@@ -17,6 +40,8 @@ pub fn gradient_voronoi(
     color1: image::Rgb<u8>,
     color2: image::Rgb<u8>,
     npoints: usize,
+    metric: kdtree::DistanceMetric,
+    wrap: Wrap,
 ) {
     if npoints == 0 {
         return;
@@ -36,9 +61,13 @@ pub fn gradient_voronoi(
     let db = f64::from(color2[2]) - f64::from(color1[2]);
 
     let img_width = img.width();
+    let img_height = img.height();
+    let wrap_dims = wrap.dimensions(img_width, img_height);
 
     for (x, y, pix) in img.enumerate_pixels_mut() {
-        let (closest_point, _) = points.nearest_neighbor(PointU32::new(x, y)).unwrap();
+        let (closest_point, _) = points
+            .nearest_neighbor_with_metric(PointU32::new(x, y), metric, wrap_dims)
+            .unwrap();
 
         let c = f64::from(closest_point.x) / f64::from(img_width);
         *pix = image::Rgb {
@@ -56,6 +85,8 @@ pub fn random_voronoi<R: Rng>(
     img: &mut image::RgbImage,
     color_config: &mut RandomColorConfig<R>,
     npoints: usize,
+    metric: kdtree::DistanceMetric,
+    wrap: Wrap,
 ) {
     if npoints == 0 {
         return;
@@ -81,8 +112,12 @@ pub fn random_voronoi<R: Rng>(
 
     let points = kdtree::KdTree::from_vector(points);
 
+    let wrap_dims = wrap.dimensions(img.width(), img.height());
+
     for (x, y, pix) in img.enumerate_pixels_mut() {
-        let (_, closest_point_color) = points.nearest_neighbor(PointU32::new(x, y)).unwrap();
+        let (_, closest_point_color) = points
+            .nearest_neighbor_with_metric(PointU32::new(x, y), metric, wrap_dims)
+            .unwrap();
 
         *pix = *closest_point_color;
     }
@@ -92,6 +127,57 @@ pub fn random_voronoi<R: Rng>(
     // }
 }
 
+/// Generate a random Voronoi diagram as a resolution-independent SVG
+/// document, made of true cell polygons rather than a per-pixel nearest
+/// neighbor raster fill. Cells are computed as the [dual of the Delaunay
+/// triangulation](https://en.wikipedia.org/wiki/Voronoi_diagram#Relationship_with_the_Delaunay_triangulation)
+/// of the seed points, clipped to the image rectangle.
+pub fn random_voronoi_svg<R: Rng>(
+    width: u32,
+    height: u32,
+    color_config: &mut RandomColorConfig<R>,
+    npoints: usize,
+) -> VectorCanvas {
+    let mut canvas = VectorCanvas::new(f64::from(width), f64::from(height));
+
+    if npoints == 0 {
+        return canvas;
+    }
+
+    let bounding_box = Rect::new(PointF64::new(0.0, 0.0), f64::from(width), f64::from(height));
+
+    let random_points = generate_distinct_random_points(
+        &mut rand::thread_rng(),
+        npoints,
+        &BoundingBox::from_dimensions(width, height),
+    );
+
+    let points = random_points
+        .into_iter()
+        .map(|pt| PointF64::new(f64::from(pt.x), f64::from(pt.y)))
+        .collect();
+
+    let triangulation = delaunay::triangulate(&bounding_box, points);
+    let cells = delaunay::voronoi_dual(&bounding_box, &triangulation);
+
+    for cell in cells {
+        if cell.len() < 3 {
+            continue;
+        }
+
+        let fill = image::Rgb {
+            data: random_color(color_config).to_rgb(),
+        };
+
+        canvas.polygon(
+            cell,
+            &format!("rgb({},{},{})", fill.data[0], fill.data[1], fill.data[2]),
+        );
+    }
+
+    canvas
+}
+
 fn generate_distinct_random_points<R: Rng>(
     rng: &mut R,
     n: usize,
@@ -3,6 +3,7 @@
 //! reimplementing, so...
 
 pub mod line;
+pub mod svg;
 pub mod triangle;
 
 extern crate image;
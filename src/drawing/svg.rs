@@ -0,0 +1,144 @@
+//! A resolution-independent, SVG based alternative to `Drawer`. Generators
+//! that only need strokes and filled polygons can target a `VectorCanvas`
+//! instead of a raster `image::ImageBuffer` and get a crisp, editable vector
+//! file instead of a bitmap.
+
+use std::fmt::Write as FmtWrite;
+
+use geo::PointF64;
+
+#[derive(Debug, Clone)]
+enum Element {
+    Line {
+        start: PointF64,
+        end: PointF64,
+        stroke: String,
+    },
+    Polygon {
+        points: Vec<PointF64>,
+        fill: String,
+    },
+    Polyline {
+        points: Vec<PointF64>,
+        stroke: String,
+    },
+}
+
+/// Records drawing commands as `PointF64` paths and serializes them to SVG.
+#[derive(Debug, Clone)]
+pub struct VectorCanvas {
+    width: f64,
+    height: f64,
+    elements: Vec<Element>,
+}
+
+fn points_attr(points: &[PointF64]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl VectorCanvas {
+    /// Create a new, empty `VectorCanvas` with the given viewport dimensions.
+    pub fn new(width: f64, height: f64) -> Self {
+        VectorCanvas {
+            width,
+            height,
+            elements: vec![],
+        }
+    }
+
+    /// Record a stroked line from `start` to `end`. `stroke` is any valid SVG
+    /// color (e.g. `"black"` or `"#ff0000"`).
+    pub fn line(&mut self, start: PointF64, end: PointF64, stroke: &str) {
+        self.elements.push(Element::Line {
+            start,
+            end,
+            stroke: stroke.to_string(),
+        });
+    }
+
+    /// Record an open polyline through `points`, stroked with `stroke`.
+    pub fn polyline(&mut self, points: Vec<PointF64>, stroke: &str) {
+        self.elements.push(Element::Polyline {
+            points,
+            stroke: stroke.to_string(),
+        });
+    }
+
+    /// Record a filled, closed polygon through `points`. `fill` is any valid
+    /// SVG color.
+    pub fn polygon(&mut self, points: Vec<PointF64>, fill: &str) {
+        self.elements.push(Element::Polygon { points, fill });
+    }
+
+    /// Serialize the recorded elements as a standalone SVG document.
+    pub fn to_svg(&self) -> String {
+        let mut out = String::new();
+
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            self.width, self.height, self.width, self.height
+        ).unwrap();
+
+        for el in &self.elements {
+            match el {
+                Element::Line { start, end, stroke } => {
+                    writeln!(
+                        out,
+                        r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" />"#,
+                        start.x, start.y, end.x, end.y, stroke
+                    ).unwrap();
+                }
+                Element::Polyline { points, stroke } => {
+                    writeln!(
+                        out,
+                        r#"  <polyline points="{}" fill="none" stroke="{}" />"#,
+                        points_attr(points),
+                        stroke
+                    ).unwrap();
+                }
+                Element::Polygon { points, fill } => {
+                    writeln!(
+                        out,
+                        r#"  <polygon points="{}" fill="{}" />"#,
+                        points_attr(points),
+                        fill
+                    ).unwrap();
+                }
+            }
+        }
+
+        out.push_str("</svg>\n");
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_svg_emits_one_element_per_command() {
+        let mut canvas = VectorCanvas::new(100.0, 100.0);
+        canvas.line(PointF64::new(0.0, 0.0), PointF64::new(10.0, 10.0), "black");
+        canvas.polygon(
+            vec![
+                PointF64::new(0.0, 0.0),
+                PointF64::new(10.0, 0.0),
+                PointF64::new(5.0, 10.0),
+            ],
+            "red",
+        );
+
+        let svg = canvas.to_svg();
+
+        assert!(svg.contains("<line"));
+        assert!(svg.contains("<polygon"));
+        assert!(svg.starts_with("<svg"));
+    }
+}
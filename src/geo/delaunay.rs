@@ -0,0 +1,152 @@
+//! [Delaunay triangulation](https://en.wikipedia.org/wiki/Delaunay_triangulation)
+//! built with the [Bowyer-Watson
+//! algorithm](https://en.wikipedia.org/wiki/Bowyer%E2%80%93Watson_algorithm),
+//! reusing `Triangle::squared_circumcircle` to test whether a point violates
+//! the Delaunay condition of an existing triangle.
+
+use geo::{PointF64, Rect, Triangle};
+
+/// Triangulate the given set of points within `bounding_box`. Blows up if
+/// degenerate (completely flat) triangles are formed.
+pub fn triangulate(bounding_box: &Rect<f64>, points: Vec<PointF64>) -> Vec<Triangle<f64>> {
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    let mut points = points.into_iter();
+    let super_triangles = super_triangles(bounding_box, points.next().unwrap());
+
+    points.fold(super_triangles, |triangles, point| {
+        add_point(triangles, point)
+    })
+}
+
+/// The original Bowyer-Watson algorithm starts from a single "super
+/// triangle" that encloses every input point. Since we work in a finite
+/// space it's simpler (and numerically nicer) to instead pick a random point
+/// and split the bounding box into 4 triangles that always cover the whole
+/// space.
+fn super_triangles(bounding_box: &Rect<f64>, first_point: PointF64) -> Vec<Triangle<f64>> {
+    let bounds = bounding_box.points();
+
+    (0..bounds.len())
+        .map(|i| {
+            Triangle::new(
+                bounds[i].clone(),
+                bounds[(i + 1) % bounds.len()].clone(),
+                first_point.clone(),
+            )
+        })
+        .collect()
+}
+
+fn add_point(triangles: Vec<Triangle<f64>>, point: PointF64) -> Vec<Triangle<f64>> {
+    let mut edges = vec![];
+    let mut new_triangles = Vec::with_capacity(triangles.len());
+
+    for triangle in triangles {
+        let (circumcenter, radius) = triangle.squared_circumcircle().unwrap();
+
+        if circumcenter.squared_dist::<f64>(&point) <= radius {
+            edges.push((triangle.points[0].clone(), triangle.points[1].clone()));
+            edges.push((triangle.points[1].clone(), triangle.points[2].clone()));
+            edges.push((triangle.points[2].clone(), triangle.points[0].clone()));
+        } else {
+            new_triangles.push(triangle);
+        }
+    }
+
+    let edges = dedup_edges(edges);
+
+    new_triangles.extend(
+        edges
+            .into_iter()
+            .map(|(pt0, pt1)| Triangle::new(pt0, pt1, point.clone())),
+    );
+
+    new_triangles
+}
+
+fn dedup_edges(edges: Vec<(PointF64, PointF64)>) -> Vec<(PointF64, PointF64)> {
+    // an edge that's shared by two removed triangles is an interior edge and
+    // must not be re-triangulated, only the boundary of the polygonal hole
+    // should be. We cannot use a hashmap/hashset because `f64` doesn't
+    // implement `Hash`, so just do the dumb O(n^2) thing (same trick already
+    // used by the legacy `::delaunay::dedup_edges`).
+    let mut out = vec![];
+
+    for i in 0..edges.len() {
+        let mut count = 0;
+
+        for j in 0..edges.len() {
+            let (start, end) = &edges[j];
+            if edges[i] == (start.clone(), end.clone()) || edges[i] == (end.clone(), start.clone())
+            {
+                count += 1;
+            }
+        }
+
+        if count == 1 {
+            out.push(edges[i].clone());
+        }
+    }
+
+    out
+}
+
+/// Return the Voronoi diagram that's dual to `triangulation`, as a list of
+/// cell polygons. Each triangle's circumcenter becomes a Voronoi vertex;
+/// triangles that share an edge have their circumcenters joined by a Voronoi
+/// edge. Cells that would extend past `bounding_box` are left unclosed (the
+/// caller may want to clip them further).
+pub fn voronoi_dual(
+    bounding_box: &Rect<f64>,
+    triangulation: &[Triangle<f64>],
+) -> Vec<Vec<PointF64>> {
+    let circumcenters: Vec<PointF64> = triangulation
+        .iter()
+        .filter_map(Triangle::circumcenter)
+        .collect();
+
+    // group, for every input vertex, the circumcenters of the triangles that
+    // have it as a corner: those circumcenters, in order, are exactly the
+    // boundary of that vertex's Voronoi cell.
+    let mut vertices = vec![];
+    for triangle in triangulation {
+        for p in &triangle.points {
+            if !vertices.contains(p) {
+                vertices.push(p.clone());
+            }
+        }
+    }
+
+    vertices
+        .into_iter()
+        .filter(|v| bounding_box.contains(v))
+        .map(|vertex| {
+            let mut cell: Vec<PointF64> = triangulation
+                .iter()
+                .zip(circumcenters.iter())
+                .filter(|(triangle, _)| triangle.points.contains(&vertex))
+                .map(|(_, c)| c.clone())
+                .collect();
+
+            // order the cell's vertices around their centroid so they form a
+            // simple (non self-intersecting) polygon.
+            let centroid = cell.iter().fold(PointF64::new(0.0, 0.0), |acc, p| {
+                PointF64::new(acc.x + p.x, acc.y + p.y)
+            });
+            let n = cell.len().max(1) as f64;
+            let centroid = PointF64::new(centroid.x / n, centroid.y / n);
+
+            cell.sort_by(|a, b| {
+                let angle_a = (a.y - centroid.y).atan2(a.x - centroid.x);
+                let angle_b = (b.y - centroid.y).atan2(b.x - centroid.x);
+
+                angle_a.partial_cmp(&angle_b).unwrap()
+            });
+
+            cell
+        })
+        .collect()
+}
@@ -1,5 +1,7 @@
 //! A simple(and probably inefficient) implementation of a [K-d
-//! Tree](https://en.wikipedia.org/wiki/K-d_tree). Only 2D as of now.
+//! Tree](https://en.wikipedia.org/wiki/K-d_tree), generalized to any number
+//! of dimensions via a `Coordinates<DIM>` trait and a const generic `DIM`
+//! instead of a fixed 2D `Axis` enum.
 
 extern crate num;
 
@@ -9,69 +11,86 @@ use std::collections::{BinaryHeap, VecDeque};
 use geo::{Point, Rect};
 use utils::{ksmallest_by_key, split_element_at, OrdWrapper};
 
-/// The axis used to split the space at a given point.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Axis {
-    /// X axis.
-    X,
+/// A value that can be viewed as `DIM` orderable scalar coordinates, letting
+/// `KdTree` cycle its splitting axis through `depth % DIM` instead of a fixed
+/// 2D `Axis` enum. `Point` implements this for `DIM == 2`, so the existing 2D
+/// API (e.g. indexing pixel positions) is unchanged; a 3D RGB cube or a
+/// higher-dimensional feature vector just needs its own impl.
+pub trait Coordinates<const DIM: usize> {
+    /// The scalar type of a single coordinate.
+    type Value;
 
-    /// Y axis.
-    Y,
+    /// This value's coordinates, one per axis.
+    fn coords(&self) -> [Self::Value; DIM];
 }
 
-/// Trait that allows to extract the axis value for a given axis from an entity
-/// contained in the KdTree.
-pub trait AxisValue {
-    /// The value that will be returned by axis_value.
-    type Value;
+impl<T> Coordinates<2> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Value = T;
 
-    /// Return the value for the given axis.
-    fn axis_value(&self, axis: Axis) -> &Self::Value;
+    fn coords(&self) -> [T; 2] {
+        [self.x, self.y]
+    }
 }
 
-/// A [K-d Tree](https://en.wikipedia.org/wiki/K-d_tree).
+/// A [K-d Tree](https://en.wikipedia.org/wiki/K-d_tree) over `DIM`-dimensional
+/// points, 2D (e.g. `geo::Point`) by default.
 #[derive(Debug, PartialEq)]
-pub struct KdTree<T, V> {
-    root: Option<Node<T, V>>,
+pub struct KdTree<P, V, const DIM: usize = 2> {
+    root: Option<Node<P, V, DIM>>,
     length: usize,
+
+    // how many of this tree's nodes are tombstones, left behind by `remove`
+    // / `remove_if` rather than restructuring the tree.
+    deleted: usize,
+
+    // `remove`/`remove_if` call `rebuild` once `deleted` exceeds this
+    // fraction of `length`, so churn doesn't leak memory or degrade queries
+    // forever. See `set_auto_rebuild_fraction`.
+    auto_rebuild_fraction: f64,
 }
 
 #[derive(Debug, PartialEq)]
-struct Node<T, V> {
-    axis: Axis,
-    median: Point<T>,
+struct Node<P, V, const DIM: usize> {
+    axis: usize,
+    point: P,
     value: V,
+    deleted: bool,
 
-    left: Option<Box<Node<T, V>>>,
-    right: Option<Box<Node<T, V>>>,
+    left: Option<Box<Node<P, V, DIM>>>,
+    right: Option<Box<Node<P, V, DIM>>>,
 }
 
 /// Simple trait that allow to support range queries for multiple types of
-/// shapes.
-pub trait Range<T> {
+/// shapes, over `DIM`-dimensional points.
+pub trait Range<P, const DIM: usize> {
     /// Return whether the given value is contained in the range.
-    fn contains(&self, v: &T) -> bool;
+    fn contains(&self, v: &P) -> bool;
 
-    /// The type of the axis value.
-    type AxisValue;
+    /// The type of a single coordinate.
+    type Value;
 
-    /// Return the range that the axis values are in the given axis.
-    fn axis_value_range(&self, axis: Axis) -> (Self::AxisValue, Self::AxisValue);
+    /// Return the range that the given axis' coordinate is in.
+    fn axis_value_range(&self, axis: usize) -> (Self::Value, Self::Value);
 }
 
-impl<T, V> Default for KdTree<T, V> {
+impl<P, V, const DIM: usize> Default for KdTree<P, V, DIM> {
     fn default() -> Self {
         KdTree {
             root: None,
             length: 0,
+            deleted: 0,
+            auto_rebuild_fraction: 0.5,
         }
     }
 }
 
-impl<T, V> KdTree<T, V>
+impl<P, V, const DIM: usize> KdTree<P, V, DIM>
 where
-    T: Copy + Ord,
-    Point<T>: AxisValue<Value = T>,
+    P: Coordinates<DIM> + Copy,
+    P::Value: Copy + Ord,
 {
     /// Create a new empty KdTree.
     pub fn new() -> Self {
@@ -83,7 +102,8 @@ where
         self.len() == 0
     }
 
-    /// Return the length of this kdtree.
+    /// Return the length of this kdtree. Note that this counts tombstoned
+    /// nodes left behind by `remove`/`remove_if` until the next `rebuild`.
     pub fn len(&self) -> usize {
         self.length
     }
@@ -92,11 +112,11 @@ where
     /// over to add when the set of points doesn't change because it creates a
     /// tree that is often more balanced. The construction is a bit slower
     /// though.
-    pub fn from_vector(points: Vec<(Point<T>, V)>) -> Self {
+    pub fn from_vector(points: Vec<(P, V)>) -> Self {
         let mut kdtree = KdTree::default();
 
         let mut ranges = VecDeque::new();
-        ranges.push_back((points, Axis::X));
+        ranges.push_back((points, 0));
 
         while let Some((mut points, axis)) = ranges.pop_front() {
             if points.is_empty() {
@@ -104,10 +124,12 @@ where
             }
 
             let mid = points.len() / 2;
+            let next_axis = (axis + 1) % DIM;
 
             // this is actually partitioning data at the median
             ksmallest_by_key(&mut points, mid, |(pt, _val)| {
-                (*pt.axis_value(axis), *pt.axis_value(axis.next()))
+                let coords = pt.coords();
+                (coords[axis], coords[next_axis])
             }).unwrap();
 
             let (left, elem, right) = split_element_at(points, mid);
@@ -115,8 +137,8 @@ where
             let (new_point, new_val) = elem.unwrap();
             kdtree.add(new_point, new_val);
 
-            ranges.push_back((left, axis.next()));
-            ranges.push_back((right, axis.next()));
+            ranges.push_back((left, next_axis));
+            ranges.push_back((right, next_axis));
         }
 
         kdtree
@@ -124,9 +146,9 @@ where
 
     /// Add a point to this KdTree. Note that this could unbalance the tree,
     /// prefer from_vector if the set of points is not dynamic.
-    pub fn add(&mut self, point: Point<T>, value: V) -> Option<V> {
+    pub fn add(&mut self, point: P, value: V) -> Option<V> {
         if self.root.is_none() {
-            self.root = Some(Node::new(point, value, Axis::X));
+            self.root = Some(Node::new(point, value, 0));
             self.length = 1;
 
             return None;
@@ -134,25 +156,87 @@ where
 
         let root_node = self.root.as_mut().unwrap();
 
-        let old_value = root_node.add(point, value);
+        let (old_value, revived) = root_node.add(point, value);
         if old_value.is_none() {
             self.length += 1;
+        } else if revived {
+            self.deleted -= 1;
         }
 
         old_value
     }
 
+    /// Remove the point equal to `point`, if present, by marking its node as
+    /// a tombstone rather than restructuring the tree: the split invariant
+    /// stays intact and removal is `O(log n)`. Tombstoned nodes are skipped
+    /// by `iter`, `in_range_iter` and `nearest_neighbors`, but still
+    /// traversed to route through the tree. Automatically `rebuild`s once
+    /// `deleted` exceeds `auto_rebuild_fraction` of `length` (see
+    /// `set_auto_rebuild_fraction`). Returns whether a point was removed.
+    pub fn remove(&mut self, point: &P) -> bool {
+        let removed = self.root.as_mut().map_or(false, |root| root.remove(point));
+
+        if removed {
+            self.deleted += 1;
+            self.maybe_auto_rebuild();
+        }
+
+        removed
+    }
+
+    /// Remove every point for which `predicate` returns `true`, via the same
+    /// tombstoning as `remove`. Returns the number of points removed.
+    pub fn remove_if<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(&P, &V) -> bool,
+    {
+        let removed = self
+            .root
+            .as_mut()
+            .map_or(0, |root| root.remove_if(&mut predicate));
+
+        if removed > 0 {
+            self.deleted += removed;
+            self.maybe_auto_rebuild();
+        }
+
+        removed
+    }
+
+    /// Set the fraction of tombstoned nodes (relative to `len()`) past which
+    /// `remove`/`remove_if` automatically `rebuild` the tree. Defaults to
+    /// `0.5`.
+    pub fn set_auto_rebuild_fraction(&mut self, fraction: f64) {
+        self.auto_rebuild_fraction = fraction;
+    }
+
+    fn maybe_auto_rebuild(&mut self) {
+        if self.length > 0 && self.deleted as f64 / self.length as f64 > self.auto_rebuild_fraction {
+            self.rebuild();
+        }
+    }
+
+    /// Collect the live (non-tombstoned) points and reconstruct a balanced
+    /// tree via `from_vector`, reclaiming the space tombstones were holding
+    /// onto. `remove`/`remove_if` already trigger this automatically once
+    /// `deleted` crosses `auto_rebuild_fraction`, so manual calls are only
+    /// needed to force it sooner.
+    pub fn rebuild(&mut self) {
+        let live = std::mem::replace(self, Self::default()).into_vec();
+        *self = Self::from_vector(live);
+    }
+
     /// Return an iterator over all the elements of the tree.
-    pub fn iter<'a>(self: &'a Self) -> KdTreeIter<'a, T, V> {
+    pub fn iter<'a>(self: &'a Self) -> KdTreeIter<'a, P, V, DIM> {
         KdTreeIter {
             nodes: self.root.as_ref().map(|r| vec![r]).unwrap_or_else(Vec::new),
         }
     }
 
     /// Return all the points that are in the given range.
-    pub fn in_range_iter<'s, 'r, R>(self: &'s Self, range: &'r R) -> InRangeIter<'s, 'r, T, V, R>
+    pub fn in_range_iter<'s, 'r, R>(self: &'s Self, range: &'r R) -> InRangeIter<'s, 'r, P, V, DIM, R>
     where
-        R: Range<Point<T>, AxisValue = T>,
+        R: Range<P, DIM, Value = P::Value>,
     {
         InRangeIter {
             nodes: self.root.as_ref().map(|r| vec![r]).unwrap_or_else(Vec::new),
@@ -160,22 +244,65 @@ where
         }
     }
 
-    /// Return the nearest neighbor to the given point.
-    pub fn nearest_neighbor(&self, point: Point<T>) -> Option<(&Point<T>, &V)>
+    /// Return the nearest neighbor to the given point, assuming squared
+    /// euclidean distance. See `nearest_neighbor_with_metric` to use a
+    /// different `DistanceMetric`.
+    pub fn nearest_neighbor(&self, point: P) -> Option<(&P, &V)>
     where
-        T: num::Num + From<u8> + ::std::fmt::Debug,
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
         V: ::std::fmt::Debug,
-        i64: From<T>,
+        i64: From<P::Value>,
     {
         self.nearest_neighbors(point, 1).into_iter().next()
     }
 
-    /// Return, at most, the k nearest neighbors to the given point.
-    pub fn nearest_neighbors(&self, point: Point<T>, k: usize) -> Vec<(&Point<T>, &V)>
+    /// Return, at most, the k nearest neighbors to the given point, assuming
+    /// squared euclidean distance. See `nearest_neighbors_with_metric` to use
+    /// a different `DistanceMetric`.
+    pub fn nearest_neighbors(&self, point: P, k: usize) -> Vec<(&P, &V)>
     where
-        T: num::Num + From<u8> + ::std::fmt::Debug,
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
         V: ::std::fmt::Debug,
-        i64: From<T>,
+        i64: From<P::Value>,
+    {
+        self.nearest_neighbors_with_metric(point, k, DistanceMetric::Euclidean, None)
+    }
+
+    /// Return the nearest neighbor to the given point, using `metric` to
+    /// compute distances. `wrap` makes the search toroidal on the first two
+    /// axes: passing the `(width, height)` of the space makes the image edges
+    /// connect, so that a distance along those axes is the shorter of `|d|`
+    /// and `size - |d|`. Ignored on axes beyond the second.
+    pub fn nearest_neighbor_with_metric(
+        &self,
+        point: P,
+        metric: DistanceMetric,
+        wrap: Option<(P::Value, P::Value)>,
+    ) -> Option<(&P, &V)>
+    where
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
+        V: ::std::fmt::Debug,
+        i64: From<P::Value>,
+    {
+        self.nearest_neighbors_with_metric(point, 1, metric, wrap)
+            .into_iter()
+            .next()
+    }
+
+    /// Return, at most, the k nearest neighbors to the given point, using
+    /// `metric` to compute distances and optionally wrapping around the
+    /// `(width, height)` given in `wrap` (see `nearest_neighbor_with_metric`).
+    pub fn nearest_neighbors_with_metric(
+        &self,
+        point: P,
+        k: usize,
+        metric: DistanceMetric,
+        wrap: Option<(P::Value, P::Value)>,
+    ) -> Vec<(&P, &V)>
+    where
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
+        V: ::std::fmt::Debug,
+        i64: From<P::Value>,
     {
         if self.root.is_none() || k == 0 {
             return vec![];
@@ -188,13 +315,15 @@ where
         let mut min_dist = i64::max_value();
 
         while let Some(node) = nodes.pop() {
-            let node_dist = node.median.squared_dist(&point);
-
+            let node_dist = metric.distance(&node.point, &point, wrap);
             min_dist = min_dist.min(node_dist);
-            neighbors.push(OrdWrapper::new(node, node_dist));
 
-            if neighbors.len() > k {
-                neighbors.pop();
+            if !node.deleted {
+                neighbors.push(OrdWrapper::new(node, node_dist));
+
+                if neighbors.len() > k {
+                    neighbors.pop();
+                }
             }
 
             // since nodes is a stack, push first the data that must be computed
@@ -210,11 +339,14 @@ where
                 // check if there could be intersection on the wrong side of the
                 // plane. This is done by checking whether the candidate point's
                 // axis is still reachable within the current minimum distance.
-                let split_plane = i64::from(*node.median.axis_value(node.axis));
-                let plane_dist = i64::from(*point.axis_value(node.axis)) - split_plane;
-                let plane_dist2 = plane_dist * plane_dist;
+                let axis = node.axis;
+                let split_plane = node.point.coords()[axis];
+                let point_value = point.coords()[axis];
+                let axis_size = wrap_axis_size(wrap, axis);
+
+                let plane_dist = metric.axis_distance(point_value, split_plane, axis_size);
 
-                if plane_dist2 <= min_dist {
+                if plane_dist <= min_dist {
                     nodes.push(candidate_node);
                 }
             }
@@ -229,105 +361,497 @@ where
             .into_iter()
             .map(|ow| {
                 let (node, _) = ow.into();
-                (&node.median, &node.value)
+                (&node.point, &node.value)
             })
             .collect()
     }
+
+    /// Like `nearest_neighbors`, but allowed to return neighbors up to a
+    /// factor of `(1 + epsilon)` farther away than the true nearest ones, and
+    /// to give up early after visiting `limit` nodes (if given), returning
+    /// whatever is in the heap at that point. Useful for large trees where an
+    /// exact answer isn't worth the extra nodes it costs to rule out every
+    /// wrong-side subtree. See `nearest_neighbors_approx_with_metric` to use a
+    /// different `DistanceMetric`.
+    pub fn nearest_neighbors_approx(
+        &self,
+        point: P,
+        k: usize,
+        epsilon: f64,
+        limit: Option<usize>,
+    ) -> Vec<(&P, &V)>
+    where
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
+        V: ::std::fmt::Debug,
+        i64: From<P::Value>,
+    {
+        self.nearest_neighbors_approx_with_metric(point, k, DistanceMetric::Euclidean, epsilon, limit)
+    }
+
+    /// Return, at most, the k approximate nearest neighbors to the given
+    /// point, using `metric` to compute distances (see
+    /// `nearest_neighbors_approx` and `nearest_neighbors_with_metric`).
+    pub fn nearest_neighbors_approx_with_metric(
+        &self,
+        point: P,
+        k: usize,
+        metric: DistanceMetric,
+        epsilon: f64,
+        limit: Option<usize>,
+    ) -> Vec<(&P, &V)>
+    where
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
+        V: ::std::fmt::Debug,
+        i64: From<P::Value>,
+    {
+        if self.root.is_none() || k == 0 {
+            return vec![];
+        }
+
+        let root_node = self.root.as_ref().unwrap();
+        let mut nodes = vec![root_node];
+
+        let mut neighbors = BinaryHeap::new();
+        let mut min_dist = i64::max_value();
+        let mut visited = 0;
+
+        while let Some(node) = nodes.pop() {
+            if limit.map_or(false, |limit| visited >= limit) {
+                break;
+            }
+            visited += 1;
+
+            let node_dist = metric.distance(&node.point, &point, None);
+            min_dist = min_dist.min(node_dist);
+
+            if !node.deleted {
+                neighbors.push(OrdWrapper::new(node, node_dist));
+
+                if neighbors.len() > k {
+                    neighbors.pop();
+                }
+            }
+
+            let (next, candidate) = match node.cmp_to_point_value(point) {
+                Ordering::Less | Ordering::Equal => (&node.left, &node.right),
+                Ordering::Greater => (&node.right, &node.left),
+            };
+
+            if let Some(candidate_node) = candidate {
+                let axis = node.axis;
+                let split_plane = node.point.coords()[axis];
+                let point_value = point.coords()[axis];
+
+                // tighten the usual prune bound by `(1 + epsilon)`: fewer
+                // wrong-side subtrees get visited, at the cost of the result
+                // only being guaranteed within that factor of the true
+                // nearest neighbor.
+                let plane_dist = metric.axis_distance(point_value, split_plane, None);
+                let plane_dist = metric.tighten(plane_dist, epsilon);
+
+                if plane_dist <= min_dist {
+                    nodes.push(candidate_node);
+                }
+            }
+
+            if let Some(next_node) = next {
+                nodes.push(next_node);
+            }
+        }
+
+        neighbors
+            .into_sorted_vec()
+            .into_iter()
+            .map(|ow| {
+                let (node, _) = ow.into();
+                (&node.point, &node.value)
+            })
+            .collect()
+    }
+
+    /// Consume this tree and return all of its points, discarding the tree
+    /// structure. Used by `KdForest` to rebuild a bigger balanced tree out of
+    /// two equally-sized ones.
+    pub fn into_vec(self) -> Vec<(P, V)> {
+        let mut out = Vec::with_capacity(self.length);
+
+        if let Some(root) = self.root {
+            root.into_vec(&mut out);
+        }
+
+        out
+    }
 }
 
-impl<T, V> Node<T, V>
+/// The `wrap` tuple only carries a size for the first two axes (it mirrors an
+/// image's `(width, height)`), so anything beyond that never wraps.
+fn wrap_axis_size<T: Copy>(wrap: Option<(T, T)>, axis: usize) -> Option<T> {
+    match axis {
+        0 => wrap.map(|(w, _)| w),
+        1 => wrap.map(|(_, h)| h),
+        _ => None,
+    }
+}
+
+/// Distance metric used by nearest-neighbor queries. Different metrics yield
+/// differently shaped Voronoi cells when used to assign each point of a space
+/// to its nearest seed.
+///
+/// This is an enum rather than a `distance`/`axis_distance` trait because the
+/// metric is a runtime choice (a CLI flag, same as `ColorOrder` or
+/// `QuantizeMethod` elsewhere in this crate), not something picked at compile
+/// time; `nearest_neighbors` (Euclidean) and `nearest_neighbors_with_metric`
+/// (any variant) cover the "pluggable metric" need without needing callers to
+/// monomorphize over a generic parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// Squared euclidean distance, i.e. `dx^2 + dy^2`. Yields circular cells.
+    Euclidean,
+
+    /// Manhattan/L1 distance, i.e. `|dx| + |dy|`. Yields diamond-shaped cells.
+    Manhattan,
+
+    /// Chebyshev/L∞ distance, i.e. `max(|dx|, |dy|)`. Yields square cells.
+    Chebyshev,
+}
+
+impl DistanceMetric {
+    /// Distance between two points according to this metric, optionally
+    /// wrapping the first two axes around a `(width, height)` toroidal space.
+    fn distance<P, const DIM: usize>(self, a: &P, b: &P, wrap: Option<(P::Value, P::Value)>) -> i64
+    where
+        P: Coordinates<DIM>,
+        P::Value: Copy,
+        i64: From<P::Value>,
+    {
+        let (ac, bc) = (a.coords(), b.coords());
+
+        let deltas = (0..DIM)
+            .map(|axis| Self::axis_delta(ac[axis], bc[axis], wrap_axis_size(wrap, axis)));
+
+        match self {
+            DistanceMetric::Euclidean => deltas.map(|d| d * d).sum(),
+            DistanceMetric::Manhattan => deltas.map(|d| d.abs()).sum(),
+            DistanceMetric::Chebyshev => deltas.fold(0, |acc, d| acc.max(d.abs())),
+        }
+    }
+
+    /// A lower bound, comparable to values returned by `distance`, on the
+    /// distance from `point_value` to anything across the splitting plane at
+    /// `split_value` on a single axis. This is what makes kd-tree pruning
+    /// correct for each metric: for `Euclidean` it's the squared delta, for
+    /// `Manhattan` and `Chebyshev` the plain delta already is a valid lower
+    /// bound on the full distance.
+    fn axis_distance<T>(self, point_value: T, split_value: T, wrap: Option<T>) -> i64
+    where
+        T: Copy,
+        i64: From<T>,
+    {
+        let delta = Self::axis_delta(point_value, split_value, wrap);
+
+        match self {
+            DistanceMetric::Euclidean => delta * delta,
+            DistanceMetric::Manhattan | DistanceMetric::Chebyshev => delta.abs(),
+        }
+    }
+
+    fn axis_delta<T>(a: T, b: T, wrap: Option<T>) -> i64
+    where
+        T: Copy,
+        i64: From<T>,
+    {
+        let d = i64::from(a) - i64::from(b);
+
+        match wrap {
+            Some(size) => {
+                let size = i64::from(size);
+                d.abs().min(size - d.abs())
+            }
+            None => d,
+        }
+    }
+
+    /// Scale an `axis_distance` bound up by a `(1 + epsilon)` factor, used by
+    /// `nearest_neighbors_approx` to prune more aggressively than an exact
+    /// search would allow. `Euclidean`'s bound is already squared, so the
+    /// factor itself must be squared to stay comparable.
+    fn tighten(self, axis_dist: i64, epsilon: f64) -> i64 {
+        let factor = 1.0 + epsilon;
+        let factor = match self {
+            DistanceMetric::Euclidean => factor * factor,
+            DistanceMetric::Manhattan | DistanceMetric::Chebyshev => factor,
+        };
+
+        (axis_dist as f64 * factor) as i64
+    }
+}
+
+impl<P, V, const DIM: usize> Node<P, V, DIM>
 where
-    T: Copy + Ord,
-    Point<T>: AxisValue<Value = T>,
+    P: Coordinates<DIM> + Copy,
+    P::Value: Copy + Ord,
 {
-    fn new(pt: Point<T>, value: V, axis: Axis) -> Self {
+    fn new(point: P, value: V, axis: usize) -> Self {
         Node {
-            median: pt,
+            point,
             axis,
             value,
+            deleted: false,
             left: None,
             right: None,
         }
     }
 
-    fn add(&mut self, point: Point<T>, value: V) -> Option<V> {
-        if point == self.median {
+    /// Insert `point`/`value`, returning the value it replaced (if any) and,
+    /// when it replaced a tombstoned node, whether that node was revived.
+    fn add(&mut self, point: P, value: V) -> (Option<V>, bool) {
+        if point.coords() == self.point.coords() {
+            let was_deleted = self.deleted;
+            self.deleted = false;
             let old_value = ::std::mem::replace(&mut self.value, value);
-            return Some(old_value);
+            return (Some(old_value), was_deleted);
         }
 
+        let next_axis = (self.axis + 1) % DIM;
+
         let child = match self.cmp_to_point_value(point) {
             Ordering::Less | Ordering::Equal => &mut self.left,
             Ordering::Greater => &mut self.right,
         };
 
         if child.is_none() {
-            *child = Some(Box::new(Node::new(point, value, self.axis.next())));
-            return None;
+            *child = Some(Box::new(Node::new(point, value, next_axis)));
+            return (None, false);
         }
 
         child.as_mut().unwrap().add(point, value)
     }
 
-    /// Return whether the given point lies before, in the same place or after
-    /// this point.
-    fn cmp_to_point_value(&self, point: Point<T>) -> Ordering {
-        let cur_axis_value = self.median.axis_value(self.axis);
-        let point_axis_value = point.axis_value(self.axis);
+    /// Tombstone the node holding `point`, if any, returning whether a live
+    /// node was found and marked deleted. The node is kept in place (and kept
+    /// routing searches through it) until the next `rebuild`.
+    fn remove(&mut self, point: &P) -> bool {
+        if point.coords() == self.point.coords() {
+            if self.deleted {
+                return false;
+            }
+            self.deleted = true;
+            return true;
+        }
 
-        point_axis_value.cmp(&cur_axis_value)
+        let child = match self.cmp_to_point_value(*point) {
+            Ordering::Less | Ordering::Equal => &mut self.left,
+            Ordering::Greater => &mut self.right,
+        };
+
+        child.as_mut().map_or(false, |child| child.remove(point))
     }
-}
 
-impl Axis {
-    /// Return the next axis, going back to the beginning if necessary.
-    pub fn next(self) -> Self {
-        match self {
-            Axis::X => Axis::Y,
-            Axis::Y => Axis::X,
+    /// Tombstone every live node matching `predicate`, returning how many
+    /// were newly marked deleted.
+    fn remove_if<F>(&mut self, predicate: &mut F) -> usize
+    where
+        F: FnMut(&P, &V) -> bool,
+    {
+        let mut removed = 0;
+
+        if !self.deleted && predicate(&self.point, &self.value) {
+            self.deleted = true;
+            removed += 1;
         }
+
+        if let Some(left) = self.left.as_mut() {
+            removed += left.remove_if(predicate);
+        }
+
+        if let Some(right) = self.right.as_mut() {
+            removed += right.remove_if(predicate);
+        }
+
+        removed
     }
-}
 
-impl<T> AxisValue for Point<T> {
-    type Value = T;
+    fn into_vec(self, out: &mut Vec<(P, V)>) {
+        if !self.deleted {
+            out.push((self.point, self.value));
+        }
 
-    fn axis_value(&self, axis: Axis) -> &Self::Value {
-        match axis {
-            Axis::X => &self.x,
-            Axis::Y => &self.y,
+        if let Some(left) = self.left {
+            left.into_vec(out);
+        }
+
+        if let Some(right) = self.right {
+            right.into_vec(out);
         }
     }
+
+    /// Return whether the given point lies before, in the same place or after
+    /// this point, on this node's axis.
+    fn cmp_to_point_value(&self, point: P) -> Ordering {
+        let cur_axis_value = self.point.coords()[self.axis];
+        let point_axis_value = point.coords()[self.axis];
+
+        point_axis_value.cmp(&cur_axis_value)
+    }
 }
 
-impl<T> Range<Point<T>> for Rect<T>
+impl<T> Range<Point<T>, 2> for Rect<T>
 where
     T: num::Num + From<u8> + Copy + PartialOrd,
 {
-    type AxisValue = T;
+    type Value = T;
 
     fn contains(&self, point: &Point<T>) -> bool {
         Rect::contains(self, point)
     }
 
-    fn axis_value_range(&self, axis: Axis) -> (Self::AxisValue, Self::AxisValue) {
+    fn axis_value_range(&self, axis: usize) -> (Self::Value, Self::Value) {
         match axis {
-            Axis::X => (self.origin.x, self.origin.x + self.width),
-            Axis::Y => (self.origin.y, self.origin.y + self.height),
+            0 => (self.origin.x, self.origin.x + self.width),
+            1 => (self.origin.y, self.origin.y + self.height),
+            _ => unreachable!("Rect only supports 2 dimensions"),
+        }
+    }
+}
+
+/// A dynamic companion to `KdTree` that supports cheap incremental
+/// insertion while keeping queries close to balanced. `KdTree::add` warns
+/// that repeated insertion can unbalance the tree, and `from_vector` only
+/// helps for a fixed point set; `KdForest` instead uses the
+/// log-structured/binary-counter technique: it maintains a set of fully
+/// balanced `KdTree`s whose sizes are distinct powers of two (at most one per
+/// size, mirroring the set bits of a binary counter). Inserting wraps the new
+/// point in a size-1 tree, then, while two trees of equal size exist, merges
+/// them into one balanced tree of double the size via `from_vector`. This
+/// keeps the forest at `O(log n)` trees, makes insertion amortized
+/// `O(log^2 n)`, and keeps queries from ever degrading to a linked list the
+/// way repeated plain `add` calls can.
+pub struct KdForest<P, V, const DIM: usize = 2> {
+    // trees[i] is either empty or holds exactly 2^i points, same as the bits
+    // of a binary counter counting `length`.
+    trees: Vec<Option<KdTree<P, V, DIM>>>,
+    length: usize,
+}
+
+impl<P, V, const DIM: usize> Default for KdForest<P, V, DIM> {
+    fn default() -> Self {
+        KdForest {
+            trees: vec![],
+            length: 0,
+        }
+    }
+}
+
+impl<P, V, const DIM: usize> KdForest<P, V, DIM>
+where
+    P: Coordinates<DIM> + Copy,
+    P::Value: Copy + Ord,
+{
+    /// Create a new empty KdForest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if this forest is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of points in this forest.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Insert a point, amortized `O(log^2 n)`.
+    pub fn insert(&mut self, point: P, value: V) {
+        let mut carry = vec![(point, value)];
+        let mut i = 0;
+
+        loop {
+            if i == self.trees.len() {
+                self.trees.push(None);
+            }
+
+            match self.trees[i].take() {
+                None => {
+                    self.trees[i] = Some(KdTree::from_vector(carry));
+                    break;
+                }
+                Some(tree) => {
+                    carry.extend(tree.into_vec());
+                    i += 1;
+                }
+            }
         }
+
+        self.length += 1;
+    }
+
+    /// Return, at most, the k nearest neighbors to the given point, assuming
+    /// squared euclidean distance. See `nearest_neighbors_with_metric` to use
+    /// a different `DistanceMetric`.
+    pub fn nearest_neighbors(&self, point: P, k: usize) -> Vec<(&P, &V)>
+    where
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
+        V: ::std::fmt::Debug,
+        i64: From<P::Value>,
+    {
+        self.nearest_neighbors_with_metric(point, k, DistanceMetric::Euclidean, None)
+    }
+
+    /// Return, at most, the k nearest neighbors to the given point across
+    /// every tree in the forest, using `metric` to compute distances (see
+    /// `KdTree::nearest_neighbors_with_metric`). Each tree's own top-k is
+    /// merged into a single global top-k, since the true answer can only be
+    /// made up of points that are in at least one tree's own top-k.
+    pub fn nearest_neighbors_with_metric(
+        &self,
+        point: P,
+        k: usize,
+        metric: DistanceMetric,
+        wrap: Option<(P::Value, P::Value)>,
+    ) -> Vec<(&P, &V)>
+    where
+        P::Value: num::Num + From<u8> + ::std::fmt::Debug,
+        V: ::std::fmt::Debug,
+        i64: From<P::Value>,
+    {
+        let mut candidates = self
+            .trees
+            .iter()
+            .filter_map(Option::as_ref)
+            .flat_map(|tree| tree.nearest_neighbors_with_metric(point, k, metric, wrap))
+            .collect::<Vec<_>>();
+
+        candidates.sort_by_key(|(p, _)| metric.distance(p, &point, wrap));
+        candidates.truncate(k);
+
+        candidates
+    }
+
+    /// Return all the points, across every tree in the forest, that are in
+    /// the given range.
+    pub fn in_range_iter<'s, R>(&'s self, range: &'s R) -> impl Iterator<Item = (&'s P, &'s V)> + 's
+    where
+        R: Range<P, DIM, Value = P::Value>,
+    {
+        self.trees
+            .iter()
+            .filter_map(Option::as_ref)
+            .flat_map(move |tree| tree.in_range_iter(range))
     }
 }
 
 /// Iterator over all the elements of a KdTree.
-pub struct KdTreeIter<'a, T: 'a, V: 'a> {
-    nodes: Vec<&'a Node<T, V>>,
+pub struct KdTreeIter<'a, P: 'a, V: 'a, const DIM: usize> {
+    nodes: Vec<&'a Node<P, V, DIM>>,
 }
 
-impl<'a, T, V> Iterator for KdTreeIter<'a, T, V> {
-    type Item = (&'a Point<T>, &'a V);
+impl<'a, P, V, const DIM: usize> Iterator for KdTreeIter<'a, P, V, DIM> {
+    type Item = (&'a P, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.nodes.pop().map(|node| {
+        while let Some(node) = self.nodes.pop() {
             if let Some(ref n) = node.right {
                 self.nodes.push(n);
             }
@@ -336,30 +860,35 @@ impl<'a, T, V> Iterator for KdTreeIter<'a, T, V> {
                 self.nodes.push(n);
             }
 
-            (&node.median, &node.value)
-        })
+            if !node.deleted {
+                return Some((&node.point, &node.value));
+            }
+        }
+
+        None
     }
 }
 
 /// Iterator over the points contained in the given range in the kdtree.
-pub struct InRangeIter<'a, 'r, T: 'a, V: 'a, R: 'r> {
-    nodes: Vec<&'a Node<T, V>>,
+pub struct InRangeIter<'a, 'r, P: 'a, V: 'a, const DIM: usize, R: 'r> {
+    nodes: Vec<&'a Node<P, V, DIM>>,
     range: &'r R,
 }
 
-impl<'a, 'r, T, V, R> Iterator for InRangeIter<'a, 'r, T, V, R>
+impl<'a, 'r, P, V, const DIM: usize, R> Iterator for InRangeIter<'a, 'r, P, V, DIM, R>
 where
-    T: Copy + Ord,
-    R: Range<Point<T>, AxisValue = T>,
+    P: Coordinates<DIM> + Copy,
+    P::Value: Copy + Ord,
+    R: Range<P, DIM, Value = P::Value>,
 {
-    type Item = (&'a Point<T>, &'a V);
+    type Item = (&'a P, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(node) = self.nodes.pop() {
             let (range_low, range_high) = self.range.axis_value_range(node.axis);
-            let median_axis_value = node.median.axis_value(node.axis);
+            let axis_value = node.point.coords()[node.axis];
 
-            let mut push_node = |node: &'a Option<Box<Node<T, V>>>| {
+            let mut push_node = |node: &'a Option<Box<Node<P, V, DIM>>>| {
                 if let Some(ref n) = node {
                     self.nodes.push(n);
                 }
@@ -369,17 +898,17 @@ where
             // axis value then search only on the side that contains the range.
             // If there is an intersection then we must check both sides since
             // the range could contain both of them.
-            if *median_axis_value < range_low {
+            if axis_value < range_low {
                 push_node(&node.right);
-            } else if *median_axis_value > range_high {
+            } else if axis_value > range_high {
                 push_node(&node.left);
             } else {
                 push_node(&node.right);
                 push_node(&node.left);
             }
 
-            if self.range.contains(&node.median) {
-                return Some((&node.median, &node.value));
+            if !node.deleted && self.range.contains(&node.point) {
+                return Some((&node.point, &node.value));
             }
         }
 
@@ -389,7 +918,7 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::{Axis, KdTree, Node};
+    use super::{KdTree, Node};
 
     extern crate num;
     extern crate proptest;
@@ -426,31 +955,37 @@ mod test {
             kdtree,
             KdTree {
                 length: 7,
+                deleted: 0,
+                auto_rebuild_fraction: 0.5,
                 root: Some(Node {
-                    median: PointU32::new(4, 5),
-                    axis: Axis::X,
+                    point: PointU32::new(4, 5),
+                    axis: 0,
                     value: "root",
+                    deleted: false,
 
                     left: Some(Box::new(Node {
-                        median: PointU32::new(1, 2),
-                        axis: Axis::Y,
+                        point: PointU32::new(1, 2),
+                        axis: 1,
                         value: "p(1,2)",
-                        left: Some(Box::new(Node::new(PointU32::new(0, 0), "p(0,0)", Axis::X))),
+                        deleted: false,
+                        left: Some(Box::new(Node::new(PointU32::new(0, 0), "p(0,0)", 0))),
                         right: Some(Box::new(Node {
-                            median: PointU32::new(2, 9),
-                            axis: Axis::X,
-                            value: "p(2,9)",
+                            point: PointU32::new(2, 9),
+                            axis: 0,
+                            deleted: false,
 
-                            left: Some(Box::new(Node::new(PointU32::new(2, 8), "p(2,8)", Axis::Y))),
+                            left: Some(Box::new(Node::new(PointU32::new(2, 8), "p(2,8)", 1))),
                             right: None,
+                            value: "p(2,9)",
                         })),
                     })),
 
                     right: Some(Box::new(Node {
-                        median: PointU32::new(7, 8),
-                        axis: Axis::Y,
+                        point: PointU32::new(7, 8),
+                        axis: 1,
                         value: "p(7,8)",
-                        left: Some(Box::new(Node::new(PointU32::new(5, 2), "p(5,2)", Axis::X))),
+                        deleted: false,
+                        left: Some(Box::new(Node::new(PointU32::new(5, 2), "p(5,2)", 0))),
                         right: None,
                     })),
                 })
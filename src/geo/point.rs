@@ -3,6 +3,7 @@
 extern crate num;
 
 use std::error::Error;
+use std::ops::{Add, Div, Mul, Sub};
 use std::str::FromStr;
 
 /// Point specialized for `f64`
@@ -89,6 +90,107 @@ where
     {
         Point::new(O::from(self.x), O::from(self.y))
     }
+
+    /// The dot product of this point (as a vector from the origin) and
+    /// another.
+    pub fn dot(&self, p: &Self) -> T {
+        self.x * p.x + self.y * p.y
+    }
+
+    /// The 2D cross product (aka the `z` component of the 3D cross product of
+    /// the two vectors padded with a 0 `z`), i.e. `x1*y2 - y1*x2`. Its sign
+    /// tells the orientation of `p` relative to this point, and its absolute
+    /// value is the area of the parallelogram spanned by the two vectors.
+    pub fn det(&self, p: &Self) -> T {
+        self.x * p.y - self.y * p.x
+    }
+}
+
+impl<T> Add for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T> Sub for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T> Mul<T> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Point::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl<T> Div<T> for Point<T>
+where
+    T: num::Num + From<u8> + Copy,
+{
+    type Output = Self;
+
+    fn div(self, scalar: T) -> Self {
+        Point::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl Point<f64> {
+    /// The euclidean length of this point, seen as a vector from the origin.
+    pub fn length(&self) -> f64 {
+        self.dist::<f64>(&Point::new(0.0, 0.0))
+    }
+
+    /// Return this vector scaled to unit length. Returns the zero vector if
+    /// this vector is the origin.
+    pub fn normalized(&self) -> Self {
+        let len = self.length();
+
+        if len == 0.0 {
+            return self.clone();
+        }
+
+        Point::new(self.x / len, self.y / len)
+    }
+
+    /// The angle, in radians, of this point seen as a vector from the origin
+    /// (`atan2(y, x)`).
+    pub fn to_angle(&self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// Rotate this point by `angle` radians around the origin.
+    pub fn rotate(&self, angle: f64) -> Self {
+        self.rotate_around(angle, &Point::new(0.0, 0.0))
+    }
+
+    /// Rotate this point by `angle` radians around `pivot`.
+    pub fn rotate_around(&self, angle: f64, pivot: &Self) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        let dx = self.x - pivot.x;
+        let dy = self.y - pivot.y;
+
+        Point::new(
+            pivot.x + dx * cos - dy * sin,
+            pivot.y + dx * sin + dy * cos,
+        )
+    }
 }
 
 impl<T> Point<T>
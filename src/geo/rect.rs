@@ -2,7 +2,7 @@
 
 extern crate num;
 
-use geo::Point;
+use geo::{Point, Polygon};
 
 /// Simple struct representing a rectangle.
 #[derive(Clone, Debug, PartialEq)]
@@ -58,6 +58,99 @@ where
             (self.origin.y + self.height) / T::from(2),
         )
     }
+
+    /// Return the smallest rectangle that covers both this rectangle and
+    /// `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        let min_x = min(self.origin.x, other.origin.x);
+        let min_y = min(self.origin.y, other.origin.y);
+        let max_x = max(self.origin.x + self.width, other.origin.x + other.width);
+        let max_y = max(
+            self.origin.y + self.height,
+            other.origin.y + other.height,
+        );
+
+        Self::new(Point::new(min_x, min_y), max_x - min_x, max_y - min_y)
+    }
+
+    /// Return the overlap between this rectangle and `other`, or `None` if
+    /// they don't overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min_x = max(self.origin.x, other.origin.x);
+        let min_y = max(self.origin.y, other.origin.y);
+        let max_x = min(self.origin.x + self.width, other.origin.x + other.width);
+        let max_y = min(
+            self.origin.y + self.height,
+            other.origin.y + other.height,
+        );
+
+        if min_x >= max_x || min_y >= max_y {
+            None
+        } else {
+            Some(Self::new(
+                Point::new(min_x, min_y),
+                max_x - min_x,
+                max_y - min_y,
+            ))
+        }
+    }
+
+    /// Return a copy of this rectangle padded by `dx`/`dy` on every side.
+    pub fn inflate(&self, dx: T, dy: T) -> Self {
+        Self::new(
+            Point::new(self.origin.x - dx, self.origin.y - dy),
+            self.width + dx + dx,
+            self.height + dy + dy,
+        )
+    }
+
+    /// Return a copy of this rectangle shrunk by `dx`/`dy` on every side,
+    /// i.e. the inverse of `inflate`.
+    pub fn deflate(&self, dx: T, dy: T) -> Self {
+        Self::new(
+            Point::new(self.origin.x + dx, self.origin.y + dy),
+            self.width - dx - dx,
+            self.height - dy - dy,
+        )
+    }
+
+    /// Return a copy of this rectangle moved by `delta`.
+    pub fn translate(&self, delta: &Point<T>) -> Self {
+        Self::new(
+            Point::new(self.origin.x + delta.x, self.origin.y + delta.y),
+            self.width,
+            self.height,
+        )
+    }
+
+    /// Whether `other` lies entirely inside this rectangle.
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        self.origin.x <= other.origin.x
+            && self.origin.y <= other.origin.y
+            && self.origin.x + self.width >= other.origin.x + other.width
+            && self.origin.y + self.height >= other.origin.y + other.height
+    }
+
+    /// Return this rectangle as a `Polygon`.
+    pub fn to_polygon(&self) -> Polygon<T> {
+        Polygon::new(self.points().to_vec()).expect("a rect always has enough points")
+    }
+}
+
+fn min<T: PartialOrd>(a: T, b: T) -> T {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if a > b {
+        a
+    } else {
+        b
+    }
 }
 
 #[cfg(test)]
@@ -101,4 +194,65 @@ mod test {
 
         assert_eq!(rec.center(), PointU32::new(5, 5));
     }
+
+    #[test]
+    fn test_union() {
+        let a = Rect::new(PointU32::new(0, 0), 4, 4);
+        let b = Rect::new(PointU32::new(2, 2), 4, 4);
+
+        assert_eq!(a.union(&b), Rect::new(PointU32::new(0, 0), 6, 6));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = Rect::new(PointU32::new(0, 0), 4, 4);
+        let b = Rect::new(PointU32::new(2, 2), 4, 4);
+
+        assert_eq!(
+            a.intersection(&b),
+            Some(Rect::new(PointU32::new(2, 2), 2, 2))
+        );
+
+        let c = Rect::new(PointU32::new(10, 10), 4, 4);
+        assert_eq!(a.intersection(&c), None);
+    }
+
+    #[test]
+    fn test_inflate_deflate() {
+        let rec = Rect::new(PointU32::new(5, 5), 4, 4);
+
+        let inflated = rec.inflate(2, 2);
+        assert_eq!(inflated, Rect::new(PointU32::new(3, 3), 8, 8));
+        assert_eq!(inflated.deflate(2, 2), rec);
+    }
+
+    #[test]
+    fn test_translate() {
+        let rec = Rect::new(PointU32::new(5, 5), 4, 4);
+
+        assert_eq!(
+            rec.translate(&PointU32::new(1, 2)),
+            Rect::new(PointU32::new(6, 7), 4, 4)
+        );
+    }
+
+    #[test]
+    fn test_contains_rect() {
+        let outer = Rect::new(PointU32::new(0, 0), 10, 10);
+        let inner = Rect::new(PointU32::new(2, 2), 4, 4);
+        let overlapping = Rect::new(PointU32::new(8, 8), 4, 4);
+
+        assert!(outer.contains_rect(&inner));
+        assert!(!outer.contains_rect(&overlapping));
+    }
+
+    #[test]
+    fn test_to_polygon() {
+        let rec = Rect::new(PointU32::new(3, 5), 7, 5);
+
+        let mut expected = rec.points().to_vec();
+        expected.push(rec.points()[0]);
+
+        assert_eq!(rec.to_polygon().points(), &expected[..]);
+    }
 }
@@ -1,7 +1,14 @@
 extern crate image;
 extern crate num;
+extern crate rand;
+extern crate rayon;
+extern crate rug;
 
 use self::num::complex::Complex64;
+use self::rand::Rng;
+use self::rayon::prelude::*;
+use self::rug::Complex as BigComplex;
+use self::rug::Float;
 
 use point::Point;
 
@@ -18,19 +25,139 @@ impl FractalPoint {
     /// Calculate if the given `f`(that is point) is in the [Mandelbrot
     /// Set](https://en.wikipedia.org/wiki/Mandelbrot_set).
     pub fn mandelbrot(f: Complex64, iterations: u32) -> FractalPoint {
-        FractalPoint::julia(f, f, iterations)
+        FractalKind::Mandelbrot.escape(f, iterations)
     }
 
     /// Calculate if the given `f`(that is point) with param `c` is in the
     /// [Julia Set](https://en.wikipedia.org/wiki/Julia_set).
-    pub fn julia(mut f: Complex64, c: Complex64, iterations: u32) -> FractalPoint {
+    pub fn julia(f: Complex64, c: Complex64, iterations: u32) -> FractalPoint {
+        FractalKind::Julia { c }.escape(f, iterations)
+    }
+
+    fn to_pixels(&self) -> Vec<u8> {
+        if self.is_inside {
+            vec![
+                0,
+                (self.last_value * 128.0) as u8,
+                ((2.0 - self.last_value) * 100.0) as u8,
+            ]
+
+        //let last_value = (self.last_value * 1_000_000.0) as u32;
+        // vec![0, (last_value % 255) as u8, (last_value % 255) as u8]
+        } else {
+            u32_to_vec(self.iterations)
+        }
+    }
+
+    /// Normalized, continuous iteration count `nu`, suitable for driving a
+    /// gradient without the banding the integer `iterations` produces.
+    /// Interior points have no escape radius to smooth over, so they just
+    /// return `iterations` unchanged. For an accurate result `self` should
+    /// have been produced with a large bailout radius, e.g. via
+    /// `FractalKind::escape_smooth`.
+    pub fn smooth_iterations(&self) -> f64 {
+        if self.is_inside {
+            f64::from(self.iterations)
+        } else {
+            let norm = self.last_value.max(1.0 + ::std::f64::EPSILON);
+            f64::from(self.iterations) + 1.0 - (norm.ln().ln() / 2f64.ln())
+        }
+    }
+
+    /// Like `to_pixels` but maps `smooth_iterations` through a grayscale
+    /// gradient instead of banding on the integer iteration count.
+    /// `max_iterations` should be the same value passed to the `escape*`
+    /// call that produced this point.
+    pub fn to_pixels_smooth(&self, max_iterations: u32) -> Vec<u8> {
+        if self.is_inside {
+            vec![0, 0, 0]
+        } else {
+            let t = (self.smooth_iterations() / f64::from(max_iterations))
+                .min(1.0)
+                .max(0.0);
+            let v = (t * 255.0) as u8;
+
+            vec![v, v, v]
+        }
+    }
+}
+
+/// The escape-time family to use when turning a complex point into a
+/// `FractalPoint`. `FractalPoint::julia` and `FractalPoint::mandelbrot` are
+/// just `escape` called with the `Julia` and `Mandelbrot` variants; `gen_fractal`
+/// accepts any `gen` closure, so passing `|f, it| kind.escape(f, it)` is enough
+/// to render any of these families.
+#[derive(Debug, Clone, Copy)]
+pub enum FractalKind {
+    /// The classic [Mandelbrot Set](https://en.wikipedia.org/wiki/Mandelbrot_set),
+    /// i.e. `f = f * f + c` with `c` equal to the starting point.
+    Mandelbrot,
+
+    /// Mandelbrot generalized to `f = f.powu(power) + c`.
+    Multibrot {
+        /// Exponent used instead of the usual square.
+        power: u32,
+    },
+
+    /// Mandelbrot with `f` replaced by `Complex::new(f.re.abs(), f.im.abs())`
+    /// before squaring, which folds the set into the characteristic "ship"
+    /// silhouette.
+    BurningShip,
+
+    /// The [Julia Set](https://en.wikipedia.org/wiki/Julia_set) for a fixed
+    /// `c`, i.e. `f = f * f + c`.
+    Julia {
+        /// The constant added at every iteration.
+        c: Complex64,
+    },
+
+    /// Mandelbrot with `f` conjugated before squaring, giving the mirrored
+    /// [Tricorn](https://en.wikipedia.org/wiki/Tricorn_(mathematics)) set.
+    Tricorn,
+}
+
+impl FractalKind {
+    /// Run the escape-time recurrence for this fractal family starting at
+    /// `f`, for at most `iterations` steps, and return the resulting
+    /// `FractalPoint`. This is the single place where the different
+    /// recurrences are dispatched; the `norm() > bailout` escape test is
+    /// shared by all of them.
+    pub fn escape(self, f: Complex64, iterations: u32) -> FractalPoint {
+        self.escape_with_bailout(f, iterations, 2.0)
+    }
+
+    /// Like `escape` but with a bailout radius of `2^16` instead of `2.0`,
+    /// which is large enough to make `FractalPoint::smooth_iterations`
+    /// accurate. Use this when the resulting grid is going to be rendered
+    /// with `to_pixels_smooth`.
+    pub fn escape_smooth(self, f: Complex64, iterations: u32) -> FractalPoint {
+        self.escape_with_bailout(f, iterations, 65536.0)
+    }
+
+    fn escape_with_bailout(self, mut f: Complex64, iterations: u32, bailout: f64) -> FractalPoint {
+        let c = match self {
+            FractalKind::Julia { c } => c,
+            _ => f,
+        };
+
         let mut is_inside = true;
         let mut i = 0;
 
         while i < iterations {
-            f = f * f + c;
+            f = match self {
+                FractalKind::Mandelbrot | FractalKind::Julia { .. } => f * f + c,
+                FractalKind::Multibrot { power } => f.powu(power) + c,
+                FractalKind::BurningShip => {
+                    let f = Complex64::new(f.re.abs(), f.im.abs());
+                    f * f + c
+                }
+                FractalKind::Tricorn => {
+                    let f = f.conj();
+                    f * f + c
+                }
+            };
 
-            if f.norm() > 2.0 {
+            if f.norm() > bailout {
                 is_inside = false;
                 break;
             }
@@ -44,21 +171,6 @@ impl FractalPoint {
             is_inside,
         }
     }
-
-    fn to_pixels(&self) -> Vec<u8> {
-        if self.is_inside {
-            vec![
-                0,
-                (self.last_value * 128.0) as u8,
-                ((2.0 - self.last_value) * 100.0) as u8,
-            ]
-
-        //let last_value = (self.last_value * 1_000_000.0) as u32;
-        // vec![0, (last_value % 255) as u8, (last_value % 255) as u8]
-        } else {
-            u32_to_vec(self.iterations)
-        }
-    }
 }
 
 /// Generate a fractal starting from the given `point` and incrementing x by
@@ -93,6 +205,100 @@ where
         .collect()
 }
 
+/// Same as `gen_fractal` but computes the rows in parallel with rayon,
+/// splitting the work across all available cores. `gen` already requires
+/// `Sync + Send` so it is sound to call it from any thread; for anything
+/// bigger than a thumbnail this is close to a linear speedup over
+/// `gen_fractal`.
+pub fn gen_fractal_parallel<F>(
+    start: &Point,
+    xcount: u32,
+    ycount: u32,
+    stepx: f64,
+    stepy: f64,
+    iterations: u32,
+    gen: F,
+) -> Vec<Vec<FractalPoint>>
+where
+    F: Sync + Send + Fn(Complex64, u32) -> FractalPoint,
+{
+    (0..xcount)
+        .into_par_iter()
+        .map(|ix| {
+            (0..ycount)
+                .map(|iy| {
+                    let x = start.x + f64::from(ix) * stepx;
+                    let y = start.y + f64::from(iy) * stepy;
+
+                    gen(Complex64::new(x, y), iterations)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Generate `frame_count` frames zooming into `center`, interpolating the
+/// zoom factor geometrically between `start_zoom` and `end_zoom`
+/// (`zoom_k = start_zoom * (end_zoom / start_zoom) ^ (k / (frame_count - 1))`).
+/// Each frame reuses `gen_fractal_parallel`, so rendering stays spread
+/// across all cores; the returned images are meant to be written out as
+/// numbered PNGs for an external tool like ffmpeg to stitch into a video,
+/// rather than hand-rolling the view bounds for every frame.
+pub fn gen_fractal_sequence<F>(
+    center: &Point,
+    width: u32,
+    height: u32,
+    start_zoom: f64,
+    end_zoom: f64,
+    frame_count: u32,
+    iterations: u32,
+    gen: F,
+) -> Vec<image::DynamicImage>
+where
+    F: Sync + Send + Fn(Complex64, u32) -> FractalPoint,
+{
+    (0..frame_count)
+        .map(|k| {
+            let t = if frame_count <= 1 {
+                0.0
+            } else {
+                f64::from(k) / f64::from(frame_count - 1)
+            };
+            let zoom = start_zoom * (end_zoom / start_zoom).powf(t);
+
+            let stepx = 1.0 / zoom / f64::from(width);
+            let stepy = stepx;
+            let start = Point::new(
+                center.x - stepx * f64::from(width) / 2.0,
+                center.y - stepy * f64::from(height) / 2.0,
+            );
+
+            let frac = gen_fractal_parallel(&start, width, height, stepx, stepy, iterations, &gen);
+            fractal_to_image(&frac)
+        })
+        .collect()
+}
+
+/// Create an image from the given fractal using the smooth, gradient-free
+/// coloring from `FractalPoint::to_pixels_smooth`. `max_iterations` must
+/// match the `iterations` the fractal was generated with.
+pub fn fractal_to_image_smooth(
+    frac: &[Vec<FractalPoint>],
+    max_iterations: u32,
+) -> image::DynamicImage {
+    let width = frac.len();
+    let height = frac[0].len();
+
+    let v = (0..height)
+        .flat_map(move |y| {
+            (0..width).flat_map(move |x| frac[x][y].to_pixels_smooth(max_iterations))
+        })
+        .collect();
+
+    let imgbuf = image::ImageBuffer::from_raw(width as u32, height as u32, v).unwrap();
+    image::ImageRgb8(imgbuf)
+}
+
 /// Create an image from the given fractal.
 pub fn fractal_to_image(frac: &[Vec<FractalPoint>]) -> image::DynamicImage {
     let width = frac.len();
@@ -110,9 +316,423 @@ fn u32_to_vec(n: u32) -> Vec<u8> {
     vec![(n >> 16) as u8, (n >> 8) as u8, n as u8]
 }
 
+/// A full-precision Mandelbrot orbit for a single reference point `C`,
+/// computed once with `rug::Complex` and then truncated to `f64` at every
+/// step. `gen_fractal_perturbation` only ever does `f64` math per pixel,
+/// using this orbit to reach zoom depths (1e-100 and beyond) that break
+/// down into flat, pixelated blocks under plain `f64`.
+pub struct ReferenceOrbit {
+    /// `Z_0, Z_1, ...` of the reference orbit, truncated to `f64` after
+    /// each high-precision iteration. Stops early if the orbit itself
+    /// escapes `bailout` before `max_iterations`.
+    values: Vec<Complex64>,
+}
+
+impl ReferenceOrbit {
+    /// Compute the reference orbit for `c`, iterating `z = z * z + c` at
+    /// `precision_bits` of precision for at most `max_iterations` steps (or
+    /// until the orbit escapes `bailout`).
+    pub fn compute(
+        c: BigComplex,
+        precision_bits: u32,
+        max_iterations: u32,
+        bailout: f64,
+    ) -> ReferenceOrbit {
+        let zero = Float::with_val(precision_bits, 0);
+        let mut z = BigComplex::with_val(precision_bits, (zero.clone(), zero));
+        let mut values = Vec::with_capacity(max_iterations as usize);
+
+        for _ in 0..max_iterations {
+            let re = z.real().to_f64();
+            let im = z.imag().to_f64();
+
+            values.push(Complex64::new(re, im));
+
+            if re * re + im * im > bailout * bailout {
+                break;
+            }
+
+            z = z.clone() * z.clone() + c.clone();
+        }
+
+        ReferenceOrbit { values }
+    }
+}
+
+/// Render a single pixel with the perturbation-theory variant of the
+/// Mandelbrot recurrence. Instead of iterating the pixel's own `f64`
+/// coordinate, this iterates `delta_n`, the (tiny, `f64`-representable)
+/// offset between the pixel and the high-precision `reference` orbit:
+/// `delta_{n+1} = 2 * Z_n * delta_n + delta_n^2 + delta_c`, where `delta_c`
+/// is `c - C`. The pixel's actual value is `Z_n + delta_n`, and escape is
+/// tested on that sum against `bailout`.
+///
+/// When `|Z_n + delta_n|` becomes small relative to `|delta_n|` the
+/// reference orbit and the pixel have diverged too far to trust (a
+/// "glitch"); this rebases by resetting the reference index to `0` and
+/// `delta_n` to the full current value. The same happens if `reference`
+/// itself ran out (i.e. its own orbit escaped) before `iterations` steps.
+pub fn gen_fractal_perturbation(
+    reference: &ReferenceOrbit,
+    delta_c: Complex64,
+    iterations: u32,
+    bailout: f64,
+) -> FractalPoint {
+    let mut delta = Complex64::new(0.0, 0.0);
+    let mut ref_index = 0;
+    let mut is_inside = true;
+    let mut i = 0;
+    let mut last_value = 0.0;
+
+    while i < iterations {
+        if ref_index >= reference.values.len() {
+            ref_index = 0;
+        }
+
+        let z_ref = reference.values[ref_index];
+        delta = Complex64::new(2.0, 0.0) * z_ref * delta + delta * delta + delta_c;
+        ref_index += 1;
+
+        let z = z_ref + delta;
+        let norm = z.norm();
+        last_value = norm;
+
+        if norm > bailout {
+            is_inside = false;
+            break;
+        }
+
+        if norm < delta.norm() {
+            ref_index = 0;
+            delta = z;
+        }
+
+        i += 1;
+    }
+
+    FractalPoint {
+        last_value,
+        iterations: i,
+        is_inside,
+    }
+}
+
+/// A polynomial `p(z) = coefficients[0] * z^n + ... + coefficients[n]`,
+/// used as the root-finding target for `gen_newton_fractal`. This is a
+/// root-finding fractal mode distinct from the escape-time family above:
+/// pixels are colored by which root Newton's method converges to rather
+/// than by an escape count.
+pub struct Polynomial {
+    coefficients: Vec<Complex64>,
+    roots: Vec<Complex64>,
+}
+
+impl Polynomial {
+    /// Create a polynomial from its `coefficients` (highest degree first)
+    /// together with its (already known or clustered) `roots`, used to
+    /// classify which root a given starting point converges to.
+    pub fn new(coefficients: Vec<Complex64>, roots: Vec<Complex64>) -> Polynomial {
+        Polynomial {
+            coefficients,
+            roots,
+        }
+    }
+
+    /// The classic `z^3 - 1`, whose three roots are the cube roots of unity.
+    pub fn z_cubed_minus_one() -> Polynomial {
+        Polynomial::new(
+            vec![
+                Complex64::new(1.0, 0.0),
+                Complex64::new(0.0, 0.0),
+                Complex64::new(0.0, 0.0),
+                Complex64::new(-1.0, 0.0),
+            ],
+            vec![
+                Complex64::new(1.0, 0.0),
+                Complex64::new(-0.5, 0.866_025_403_784_438_6),
+                Complex64::new(-0.5, -0.866_025_403_784_438_6),
+            ],
+        )
+    }
+
+    /// Evaluate `p(z)` using Horner's method.
+    pub fn eval(&self, z: Complex64) -> Complex64 {
+        self.coefficients
+            .iter()
+            .fold(Complex64::new(0.0, 0.0), |acc, &coeff| acc * z + coeff)
+    }
+
+    /// Evaluate `p'(z)`, the derivative of `p`, using Horner's method.
+    pub fn eval_derivative(&self, z: Complex64) -> Complex64 {
+        let degree = self.coefficients.len() - 1;
+
+        self.coefficients[..degree]
+            .iter()
+            .enumerate()
+            .fold(Complex64::new(0.0, 0.0), |acc, (i, &coeff)| {
+                acc * z + coeff * f64::from((degree - i) as u32)
+            })
+    }
+
+    /// Index, in `roots`, of the root nearest to `z`.
+    fn nearest_root(&self, z: Complex64) -> usize {
+        self.roots
+            .iter()
+            .enumerate()
+            .map(|(i, &root)| (i, (z - root).norm()))
+            .fold((0, ::std::f64::INFINITY), |best, cur| {
+                if cur.1 < best.1 {
+                    cur
+                } else {
+                    best
+                }
+            })
+            .0
+    }
+}
+
+/// Convert a `hue` in `[0, 360)` (full saturation, full value) to `[r, g, b]`
+/// bytes.
+fn hue_to_rgb(hue: f64) -> [u8; 3] {
+    let hue = hue.rem_euclid(360.0);
+    let c = 255.0;
+    let x = c * (1.0 - (((hue / 60.0) % 2.0) - 1.0).abs());
+
+    let (r, g, b) = match hue as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    [r as u8, g as u8, b as u8]
+}
+
+/// The result of running Newton's method on a single pixel: which root (by
+/// index into `Polynomial::roots`) the iteration converged to, and how many
+/// iterations it took.
+#[derive(Debug, Clone, Copy)]
+pub struct NewtonPoint {
+    root: usize,
+    iterations: u32,
+}
+
+impl NewtonPoint {
+    fn to_pixels(&self, max_iterations: u32, root_count: usize) -> Vec<u8> {
+        let hue = 360.0 * self.root as f64 / (root_count.max(1) as f64);
+        let shade = 1.0 - 0.7 * f64::from(self.iterations) / f64::from(max_iterations.max(1));
+        let shade = shade.max(0.0).min(1.0);
+
+        let [r, g, b] = hue_to_rgb(hue);
+
+        vec![
+            (f64::from(r) * shade) as u8,
+            (f64::from(g) * shade) as u8,
+            (f64::from(b) * shade) as u8,
+        ]
+    }
+}
+
+/// Run Newton's method (`z_{n+1} = z_n - p(z_n) / p'(z_n)`) from `z`,
+/// stopping when `|z_{n+1} - z_n|` drops below `tolerance` or `max_iter` is
+/// hit, and classify the result by its nearest known root in `poly`.
+pub fn newton(poly: &Polynomial, mut z: Complex64, max_iter: u32, tolerance: f64) -> NewtonPoint {
+    let mut i = 0;
+
+    while i < max_iter {
+        let next = z - poly.eval(z) / poly.eval_derivative(z);
+
+        if (next - z).norm() < tolerance {
+            z = next;
+            break;
+        }
+
+        z = next;
+        i += 1;
+    }
+
+    NewtonPoint {
+        root: poly.nearest_root(z),
+        iterations: i,
+    }
+}
+
+/// Render a Newton-fractal/polynomiography grid: same tiling as
+/// `gen_fractal`, but every pixel classifies which root of `poly` Newton's
+/// method converges to instead of an escape count.
+pub fn gen_newton_fractal(
+    poly: &Polynomial,
+    start: &Point,
+    xcount: u32,
+    ycount: u32,
+    stepx: f64,
+    stepy: f64,
+    max_iter: u32,
+    tolerance: f64,
+) -> Vec<Vec<NewtonPoint>> {
+    (0..xcount)
+        .map(|ix| {
+            (0..ycount)
+                .map(|iy| {
+                    let x = start.x + f64::from(ix) * stepx;
+                    let y = start.y + f64::from(iy) * stepy;
+
+                    newton(poly, Complex64::new(x, y), max_iter, tolerance)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Create an image from a Newton-fractal grid: hue encodes which root each
+/// pixel converged to, brightness encodes how many iterations it took.
+pub fn newton_fractal_to_image(
+    frac: &[Vec<NewtonPoint>],
+    max_iterations: u32,
+    root_count: usize,
+) -> image::DynamicImage {
+    let width = frac.len();
+    let height = if width == 0 { 0 } else { frac[0].len() };
+
+    let v = (0..height)
+        .flat_map(|y| {
+            (0..width).flat_map(move |x| frac[x][y].to_pixels(max_iterations, root_count))
+        })
+        .collect();
+
+    let imgbuf = image::ImageBuffer::from_raw(width as u32, height as u32, v).unwrap();
+    image::ImageRgb8(imgbuf)
+}
+
+/// Render a [Buddhabrot](https://en.wikipedia.org/wiki/Buddhabrot) density
+/// grid. Unlike `gen_fractal`, which colors by escape time, this samples
+/// `samples` random starting points `c` in the `xcount * ycount` view
+/// starting at `start` and, for every orbit of `z = z * z + c` (from
+/// `z = 0`) that escapes within `max_iterations`, replays it and increments
+/// the accumulation grid at every intermediate `z_k` that lands inside the
+/// image bounds. Orbits that never escape are discarded. This is orthogonal
+/// to `gen_fractal` and reuses the same `Point`/view parameters; the
+/// resulting grid can be turned into bytes with `density_to_pixels`.
+pub fn buddhabrot(
+    start: &Point,
+    xcount: u32,
+    ycount: u32,
+    stepx: f64,
+    stepy: f64,
+    samples: u32,
+    max_iterations: u32,
+) -> Vec<Vec<u32>> {
+    let mut grid = vec![vec![0u32; ycount as usize]; xcount as usize];
+    let mut rng = rand::thread_rng();
+    let mut orbit = Vec::with_capacity(max_iterations as usize);
+
+    for _ in 0..samples {
+        let c = Complex64::new(
+            start.x + rng.gen_range(0.0, f64::from(xcount)) * stepx,
+            start.y + rng.gen_range(0.0, f64::from(ycount)) * stepy,
+        );
+
+        orbit.clear();
+
+        let mut z = Complex64::new(0.0, 0.0);
+        let mut escaped = false;
+
+        for _ in 0..max_iterations {
+            z = z * z + c;
+            orbit.push(z);
+
+            if z.norm() > 2.0 {
+                escaped = true;
+                break;
+            }
+        }
+
+        if !escaped {
+            continue;
+        }
+
+        for z in &orbit {
+            let px = ((z.re - start.x) / stepx) as i64;
+            let py = ((z.im - start.y) / stepy) as i64;
+
+            if px >= 0 && py >= 0 && (px as u32) < xcount && (py as u32) < ycount {
+                grid[px as usize][py as usize] += 1;
+            }
+        }
+    }
+
+    grid
+}
+
+/// Normalize a density grid produced by `buddhabrot` to grayscale bytes by
+/// dividing by the largest count and applying a gamma curve, so the (very
+/// uneven) counts become a viewable image instead of a few bright specks on
+/// an otherwise black background.
+pub fn density_to_pixels(grid: &[Vec<u32>], gamma: f64) -> Vec<u8> {
+    let width = grid.len();
+    let height = if width == 0 { 0 } else { grid[0].len() };
+
+    let max = grid
+        .iter()
+        .flat_map(|row| row.iter())
+        .cloned()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    (0..height)
+        .flat_map(|y| {
+            (0..width).map(move |x| {
+                let t = f64::from(grid[x][y]) / f64::from(max);
+                (t.powf(gamma) * 255.0) as u8
+            })
+        })
+        .collect()
+}
+
+/// Render a classic [Nebulabrot](https://en.wikipedia.org/wiki/Buddhabrot#Nebulabrot):
+/// three `buddhabrot` density grids sampled with different `max_iterations`
+/// thresholds (traditionally short/medium/long, e.g. 500/5000/50000), mapped
+/// to the red, green and blue channels respectively.
+pub fn nebulabrot(
+    start: &Point,
+    xcount: u32,
+    ycount: u32,
+    stepx: f64,
+    stepy: f64,
+    samples: u32,
+    iterations: (u32, u32, u32),
+) -> image::DynamicImage {
+    let r = density_to_pixels(
+        &buddhabrot(start, xcount, ycount, stepx, stepy, samples, iterations.0),
+        0.5,
+    );
+    let g = density_to_pixels(
+        &buddhabrot(start, xcount, ycount, stepx, stepy, samples, iterations.1),
+        0.5,
+    );
+    let b = density_to_pixels(
+        &buddhabrot(start, xcount, ycount, stepx, stepy, samples, iterations.2),
+        0.5,
+    );
+
+    let mut v = Vec::with_capacity(r.len() * 3);
+    for i in 0..r.len() {
+        v.push(r[i]);
+        v.push(g[i]);
+        v.push(b[i]);
+    }
+
+    let imgbuf = image::ImageBuffer::from_raw(xcount, ycount, v).unwrap();
+    image::ImageRgb8(imgbuf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use image::GenericImageView;
 
     #[test]
     fn sanity() {
@@ -129,4 +749,102 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn gen_fractal_sequence_yields_one_frame_per_step() {
+        let center = Point::new(-0.5, 0.0);
+        let frames = gen_fractal_sequence(
+            &center,
+            8,
+            8,
+            0.5,
+            2.0,
+            4,
+            16,
+            FractalPoint::mandelbrot,
+        );
+
+        assert_eq!(frames.len(), 4);
+        for frame in &frames {
+            assert_eq!(frame.dimensions(), (8, 8));
+        }
+    }
+
+    #[test]
+    fn perturbation_matches_plain_f64_near_the_reference_point() {
+        let c = BigComplex::with_val(53, (Float::with_val(53, -1.0), Float::with_val(53, 0.0)));
+        let reference = ReferenceOrbit::compute(c, 53, 64, 2.0);
+
+        let plain = FractalPoint::mandelbrot(Complex64::new(-1.0, 0.0), 64);
+        let perturbed = gen_fractal_perturbation(&reference, Complex64::new(0.0, 0.0), 64, 2.0);
+
+        assert_eq!(plain.is_inside, perturbed.is_inside);
+    }
+
+    #[test]
+    fn buddhabrot_stays_in_bounds_and_has_some_density() {
+        let start = Point::new(-2.0, -2.0);
+        let grid = buddhabrot(&start, 16, 16, 0.25, 0.25, 2000, 64);
+
+        assert_eq!(grid.len(), 16);
+        assert_eq!(grid[0].len(), 16);
+        assert!(grid.iter().flat_map(|row| row.iter()).any(|&n| n > 0));
+
+        let pixels = density_to_pixels(&grid, 0.5);
+        assert_eq!(pixels.len(), 16 * 16);
+    }
+
+    #[test]
+    fn smooth_iterations_is_continuous_and_bounded() {
+        let inside = FractalKind::Mandelbrot.escape_smooth(Complex64::new(0.0, 0.0), 128);
+        assert_eq!(inside.smooth_iterations(), 128.0);
+
+        let escaping = FractalKind::Mandelbrot.escape_smooth(Complex64::new(1.0, 0.0), 64);
+        assert!(!escaping.is_inside);
+        assert!(escaping.smooth_iterations() >= 0.0);
+        assert!(escaping.smooth_iterations() <= f64::from(escaping.iterations) + 1.0);
+    }
+
+    #[test]
+    fn gen_fractal_parallel_matches_serial() {
+        let start = Point::new(-1.0, -1.0);
+
+        let serial = gen_fractal(&start, 8, 8, 0.25, 0.25, 32, FractalPoint::mandelbrot);
+        let parallel = gen_fractal_parallel(&start, 8, 8, 0.25, 0.25, 32, FractalPoint::mandelbrot);
+
+        for (row_a, row_b) in serial.iter().zip(parallel.iter()) {
+            for (a, b) in row_a.iter().zip(row_b.iter()) {
+                assert_eq!(a.is_inside, b.is_inside);
+                assert_eq!(a.iterations, b.iterations);
+            }
+        }
+    }
+
+    #[test]
+    fn fractal_kind_dispatch() {
+        assert_eq!(
+            FractalKind::Mandelbrot
+                .escape(Complex64::new(0.0, 0.0), 128)
+                .is_inside,
+            true
+        );
+        assert_eq!(
+            FractalKind::BurningShip
+                .escape(Complex64::new(5.0, 5.0), 64)
+                .is_inside,
+            false
+        );
+        assert_eq!(
+            FractalKind::Tricorn
+                .escape(Complex64::new(0.0, 0.0), 64)
+                .is_inside,
+            true
+        );
+        assert_eq!(
+            FractalKind::Multibrot { power: 3 }
+                .escape(Complex64::new(5.0, 5.0), 64)
+                .is_inside,
+            false
+        );
+    }
 }
@@ -7,6 +7,7 @@ extern crate structopt;
 extern crate image;
 extern crate matto;
 extern crate num;
+extern crate rand;
 
 use std::f64;
 use std::num::ParseFloatError;
@@ -20,6 +21,7 @@ use num::complex::{Complex64, ParseComplexError};
 use structopt::StructOpt;
 
 use matto::dragon;
+use matto::drawing::Drawer;
 use matto::fractree;
 use matto::geo;
 use matto::geo::{PointF64, PointU32};
@@ -209,6 +211,15 @@ pub struct Primirs {
     #[structopt(long = "dy", default_value = "16")]
     dy: u32,
 
+    /// Alpha every shape is blended with, in [0, 255].
+    #[structopt(short = "a", long = "alpha", default_value = "127")]
+    alpha: u8,
+
+    /// Which kind of shape to approximate the image with: triangle, rect,
+    /// rotated-rect or ellipse.
+    #[structopt(long = "shape", default_value = "triangle")]
+    shape_kind: primi::ShapeKind,
+
     /// Scale the original image down by this percentage so that's faster.
     #[structopt(long = "scale-down", default_value = "1")]
     scale_down: u32,
@@ -491,49 +502,64 @@ fn primirs(config: &Primirs) {
     let img = image::open(&config.img_path).expect("cannot open source image file");
     let rgba = img.to_rgba();
 
-    let primitized = if config.scale_down > 1 {
+    let primify_config = primi::PrimifyConfig {
+        alpha: config.alpha,
+        shape_kind: config.shape_kind,
+        dx: config.dx,
+        dy: config.dy,
+        nmutations: config.nmutations,
+    };
+
+    let (best_image, best_error) = match config.shape_kind {
+        primi::ShapeKind::Triangle => {
+            run_primirs::<geo::Triangle<u32>>(&rgba, config, &primify_config)
+        }
+        primi::ShapeKind::Rect => run_primirs::<geo::Rect<u32>>(&rgba, config, &primify_config),
+        primi::ShapeKind::RotatedRect => {
+            run_primirs::<primi::RotatedRect>(&rgba, config, &primify_config)
+        }
+        primi::ShapeKind::Ellipse => run_primirs::<primi::Ellipse>(&rgba, config, &primify_config),
+    };
+
+    println!("best error {:?}", best_error);
+
+    best_image
+        .save(&config.output_path)
+        .expect("cannot save primitized file");
+}
+
+fn run_primirs<S: primi::Shape>(
+    rgba: &image::RgbaImage,
+    config: &Primirs,
+    primify_config: &primi::PrimifyConfig,
+) -> (image::RgbaImage, f64) {
+    if config.scale_down > 1 {
         let resized = image::imageops::resize(
-            &rgba,
-            img.width() / config.scale_down,
-            img.height() / config.scale_down,
+            rgba,
+            rgba.width() / config.scale_down,
+            rgba.height() / config.scale_down,
             image::Triangle,
         );
 
-        primi::primify::<_, geo::Triangle<u32>>(
-            &resized,
-            config.nshapes,
-            config.nmutations,
-            config.dx,
-            config.dy,
-        ).map(|prim| {
-            let mut upscaled_img =
-                image::RgbaImage::from_pixel(rgba.width(), rgba.height(), prim.dominant_color);
+        let prim = primi::primify::<_, S>(&resized, config.nshapes, primify_config)
+            .expect("primirs error");
 
-            for shape in prim.shapes {
-                let upscaled_shape = shape.upscale(config.scale_down);
+        let mut upscaled_img =
+            image::RgbaImage::from_pixel(rgba.width(), rgba.height(), prim.dominant_color);
 
-                upscaled_shape.draw(&rgba, &mut upscaled_img);
-            }
+        for shape in prim.shapes {
+            let upscaled_shape = shape.upscale(config.scale_down);
 
-            (upscaled_img, prim.best_error)
-        })
-    } else {
-        primi::primify::<_, geo::Triangle<u32>>(
-            &rgba,
-            config.nshapes,
-            config.nmutations,
-            config.dx,
-            config.dy,
-        ).map(|prim| (prim.best_image, prim.best_error))
-    };
-
-    let (best_image, best_error) = primitized.expect("primirs error");
+            upscaled_shape.draw(rgba, &mut upscaled_img, primify_config.alpha);
+        }
 
-    println!("best error {:?}", best_error);
+        (upscaled_img, prim.best_error)
+    } else {
+        let prim =
+            primi::primify::<_, S>(rgba, config.nshapes, primify_config).expect("primirs error");
 
-    best_image
-        .save(&config.output_path)
-        .expect("cannot save primitized file");
+        (prim.best_image, prim.best_error)
+    }
 }
 
 fn fractal_tree(config: &FractalTree) {
@@ -554,3 +580,4 @@ fn fractal_tree(config: &FractalTree) {
     img.save(&config.output_path)
         .expect("cannot save primitized file");
 }
+